@@ -0,0 +1,53 @@
+#![no_main]
+#![no_std]
+
+extern crate panic_halt;
+
+use lpc8xx_hal::{
+    cortex_m_rt::entry, prelude::*, ultrasonic::Sensor, Peripherals,
+};
+
+#[entry]
+fn main() -> ! {
+    // Get access to the device's peripherals. Since only one instance of this
+    // struct can exist, the call to `take` returns an `Option<Peripherals>`.
+    // If we tried to call the method a second time, it would return `None`, but
+    // we're only calling it the one time here, so we can safely `unwrap` the
+    // `Option` without causing a panic.
+    let p = Peripherals::take().unwrap();
+
+    // Initialize the APIs of the peripherals we need.
+    let swm = p.SWM.split();
+    let mut syscon = p.SYSCON.split();
+
+    let mut handle = swm.handle.enable(&mut syscon.handle);
+    let gpio = p.GPIO.enable(&mut syscon.handle);
+
+    // Select pin for LED
+    let led = swm.pins.pio1_1;
+
+    // Select pins for the sensor's trigger input and echo output
+    let trigger = swm.pins.pio0_16;
+    let echo = swm.pins.pio0_17.into_swm_pin();
+
+    // Configure the pins. The API tracks the state of pins at compile time,
+    // to prevent any mistakes.
+    let mut led = led.into_gpio_pin(&gpio).into_output();
+    let trigger = trigger.into_gpio_pin(&gpio).into_output();
+    let (echo, _) = swm.movable_functions.t0_cap0.assign(echo, &mut handle);
+
+    let mut sensor = Sensor::new(p.CTIMER0, trigger, echo, &mut syscon.handle);
+
+    // Light the LED (which is active-low), while an obstacle is closer than
+    // 10 cm
+    loop {
+        match sensor.measure(30.millis()) {
+            Ok(measurement) if measurement.distance_mm() < 100 => {
+                led.set_low().unwrap();
+            }
+            _ => {
+                led.set_high().unwrap();
+            }
+        }
+    }
+}
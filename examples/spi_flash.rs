@@ -0,0 +1,212 @@
+//! SPI example using a 25-series serial NOR flash (like the W25Q32)
+//!
+//! Contains a minimal flash driver, with an interface modeled after the
+//! `spi-memory` crate: reads use the fast read command, which requires a dummy
+//! byte after the address, and the flash's chip select is controlled by the
+//! SPI peripheral, which ensures the chip select timing the flash requires.
+//!
+//! This example expects the microcontroller to be connected to the flash in
+//! the following way:
+//! - PIO0_6/SPI0_SCK to CLK
+//! - PIO0_7/SPI0_MOSI to DI
+//! - PIO0_8/SPI0_MISO to DO
+//! - PIO0_9/SPI0_SSEL0 to /CS
+//! - VSS to GND, /WP, and /HOLD
+//! - VDD to VCC
+
+#![no_main]
+#![no_std]
+
+extern crate panic_halt;
+
+use lpc8xx_hal::{
+    cortex_m_rt::entry,
+    pac::SPI0,
+    prelude::*,
+    spi::{
+        self,
+        shared::{Config, SpiBus, SpiDevice, Ssel},
+        BitOrder, Delays, Error,
+    },
+    swm::{PIO0_9, SPI0_SSEL0},
+    syscon::clocksource::SpiClock,
+    Peripherals,
+};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+
+    let swm = p.SWM.split();
+    let mut syscon = p.SYSCON.split();
+
+    #[cfg(feature = "82x")]
+    let mut handle = swm.handle;
+    #[cfg(feature = "845")]
+    let mut handle = swm.handle.enable(&mut syscon.handle); // SWM isn't enabled by default on LPC845.
+
+    let (sck, _) = swm
+        .movable_functions
+        .spi0_sck
+        .assign(swm.pins.pio0_6.into_swm_pin(), &mut handle);
+    let (mosi, _) = swm
+        .movable_functions
+        .spi0_mosi
+        .assign(swm.pins.pio0_7.into_swm_pin(), &mut handle);
+    let (miso, _) = swm
+        .movable_functions
+        .spi0_miso
+        .assign(swm.pins.pio0_8.into_swm_pin(), &mut handle);
+    let (ssel, _) = swm
+        .movable_functions
+        .spi0_ssel0
+        .assign(swm.pins.pio0_9.into_swm_pin(), &mut handle);
+
+    #[cfg(feature = "82x")]
+    let clock = SpiClock::new();
+    #[cfg(feature = "845")]
+    let clock = SpiClock::new(&syscon.iosc);
+
+    let spi =
+        p.SPI0
+            .enable(&clock, &mut syscon.handle, spi::MODE_0, sck, mosi, miso);
+
+    let bus = SpiBus::new(spi);
+
+    // At 6 MHz, a clock cycle takes 167 ns. That's more than enough for the
+    // chip select setup and hold times of 25-series flash, but the chip
+    // select needs to be high for at least 50 ns (some parts need 100 ns)
+    // between commands.
+    let config = Config::new(spi::MODE_0, BitOrder::MsbFirst, 6_000_000)
+        .with_delays(Delays {
+            transfer: 1,
+            ..Delays::default()
+        });
+    let mut flash = Flash::new(bus.device(Ssel::new(ssel), config));
+
+    let _id = flash.read_jedec_id().unwrap();
+
+    let data = [0x12, 0x34, 0x56, 0x78];
+    flash.erase_sectors(0, 1).unwrap();
+    flash.write_bytes(0, &data).unwrap();
+
+    let mut buffer = [0; 4];
+    flash.read(0, &mut buffer).unwrap();
+    assert_eq!(buffer, data);
+
+    loop {}
+}
+
+const WRITE_ENABLE: u8 = 0x06;
+const READ_STATUS: u8 = 0x05;
+const PAGE_PROGRAM: u8 = 0x02;
+const FAST_READ: u8 = 0x0b;
+const SECTOR_ERASE: u8 = 0x20;
+const JEDEC_ID: u8 = 0x9f;
+
+const STATUS_BUSY: u8 = 0x01;
+
+const SECTOR_SIZE: u32 = 4096;
+const PAGE_SIZE: u32 = 256;
+
+type Device<'bus, Pins> = SpiDevice<'bus, SPI0, Pins, Ssel<SPI0_SSEL0, PIO0_9>>;
+
+/// A minimal driver for 25-series serial NOR flash
+struct Flash<'bus, Pins> {
+    device: Device<'bus, Pins>,
+}
+
+impl<'bus, Pins> Flash<'bus, Pins> {
+    fn new(device: Device<'bus, Pins>) -> Self {
+        Flash { device }
+    }
+
+    /// Read the manufacturer and device ID
+    fn read_jedec_id(&mut self) -> Result<[u8; 3], Error> {
+        let mut id = [0; 3];
+
+        self.device.transaction(|spi| {
+            spi.write(&[JEDEC_ID]).unwrap();
+            spi.transfer(&mut id).unwrap();
+        })?;
+
+        Ok(id)
+    }
+
+    /// Read data, starting at `address`
+    fn read(&mut self, address: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        self.device.transaction(|spi| {
+            spi.write(&command(FAST_READ, address)).unwrap();
+            // The fast read command requires 8 dummy cycles.
+            spi.send_dummy_bytes(1);
+            spi.transfer(buffer).unwrap();
+        })
+    }
+
+    /// Erase `n` sectors, starting at the sector that contains `address`
+    fn erase_sectors(&mut self, address: u32, n: usize) -> Result<(), Error> {
+        for i in 0..n as u32 {
+            let address = address + i * SECTOR_SIZE;
+
+            self.write_enable()?;
+            self.device.transaction(|spi| {
+                spi.write(&command(SECTOR_ERASE, address)).unwrap();
+            })?;
+            self.wait_done()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write data, starting at `address`
+    ///
+    /// The data must have been erased before.
+    fn write_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        let mut address = address;
+        let mut data = data;
+
+        while !data.is_empty() {
+            // A page program command must not cross a page boundary.
+            let n = (PAGE_SIZE - address % PAGE_SIZE) as usize;
+            let (chunk, rest) = data.split_at(n.min(data.len()));
+
+            self.write_enable()?;
+            self.device.transaction(|spi| {
+                spi.write(&command(PAGE_PROGRAM, address)).unwrap();
+                spi.write(chunk).unwrap();
+            })?;
+            self.wait_done()?;
+
+            address += chunk.len() as u32;
+            data = rest;
+        }
+
+        Ok(())
+    }
+
+    fn write_enable(&mut self) -> Result<(), Error> {
+        self.device
+            .transaction(|spi| spi.write(&[WRITE_ENABLE]).unwrap())
+    }
+
+    /// Wait until an erase or program command has finished
+    fn wait_done(&mut self) -> Result<(), Error> {
+        loop {
+            let mut status = [0];
+            self.device.transaction(|spi| {
+                spi.write(&[READ_STATUS]).unwrap();
+                spi.transfer(&mut status).unwrap();
+            })?;
+
+            if status[0] & STATUS_BUSY == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Return a command, followed by a 24-bit address
+fn command(command: u8, address: u32) -> [u8; 4] {
+    let [_, a2, a1, a0] = address.to_be_bytes();
+    [command, a2, a1, a0]
+}
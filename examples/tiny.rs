@@ -0,0 +1,90 @@
+//! A minimal application, to keep an eye on code size
+//!
+//! Blinks an LED and sends a message via USART0 on every blink. Build it
+//! without the default features, so neither formatting code nor the panicking
+//! constructors can end up in the binary:
+//!
+//! ``` ignore
+//! cargo build --release --no-default-features --features=82x-rt --example tiny
+//! ```
+//!
+//! `scripts/size.sh` does this for both families, and checks that the result
+//! fits into 4 KiB of flash.
+
+#![no_main]
+#![no_std]
+
+extern crate panic_halt;
+
+use lpc8xx_hal::{
+    cortex_m_rt::entry, delay::Delay, prelude::*,
+    syscon::clocksource::UsartClock, Peripherals,
+};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+
+    let swm = p.SWM.split();
+    let mut syscon = p.SYSCON.split();
+    let mut delay = Delay::new(p.SYST);
+
+    #[cfg(feature = "82x")]
+    let mut handle = swm.handle;
+    #[cfg(feature = "845")]
+    let mut handle = swm.handle.enable(&mut syscon.handle);
+
+    #[cfg(feature = "82x")]
+    let gpio = p.GPIO;
+    #[cfg(feature = "845")]
+    let gpio = p.GPIO.enable(&mut syscon.handle);
+
+    // 115200 baud, assuming a main clock of 12 MHz. See the USART example for
+    // an explanation of these values.
+    #[cfg(feature = "82x")]
+    let clock_config = {
+        syscon.uartfrg.set_clkdiv(6);
+        syscon.uartfrg.set_frgmult(22);
+        syscon.uartfrg.set_frgdiv(0xff);
+
+        // The arguments are known to be valid. Avoid `unwrap`, as that would
+        // pull in the code to format the error.
+        match UsartClock::try_new(&syscon.uartfrg, 0, 16) {
+            Ok(clock_config) => clock_config,
+            Err(_) => loop {},
+        }
+    };
+    #[cfg(feature = "845")]
+    let clock_config = UsartClock::new_with_baudrate(115200);
+
+    #[cfg(feature = "82x")]
+    let (led, tx_pin, rx_pin) =
+        (swm.pins.pio0_12, swm.pins.pio0_7, swm.pins.pio0_18);
+    #[cfg(feature = "845")]
+    let (led, tx_pin, rx_pin) =
+        (swm.pins.pio1_1, swm.pins.pio0_25, swm.pins.pio0_24);
+
+    let mut led = led.into_gpio_pin(&gpio).into_output();
+
+    let (u0_rxd, _) = swm
+        .movable_functions
+        .u0_rxd
+        .assign(rx_pin.into_swm_pin(), &mut handle);
+    let (u0_txd, _) = swm
+        .movable_functions
+        .u0_txd
+        .assign(tx_pin.into_swm_pin(), &mut handle);
+
+    let serial =
+        p.USART0
+            .enable(&clock_config, &mut syscon.handle, u0_rxd, u0_txd);
+
+    loop {
+        // Neither of these can fail, so ignoring the results is fine.
+        let _ = serial.tx().bwrite_all(b"blink\n");
+        let _ = led.set_high();
+        delay.delay_ms(500_u16);
+        let _ = led.set_low();
+        delay.delay_ms(500_u16);
+    }
+}
@@ -0,0 +1,105 @@
+//! Hardware-in-the-loop test for the data logger, using the internal flash
+//!
+//! Expects an LPC845-BRK. Nothing needs to be connected, but a voltage on
+//! PIO0_7/ADC_0 makes the logged samples more interesting. Overwrites the
+//! last 16 pages of flash. See `scripts/hil.sh` for how to run it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        data_logger::{DataLogger, IapStorage, Record},
+        swm::{self, PIO0_7},
+        sync::Queue,
+        syscon::clocksource::AdcClock,
+        uptime::Uptime,
+        Peripherals, ADC,
+    };
+
+    const FIRST_PAGE: u32 = 1008;
+    const PAGES: u32 = 16;
+
+    static RING: Queue<Record<1>, 8> = Queue::new();
+
+    struct State {
+        adc: ADC,
+        adc_0: swm::Function<swm::ADC_0, swm::state::Assigned<PIO0_7>>,
+        uptime: Uptime,
+    }
+
+    #[init]
+    fn init() -> State {
+        let p = Peripherals::take().unwrap();
+
+        let swm = p.SWM.split();
+        let mut syscon = p.SYSCON.split();
+
+        let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+
+        let (adc_0, _) = swm
+            .fixed_functions
+            .adc_0
+            .assign(swm.pins.pio0_7.into_swm_pin(), &mut swm_handle);
+
+        let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+
+        State {
+            adc: p
+                .ADC0
+                .enable(&AdcClock::new(&syscon.iosc), &mut syscon.handle),
+            adc_0,
+            uptime: Uptime::new(timer),
+        }
+    }
+
+    #[test]
+    fn logs_adc_samples(state: &mut State) {
+        IapStorage::new(FIRST_PAGE, PAGES).erase().unwrap();
+
+        let mut logger: DataLogger<_, 1> =
+            DataLogger::new(IapStorage::new(FIRST_PAGE, PAGES)).unwrap();
+        defmt::assert_eq!(logger.pages_written(), 0);
+
+        let mut logged = [Record::new(0, [0]); 20];
+        for record in logged.iter_mut() {
+            let (sample,) = state.adc.scan((&state.adc_0,));
+            *record = Record::new(state.uptime.now_ms(), [sample]);
+
+            RING.enqueue(*record).unwrap();
+            if RING.len() == 8 {
+                logger.flush(&RING).unwrap();
+            }
+        }
+        logger.flush(&RING).unwrap();
+        logger.sync().unwrap();
+
+        // 10 records fit into a 64-byte page.
+        defmt::assert_eq!(logger.pages_written(), 2);
+
+        for (i, record) in logged.iter().enumerate() {
+            defmt::assert!(logger.read(i as u32).unwrap() == Some(*record));
+        }
+        defmt::assert!(logger.read(20).unwrap().is_none());
+    }
+
+    #[test]
+    fn resumes_after_last_written_page(state: &mut State) {
+        let mut logger: DataLogger<_, 1> =
+            DataLogger::new(IapStorage::new(FIRST_PAGE, PAGES)).unwrap();
+        defmt::assert_eq!(logger.pages_written(), 2);
+
+        let record = Record::new(state.uptime.now_ms(), [0x123]);
+        RING.enqueue(record).unwrap();
+        logger.flush(&RING).unwrap();
+        logger.sync().unwrap();
+
+        // The first record after the last written page
+        defmt::assert!(logger.read(20).unwrap() == Some(record));
+    }
+}
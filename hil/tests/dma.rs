@@ -0,0 +1,70 @@
+//! Hardware-in-the-loop test for DMA
+//!
+//! Expects an LPC845-BRK. No wiring is required. See `scripts/hil.sh` for how
+//! to run it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        cortex_m::singleton,
+        dma::{self, Channel, Channel0},
+        init_state::Enabled,
+        Peripherals,
+    };
+
+    type State = Option<Channel<Channel0, Enabled<&'static dma::Handle>>>;
+
+    #[init]
+    fn init() -> State {
+        let p = Peripherals::take().unwrap();
+
+        let mut syscon = p.SYSCON.split();
+
+        let descriptors = singleton!(: dma::DescriptorTable =
+            dma::DescriptorTable::new())
+        .unwrap();
+        let dma = p.DMA.split(descriptors);
+
+        let handle = singleton!(: dma::Handle =
+            dma.handle.enable(&mut syscon.handle))
+        .unwrap();
+
+        Some(dma.channels.channel0.enable(handle))
+    }
+
+    #[test]
+    fn copies_memory(channel: &mut State) {
+        let source = singleton!(: [u8; 64] = [0; 64]).unwrap();
+        let dest = singleton!(: [u8; 64] = [0; 64]).unwrap();
+
+        for (i, b) in source.iter_mut().enumerate() {
+            *b = i as u8 ^ 0xa5;
+        }
+        let source: &'static [u8; 64] = source;
+
+        let transfer = channel.take().unwrap().start_copy(source, dest);
+        let (c, source, dest) = transfer.wait().unwrap();
+        *channel = Some(c);
+
+        defmt::assert_eq!(source, dest.free());
+    }
+
+    #[test]
+    fn copies_into_larger_buffer(channel: &mut State) {
+        let source = singleton!(: [u8; 4] = [1, 2, 3, 4]).unwrap();
+        let dest = singleton!(: [u8; 8] = [0; 8]).unwrap();
+
+        let transfer = channel.take().unwrap().start_copy(source, dest);
+        let (c, _, dest) = transfer.wait().unwrap();
+        *channel = Some(c);
+
+        defmt::assert_eq!(dest.free(), [1, 2, 3, 4, 0, 0, 0, 0]);
+    }
+}
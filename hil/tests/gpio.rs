@@ -0,0 +1,66 @@
+//! Hardware-in-the-loop test for GPIO
+//!
+//! Expects an LPC845-BRK, with PIO0_16 connected to PIO0_17. See
+//! `scripts/hil.sh` for how to run it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        cortex_m::singleton,
+        gpio::direction::{Input, Output},
+        prelude::*,
+        swm::{pin_state::Gpio, Pin, PIO0_16, PIO0_17},
+        Peripherals,
+    };
+
+    struct State {
+        output: Pin<PIO0_16, Gpio<'static, Output>>,
+        input: Pin<PIO0_17, Gpio<'static, Input>>,
+    }
+
+    #[init]
+    fn init() -> State {
+        let p = Peripherals::take().unwrap();
+
+        let swm = p.SWM.split();
+        let mut syscon = p.SYSCON.split();
+
+        let gpio = singleton!(: lpc8xx_hal::GPIO =
+            p.GPIO.enable(&mut syscon.handle))
+        .unwrap();
+
+        State {
+            output: swm.pins.pio0_16.into_gpio_pin(gpio).into_output_low(),
+            input: swm.pins.pio0_17.into_gpio_pin(gpio).into_input(),
+        }
+    }
+
+    #[test]
+    fn reads_low(state: &mut State) {
+        state.output.set_low().unwrap();
+        defmt::assert!(state.input.is_low().unwrap());
+    }
+
+    #[test]
+    fn reads_high(state: &mut State) {
+        state.output.set_high().unwrap();
+        defmt::assert!(state.input.is_high().unwrap());
+    }
+
+    #[test]
+    fn follows_toggle(state: &mut State) {
+        state.output.set_low().unwrap();
+        state.output.toggle().unwrap();
+        defmt::assert!(state.input.is_high().unwrap());
+
+        state.output.toggle().unwrap();
+        defmt::assert!(state.input.is_low().unwrap());
+    }
+}
@@ -0,0 +1,93 @@
+//! Hardware-in-the-loop test for I2C, using an EEPROM
+//!
+//! Expects an LPC845-BRK, connected to a 256 byte EEPROM (like the 24C02) at
+//! address 0b1010_0000, in the following way:
+//! - PIO0_11/I2C0_SDA to SDA
+//! - PIO0_10/I2C0_SCL to SCL
+//! - VSS to GND
+//! - VDD to VDD
+//!
+//! See `scripts/hil.sh` for how to run it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        delay::Delay,
+        i2c,
+        init_state::Enabled,
+        pac::I2C0,
+        prelude::*,
+        swm::{PIO0_10, PIO0_11},
+        syscon::clocksource::I2cClock,
+        Peripherals, I2C,
+    };
+
+    const ADDRESS: u8 = 0b1010_0000;
+
+    struct State {
+        i2c: I2C<I2C0, Enabled<i2c::Functions<I2C0, PIO0_11, PIO0_10>>>,
+        delay: Delay,
+    }
+
+    #[init]
+    fn init() -> State {
+        let p = Peripherals::take().unwrap();
+
+        let swm = p.SWM.split();
+        let mut syscon = p.SYSCON.split();
+
+        let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+
+        let (i2c0_sda, _) = swm
+            .fixed_functions
+            .i2c0_sda
+            .assign(swm.pins.pio0_11.into_swm_pin(), &mut swm_handle);
+        let (i2c0_scl, _) = swm
+            .fixed_functions
+            .i2c0_scl
+            .assign(swm.pins.pio0_10.into_swm_pin(), &mut swm_handle);
+
+        let i2c = p.I2C0.enable(
+            &I2cClock::new_400khz(),
+            &mut syscon.handle,
+            i2c0_sda,
+            i2c0_scl,
+        );
+
+        State {
+            i2c,
+            delay: Delay::new(p.SYST),
+        }
+    }
+
+    #[test]
+    fn reads_back_written_data(state: &mut State) {
+        let data = [0x12, 0x34, 0x56, 0x78];
+
+        let mut command = [0; 5];
+        command[1..].copy_from_slice(&data);
+        state.i2c.write(ADDRESS, &command).unwrap();
+
+        // The EEPROM doesn't respond while it's writing.
+        state.delay.delay_ms(10_u8);
+
+        let mut buffer = [0; 4];
+        state.i2c.write(ADDRESS, &[0]).unwrap();
+        state.i2c.read(ADDRESS, &mut buffer).unwrap();
+
+        defmt::assert_eq!(buffer, data);
+    }
+
+    #[test]
+    fn nacks_missing_device(state: &mut State) {
+        // Nothing is expected to be connected at 7-bit address 0x08.
+        defmt::assert!(state.i2c.write(0x08 << 1, &[0]).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Hardware-in-the-loop test for SPI, using a 25-series serial NOR flash
+//!
+//! Expects an LPC845-BRK, connected to a 25-series flash (like the W25Q32) in
+//! the following way:
+//! - PIO0_6/SPI0_SCK to CLK
+//! - PIO0_7/SPI0_MOSI to DI
+//! - PIO0_8/SPI0_MISO to DO
+//! - PIO0_9/SPI0_SSEL0 to /CS
+//! - VSS to GND, /WP, and /HOLD
+//! - VDD to VCC
+//!
+//! Erases the first sector of the flash. See `examples/spi_flash.rs` for a
+//! driver that uses the same commands, and `scripts/hil.sh` for how to run
+//! it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        cortex_m::singleton,
+        pac::SPI0,
+        prelude::*,
+        spi::{
+            self,
+            shared::{Config, SpiBus, SpiDevice, Ssel},
+            BitOrder, Delays,
+        },
+        swm::{PIO0_6, PIO0_7, PIO0_8, PIO0_9, SPI0_SSEL0},
+        syscon::clocksource::SpiClock,
+        Peripherals,
+    };
+
+    const WRITE_ENABLE: u8 = 0x06;
+    const READ_STATUS: u8 = 0x05;
+    const PAGE_PROGRAM: u8 = 0x02;
+    const READ: u8 = 0x03;
+    const FAST_READ: u8 = 0x0b;
+    const SECTOR_ERASE: u8 = 0x20;
+    const JEDEC_ID: u8 = 0x9f;
+
+    const STATUS_BUSY: u8 = 0x01;
+
+    type Device = SpiDevice<
+        'static,
+        SPI0,
+        spi::Functions<SPI0, PIO0_6, PIO0_7, PIO0_8>,
+        Ssel<SPI0_SSEL0, PIO0_9>,
+    >;
+
+    struct State {
+        flash: Device,
+    }
+
+    #[init]
+    fn init() -> State {
+        let p = Peripherals::take().unwrap();
+
+        let swm = p.SWM.split();
+        let mut syscon = p.SYSCON.split();
+
+        let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+
+        let (sck, _) = swm
+            .movable_functions
+            .spi0_sck
+            .assign(swm.pins.pio0_6.into_swm_pin(), &mut swm_handle);
+        let (mosi, _) = swm
+            .movable_functions
+            .spi0_mosi
+            .assign(swm.pins.pio0_7.into_swm_pin(), &mut swm_handle);
+        let (miso, _) = swm
+            .movable_functions
+            .spi0_miso
+            .assign(swm.pins.pio0_8.into_swm_pin(), &mut swm_handle);
+        let (ssel, _) = swm
+            .movable_functions
+            .spi0_ssel0
+            .assign(swm.pins.pio0_9.into_swm_pin(), &mut swm_handle);
+
+        let spi = p.SPI0.enable(
+            &SpiClock::new(&syscon.iosc),
+            &mut syscon.handle,
+            spi::MODE_0,
+            sck,
+            mosi,
+            miso,
+        );
+
+        let bus = singleton!(
+            : SpiBus<SPI0, spi::Functions<SPI0, PIO0_6, PIO0_7, PIO0_8>> =
+                SpiBus::new(spi)
+        )
+        .unwrap();
+
+        let config = Config::new(spi::MODE_0, BitOrder::MsbFirst, 6_000_000)
+            .with_delays(Delays {
+                transfer: 1,
+                ..Delays::default()
+            });
+
+        State {
+            flash: bus.device(Ssel::new(ssel), config),
+        }
+    }
+
+    #[test]
+    fn reads_jedec_id(state: &mut State) {
+        let mut id = [0; 3];
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[JEDEC_ID]).unwrap();
+                spi.transfer(&mut id).unwrap();
+            })
+            .unwrap();
+
+        // No flash would read as all zeros or all ones.
+        defmt::assert!(id != [0x00; 3] && id != [0xff; 3]);
+    }
+
+    #[test]
+    fn fast_read_returns_written_data(state: &mut State) {
+        let data = [0x12, 0x34, 0x56, 0x78];
+
+        write_enable(&mut state.flash);
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[SECTOR_ERASE, 0, 0, 0]).unwrap();
+            })
+            .unwrap();
+        wait_done(&mut state.flash);
+
+        write_enable(&mut state.flash);
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[PAGE_PROGRAM, 0, 0, 0]).unwrap();
+                spi.write(&data).unwrap();
+            })
+            .unwrap();
+        wait_done(&mut state.flash);
+
+        let mut buffer = [0; 4];
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[FAST_READ, 0, 0, 0]).unwrap();
+                spi.send_dummy_bytes(1);
+                spi.transfer(&mut buffer).unwrap();
+            })
+            .unwrap();
+
+        defmt::assert_eq!(buffer, data);
+    }
+
+    #[test]
+    fn fast_read_matches_normal_read(state: &mut State) {
+        let mut normal = [0; 16];
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[READ, 0, 0, 0]).unwrap();
+                spi.transfer(&mut normal).unwrap();
+            })
+            .unwrap();
+
+        let mut fast = [0; 16];
+        state
+            .flash
+            .transaction(|spi| {
+                spi.write(&[FAST_READ, 0, 0, 0]).unwrap();
+                spi.send_dummy_bytes(1);
+                spi.transfer(&mut fast).unwrap();
+            })
+            .unwrap();
+
+        defmt::assert_eq!(fast, normal);
+    }
+
+    fn write_enable(flash: &mut Device) {
+        flash
+            .transaction(|spi| spi.write(&[WRITE_ENABLE]).unwrap())
+            .unwrap();
+    }
+
+    fn wait_done(flash: &mut Device) {
+        loop {
+            let mut status = [0];
+            flash
+                .transaction(|spi| {
+                    spi.write(&[READ_STATUS]).unwrap();
+                    spi.transfer(&mut status).unwrap();
+                })
+                .unwrap();
+
+            if status[0] & STATUS_BUSY == 0 {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Hardware-in-the-loop test for USART
+//!
+//! Expects an LPC845-BRK. No wiring is required, as U0_TXD and U0_RXD are
+//! assigned to the same pin (PIO0_16), which loops the USART back onto
+//! itself. See `scripts/hil.sh` for how to run it.
+
+#![no_main]
+#![no_std]
+
+use cortex_m as _;
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[defmt_test::tests]
+mod tests {
+    use lpc8xx_hal::{
+        init_state::Enabled,
+        nb::block,
+        pac::USART0,
+        prelude::*,
+        swm::PIO0_16,
+        syscon::clocksource::UsartClock,
+        usart::{self, Parity, Settings, StopBits},
+        Peripherals, USART,
+    };
+
+    type Usart =
+        USART<USART0, Enabled<usart::Functions<USART0, PIO0_16, PIO0_16>>>;
+
+    #[init]
+    fn init() -> Usart {
+        let p = Peripherals::take().unwrap();
+
+        let swm = p.SWM.split();
+        let mut syscon = p.SYSCON.split();
+
+        let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+
+        let pin = swm.pins.pio0_16.into_swm_pin();
+        let (u0_txd, pin) =
+            swm.movable_functions.u0_txd.assign(pin, &mut swm_handle);
+        let (u0_rxd, _) =
+            swm.movable_functions.u0_rxd.assign(pin, &mut swm_handle);
+
+        let clock = UsartClock::new_with_baudrate(115200);
+
+        p.USART0.enable(&clock, &mut syscon.handle, u0_rxd, u0_txd)
+    }
+
+    #[test]
+    fn receives_what_was_sent(usart: &mut Usart) {
+        for &word in b"Hello, world!" {
+            block!(usart.tx().write(word)).unwrap();
+            defmt::assert_eq!(block!(usart.rx().read()).unwrap(), word);
+        }
+    }
+
+    #[test]
+    fn receives_with_parity(usart: &mut Usart) {
+        usart.configure(
+            &Settings::default()
+                .parity(Parity::Odd)
+                .stop_bits(StopBits::Two),
+        );
+
+        block!(usart.tx().write(0x5a)).unwrap();
+        defmt::assert_eq!(block!(usart.rx().read()).unwrap(), 0x5a);
+
+        usart.configure(&Settings::default());
+    }
+}
@@ -1,5 +1,6 @@
 MEMORY
 {
     FLASH : ORIGIN = 0x00000000, LENGTH = 64K
-    RAM   : ORIGIN = 0x10000000, LENGTH = 16K
+    /* The IAP functions of the boot ROM use the top 32 bytes of RAM */
+    RAM   : ORIGIN = 0x10000000, LENGTH = 16K - 32
 }
@@ -11,3 +11,18 @@ fi
 
 cargo build --verbose --features=82x-rt,no-target-warning --examples
 cargo build --verbose --features=845-rt,no-target-warning --examples
+
+cargo build --verbose --features=82x,no-target-warning,debug_dump
+cargo build --verbose --features=845,no-target-warning,debug_dump
+
+cargo build --verbose --features=82x,no-target-warning,timeouts
+cargo build --verbose --features=845,no-target-warning,timeouts
+
+cargo build --verbose --features=82x,no-target-warning,fault_injection
+cargo build --verbose --features=845,no-target-warning,fault_injection
+
+cargo build --verbose --features=82x,no-target-warning,bbqueue
+cargo build --verbose --features=845,no-target-warning,bbqueue
+
+cargo build --verbose --no-default-features --features=82x-rt,no-target-warning --example tiny
+cargo build --verbose --no-default-features --features=845-rt,no-target-warning --example tiny
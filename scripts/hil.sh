@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+set -e
+
+# Runs the hardware-in-the-loop tests in `hil/tests` on an LPC845-BRK, which
+# must be connected via its on-board debugger. Requires probe-rs:
+# https://probe.rs/
+#
+# Please refer to the documentation at the top of each test for the wiring it
+# expects. Pass the name of a test (like `hil_gpio`) to run only that test.
+#
+# The tests are a separate crate, as they use a different version of
+# cortex-m-rt than the HAL. See `hil/Cargo.toml`.
+
+cd "$(dirname "$0")/../hil"
+
+# This overrides the flags in `.cargo/config`, so `-Tlink.x` is repeated here.
+export RUSTFLAGS="-C link-arg=-Tlink.x -C link-arg=-Tdefmt.x"
+export CARGO_TARGET_THUMBV6M_NONE_EABI_RUNNER="probe-rs run --chip LPC845M301JBD48"
+export DEFMT_LOG=info
+
+if [ -n "$1" ]; then
+    cargo test --test "$1"
+else
+    for test in hil_gpio hil_usart hil_dma hil_i2c_eeprom hil_spi_flash \
+        hil_data_logger; do
+        cargo test --test $test
+    done
+fi
@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+set -e
+
+# Builds the `tiny` example without the default features, and fails, if it
+# doesn't fit into 4 KiB of flash. Requires `arm-none-eabi-size`.
+#
+# This guards against changes that make the basic GPIO/USART/delay path
+# larger, like pulling formatting or panicking code into it.
+
+LIMIT=4096
+
+for family in 82x 845; do
+    cargo build --release --no-default-features \
+        --features=$family-rt,no-target-warning --example tiny
+
+    # Everything that ends up in flash: code, read-only data, the vector
+    # table, and the initial values of `.data`.
+    size=$(arm-none-eabi-size -A target/thumbv6m-none-eabi/release/examples/tiny |
+        awk '$1 ~ /^\.(vector_table|text|rodata|data)$/ { sum += $2 } END { print sum }')
+
+    echo "tiny ($family): $size of $LIMIT bytes"
+    if [ "$size" -gt "$LIMIT" ]; then
+        echo "tiny ($family) exceeds the size limit"
+        exit 1
+    fi
+done
@@ -0,0 +1,369 @@
+//! Helpers for A/B firmware updates
+//!
+//! A safe way to update firmware in the field is to keep two firmware images
+//! in flash: the one that is running, and a second one that is being
+//! updated (for example via [`xmodem`]). Only once the new image has been
+//! written and verified, a small bootloader at the start of flash switches
+//! over to it. If anything goes wrong during the update, the old image is
+//! still there.
+//!
+//! This module provides the building blocks for such a bootloader:
+//!
+//! - [`Slot`] describes the part of flash that holds an image. It can
+//!   calculate the image's CRC-32 using the CRC engine, and start the image.
+//! - [`BootRecord`] tells the bootloader which slot to start, and how to
+//!   verify it. It is stored in a flash page of its own.
+//! - [`mark_gpreg`] and [`read_gpreg`] pass a slot to the bootloader through
+//!   a PMU general-purpose register. This can be used to try out a new image
+//!   once, without writing to flash. The register is cleared on power-on, so
+//!   the next power cycle starts the slot from the boot record again.
+//!
+//! Each image must be linked to run from its slot. Its vector table must be
+//! at the start of the slot, which needs to be aligned to 256 bytes (4
+//! pages).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     ab_image::{self, BootRecord, Slot, SlotId},
+//!     Peripherals,
+//! };
+//!
+//! // The bootloader occupies the first 4 KiB, the boot record the last page
+//! // of the LPC822. Both slots start at a multiple of 4 pages.
+//! const SLOT_A: Slot = Slot::new(64, 92);
+//! const SLOT_B: Slot = Slot::new(156, 92);
+//! const RECORD_PAGE: u32 = 255;
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut crc = p.CRC.enable(&mut syscon.handle);
+//! let mut pmu = p.PMU.split();
+//!
+//! if let Some(record) = BootRecord::read(RECORD_PAGE) {
+//!     // A slot that is being tried out takes precedence.
+//!     let id = ab_image::read_gpreg(&mut pmu.handle, 0)
+//!         .unwrap_or(record.slot);
+//!     let slot = match id {
+//!         SlotId::A => SLOT_A,
+//!         SlotId::B => SLOT_B,
+//!     };
+//!
+//!     if slot.checksum(&mut crc, record.len) == record.crc32 {
+//!         // Sound, as the slot contains a verified image.
+//!         unsafe { slot.start(&mut p.NVIC, &mut p.SCB, &mut p.SYST) }
+//!     }
+//! }
+//!
+//! // No valid image. Wait for an update.
+//! ```
+//!
+//! [`xmodem`]: ../xmodem/index.html
+//! [`Slot`]: struct.Slot.html
+//! [`BootRecord`]: struct.BootRecord.html
+//! [`mark_gpreg`]: fn.mark_gpreg.html
+//! [`read_gpreg`]: fn.read_gpreg.html
+
+#[cfg(target_arch = "arm")]
+use core::arch::asm;
+use core::ptr;
+
+use cortex_m::interrupt;
+
+use crate::{
+    crc::{self, CRC},
+    iap::{self, Page, PAGE_SIZE},
+    pac, pmu,
+};
+
+/// Marks a valid boot record ("BOOT")
+const RECORD_MAGIC: u32 = 0x544f_4f42;
+
+/// Marks a slot in a general-purpose register, in the upper 24 bits
+const GPREG_MAGIC: u32 = 0x534c_5400;
+
+/// The required alignment of a vector table, in bytes
+///
+/// VTOR ignores the lower 7 bits, but the vector table of LPC845 is longer
+/// than 128 bytes, so it needs to be aligned to the next power of two.
+const VECTOR_TABLE_ALIGN: u32 = 256;
+
+/// The part of flash that holds a firmware image
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Slot {
+    /// The first flash page of the slot
+    pub first_page: u32,
+
+    /// The number of flash pages in the slot
+    pub pages: u32,
+}
+
+impl Slot {
+    /// Describe a slot
+    pub const fn new(first_page: u32, pages: u32) -> Self {
+        Slot { first_page, pages }
+    }
+
+    /// Return the address of the start of the slot
+    pub fn address(&self) -> u32 {
+        self.first_page * PAGE_SIZE as u32
+    }
+
+    /// Calculate the CRC-32 of the first `len` bytes of the slot
+    ///
+    /// Uses the same CRC as [`xmodem::Image::crc32`], so the two can be
+    /// compared directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `len` is larger than the slot.
+    ///
+    /// [`xmodem::Image::crc32`]: ../xmodem/struct.Image.html#structfield.crc32
+    pub fn checksum(&self, crc: &mut CRC, len: u32) -> u32 {
+        assert!(len <= self.pages * PAGE_SIZE as u32);
+
+        let mut hasher = crc.start(crc::Config::CRC32);
+        let mut remaining = len as usize;
+        let mut page = self.first_page;
+
+        while remaining > 0 {
+            let n = remaining.min(PAGE_SIZE);
+            hasher.write_bytes(&iap::read_page(page).0[..n]);
+
+            remaining -= n;
+            page += 1;
+        }
+
+        hasher.sum()
+    }
+
+    /// Start the image in this slot
+    ///
+    /// Disables all interrupts in the NVIC and stops SysTick, points VTOR at
+    /// the image's vector table, loads the image's initial stack pointer,
+    /// and jumps to its reset handler.
+    ///
+    /// Interrupts stay masked in PRIMASK, so no interrupt can be taken before
+    /// the image has initialized its memory. The image needs to enable them,
+    /// once it is ready, for example using `cortex_m::interrupt::enable`.
+    ///
+    /// Peripherals are left in whatever state they're in. Ideally, this is
+    /// called before the bootloader has configured any of them.
+    ///
+    /// # Safety
+    ///
+    /// The slot must contain a valid image that has been linked to run from
+    /// this slot. Use [`checksum`] to verify it first. The stack is reset, so
+    /// nothing that lives on the current stack may be used by the image.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the slot isn't aligned to 256 bytes.
+    ///
+    /// [`checksum`]: #method.checksum
+    pub unsafe fn start(
+        &self,
+        nvic: &mut pac::NVIC,
+        scb: &mut pac::SCB,
+        syst: &mut pac::SYST,
+    ) -> ! {
+        let address = self.address();
+        assert!(address % VECTOR_TABLE_ALIGN == 0);
+
+        interrupt::disable();
+
+        // The Cortex-M0+ has at most 32 external interrupts.
+        nvic.icer[0].write(0xffff_ffff);
+        nvic.icpr[0].write(0xffff_ffff);
+
+        syst.disable_interrupt();
+        syst.disable_counter();
+
+        scb.vtor.write(address);
+
+        let vector_table = address as *const u32;
+        let stack_pointer = ptr::read_volatile(vector_table);
+        let reset_handler = ptr::read_volatile(vector_table.add(1));
+
+        // Once the stack pointer has been changed, no compiler-generated code
+        // may run anymore, so this needs to happen in a single block. The
+        // main stack is selected first, in case the bootloader runs on the
+        // process stack. This is the same sequence that cortex-m 0.7 uses in
+        // `asm::bootstrap`, which isn't available in cortex-m 0.6.
+        #[cfg(target_arch = "arm")]
+        asm!(
+            "mrs {tmp}, CONTROL",
+            "bics {tmp}, {spsel}",
+            "msr CONTROL, {tmp}",
+            "isb",
+            "msr MSP, {msp}",
+            "bx {rv}",
+            tmp = in(reg) 0,
+            spsel = in(reg) 2,
+            msp = in(reg) stack_pointer,
+            rv = in(reg) reset_handler,
+            options(noreturn, nomem, nostack),
+        );
+
+        // Keeps the HAL buildable for other targets, like the host. Images can
+        // only be started on the microcontroller.
+        #[cfg(not(target_arch = "arm"))]
+        {
+            let _ = (stack_pointer, reset_handler);
+            unimplemented!("images can only be started on ARM targets");
+        }
+    }
+}
+
+/// One of the two slots
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlotId {
+    /// Slot A
+    A = 0,
+
+    /// Slot B
+    B = 1,
+}
+
+impl SlotId {
+    /// Return the other slot
+    ///
+    /// This is the slot an update should be written to.
+    pub fn other(self) -> Self {
+        match self {
+            SlotId::A => SlotId::B,
+            SlotId::B => SlotId::A,
+        }
+    }
+
+    fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0 => Some(SlotId::A),
+            1 => Some(SlotId::B),
+            _ => None,
+        }
+    }
+}
+
+/// Tells the bootloader which slot to start
+///
+/// Stored in a flash page of its own. Please refer to the
+/// [module documentation] for more information.
+///
+/// [module documentation]: index.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootRecord {
+    /// The slot to start
+    pub slot: SlotId,
+
+    /// The length of the image in the slot, in bytes
+    pub len: u32,
+
+    /// The CRC-32 of the image in the slot
+    pub crc32: u32,
+}
+
+impl BootRecord {
+    /// Read the boot record from flash page `page`
+    ///
+    /// Returns `None`, if the page doesn't contain a valid boot record, for
+    /// example because it has never been written.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `page` is beyond the end of flash.
+    pub fn read(page: u32) -> Option<Self> {
+        let data = &iap::read_page(page).0;
+
+        let mut words = [0; 5];
+        for (word, bytes) in words.iter_mut().zip(data.chunks_exact(4)) {
+            *word =
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let [magic, slot, len, crc32, check] = words;
+        if magic != RECORD_MAGIC || check != !(slot ^ len ^ crc32) {
+            return None;
+        }
+
+        Some(BootRecord {
+            slot: SlotId::from_bits(slot)?,
+            len,
+            crc32,
+        })
+    }
+
+    /// Write the boot record to flash page `page`
+    ///
+    /// Please refer to [`iap::write_page`] for the restrictions that apply.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `page` is beyond the end of flash.
+    ///
+    /// [`iap::write_page`]: ../iap/fn.write_page.html
+    pub fn write(&self, page: u32) -> Result<(), iap::Error> {
+        let slot = self.slot as u32;
+        let words = [
+            RECORD_MAGIC,
+            slot,
+            self.len,
+            self.crc32,
+            !(slot ^ self.len ^ self.crc32),
+        ];
+
+        let mut data = Page([0xff; PAGE_SIZE]);
+        for (bytes, word) in data.0.chunks_exact_mut(4).zip(&words) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+
+        iap::write_page(page, &data)
+    }
+}
+
+/// Pass a slot to the bootloader through general-purpose register `n`
+///
+/// The bootloader can read it using [`read_gpreg`], after a software reset.
+/// The register is cleared on power-on, which makes this suitable for trying
+/// out a new image, before committing to it by writing a [`BootRecord`].
+///
+/// # Panics
+///
+/// Panics, if `n` is larger than 3.
+///
+/// [`read_gpreg`]: fn.read_gpreg.html
+/// [`BootRecord`]: struct.BootRecord.html
+pub fn mark_gpreg(pmu: &mut pmu::Handle, n: usize, slot: SlotId) {
+    pmu.write_gpreg(n, GPREG_MAGIC | slot as u32);
+}
+
+/// Read a slot from general-purpose register `n`, and clear it
+///
+/// Returns `None`, if [`mark_gpreg`] hasn't been called since the last
+/// power-on. The register is cleared, so the slot is only used once, even if
+/// the image in it resets without confirming it.
+///
+/// # Panics
+///
+/// Panics, if `n` is larger than 3.
+///
+/// [`mark_gpreg`]: fn.mark_gpreg.html
+pub fn read_gpreg(pmu: &mut pmu::Handle, n: usize) -> Option<SlotId> {
+    let value = pmu.read_gpreg(n);
+    pmu.write_gpreg(n, 0);
+
+    if value & !0xff != GPREG_MAGIC {
+        return None;
+    }
+
+    SlotId::from_bits(value & 0xff)
+}
@@ -0,0 +1,356 @@
+//! API for the analog comparator (ACMP)
+//!
+//! The entry point to this API is [`ACMP`]. It can compare two voltages, taken
+//! from input pins or from internal sources, and detect edges of the result.
+//! This can be used to detect zero crossings, for example.
+//!
+//! The comparator output can be routed to a pin, using the `ACMP_O` movable
+//! function of the switch matrix.
+//!
+//! Noisy inputs, like a battery voltage under changing load, can make the
+//! output toggle rapidly while the input is close to the threshold, causing an
+//! interrupt storm. Use [`ACMP::set_hysteresis`] to avoid that, and
+//! [`ACMP::set_synchronized`] to filter out glitches.
+//!
+//! The analog comparator is described in the user manual, chapter 22 (LPC82x)
+//! or chapter 31 (LPC845).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{acmp, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let (acmp_i1, _) = swm.fixed_functions.acmp_i1.assign(
+//!     swm.pins.pio0_0.into_swm_pin(),
+//!     &mut swm.handle,
+//! );
+//!
+//! let mut acmp = p.ACOMP.enable(&mut syscon.handle);
+//!
+//! // Compare the input against half of the supply voltage
+//! acmp.set_ladder(16, acmp::LadderReference::Supply);
+//! acmp.set_hysteresis(acmp::Hysteresis::Mv20);
+//! acmp.set_synchronized(true);
+//! acmp.configure(&acmp_i1, &acmp::Ladder, acmp::Edge::Both);
+//!
+//! #[cfg(feature = "845")]
+//! acmp.enable_interrupt();
+//!
+//! loop {
+//!     if acmp.edge_detected() {
+//!         acmp.clear_edge();
+//!         // The input crossed the threshold
+//!     }
+//! }
+//! ```
+//!
+//! [`ACMP::set_hysteresis`]: struct.ACMP.html#method.set_hysteresis
+//! [`ACMP::set_synchronized`]: struct.ACMP.html#method.set_synchronized
+
+use crate::{init_state, pac, swm, syscon};
+
+/// Interface to the analog comparator
+///
+/// Controls the analog comparator. Use [`Peripherals`] to gain access to an
+/// instance of this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct ACMP<State = init_state::Enabled> {
+    acmp: pac::ACOMP,
+    _state: State,
+}
+
+impl ACMP<init_state::Disabled> {
+    pub(crate) fn new(acmp: pac::ACOMP) -> Self {
+        ACMP {
+            acmp,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the analog comparator
+    ///
+    /// This method is only available, if `ACMP` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `ACMP` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    pub fn enable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> ACMP<init_state::Enabled> {
+        syscon.power_up(&self.acmp);
+        syscon.enable_clock(&self.acmp);
+
+        ACMP {
+            acmp: self.acmp,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl ACMP<init_state::Enabled> {
+    /// Disable the analog comparator
+    ///
+    /// This method is only available, if `ACMP` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `ACMP` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> ACMP<init_state::Disabled> {
+        syscon.disable_clock(&self.acmp);
+        syscon.power_down(&self.acmp);
+
+        ACMP {
+            acmp: self.acmp,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Select the inputs and the edge to detect
+    ///
+    /// The output is high, while the voltage at `positive` is higher than the
+    /// voltage at `negative`. Input pins can only be selected, if their switch
+    /// matrix function has been assigned.
+    ///
+    /// Clears the edge detection flag, as changing the inputs might cause a
+    /// spurious edge.
+    pub fn configure<P, N>(&mut self, _: &P, _: &N, edge: Edge)
+    where
+        P: Input,
+        N: Input,
+    {
+        self.acmp.ctrl.modify(|_, w| {
+            // Sound, as the `SEL` values of all inputs are valid. Only the
+            // LPC82x PAC considers these writes unsafe.
+            #[allow(unused_unsafe)]
+            unsafe {
+                w.comp_vp_sel().bits(P::SEL);
+                w.comp_vm_sel().bits(N::SEL);
+            }
+            w.edgesel().bits(edge as u8);
+            w.edgeclr().set_bit()
+        });
+        self.acmp.ctrl.modify(|_, w| w.edgeclr().clear_bit());
+    }
+
+    /// Configure the voltage ladder
+    ///
+    /// The ladder output is `value / 31` times the reference voltage. It can
+    /// be selected as an input using [`Ladder`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `value` is larger than 31.
+    ///
+    /// [`Ladder`]: struct.Ladder.html
+    pub fn set_ladder(&mut self, value: u8, reference: LadderReference) {
+        assert!(value <= 31);
+
+        self.acmp.lad.write(|w| {
+            w.laden().set_bit();
+            unsafe { w.ladsel().bits(value) };
+            match reference {
+                LadderReference::Supply => w.ladref().clear_bit(),
+                LadderReference::External => w.ladref().set_bit(),
+            }
+        });
+    }
+
+    /// Set the hysteresis
+    ///
+    /// With hysteresis, the output only changes once the input voltages
+    /// differ by more than the selected amount. This keeps a slowly changing
+    /// or noisy input from toggling the output (and triggering an edge)
+    /// repeatedly, while it is close to the threshold.
+    pub fn set_hysteresis(&mut self, hysteresis: Hysteresis) {
+        self.acmp.ctrl.modify(|_, w| w.hys().bits(hysteresis as u8));
+    }
+
+    /// Select whether the output is synchronized to the bus clock
+    ///
+    /// By default, the output is used directly, which means edges are
+    /// detected with the lowest possible latency. If synchronized, the output
+    /// is sampled by the bus clock, before it's used for edge detection and
+    /// routed to the `ACMP_O` function. This filters out glitches that are
+    /// shorter than a clock cycle.
+    ///
+    /// Clears the edge detection flag, as the switch might cause a spurious
+    /// edge.
+    pub fn set_synchronized(&mut self, synchronized: bool) {
+        self.acmp.ctrl.modify(|_, w| {
+            w.compsa().bit(synchronized);
+            w.edgeclr().set_bit()
+        });
+        self.acmp.ctrl.modify(|_, w| w.edgeclr().clear_bit());
+    }
+
+    /// Return the current comparator output
+    pub fn output(&self) -> bool {
+        self.acmp.ctrl.read().compstat().bit_is_set()
+    }
+
+    /// Indicates whether the configured edge has been detected
+    ///
+    /// The flag stays set, until [`clear_edge`] is called.
+    ///
+    /// [`clear_edge`]: #method.clear_edge
+    pub fn edge_detected(&self) -> bool {
+        self.acmp.ctrl.read().compedge().bit_is_set()
+    }
+
+    /// Clear the edge detection flag
+    pub fn clear_edge(&mut self) {
+        self.acmp.ctrl.modify(|_, w| w.edgeclr().set_bit());
+        self.acmp.ctrl.modify(|_, w| w.edgeclr().clear_bit());
+    }
+
+    /// Request the comparator interrupt, when the configured edge is detected
+    ///
+    /// The interrupt stays pending, until the edge detection flag is cleared,
+    /// using [`clear_edge`]. It needs to be unmasked in the NVIC separately.
+    ///
+    /// On LPC82x, the comparator always requests its interrupt, so it only
+    /// needs to be unmasked in the NVIC.
+    ///
+    /// [`clear_edge`]: #method.clear_edge
+    #[cfg(feature = "845")]
+    pub fn enable_interrupt(&mut self) {
+        self.acmp.ctrl.modify(|_, w| w.intena().set_bit());
+    }
+
+    /// Don't request the comparator interrupt
+    #[cfg(feature = "845")]
+    pub fn disable_interrupt(&mut self) {
+        self.acmp.ctrl.modify(|_, w| w.intena().clear_bit());
+    }
+}
+
+impl<State> ACMP<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::ACOMP {
+        self.acmp
+    }
+}
+
+/// Implemented for everything that can be an input of the comparator
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Input {
+    /// The value that selects this input
+    const SEL: u8;
+}
+
+/// The voltage ladder, as an input of the comparator
+///
+/// See [`ACMP::set_ladder`].
+///
+/// [`ACMP::set_ladder`]: struct.ACMP.html#method.set_ladder
+pub struct Ladder;
+
+impl Input for Ladder {
+    const SEL: u8 = 0;
+}
+
+/// The internal bandgap reference, as an input of the comparator
+pub struct Bandgap;
+
+impl Input for Bandgap {
+    #[cfg(feature = "82x")]
+    const SEL: u8 = 5;
+    #[cfg(feature = "845")]
+    const SEL: u8 = 6;
+}
+
+macro_rules! inputs {
+    ($($function:ident, $sel:expr;)*) => {
+        $(
+            impl<P> Input
+                for swm::Function<swm::$function, swm::state::Assigned<P>>
+            {
+                const SEL: u8 = $sel;
+            }
+        )*
+    };
+}
+
+inputs!(
+    ACMP_I1, 1;
+    ACMP_I2, 2;
+    ACMP_I3, 3;
+    ACMP_I4, 4;
+);
+
+/// The edges of the comparator output that are detected
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// Falling edges
+    Falling = 0,
+
+    /// Rising edges
+    Rising = 1,
+
+    /// Both edges
+    Both = 2,
+}
+
+/// The reference voltage of the voltage ladder
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LadderReference {
+    /// The supply voltage (VDD)
+    Supply,
+
+    /// The voltage at the VDDCMP pin
+    External,
+}
+
+/// The hysteresis of the comparator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Hysteresis {
+    /// No hysteresis
+    None = 0,
+
+    /// 5 mV
+    Mv5 = 1,
+
+    /// 10 mV
+    Mv10 = 2,
+
+    /// 20 mV
+    Mv20 = 3,
+}
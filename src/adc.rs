@@ -0,0 +1,465 @@
+//! API for the analog-to-digital converter (ADC)
+//!
+//! The entry point to this API is [`ADC`]. It can convert a set of channels
+//! once, using [`ADC::scan`], or in burst mode, in which the ADC converts a
+//! sequence of channels over and over, without any intervention from software.
+//!
+//! The ADC is described in the user manual, chapter 21 (LPC82x) or chapter 33
+//! (LPC845).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     adc::Sequence, syscon::clocksource::AdcClock, Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let (adc_0, _) = swm.fixed_functions.adc_0.assign(
+//!     swm.pins.pio0_7.into_swm_pin(),
+//!     &mut swm.handle,
+//! );
+//!
+//! #[cfg(feature = "82x")]
+//! let clock = AdcClock::new();
+//! #[cfg(feature = "845")]
+//! let clock = AdcClock::new(&syscon.iosc);
+//!
+//! let adc = p.ADC0.enable(&clock, &mut syscon.handle);
+//! let burst = adc.start_burst(Sequence::new().channel(&adc_0));
+//!
+//! loop {
+//!     // Average 16 conversions, to filter out noise
+//!     let value = burst.read_averaged(&adc_0, 16);
+//! }
+//! ```
+//!
+//! [`ADC`]: struct.ADC.html
+//! [`ADC::scan`]: struct.ADC.html#method.scan
+
+use embedded_hal::adc;
+
+use crate::{
+    init_state, pac, swm,
+    syscon::{self, clocksource::AdcClock, PeripheralClock as _},
+};
+
+/// Interface to the ADC
+///
+/// Controls the ADC. Use [`Peripherals`] to gain access to an instance of
+/// this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct ADC<State = init_state::Enabled> {
+    adc: pac::ADC0,
+    _state: State,
+}
+
+impl ADC<init_state::Disabled> {
+    pub(crate) fn new(adc: pac::ADC0) -> Self {
+        ADC {
+            adc,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the ADC
+    ///
+    /// This method is only available, if `ADC` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `ADC` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// Selects the ADC clock, as configured by `clock`, powers up the ADC, and
+    /// runs its self-calibration, which takes about 290 µs. Assumes that the
+    /// ADC clock runs at 12 MHz.
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    pub fn enable(
+        self,
+        clock: &AdcClock,
+        syscon: &mut syscon::Handle,
+    ) -> ADC<init_state::Enabled> {
+        clock.select_clock(syscon);
+        syscon.power_up(&self.adc);
+        syscon.enable_clock(&self.adc);
+
+        // The calibration must run at 500 kHz or less. See user manual,
+        // section 21.3.4/33.3.4.
+        self.adc.ctrl.write(|w| {
+            unsafe { w.clkdiv().bits(CALIBRATION_CLKDIV) };
+            w.calmode().set_bit()
+        });
+        while self.adc.ctrl.read().calmode().bit_is_set() {}
+
+        // Run conversions at the full system clock.
+        self.adc.ctrl.write(|w| unsafe { w.clkdiv().bits(0) });
+
+        ADC {
+            adc: self.adc,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl ADC<init_state::Enabled> {
+    /// Disable the ADC
+    ///
+    /// This method is only available, if `ADC` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `ADC` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> ADC<init_state::Disabled> {
+        syscon.disable_clock(&self.adc);
+        syscon.power_down(&self.adc);
+
+        ADC {
+            adc: self.adc,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Convert a set of channels once
+    ///
+    /// `channels` is a tuple of up to 12 ADC functions, each of which must be
+    /// assigned to its pin. Starts a single conversion of sequence A and waits
+    /// until all channels have been converted. Returns a tuple with the 12-bit
+    /// result of each channel, in the same order as `channels`.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{adc::ADC, swm};
+    ///
+    /// fn read_sensors(
+    ///     adc: &mut ADC,
+    ///     temperature: &swm::Function<swm::ADC_0, swm::state::Assigned<swm::PIO0_7>>,
+    ///     light: &swm::Function<swm::ADC_3, swm::state::Assigned<swm::PIO0_23>>,
+    /// ) -> (u16, u16) {
+    ///     adc.scan((temperature, light))
+    /// }
+    /// ```
+    pub fn scan<S>(&mut self, channels: S) -> S::Results
+    where
+        S: Scan,
+    {
+        let mask = channels.mask();
+
+        // Reading the data registers clears their DATAVALID flags, so only
+        // results of the conversion started below are waited for.
+        for_each_channel(mask, |id| {
+            let _ = self.adc.dat[id].read();
+        });
+
+        self.adc.seq_ctrla.write(|w| {
+            unsafe { w.channels().bits(mask) };
+            w.seq_ena().set_bit()
+        });
+        self.adc.seq_ctrla.modify(|_, w| w.start().set_bit());
+
+        for_each_channel(mask, |id| {
+            while self.adc.dat[id].read().datavalid().bit_is_clear() {}
+        });
+
+        self.adc.seq_ctrla.reset();
+
+        channels.results(&self.adc)
+    }
+
+    /// Start converting a sequence of channels continuously
+    ///
+    /// Configures conversion sequence A for burst mode. The ADC will convert
+    /// all channels in `sequence` over and over, until [`Burst::stop`] is
+    /// called. See user manual, section 21.7.2/33.7.2.
+    ///
+    /// [`Burst::stop`]: struct.Burst.html#method.stop
+    pub fn start_burst(self, sequence: Sequence) -> Burst {
+        self.adc.seq_ctrla.write(|w| {
+            unsafe { w.channels().bits(sequence.channels) };
+            w.burst().set_bit();
+            w.seq_ena().set_bit()
+        });
+
+        Burst { adc: self }
+    }
+}
+
+impl<State> ADC<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::ADC0 {
+        self.adc
+    }
+}
+
+/// A set of channels to convert
+///
+/// Passed to [`ADC::start_burst`]. Channels can only be added, if their switch
+/// matrix function has been assigned to a pin.
+///
+/// [`ADC::start_burst`]: struct.ADC.html#method.start_burst
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sequence {
+    channels: u16,
+}
+
+impl Sequence {
+    /// Create an empty sequence
+    pub fn new() -> Self {
+        Sequence { channels: 0 }
+    }
+
+    /// Add a channel to the sequence
+    ///
+    /// Requires the channel's switch matrix function, assigned to the pin that
+    /// it is fixed to. The pin stays in the analog state, as long as the
+    /// function is assigned.
+    pub fn channel<C>(
+        mut self,
+        _: &swm::Function<C, swm::state::Assigned<C::Pin>>,
+    ) -> Self
+    where
+        C: Channel,
+    {
+        self.channels |= 0x1 << C::ID;
+        self
+    }
+}
+
+/// The ADC in burst mode
+///
+/// Created by [`ADC::start_burst`]. The results of the most recent conversion
+/// of each channel can be read at any time, without waiting.
+///
+/// [`ADC::start_burst`]: struct.ADC.html#method.start_burst
+pub struct Burst {
+    adc: ADC<init_state::Enabled>,
+}
+
+impl Burst {
+    /// Return the result of the most recent conversion of a channel
+    ///
+    /// The result is a 12-bit value. If the channel hasn't been converted yet,
+    /// this is `0`.
+    pub fn read<C>(
+        &self,
+        _: &swm::Function<C, swm::state::Assigned<C::Pin>>,
+    ) -> u16
+    where
+        C: Channel,
+    {
+        self.adc.adc.dat[C::ID as usize].read().result().bits()
+    }
+
+    /// Return the average of the next `n` conversions of a channel
+    ///
+    /// Waits for `n` new conversion results, which takes `n` times the
+    /// duration of the whole sequence. This is a simple way to filter out
+    /// noise, at the cost of a lower effective sample rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is zero.
+    pub fn read_averaged<C>(
+        &self,
+        _: &swm::Function<C, swm::state::Assigned<C::Pin>>,
+        n: u16,
+    ) -> u16
+    where
+        C: Channel,
+    {
+        assert!(n > 0);
+
+        let dat = &self.adc.adc.dat[C::ID as usize];
+
+        // Reading the register clears the DATAVALID flag, so the next reads
+        // below only return new results.
+        let _ = dat.read();
+
+        let mut sum: u32 = 0;
+        for _ in 0..n {
+            let result = loop {
+                let dat = dat.read();
+                if dat.datavalid().bit_is_set() {
+                    break dat.result().bits();
+                }
+            };
+
+            sum += result as u32;
+        }
+
+        (sum / n as u32) as u16
+    }
+
+    /// Stop burst mode
+    pub fn stop(self) -> ADC<init_state::Enabled> {
+        self.adc.adc.seq_ctrla.reset();
+        self.adc
+    }
+}
+
+/// Implemented for all switch matrix functions that are ADC channels
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Channel {
+    /// The number of the channel
+    const ID: u8;
+
+    /// The pin that the channel is fixed to
+    type Pin;
+}
+
+macro_rules! channels {
+    ($($function:ident, $id:expr, $pin:ident;)*) => {
+        $(
+            impl Channel for swm::$function {
+                const ID: u8 = $id;
+
+                type Pin = swm::$pin;
+            }
+        )*
+    };
+}
+
+channels!(
+    ADC_0 , 0 , PIO0_7;
+    ADC_1 , 1 , PIO0_6;
+    ADC_2 , 2 , PIO0_14;
+    ADC_3 , 3 , PIO0_23;
+    ADC_4 , 4 , PIO0_22;
+    ADC_5 , 5 , PIO0_21;
+    ADC_6 , 6 , PIO0_20;
+    ADC_7 , 7 , PIO0_19;
+    ADC_8 , 8 , PIO0_18;
+    ADC_9 , 9 , PIO0_17;
+    ADC_10, 10, PIO0_13;
+    ADC_11, 11, PIO0_4;
+);
+
+/// An assigned ADC function identifies its channel
+///
+/// This allows drivers that are written against the embedded-hal traits to
+/// refer to ADC channels. The function can only be in this state, while its
+/// pin is in the analog state.
+impl<C, State> adc::Channel<ADC<State>>
+    for swm::Function<C, swm::state::Assigned<C::Pin>>
+where
+    C: Channel,
+{
+    type ID = u8;
+
+    fn channel() -> Self::ID {
+        C::ID
+    }
+}
+
+/// A set of channels that can be converted using [`ADC::scan`]
+///
+/// Implemented for tuples of up to 12 references to ADC functions that are
+/// assigned to their pins.
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+///
+/// [`ADC::scan`]: struct.ADC.html#method.scan
+pub trait Scan {
+    /// One 12-bit result per channel, in the same order as the channels
+    type Results;
+
+    /// The channels, as a bitmask for the CHANNELS field
+    fn mask(&self) -> u16;
+
+    /// Read the results of the most recent conversion
+    fn results(&self, adc: &pac::ADC0) -> Self::Results;
+}
+
+/// Expands to the type of one result, which is the same for every channel
+macro_rules! sample {
+    ($channel:ident) => {
+        u16
+    };
+}
+
+macro_rules! scan {
+    ($($channel:ident),*) => {
+        impl<'a, $($channel,)*> Scan for (
+            $(&'a swm::Function<
+                $channel,
+                swm::state::Assigned<<$channel as Channel>::Pin>,
+            >,)*
+        )
+        where
+            $($channel: Channel,)*
+        {
+            type Results = ($(sample!($channel),)*);
+
+            fn mask(&self) -> u16 {
+                0 $(| 0x1 << $channel::ID)*
+            }
+
+            fn results(&self, adc: &pac::ADC0) -> Self::Results {
+                (
+                    $(
+                        adc.dat[$channel::ID as usize].read().result().bits(),
+                    )*
+                )
+            }
+        }
+    };
+}
+
+scan!(A);
+scan!(A, B);
+scan!(A, B, C);
+scan!(A, B, C, D);
+scan!(A, B, C, D, E);
+scan!(A, B, C, D, E, F);
+scan!(A, B, C, D, E, F, G);
+scan!(A, B, C, D, E, F, G, H);
+scan!(A, B, C, D, E, F, G, H, I);
+scan!(A, B, C, D, E, F, G, H, I, J);
+scan!(A, B, C, D, E, F, G, H, I, J, K);
+scan!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+fn for_each_channel(mask: u16, mut f: impl FnMut(usize)) {
+    for id in 0..12 {
+        if mask & (0x1 << id) != 0 {
+            f(id);
+        }
+    }
+}
+
+/// Divides the 12 MHz ADC clock down to 500 kHz
+const CALIBRATION_CLKDIV: u8 = 23;
@@ -0,0 +1,136 @@
+//! Calibration of the IRC, stored in flash
+//!
+//! The IRC is factory-calibrated to 12 MHz, with an accuracy of ±1.5 % over
+//! the full temperature range. That's not enough for some uses, for example
+//! for USART communication with a host that has a less accurate clock itself.
+//! On boards without a crystal, the IRC trim can be adjusted to compensate.
+//!
+//! The trim value needs to be measured by the application, for example by
+//! comparing the IRC against a clock input, or by letting a host measure a
+//! known USART bit pattern. [`Calibration`] can then store the result in a
+//! flash page, and apply it on every startup.
+//!
+//! Only available on LPC82x. The trim of the LPC845's FRO is not accessible to
+//! the application.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{calibration::Calibration, Peripherals};
+//!
+//! // The last flash page of the LPC822, which must be excluded from the
+//! // linker script
+//! const CALIBRATION_PAGE: u32 = 255;
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//!
+//! match Calibration::load(CALIBRATION_PAGE) {
+//!     Some(calibration) => calibration.apply(&mut syscon.handle),
+//!     None => {
+//!         // Measure the trim value, apply it, and store it for next time
+//!         let calibration = Calibration::new(0x80);
+//!         calibration.apply(&mut syscon.handle);
+//!         calibration.store(CALIBRATION_PAGE).unwrap();
+//!     }
+//! }
+//! ```
+
+use crate::{iap, syscon};
+
+/// Marks a flash page as containing a calibration record ("CAL1")
+const MAGIC: u32 = 0x314c_4143;
+
+/// Calibration data for the IRC
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    /// The IRC trim value
+    ///
+    /// See [`syscon::Handle::set_irc_trim`].
+    ///
+    /// [`syscon::Handle::set_irc_trim`]: ../syscon/struct.Handle.html#method.set_irc_trim
+    pub irc_trim: u8,
+}
+
+impl Calibration {
+    /// Create calibration data from a measured trim value
+    pub fn new(irc_trim: u8) -> Self {
+        Calibration { irc_trim }
+    }
+
+    /// Read the calibration data that is currently in effect
+    pub fn current(syscon: &syscon::Handle) -> Self {
+        Calibration {
+            irc_trim: syscon.irc_trim(),
+        }
+    }
+
+    /// Apply the calibration data
+    pub fn apply(&self, syscon: &mut syscon::Handle) {
+        syscon.set_irc_trim(self.irc_trim);
+    }
+
+    /// Load calibration data from a flash page
+    ///
+    /// Returns `None`, if the page doesn't contain a valid calibration record,
+    /// for example because it has never been written to.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `page` is beyond the end of flash.
+    pub fn load(page: u32) -> Option<Self> {
+        let page = iap::read_page(page);
+
+        let magic = word(&page, 0);
+        let irc_trim = word(&page, 1);
+        let checksum = word(&page, 2);
+
+        if magic != MAGIC
+            || checksum != checksum_of(irc_trim)
+            || irc_trim > u8::MAX as u32
+        {
+            return None;
+        }
+
+        Some(Calibration {
+            irc_trim: irc_trim as u8,
+        })
+    }
+
+    /// Store the calibration data in a flash page
+    ///
+    /// The whole page is overwritten. Please refer to [`iap::write_page`] for
+    /// the requirements of writing to flash.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `page` is beyond the end of flash.
+    ///
+    /// [`iap::write_page`]: ../iap/fn.write_page.html
+    pub fn store(&self, page: u32) -> Result<(), iap::Error> {
+        let irc_trim = self.irc_trim as u32;
+
+        let mut data = iap::Page([0xff; iap::PAGE_SIZE]);
+        data.0[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        data.0[4..8].copy_from_slice(&irc_trim.to_le_bytes());
+        data.0[8..12].copy_from_slice(&checksum_of(irc_trim).to_le_bytes());
+
+        iap::write_page(page, &data)
+    }
+}
+
+fn word(page: &iap::Page, i: usize) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&page.0[i * 4..i * 4 + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn checksum_of(irc_trim: u32) -> u32 {
+    !(MAGIC ^ irc_trim)
+}
@@ -0,0 +1,283 @@
+//! Clock calibration against an external reference
+//!
+//! Boards without a crystal run from the internal oscillator, whose accuracy
+//! might not be good enough for some uses (see [`calibration`]). To find out
+//! how far off it is, [`ClockCheck`] does two things at the same time, using
+//! the two halves of the SCT (see [`sct`]):
+//!
+//! - The low half outputs a divided version of the main clock on a pin, using
+//!   [`Fgen`]. Any SCT output can be used, which makes this work on boards
+//!   that don't route the CLKOUT function to an accessible pin. Measure it
+//!   with a frequency counter or a logic analyzer.
+//! - The high half measures an external reference of known frequency, for
+//!   example the 1 PPS output of a GPS receiver, divided down, or a crystal
+//!   oscillator on a test fixture. The result is the error of the main clock
+//!   in parts per million.
+//!
+//! Both take the nominal frequency from [`syscon::system_clock_hz`], as do the
+//! [`sct`] and [`fgen`] modules.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{clock_check::ClockCheck, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (output, _) = swm
+//!     .movable_functions
+//!     .sct_out0
+//!     .assign(swm.pins.pio0_16.into_swm_pin(), &mut swm_handle);
+//! let (input, _) = swm
+//!     .movable_functions
+//!     .sct_pin0
+//!     .assign(swm.pins.pio0_17.into_swm_pin(), &mut swm_handle);
+//!
+//! let [mut timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//!
+//! let counters = p.SCT0.split(&mut syscon.handle);
+//! let mut check =
+//!     ClockCheck::new(counters, output, input, &mut syscon.handle);
+//!
+//! // Output 1 MHz, and measure a 1 kHz reference over one second
+//! check.start_output(1_000_000).unwrap();
+//! let measurement = check.measure(&mut timer, 1_000, 1_000).unwrap();
+//! ```
+//!
+//! [`calibration`]: ../calibration/index.html
+//! [`ClockCheck`]: struct.ClockCheck.html
+//! [`sct`]: ../sct/index.html
+//! [`Fgen`]: ../fgen/struct.Fgen.html
+//! [`fgen`]: ../fgen/index.html
+//! [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+
+use core::fmt;
+
+use embedded_hal::timer::CountDown as _;
+
+use crate::{
+    fgen::{self, Fgen},
+    mrt::MrtChannel,
+    sct::{self, Counter, High, Low},
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+/// The largest number of ticks the capture register can measure
+const MAX_TICKS: u32 = 0xffff;
+
+/// The time allowed for the first edge of the reference to arrive
+const START_TIMEOUT_US: u32 = 100_000;
+
+/// Outputs the main clock, and measures it against a reference
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct ClockCheck<O, Po, I, Pi> {
+    output: Fgen<Counter<Low>, O, Po>,
+    // Only `None` during `measure`.
+    input: Option<(Counter<High>, swm::Function<I, Assigned<Pi>>)>,
+}
+
+impl<O, Po, I, Pi> ClockCheck<O, Po, I, Pi>
+where
+    O: sct::OutputTrait,
+    I: sct::InputTrait,
+{
+    /// Create the clock check
+    ///
+    /// `output` receives the divided main clock, once [`start_output`] is
+    /// called. `input` must be connected to the reference.
+    ///
+    /// [`start_output`]: #method.start_output
+    pub fn new(
+        counters: sct::Counters,
+        output: swm::Function<O, Assigned<Po>>,
+        input: swm::Function<I, Assigned<Pi>>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        ClockCheck {
+            output: Fgen::new(counters.low, output, syscon),
+            input: Some((counters.high, input)),
+        }
+    }
+
+    /// Start outputting the main clock, divided down to `hz`
+    ///
+    /// Returns the achieved frequency. Please refer to [`Fgen::start`] for
+    /// details.
+    ///
+    /// [`Fgen::start`]: ../fgen/struct.Fgen.html#method.start
+    pub fn start_output(&mut self, hz: u32) -> Result<u32, fgen::Error> {
+        self.output.start(hz)
+    }
+
+    /// Stop outputting the main clock
+    ///
+    /// The output is left low.
+    pub fn stop_output(&mut self) {
+        self.output.stop();
+    }
+
+    /// Measure the main clock against the reference
+    ///
+    /// `reference_hz` is the nominal frequency of the reference. Counts the
+    /// main clock over `periods` periods of the reference, and compares the
+    /// result to the expected count. The resolution improves with the length
+    /// of the measurement: one tick of the SCT in the total count.
+    ///
+    /// Blocks until the measurement is complete. The reference is sampled by
+    /// polling, so interrupt handlers must not delay this method by more than
+    /// one period of the reference, or an edge is missed. A reference of up
+    /// to about 10 kHz is practical. `timer` limits the time spent waiting for
+    /// the reference.
+    ///
+    /// The output is unaffected, so both can be used at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `reference_hz` or `periods` is `0`.
+    pub fn measure(
+        &mut self,
+        timer: &mut MrtChannel,
+        reference_hz: u32,
+        periods: u32,
+    ) -> Result<Measurement, Error> {
+        assert!(reference_hz > 0);
+        assert!(periods > 0);
+
+        let sct_clock_hz = syscon::system_clock_hz();
+        let ticks_per_period = sct_clock_hz / reference_hz;
+
+        // Choose the smallest prescaler that lets two periods fit the capture
+        // register, so a missed edge can still be detected.
+        let prescaler = ticks_per_period / (MAX_TICKS / 2) + 1;
+        if prescaler > 256 || ticks_per_period / prescaler < 2 {
+            return Err(Error::OutOfRange);
+        }
+
+        let expected = periods as u64 * sct_clock_hz as u64
+            / prescaler as u64
+            / reference_hz as u64;
+        let nominal = ticks_per_period / prescaler;
+
+        // Wait for the first edge, then allow for twice the expected
+        // duration of the measurement.
+        let duration_us = periods as u64 * 1_000_000 / reference_hz as u64;
+        let timeout_us = (START_TIMEOUT_US as u64 + duration_us * 2)
+            .min(timer.max_us() as u64) as u32;
+
+        let (counter, input) = self.input.take().unwrap();
+        let mut counter =
+            counter.into_frequency_counter((prescaler - 1) as u8, input);
+
+        // Can't fail, as the timeout is limited to the maximum.
+        let _ = timer.start_us(timeout_us);
+
+        let mut total: u64 = 0;
+        let mut counted = 0;
+        let result = loop {
+            if counted == periods {
+                break Ok(total);
+            }
+
+            match counter.period() {
+                Ok(period) => {
+                    // A period that is much longer than expected means that
+                    // an edge has been missed.
+                    if period as u32 > nominal * 3 / 2 {
+                        break Err(Error::MissedEdge);
+                    }
+
+                    total += period as u64;
+                    counted += 1;
+                }
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(void)) => void::unreachable(void),
+            }
+
+            if timer.wait().is_ok() {
+                break Err(Error::NoReference);
+            }
+        };
+
+        self.input = Some(counter.free());
+
+        let measured = result?;
+        let error_ppm =
+            (measured as i64 - expected as i64) * 1_000_000 / expected as i64;
+
+        Ok(Measurement {
+            clock_hz: (measured * sct_clock_hz as u64 / expected) as u32,
+            error_ppm: error_ppm as i32,
+        })
+    }
+
+    /// Stop the output and release the resources used
+    pub fn free(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (
+        sct::Counters,
+        swm::Function<O, Assigned<Po>>,
+        swm::Function<I, Assigned<Pi>>,
+    ) {
+        let (low, output) = self.output.free(syscon);
+        let (high, input) = self.input.unwrap();
+
+        (sct::Counters { low, high }, output, input)
+    }
+}
+
+/// The result of a measurement
+///
+/// Returned by [`ClockCheck::measure`].
+///
+/// [`ClockCheck::measure`]: struct.ClockCheck.html#method.measure
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    /// The actual frequency of the main clock, in Hz
+    pub clock_hz: u32,
+
+    /// The error of the main clock, in parts per million
+    ///
+    /// Positive, if the main clock runs faster than its nominal frequency.
+    pub error_ppm: i32,
+}
+
+/// An error that can occur while measuring the main clock
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The reference frequency is too high or too low for the SCT
+    OutOfRange,
+
+    /// The reference didn't deliver enough edges in time
+    NoReference,
+
+    /// An edge of the reference was missed
+    ///
+    /// Either the reference is slower than specified, or interrupt handlers
+    /// delayed the measurement for too long.
+    MissedEdge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfRange => f.write_str("reference out of range"),
+            Error::NoReference => f.write_str("no reference signal"),
+            Error::MissedEdge => f.write_str("missed reference edge"),
+        }
+    }
+}
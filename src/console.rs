@@ -0,0 +1,116 @@
+//! A serial console, independent of the USART instance
+//!
+//! [`USART`] is generic over the USART instance, which means code that uses
+//! it either needs to be generic too, or is tied to a specific instance. That
+//! is inconvenient for a command-line interface or debug console, which is
+//! written once, but bound to whichever USART the board routes to its debug
+//! header, possibly only known at runtime.
+//!
+//! [`Console`] is a trait that can be used as a trait object. It is
+//! implemented for all enabled USART instances, so a `&mut dyn Console` can
+//! refer to any of them.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     console::Console, init_state::Enabled, pac, usart::USART,
+//! };
+//!
+//! // Written once, for any USART
+//! fn greet(console: &mut dyn Console) {
+//!     console.bwrite_all(b"Hello, world!\r\n");
+//! }
+//!
+//! // Selects the USART that is routed to the debug header, for example based
+//! // on a board revision pin that is read at startup.
+//! fn select<'a, P0, P1>(
+//!     usart0: &'a mut USART<pac::USART0, Enabled<P0>>,
+//!     usart1: &'a mut USART<pac::USART1, Enabled<P1>>,
+//!     use_usart1: bool,
+//! ) -> &'a mut dyn Console {
+//!     if use_usart1 {
+//!         usart1
+//!     } else {
+//!         usart0
+//!     }
+//! }
+//! ```
+//!
+//! [`USART`]: ../usart/struct.USART.html
+//! [`Console`]: trait.Console.html
+
+#[cfg(feature = "fmt")]
+use core::fmt;
+
+use embedded_hal::serial::{Read as _, Write as _};
+use nb::block;
+use void::Void;
+
+use crate::{
+    init_state,
+    usart::{self, Instance, USART},
+};
+
+/// A serial console
+///
+/// This trait is object safe, so it can be used as `&mut dyn Console`. Please
+/// refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub trait Console {
+    /// Read a character, if one has been received
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)`, if no character is available.
+    fn read(&mut self) -> nb::Result<u8, usart::Error>;
+
+    /// Write a character, if the transmitter can accept it
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)`, if the transmitter is busy.
+    fn write(&mut self, word: u8) -> nb::Result<(), Void>;
+
+    /// Wait for all written characters to be transmitted
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)`, while the transmitter is still
+    /// busy.
+    fn flush(&mut self) -> nb::Result<(), Void>;
+
+    /// Write all characters, blocking until the transmitter accepts them
+    ///
+    /// Returns, once the last character has been accepted, which means it
+    /// might still be transmitting.
+    fn bwrite_all(&mut self, words: &[u8]) {
+        for &word in words {
+            // Can't fail, as the error type is `Void`.
+            let _ = block!(self.write(word));
+        }
+    }
+}
+
+impl<I, Pins> Console for USART<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    fn read(&mut self) -> nb::Result<u8, usart::Error> {
+        self.rx().read()
+    }
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Void> {
+        self.tx().write(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Void> {
+        self.tx().drain()
+    }
+}
+
+#[cfg(feature = "fmt")]
+impl fmt::Write for dyn Console + '_ {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.bwrite_all(s.as_bytes());
+        // Can't fail, as the error type is `Void`.
+        let _ = block!(self.flush());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+//! API for the CRC engine
+//!
+//! The entry point to this API is [`CRC`].
+//!
+//! The CRC engine is described in the user manual, chapter 20 (LPC82x) or
+//! chapter 24 (LPC845).
+//!
+//! # Examples
+//!
+//! ``` no_run
+//! use core::hash::Hasher as _;
+//!
+//! use lpc8xx_hal::{crc, Peripherals};
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut crc = p.CRC.enable(&mut syscon.handle);
+//!
+//! let mut hasher = crc.start(crc::Config::CRC32);
+//! hasher.write(b"123456789");
+//!
+//! assert_eq!(hasher.sum(), 0xcbf43926);
+//! ```
+
+use core::{hash, ptr};
+
+use nb;
+use void::Void;
+
+use crate::{dma, init_state, pac, syscon};
+
+/// Interface to the CRC engine
+///
+/// Controls the CRC engine. Use [`Peripherals`] to gain access to an instance
+/// of this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct CRC<State = init_state::Enabled> {
+    crc: pac::CRC,
+    _state: State,
+}
+
+impl CRC<init_state::Disabled> {
+    pub(crate) fn new(crc: pac::CRC) -> Self {
+        CRC {
+            crc,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the CRC engine
+    ///
+    /// This method is only available, if `CRC` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `CRC` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    pub fn enable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> CRC<init_state::Enabled> {
+        syscon.enable_clock(&self.crc);
+
+        CRC {
+            crc: self.crc,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl CRC<init_state::Enabled> {
+    /// Disable the CRC engine
+    ///
+    /// This method is only available, if `CRC` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `CRC` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> CRC<init_state::Disabled> {
+        syscon.disable_clock(&self.crc);
+
+        CRC {
+            crc: self.crc,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Start a new checksum calculation
+    ///
+    /// Configures the CRC engine according to `config` and returns a
+    /// [`Hasher`], which can be used to feed data into the engine and read the
+    /// resulting checksum.
+    ///
+    /// [`Hasher`]: struct.Hasher.html
+    pub fn start(&mut self, config: Config) -> Hasher {
+        // See user manual, section 20.6.1/24.6.1.
+        self.crc.mode.write(|w| {
+            unsafe { w.crc_poly().bits(config.polynomial as u8) };
+            w.bit_rvs_wr().bit(config.reverse_input);
+            w.cmpl_wr().bit(config.complement_input);
+            w.bit_rvs_sum().bit(config.reverse_output);
+            w.cmpl_sum().bit(config.complement_output)
+        });
+
+        // Writing the seed resets the engine. See user manual, section
+        // 20.6.2/24.6.2.
+        self.crc
+            .seed
+            .write(|w| unsafe { w.crc_seed().bits(config.seed) });
+
+        Hasher { crc: &self.crc }
+    }
+}
+
+impl<State> CRC<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::CRC {
+        self.crc
+    }
+}
+
+/// A running checksum calculation
+///
+/// Created by [`CRC::start`]. Implements [`core::hash::Hasher`], so it can be
+/// used wherever a hasher is expected. Since it also implements [`dma::Dest`],
+/// it can be fed by the DMA controller.
+///
+/// [`CRC::start`]: struct.CRC.html#method.start
+/// [`core::hash::Hasher`]: https://doc.rust-lang.org/core/hash/trait.Hasher.html
+/// [`dma::Dest`]: ../dma/trait.Dest.html
+pub struct Hasher<'crc> {
+    crc: &'crc pac::CRC,
+}
+
+impl<'crc> Hasher<'crc> {
+    /// Feed data into the CRC engine
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            // The data register accepts 8-bit writes, which are processed as
+            // a single byte. See user manual, section 20.6.4/24.6.4.
+            unsafe { ptr::write_volatile(self.data_register(), b) }
+        }
+    }
+
+    /// Return the checksum of the data written so far
+    pub fn sum(&self) -> u32 {
+        self.crc.sum().read().crc_sum().bits()
+    }
+
+    fn data_register(&self) -> *mut u8 {
+        self.crc.wr_data() as *const _ as *mut u8
+    }
+}
+
+impl<'crc> hash::Hasher for Hasher<'crc> {
+    fn finish(&self) -> u64 {
+        self.sum() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.write_bytes(bytes)
+    }
+}
+
+impl<'crc> dma::Dest for Hasher<'crc> {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // The CRC engine processes each write within one clock cycle. There's
+        // nothing to wait for.
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        self.data_register()
+    }
+}
+
+/// Configuration for a checksum calculation
+///
+/// Passed to [`CRC::start`]. For common CRC variants, please use one of the
+/// presets, like [`Config::CRC32`].
+///
+/// [`CRC::start`]: struct.CRC.html#method.start
+/// [`Config::CRC32`]: #associatedconstant.CRC32
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// The CRC polynomial
+    pub polynomial: Polynomial,
+
+    /// The initial value of the checksum
+    pub seed: u32,
+
+    /// Reverse the bit order of the input data
+    pub reverse_input: bool,
+
+    /// Complement the input data
+    pub complement_input: bool,
+
+    /// Reverse the bit order of the checksum
+    pub reverse_output: bool,
+
+    /// Complement the checksum
+    pub complement_output: bool,
+}
+
+impl Config {
+    /// CRC-32, as used by zlib, Ethernet, PNG, and others
+    pub const CRC32: Self = Config {
+        polynomial: Polynomial::Crc32,
+        seed: 0xffff_ffff,
+        reverse_input: true,
+        complement_input: false,
+        reverse_output: true,
+        complement_output: true,
+    };
+
+    /// CRC-16/CCITT-FALSE
+    pub const CRC16_CCITT_FALSE: Self = Config {
+        polynomial: Polynomial::CrcCcitt,
+        seed: 0xffff,
+        reverse_input: false,
+        complement_input: false,
+        reverse_output: false,
+        complement_output: false,
+    };
+
+    /// CRC-16/XMODEM, as used by the XMODEM-CRC protocol
+    pub const XMODEM: Self = Config {
+        polynomial: Polynomial::CrcCcitt,
+        seed: 0,
+        reverse_input: false,
+        complement_input: false,
+        reverse_output: false,
+        complement_output: false,
+    };
+
+    /// CRC-16/MODBUS, as used by the MODBUS RTU protocol
+    pub const MODBUS: Self = Config {
+        polynomial: Polynomial::Crc16,
+        seed: 0xffff,
+        reverse_input: true,
+        complement_input: false,
+        reverse_output: true,
+        complement_output: false,
+    };
+}
+
+/// The polynomials supported by the CRC engine
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Polynomial {
+    /// CRC-CCITT: x^16 + x^12 + x^5 + 1
+    CrcCcitt = 0,
+
+    /// CRC-16: x^16 + x^15 + x^2 + 1
+    Crc16 = 1,
+
+    /// CRC-32: x^32 + x^26 + x^23 + x^22 + x^16 + x^12 + x^11 + x^10 + x^8 +
+    /// x^7 + x^5 + x^4 + x^2 + x + 1
+    Crc32 = 2,
+}
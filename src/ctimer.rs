@@ -33,11 +33,12 @@
 
 use crate::{
     pac::{
+        self,
         ctimer0::{MR, MSR},
         CTIMER0,
     },
     reg_proxy::RegProxy,
-    swm::{self, PinTrait, T0_MAT0, T0_MAT1, T0_MAT2},
+    swm::{self, PinTrait, T0_MAT0, T0_MAT1, T0_MAT2, T0_MAT3},
     syscon,
 };
 
@@ -70,10 +71,11 @@ pub struct DetachedPwmPin<CTOutput> {
 }
 
 /// Represents a pwm channel assigned to an output pin
-pub struct CTimerPwmPin {
+pub struct CTimerPwmPin<CTOutput, PWM> {
     mr: RegProxy<MR>,
     msr: RegProxy<MSR>,
     number: u8,
+    function: swm::Function<CTOutput, swm::state::Assigned<PWM>>,
 }
 
 impl CTimer {
@@ -137,6 +139,84 @@ impl CTimer {
         )
     }
 
+    /// Stop the PWM timer and reassemble the `CTimer` instance
+    ///
+    /// This is the inverse of [`CTimer::start_pwm`]. It requires all three
+    /// [`DetachedPwmPin`]s, which proves that none of them is still in use.
+    /// Use [`CTimerPwmPin::detach`] to get them back, if you attached them to
+    /// an output.
+    ///
+    /// [`CTimer::start_pwm`]: #method.start_pwm
+    /// [`DetachedPwmPin`]: struct.DetachedPwmPin.html
+    /// [`CTimerPwmPin::detach`]: struct.CTimerPwmPin.html#method.detach
+    pub fn stop_pwm(
+        _: (
+            DetachedPwmPin<T0_MAT0>,
+            DetachedPwmPin<T0_MAT1>,
+            DetachedPwmPin<T0_MAT2>,
+        ),
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        // Safe, as we have all PWM channels, which means nobody else has
+        // access to the CTimer.
+        let ct = unsafe { pac::Peripherals::steal().CTIMER0 };
+
+        // Stop and reset the timer
+        ct.tcr.write(|w| w.crst().set_bit());
+        ct.tcr.write(|w| w.cen().clear_bit());
+        syscon.disable_clock(&ct);
+
+        Self { ct }
+    }
+
+    /// Enable the CTimer clock, before using [`start_toggle`]
+    ///
+    /// [`start_toggle`]: #method.start_toggle
+    pub(crate) fn enable_clock(&self, syscon: &mut syscon::Handle) {
+        syscon.enable_clock(&self.ct);
+    }
+
+    /// Disable the CTimer clock
+    pub(crate) fn disable_clock(&self, syscon: &mut syscon::Handle) {
+        syscon.disable_clock(&self.ct);
+    }
+
+    /// Toggle a match output every `period` ticks
+    ///
+    /// Used by the [`fgen`] module. Call [`stop_toggle`] to stop.
+    ///
+    /// [`fgen`]: ../fgen/index.html
+    /// [`stop_toggle`]: #method.stop_toggle
+    pub(crate) fn start_toggle<M>(&mut self, prescaler: u32, period: u32)
+    where
+        M: MatchOutput,
+    {
+        // Sound, as all values are valid according to the user manual,
+        // section 20.7.
+        unsafe {
+            self.ct.tcr.write(|w| w.crst().set_bit());
+            self.ct.pwmc.write(|w| w.bits(0));
+            self.ct.pr.write(|w| w.prval().bits(prescaler));
+            self.ct.mr[M::INDEX].write(|w| w.match_().bits(period - 1));
+            // Reset the counter on the match (MRnR).
+            self.ct.mcr.write(|w| w.bits(1 << (M::INDEX * 3 + 1)));
+            // Toggle the output on the match (EMCn), starting low.
+            self.ct.emr.write(|w| w.bits(0b11 << (M::INDEX * 2 + 4)));
+        }
+
+        self.ct.tcr.write(|w| w.cen().set_bit());
+    }
+
+    /// Stop toggling a match output
+    ///
+    /// The output is left low.
+    pub(crate) fn stop_toggle(&mut self) {
+        self.ct.tcr.write(|w| w.crst().set_bit());
+        self.ct.tcr.write(|w| w.cen().clear_bit());
+        self.ct.mcr.write(|w| unsafe { w.bits(0) });
+        self.ct.emr.write(|w| unsafe { w.bits(0) });
+    }
+
     /// Return the raw peripheral
     ///
     /// This method serves as an escape hatch from the HAL API. It returns the
@@ -159,8 +239,8 @@ impl<CTOutput> DetachedPwmPin<CTOutput> {
     /// allowing it to be used as a pwm output
     pub fn attach<PWM>(
         self,
-        _: swm::Function<CTOutput, swm::state::Assigned<PWM>>,
-    ) -> CTimerPwmPin
+        function: swm::Function<CTOutput, swm::state::Assigned<PWM>>,
+    ) -> CTimerPwmPin<CTOutput, PWM>
     where
         PWM: PinTrait,
     {
@@ -168,11 +248,37 @@ impl<CTOutput> DetachedPwmPin<CTOutput> {
             mr: self.mr,
             msr: self.msr,
             number: self.number,
+            function,
         }
     }
 }
 
-impl PwmPin for CTimerPwmPin {
+impl<CTOutput, PWM> CTimerPwmPin<CTOutput, PWM> {
+    /// Detach the output from this PWM channel
+    ///
+    /// This is the inverse of [`DetachedPwmPin::attach`]. Returns the
+    /// detached channel and the switch matrix function that was passed to
+    /// [`DetachedPwmPin::attach`], so it can be unassigned from its pin.
+    ///
+    /// [`DetachedPwmPin::attach`]: struct.DetachedPwmPin.html#method.attach
+    pub fn detach(
+        self,
+    ) -> (
+        DetachedPwmPin<CTOutput>,
+        swm::Function<CTOutput, swm::state::Assigned<PWM>>,
+    ) {
+        let pin = DetachedPwmPin {
+            number: self.number,
+            mr: self.mr,
+            msr: self.msr,
+            output: PhantomData,
+        };
+
+        (pin, self.function)
+    }
+}
+
+impl<CTOutput, PWM> PwmPin for CTimerPwmPin<CTOutput, PWM> {
     type Duty = u32;
     /// The behaviour of `enable` is implementation defined and does nothing in
     /// this implementation
@@ -202,3 +308,29 @@ impl PwmPin for CTimerPwmPin {
 
 reg!(MR, [MR; 4], CTIMER0, mr);
 reg!(MSR, [MSR; 4], CTIMER0, msr);
+
+/// Implemented for the CTimer match output functions
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait MatchOutput {
+    /// The number of the match register that controls the output
+    const INDEX: usize;
+}
+
+impl MatchOutput for T0_MAT0 {
+    const INDEX: usize = 0;
+}
+
+impl MatchOutput for T0_MAT1 {
+    const INDEX: usize = 1;
+}
+
+impl MatchOutput for T0_MAT2 {
+    const INDEX: usize = 2;
+}
+
+impl MatchOutput for T0_MAT3 {
+    const INDEX: usize = 3;
+}
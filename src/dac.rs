@@ -0,0 +1,372 @@
+//! API for the digital-to-analog converter (DAC)
+//!
+//! The entry point to this API is [`DAC`]. It outputs a voltage between 0 V
+//! and the analog reference voltage on the DACOUT0 pin (PIO0_17), with a
+//! resolution of 10 bits. The voltage can be set directly, using
+//! [`DAC::set_value`], or played back as a waveform, using [`DAC::play`].
+//!
+//! For playback, the DAC's timer requests a DMA transfer once per sample
+//! period. Samples are streamed from two buffers, alternately, which can be
+//! replaced without glitches while the other one is being played back (see
+//! [`dma::ping_pong`]). This is suitable for signal generation (leave the
+//! buffers in place to repeat them) and audio prompts (swap in the next part,
+//! while the previous one is being played back).
+//!
+//! Only DAC0 is supported. DAC1 is still available as a raw peripheral.
+//!
+//! The DAC is described in the user manual, chapter 27.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     dac::DAC,
+//!     dma::{DescriptorTable, LinkedDescriptor},
+//!     Peripherals,
+//! };
+//!
+//! static mut DESCRIPTORS: DescriptorTable = DescriptorTable::new();
+//! static mut LINKED: [LinkedDescriptor; 2] =
+//!     [LinkedDescriptor::new(), LinkedDescriptor::new()];
+//! static mut RISING: [u32; 32] = [0; 32];
+//! static mut FALLING: [u32; 32] = [0; 32];
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//! let (dacout0, _) = swm
+//!     .fixed_functions
+//!     .dacout0
+//!     .assign(swm.pins.pio0_17.into_swm_pin(), &mut swm_handle);
+//!
+//! syscon.handle.enable_clock(&p.IOCON);
+//! let dac = p.DAC0.enable(&mut syscon.handle, &p.IOCON, &dacout0);
+//!
+//! let dma = p.DMA.split(unsafe { &mut DESCRIPTORS });
+//! let dma_handle = dma.handle.enable(&mut syscon.handle);
+//! let channel = dma.channels.channel22.enable(&dma_handle);
+//!
+//! // A triangle wave
+//! let (rising, falling) = unsafe { (&mut RISING, &mut FALLING) };
+//! for i in 0..32 {
+//!     rising[i] = DAC::sample(i as u16 * 32);
+//!     falling[i] = DAC::sample(1023 - i as u16 * 32);
+//! }
+//!
+//! // 375 ticks of the 12 MHz system clock, for a sample rate of 32 kHz
+//! let playback = dac.play(
+//!     channel,
+//!     [&*rising, &*falling],
+//!     unsafe { &mut LINKED },
+//!     375,
+//! );
+//! ```
+//!
+//! [`DAC`]: struct.DAC.html
+//! [`DAC::set_value`]: struct.DAC.html#method.set_value
+//! [`DAC::play`]: struct.DAC.html#method.play
+//! [`dma::ping_pong`]: ../dma/ping_pong/index.html
+
+use core::ptr;
+
+use embedded_dma::ReadBuffer;
+use void::Void;
+
+use crate::{
+    dma::{self, ping_pong::PingPong, LinkedDescriptor},
+    init_state, pac,
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+/// The position of the VALUE field in the DAC's CR register
+const CR_VALUE_SHIFT: u32 = 6;
+
+/// The DBLBUF_ENA, CNT_ENA, and DMA_ENA bits of the DAC's CTRL register
+const CTRL_PLAYBACK: u32 = 1 << 1 | 1 << 2 | 1 << 3;
+
+/// The offset of PIO0_17's IOCON register, which controls DACOUT0
+const IOCON_PIO0_17: usize = 0x00;
+
+/// The DACMODE bit of the IOCON registers
+const IOCON_DACMODE: u32 = 1 << 16;
+
+/// The DMA channel that is connected to DAC0's DMA request
+pub type DmaChannel = dma::Channel22;
+
+/// Interface to the DAC
+///
+/// Controls DAC0. Use [`Peripherals`] to gain access to an instance of this
+/// struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct DAC<State = init_state::Enabled> {
+    dac: pac::DAC0,
+    _state: State,
+}
+
+impl DAC<init_state::Disabled> {
+    pub(crate) fn new(dac: pac::DAC0) -> Self {
+        DAC {
+            dac,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the DAC
+    ///
+    /// This method is only available, if `DAC` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `DAC` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// Switches PIO0_17 to DAC mode, which requires the IOCON clock to be
+    /// enabled, using [`syscon::Handle::enable_clock`]. The output is 0 V
+    /// initially.
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`syscon::Handle::enable_clock`]: ../syscon/struct.Handle.html#method.enable_clock
+    pub fn enable(
+        self,
+        syscon: &mut syscon::Handle,
+        _iocon: &pac::IOCON,
+        _dacout0: &swm::Function<swm::DACOUT0, Assigned<swm::PIO0_17>>,
+    ) -> DAC<init_state::Enabled> {
+        syscon.power_up(&self.dac);
+        syscon.enable_clock(&self.dac);
+
+        let address = (pac::IOCON::ptr() as usize + IOCON_PIO0_17) as *mut u32;
+
+        // Sound, as the address is PIO0_17's IOCON register, and we have
+        // exclusive access to the pin, as the DACOUT0 function is assigned
+        // to it.
+        unsafe {
+            let value = ptr::read_volatile(address);
+            ptr::write_volatile(address, value | IOCON_DACMODE);
+        }
+
+        self.dac.cr.write(|w| unsafe { w.bits(0) });
+
+        DAC {
+            dac: self.dac,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl DAC<init_state::Enabled> {
+    /// Disable the DAC
+    ///
+    /// This method is only available, if `DAC` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `DAC` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> DAC<init_state::Disabled> {
+        syscon.disable_clock(&self.dac);
+        syscon.power_down(&self.dac);
+
+        DAC {
+            dac: self.dac,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Set the output voltage
+    ///
+    /// The voltage is `value / 1024` times the analog reference voltage.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `value` is larger than 1023.
+    pub fn set_value(&mut self, value: u16) {
+        let sample = Self::sample(value);
+        self.dac.cr.write(|w| unsafe { w.bits(sample) });
+    }
+
+    /// Convert a value into a sample for [`DAC::play`]
+    ///
+    /// Samples are written to the DAC's CR register as they are, which
+    /// requires them to be 32 bits wide. Convert them ahead of time, if
+    /// possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `value` is larger than 1023.
+    ///
+    /// [`DAC::play`]: #method.play
+    pub fn sample(value: u16) -> u32 {
+        assert!(value <= 1023);
+        (value as u32) << CR_VALUE_SHIFT
+    }
+
+    /// Start playing back samples from two alternating buffers
+    ///
+    /// Outputs one sample every `period` ticks of the system clock, starting
+    /// with the samples in `buffers[0]`. Samples need to be converted using
+    /// [`DAC::sample`]. Use [`Playback::swap`] to replace buffers, once they
+    /// have been played back.
+    ///
+    /// `descriptors` need to be kept in a `static`, as the DMA controller
+    /// reads them during playback.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `period` is `0`, or if either buffer is empty or longer than
+    /// 1024 samples.
+    ///
+    /// [`DAC::sample`]: #method.sample
+    /// [`Playback::swap`]: struct.Playback.html#method.swap
+    pub fn play<'dma, B>(
+        self,
+        channel: dma::Channel<
+            DmaChannel,
+            init_state::Enabled<&'dma dma::Handle>,
+        >,
+        buffers: [B; 2],
+        descriptors: &'static mut [LinkedDescriptor; 2],
+        period: u16,
+    ) -> Playback<'dma, B>
+    where
+        B: ReadBuffer<Word = u32>,
+    {
+        assert!(period > 0);
+
+        // The DMA channel waits for the request of the DAC's timer, which
+        // only starts counting below.
+        let transfer = PingPong::new(
+            channel,
+            Output::new(&self.dac),
+            buffers,
+            descriptors,
+        );
+
+        // Sound, as all values are valid according to the user manual. See
+        // section 27.6.2.
+        unsafe {
+            self.dac.cntval.write(|w| w.bits(period as u32 - 1));
+            self.dac.ctrl.write(|w| w.bits(CTRL_PLAYBACK));
+        }
+
+        Playback {
+            transfer,
+            dac: self.dac,
+        }
+    }
+}
+
+impl<State> DAC<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::DAC0 {
+        self.dac
+    }
+}
+
+/// A waveform that is being played back by the DAC
+///
+/// Returned by [`DAC::play`].
+///
+/// [`DAC::play`]: struct.DAC.html#method.play
+pub struct Playback<'dma, B> {
+    transfer: PingPong<'dma, DmaChannel, B, Output>,
+    dac: pac::DAC0,
+}
+
+impl<'dma, B> Playback<'dma, B>
+where
+    B: ReadBuffer<Word = u32>,
+{
+    /// Replace the buffer that has been played back last
+    ///
+    /// Returns the replaced buffer, or `WouldBlock`, if neither buffer has
+    /// been played back since the last call. Needs to be called while the
+    /// other buffer is being played back. Please refer to
+    /// [`PingPong::swap`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `buffer` is empty or longer than 1024 samples.
+    ///
+    /// [`PingPong::swap`]: ../dma/ping_pong/struct.PingPong.html#method.swap
+    pub fn swap(&mut self, buffer: B) -> nb::Result<B, Void> {
+        self.transfer.swap(buffer)
+    }
+
+    /// Indicates whether a buffer can be replaced using [`swap`]
+    ///
+    /// [`swap`]: #method.swap
+    pub fn can_swap(&self) -> bool {
+        self.transfer.can_swap()
+    }
+
+    /// Stop playback and release the resources used
+    ///
+    /// The output keeps the voltage of the last sample that was played back.
+    pub fn stop(
+        self,
+    ) -> (
+        DAC,
+        dma::Channel<DmaChannel, init_state::Enabled<&'dma dma::Handle>>,
+        [B; 2],
+        &'static mut [LinkedDescriptor; 2],
+    ) {
+        self.dac.ctrl.write(|w| unsafe { w.bits(0) });
+
+        let (channel, _, buffers, descriptors) = self.transfer.stop();
+
+        let dac = DAC {
+            dac: self.dac,
+            _state: init_state::Enabled(()),
+        };
+
+        (dac, channel, buffers, descriptors)
+    }
+}
+
+/// The DAC's CR register, as the destination of a DMA transfer
+struct Output(*mut u8);
+
+impl Output {
+    fn new(dac: &pac::DAC0) -> Self {
+        Output(&dac.cr as *const _ as *mut u8)
+    }
+}
+
+impl dma::Dest for Output {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        self.0
+    }
+}
@@ -0,0 +1,588 @@
+//! Logging of ADC samples to flash
+//!
+//! A data logger samples the ADC at a fixed rate, and stores the samples
+//! with a timestamp in non-volatile memory. The LPC8xx parts have too little
+//! RAM to hold more than a few seconds of data, and writing to flash takes
+//! far longer than taking a sample, so the samples are passed through a ring
+//! buffer in RAM:
+//!
+//! 1. An interrupt handler, for example that of an MRT channel, converts the
+//!    ADC channels (see [`ADC::scan`]), and adds a [`Record`] with the
+//!    samples and a timestamp (see [`Uptime::now_ms`]) to a [`sync::Queue`].
+//! 2. The main loop calls [`DataLogger::flush`], which takes the records out
+//!    of the queue, packs them into pages, and writes every full page to a
+//!    [`Storage`].
+//!
+//! Two kinds of storage are provided: [`IapStorage`] uses the
+//! microcontroller's own flash, via the IAP functions (see [`iap`]).
+//! [`SpiFlashStorage`] uses an external 25-series serial NOR flash on a shared
+//! SPI bus (see [`spi::shared`]), which is usually much larger.
+//!
+//! Records never span pages. The remainder of each page is left erased.
+//! [`DataLogger::new`] continues after the last page that has been written,
+//! so logging resumes after a reset without overwriting earlier data.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     data_logger::{DataLogger, IapStorage, Record},
+//!     sync::Queue,
+//! };
+//!
+//! // Two ADC channels, up to 32 records in RAM
+//! static RING: Queue<Record<2>, 32> = Queue::new();
+//!
+//! // Called by the interrupt handler, after converting the ADC channels
+//! fn on_samples(now_ms: u64, a: u16, b: u16) {
+//!     if RING.enqueue(Record::new(now_ms, [a, b])).is_err() {
+//!         // The main loop doesn't flush often enough
+//!     }
+//! }
+//!
+//! // Log to flash pages 192 to 255, the last 4 KiB of flash on the LPC822
+//! let mut logger = DataLogger::new(IapStorage::new(192, 64)).unwrap();
+//!
+//! loop {
+//!     logger.flush(&RING).unwrap();
+//! }
+//! ```
+//!
+//! [`ADC::scan`]: ../adc/struct.ADC.html#method.scan
+//! [`Record`]: struct.Record.html
+//! [`Uptime::now_ms`]: ../uptime/struct.Uptime.html#method.now_ms
+//! [`sync::Queue`]: ../sync/struct.Queue.html
+//! [`DataLogger::flush`]: struct.DataLogger.html#method.flush
+//! [`Storage`]: trait.Storage.html
+//! [`IapStorage`]: struct.IapStorage.html
+//! [`iap`]: ../iap/index.html
+//! [`SpiFlashStorage`]: struct.SpiFlashStorage.html
+//! [`spi::shared`]: ../spi/shared/index.html
+//! [`DataLogger::new`]: struct.DataLogger.html#method.new
+
+use core::fmt;
+
+use embedded_hal::blocking::spi::{Transfer as _, Write as _};
+use void::ResultVoidExt as _;
+
+use crate::{
+    iap::{self, Page, PAGE_SIZE},
+    spi::{
+        self,
+        shared::{ChipSelect, SpiDevice},
+    },
+    sync::Queue,
+};
+
+/// The largest page size a storage can have
+pub const MAX_PAGE_SIZE: usize = 256;
+
+/// The value of erased flash
+const ERASED: u8 = 0xff;
+
+/// A set of samples, with the time they were taken
+///
+/// `N` is the number of samples per record, usually one per ADC channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Record<const N: usize> {
+    /// The time the samples were taken, in milliseconds
+    ///
+    /// Wraps around after about 49 days. `0xffff_ffff` can't be stored, as it
+    /// marks erased flash.
+    pub timestamp_ms: u32,
+
+    /// The samples
+    pub samples: [u16; N],
+}
+
+impl<const N: usize> Record<N> {
+    /// The size of a record in storage, in bytes
+    pub const SIZE: usize = 4 + 2 * N;
+
+    /// Create a record
+    ///
+    /// Only the lower 32 bits of `timestamp_ms` are stored, so the value
+    /// returned by [`Uptime::now_ms`] can be passed directly.
+    ///
+    /// [`Uptime::now_ms`]: ../uptime/struct.Uptime.html#method.now_ms
+    pub fn new(timestamp_ms: u64, samples: [u16; N]) -> Self {
+        Record {
+            timestamp_ms: timestamp_ms as u32,
+            samples,
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.timestamp_ms.to_le_bytes());
+        for (chunk, sample) in bytes[4..].chunks_exact_mut(2).zip(&self.samples)
+        {
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let timestamp_ms =
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        if timestamp_ms == u32::MAX {
+            return None;
+        }
+
+        let mut samples = [0; N];
+        for (sample, chunk) in
+            samples.iter_mut().zip(bytes[4..].chunks_exact(2))
+        {
+            *sample = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+
+        Some(Record {
+            timestamp_ms,
+            samples,
+        })
+    }
+}
+
+/// Writes records to a storage, one page at a time
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct DataLogger<S, const N: usize> {
+    storage: S,
+    page: u32,
+    buffer: [u8; MAX_PAGE_SIZE],
+    len: usize,
+}
+
+impl<S, const N: usize> DataLogger<S, N>
+where
+    S: Storage,
+{
+    /// Create a data logger
+    ///
+    /// Searches the storage for the first page that hasn't been written yet,
+    /// and continues logging there.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the page size of `storage` is larger than
+    /// [`MAX_PAGE_SIZE`], or if a record doesn't fit into a page.
+    ///
+    /// [`MAX_PAGE_SIZE`]: constant.MAX_PAGE_SIZE.html
+    pub fn new(mut storage: S) -> Result<Self, Error> {
+        assert!(storage.page_size() <= MAX_PAGE_SIZE);
+        assert!(Record::<N>::SIZE <= storage.page_size());
+
+        let page = find_end(&mut storage)?;
+
+        Ok(DataLogger {
+            storage,
+            page,
+            buffer: [ERASED; MAX_PAGE_SIZE],
+            len: 0,
+        })
+    }
+
+    /// Take all records out of `ring`, and write every full page
+    ///
+    /// Records that don't fill a page yet are kept in RAM, until more records
+    /// arrive. Call [`sync`] to write them anyway.
+    ///
+    /// Returns the number of records that have been taken out of `ring`.
+    /// Returns [`Error::Full`], if the storage is full. The records that
+    /// didn't fit stay in `ring`.
+    ///
+    /// [`sync`]: #method.sync
+    /// [`Error::Full`]: enum.Error.html#variant.Full
+    pub fn flush<const R: usize>(
+        &mut self,
+        ring: &Queue<Record<N>, R>,
+    ) -> Result<usize, Error> {
+        let mut n = 0;
+
+        loop {
+            if ring.is_empty() {
+                return Ok(n);
+            }
+            if self.page >= self.storage.pages() {
+                return Err(Error::Full);
+            }
+
+            let record = match ring.dequeue() {
+                Some(record) => record,
+                None => return Ok(n),
+            };
+
+            record.encode(&mut self.buffer[self.len..][..Record::<N>::SIZE]);
+            self.len += Record::<N>::SIZE;
+            n += 1;
+
+            // Write the page as soon as the next record doesn't fit anymore.
+            if self.len + Record::<N>::SIZE > self.storage.page_size() {
+                self.write_page()?;
+            }
+        }
+    }
+
+    /// Write the records that are kept in RAM, even if they don't fill a page
+    ///
+    /// The rest of the page stays unused. Call this before the power is
+    /// removed, for example when a brown-out warning is received (see
+    /// [`supervisor`]).
+    ///
+    /// [`supervisor`]: ../supervisor/index.html
+    pub fn sync(&mut self) -> Result<(), Error> {
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        self.write_page()
+    }
+
+    /// Read a record from the storage
+    ///
+    /// Records are numbered by their position in the storage, starting at
+    /// `0`. Returns `None`, if record `index` hasn't been written to the
+    /// storage, or if its position has been left unused by [`sync`]. Records
+    /// that are still kept in RAM can't be read.
+    ///
+    /// [`sync`]: #method.sync
+    pub fn read(&mut self, index: u32) -> Result<Option<Record<N>>, Error> {
+        let per_page = (self.storage.page_size() / Record::<N>::SIZE) as u32;
+        let page = index / per_page;
+        let offset = (index % per_page) as usize * Record::<N>::SIZE;
+
+        if page >= self.page {
+            return Ok(None);
+        }
+
+        let mut bytes = [0; MAX_PAGE_SIZE];
+        let bytes = &mut bytes[..self.storage.page_size()];
+        self.storage.read_page(page, bytes)?;
+
+        Ok(Record::decode(&bytes[offset..][..Record::<N>::SIZE]))
+    }
+
+    /// Return the number of pages that have been written
+    pub fn pages_written(&self) -> u32 {
+        self.page
+    }
+
+    /// Return the storage
+    ///
+    /// Records that are still kept in RAM are lost. Call [`sync`] first to
+    /// keep them.
+    ///
+    /// [`sync`]: #method.sync
+    pub fn free(self) -> S {
+        self.storage
+    }
+
+    fn write_page(&mut self) -> Result<(), Error> {
+        if self.page >= self.storage.pages() {
+            return Err(Error::Full);
+        }
+
+        let page_size = self.storage.page_size();
+        self.storage
+            .write_page(self.page, &self.buffer[..page_size])?;
+
+        self.page += 1;
+        self.buffer = [ERASED; MAX_PAGE_SIZE];
+        self.len = 0;
+
+        Ok(())
+    }
+}
+
+/// Return the first page that hasn't been written
+///
+/// Pages are written in order, so this does a binary search for the first
+/// page that starts with an erased record.
+fn find_end<S: Storage>(storage: &mut S) -> Result<u32, Error> {
+    let mut bytes = [0; MAX_PAGE_SIZE];
+    let bytes = &mut bytes[..storage.page_size()];
+
+    let mut low = 0;
+    let mut high = storage.pages();
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        storage.read_page(middle, bytes)?;
+        if bytes[..4].iter().all(|&b| b == ERASED) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Non-volatile memory that a data logger can write to
+///
+/// Implemented by [`IapStorage`] and [`SpiFlashStorage`]. Can be implemented
+/// for other kinds of memory.
+///
+/// [`IapStorage`]: struct.IapStorage.html
+/// [`SpiFlashStorage`]: struct.SpiFlashStorage.html
+pub trait Storage {
+    /// Return the size of a page, in bytes
+    ///
+    /// Must not be larger than [`MAX_PAGE_SIZE`].
+    ///
+    /// [`MAX_PAGE_SIZE`]: constant.MAX_PAGE_SIZE.html
+    fn page_size(&self) -> usize;
+
+    /// Return the number of pages
+    fn pages(&self) -> u32;
+
+    /// Write a page
+    ///
+    /// Pages are written in order, each one once. `data` is exactly one page
+    /// long. Erasing, if necessary, is up to the implementation.
+    fn write_page(&mut self, page: u32, data: &[u8]) -> Result<(), Error>;
+
+    /// Read a page
+    ///
+    /// `data` is exactly one page long. Erased memory must read as `0xff`.
+    fn read_page(&mut self, page: u32, data: &mut [u8]) -> Result<(), Error>;
+}
+
+/// Pages of the microcontroller's own flash
+///
+/// Pages are 64 bytes large. Please refer to [`iap::write_page`] for the
+/// restrictions that apply.
+///
+/// [`iap::write_page`]: ../iap/fn.write_page.html
+pub struct IapStorage {
+    first_page: u32,
+    pages: u32,
+}
+
+impl IapStorage {
+    /// Use `pages` flash pages, starting at page `first_page`
+    ///
+    /// The pages must not contain any code, and should be excluded from the
+    /// linker script.
+    pub fn new(first_page: u32, pages: u32) -> Self {
+        IapStorage { first_page, pages }
+    }
+
+    /// Erase all pages
+    ///
+    /// Takes a while, as every page is erased separately.
+    pub fn erase(&mut self) -> Result<(), Error> {
+        let erased = Page([ERASED; PAGE_SIZE]);
+        for page in 0..self.pages {
+            iap::write_page(self.first_page + page, &erased)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Storage for IapStorage {
+    fn page_size(&self) -> usize {
+        PAGE_SIZE
+    }
+
+    fn pages(&self) -> u32 {
+        self.pages
+    }
+
+    fn write_page(&mut self, page: u32, data: &[u8]) -> Result<(), Error> {
+        let mut page_data = Page([ERASED; PAGE_SIZE]);
+        page_data.0.copy_from_slice(data);
+
+        iap::write_page(self.first_page + page, &page_data)?;
+
+        Ok(())
+    }
+
+    fn read_page(&mut self, page: u32, data: &mut [u8]) -> Result<(), Error> {
+        data.copy_from_slice(&iap::read_page(self.first_page + page).0);
+        Ok(())
+    }
+}
+
+/// Commands of 25-series serial NOR flash
+const WRITE_ENABLE: u8 = 0x06;
+const READ_STATUS: u8 = 0x05;
+const PAGE_PROGRAM: u8 = 0x02;
+const FAST_READ: u8 = 0x0b;
+const SECTOR_ERASE: u8 = 0x20;
+
+/// The write-in-progress bit of the status register
+const STATUS_BUSY: u8 = 0x01;
+
+/// The size of a program page of 25-series flash
+const SPI_PAGE_SIZE: usize = 256;
+
+/// The number of program pages per erase sector
+const SPI_PAGES_PER_SECTOR: u32 = 16;
+
+/// Pages of a 25-series serial NOR flash
+///
+/// Pages are 256 bytes large. Every 4 KiB sector is erased, before its first
+/// page is written. The flash is accessed via a device on a shared SPI bus,
+/// whose settings need to match the flash (see [`spi::shared`]).
+///
+/// [`spi::shared`]: ../spi/shared/index.html
+pub struct SpiFlashStorage<'bus, I, Pins, Cs> {
+    device: SpiDevice<'bus, I, Pins, Cs>,
+    first_sector: u32,
+    sectors: u32,
+}
+
+impl<'bus, I, Pins, Cs> SpiFlashStorage<'bus, I, Pins, Cs>
+where
+    I: spi::Instance,
+    Cs: ChipSelect<I>,
+{
+    /// Use `sectors` 4 KiB sectors, starting at sector `first_sector`
+    pub fn new(
+        device: SpiDevice<'bus, I, Pins, Cs>,
+        first_sector: u32,
+        sectors: u32,
+    ) -> Self {
+        SpiFlashStorage {
+            device,
+            first_sector,
+            sectors,
+        }
+    }
+
+    /// Erase all sectors
+    pub fn erase(&mut self) -> Result<(), Error> {
+        for sector in 0..self.sectors {
+            self.erase_sector(sector)?;
+        }
+
+        Ok(())
+    }
+
+    /// Release the SPI device
+    pub fn free(self) -> SpiDevice<'bus, I, Pins, Cs> {
+        self.device
+    }
+
+    fn address(&self, page: u32) -> u32 {
+        (self.first_sector * SPI_PAGES_PER_SECTOR + page) * SPI_PAGE_SIZE as u32
+    }
+
+    fn erase_sector(&mut self, sector: u32) -> Result<(), Error> {
+        let address = self.address(sector * SPI_PAGES_PER_SECTOR);
+
+        self.write_enable()?;
+        self.device.transaction(|spi| {
+            spi.write(&command(SECTOR_ERASE, address)).void_unwrap();
+        })?;
+        self.wait_done()
+    }
+
+    fn write_enable(&mut self) -> Result<(), Error> {
+        self.device
+            .transaction(|spi| spi.write(&[WRITE_ENABLE]).void_unwrap())?;
+        Ok(())
+    }
+
+    /// Wait until an erase or program command has finished
+    fn wait_done(&mut self) -> Result<(), Error> {
+        loop {
+            let mut status = [0];
+            self.device.transaction(|spi| {
+                spi.write(&[READ_STATUS]).void_unwrap();
+                spi.transfer(&mut status).void_unwrap();
+            })?;
+
+            if status[0] & STATUS_BUSY == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<'bus, I, Pins, Cs> Storage for SpiFlashStorage<'bus, I, Pins, Cs>
+where
+    I: spi::Instance,
+    Cs: ChipSelect<I>,
+{
+    fn page_size(&self) -> usize {
+        SPI_PAGE_SIZE
+    }
+
+    fn pages(&self) -> u32 {
+        self.sectors * SPI_PAGES_PER_SECTOR
+    }
+
+    fn write_page(&mut self, page: u32, data: &[u8]) -> Result<(), Error> {
+        if page % SPI_PAGES_PER_SECTOR == 0 {
+            self.erase_sector(page / SPI_PAGES_PER_SECTOR)?;
+        }
+
+        let address = self.address(page);
+
+        self.write_enable()?;
+        self.device.transaction(|spi| {
+            spi.write(&command(PAGE_PROGRAM, address)).void_unwrap();
+            spi.write(data).void_unwrap();
+        })?;
+        self.wait_done()
+    }
+
+    fn read_page(&mut self, page: u32, data: &mut [u8]) -> Result<(), Error> {
+        let address = self.address(page);
+
+        self.device.transaction(|spi| {
+            spi.write(&command(FAST_READ, address)).void_unwrap();
+            spi.send_dummy_bytes(1);
+            spi.transfer(data).void_unwrap();
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Return a command, followed by a 24-bit address
+fn command(command: u8, address: u32) -> [u8; 4] {
+    let [_, a2, a1, a0] = address.to_be_bytes();
+    [command, a2, a1, a0]
+}
+
+/// An error that can occur while logging data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// All pages of the storage have been written
+    Full,
+
+    /// Writing to the microcontroller's flash failed
+    Iap(iap::Error),
+
+    /// The SPI bus was in use
+    Spi(spi::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Full => f.write_str("data log full"),
+            Error::Iap(error) => write!(f, "data log: {}", error),
+            Error::Spi(error) => write!(f, "data log: {}", error),
+        }
+    }
+}
+
+impl From<iap::Error> for Error {
+    fn from(error: iap::Error) -> Self {
+        Error::Iap(error)
+    }
+}
+
+impl From<spi::Error> for Error {
+    fn from(error: spi::Error) -> Self {
+        Error::Spi(error)
+    }
+}
@@ -0,0 +1,220 @@
+//! Debouncing of digital inputs, using an MRT channel
+//!
+//! Mechanical switches and buttons bounce, which means their contacts open and
+//! close several times, before settling. [`Debounced`] wraps an input pin and
+//! samples it every time its MRT channel expires. The level only changes, once
+//! the pin has read the new level for several consecutive samples. Changes of
+//! the debounced level are delivered as [`Event`]s.
+//!
+//! `Debounced` implements [`InputPin`] itself, returning the debounced level,
+//! so it can be passed to code that expects a plain input pin.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     debounce::{Active, Debounced, Event},
+//!     prelude::*,
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//!
+//! let button = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_input();
+//! let mut button = Debounced::new(button, Active::Low, timer, 5.millis());
+//!
+//! loop {
+//!     // Can also be called from the MRT0 interrupt handler
+//!     button.poll();
+//!
+//!     while let Some(event) = button.next_event() {
+//!         match event {
+//!             Event::Pressed => {}
+//!             Event::Released => {}
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`Debounced`]: struct.Debounced.html
+//! [`Event`]: enum.Event.html
+//! [`InputPin`]: https://docs.rs/embedded-hal/0.2.7/embedded_hal/digital/v2/trait.InputPin.html
+
+use embedded_hal::{digital::v2::InputPin, timer::CountDown as _};
+use void::{ResultVoidExt as _, Void};
+
+use crate::{mrt::MrtChannel, sync::Queue};
+
+/// The number of entries in the event queue
+pub const QUEUE_LEN: usize = 4;
+
+/// The number of consecutive samples a pin must be stable, to change its level
+const DEBOUNCE_SAMPLES: u8 = 4;
+
+/// A debounced input pin
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Debounced<P> {
+    pin: P,
+    active: Active,
+    timer: MrtChannel,
+
+    counter: u8,
+    high: bool,
+
+    events: Queue<Event, QUEUE_LEN>,
+}
+
+impl<P> Debounced<P>
+where
+    P: InputPin<Error = Void>,
+{
+    /// Wrap an input pin and start sampling it
+    ///
+    /// `active` is the level of the pin, while the button or switch is
+    /// pressed. The pin is sampled once per `period`, and its level changes,
+    /// once it has been stable for 4 consecutive samples. The initial level is
+    /// the level of the pin when this method is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `period` is longer than `timer` supports (see
+    /// [`MrtChannel::max_us`]).
+    ///
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new(
+        pin: P,
+        active: Active,
+        mut timer: MrtChannel,
+        period: fugit::MicrosDurationU32,
+    ) -> Self {
+        let high = pin.is_high().void_unwrap();
+
+        if let Err(error) = timer.start_duration(period) {
+            panic!("{}", error);
+        }
+
+        Debounced {
+            pin,
+            active,
+            timer,
+
+            counter: if high { DEBOUNCE_SAMPLES } else { 0 },
+            high,
+
+            events: Queue::new(),
+        }
+    }
+
+    /// Sample the pin, if the timer has expired
+    ///
+    /// Call this method from the main loop, or from the MRT0 interrupt
+    /// handler, after enabling the interrupt using
+    /// [`MrtChannel::enable_interrupt`]. It needs to be called at least once
+    /// per period, or the sampling will slow down.
+    ///
+    /// [`MrtChannel::enable_interrupt`]: ../mrt/struct.MrtChannel.html#method.enable_interrupt
+    pub fn poll(&mut self) {
+        if self.timer.wait().is_err() {
+            return;
+        }
+
+        // The counter integrates the samples. The level only changes once it
+        // has reached either end of its range.
+        let high = self.pin.is_high().void_unwrap();
+        if high && self.counter < DEBOUNCE_SAMPLES {
+            self.counter += 1;
+        }
+        if !high && self.counter > 0 {
+            self.counter -= 1;
+        }
+
+        match (self.high, self.counter) {
+            (false, DEBOUNCE_SAMPLES) => self.high = true,
+            (true, 0) => self.high = false,
+            _ => return,
+        }
+
+        let event = if self.is_pressed() {
+            Event::Pressed
+        } else {
+            Event::Released
+        };
+
+        // If the queue is full, the event is dropped. See `next_event`.
+        let _ = self.events.enqueue(event);
+    }
+
+    /// Indicates whether the button or switch is pressed, after debouncing
+    pub fn is_pressed(&self) -> bool {
+        match self.active {
+            Active::Low => !self.high,
+            Active::High => self.high,
+        }
+    }
+
+    /// Return the next event from the queue
+    ///
+    /// Returns `None`, if the queue is empty. If the queue runs full, new
+    /// events are dropped, but [`Debounced::is_pressed`] still reflects the
+    /// current state.
+    ///
+    /// [`Debounced::is_pressed`]: #method.is_pressed
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.dequeue()
+    }
+
+    /// Stop sampling and release the pin and the MRT channel
+    pub fn free(self) -> (P, MrtChannel) {
+        (self.pin, self.timer)
+    }
+}
+
+impl<P> InputPin for Debounced<P>
+where
+    P: InputPin<Error = Void>,
+{
+    type Error = Void;
+
+    /// Indicates whether the debounced level is high
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.high)
+    }
+
+    /// Indicates whether the debounced level is low
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.high)
+    }
+}
+
+/// The level of a pin, while its button or switch is pressed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Active {
+    /// The pin is low, while pressed
+    ///
+    /// This is the case for a button that connects the pin to ground, with a
+    /// pull-up resistor.
+    Low,
+
+    /// The pin is high, while pressed
+    High,
+}
+
+/// A change of a pin's debounced state
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// The button or switch has been pressed
+    Pressed,
+
+    /// The button or switch has been released
+    Released,
+}
@@ -0,0 +1,176 @@
+//! Snapshots of peripheral registers, for debugging
+//!
+//! When diagnosing a unit in the field, it often helps to know how the
+//! hardware was configured at the time a problem occurred. [`snapshot`] reads
+//! the most relevant registers into a [`Snapshot`], which can be printed via
+//! its `Debug` implementation (over the debug UART, for example), or logged
+//! via defmt, if the `defmt` feature is enabled.
+//!
+//! Taking a snapshot only reads registers that have no side effects when being
+//! read. It doesn't require ownership of any peripheral, so it can be used
+//! from anywhere, including a panic or fault handler. Registers of peripherals
+//! whose clock is disabled are not read.
+//!
+//! This module is only available, if the `debug_dump` feature is enabled.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::debug_dump;
+//!
+//! let snapshot = debug_dump::snapshot();
+//!
+//! if let Some(swm) = snapshot.swm {
+//!     let pinenable0 = swm.pinenable[0];
+//! }
+//! ```
+//!
+//! [`snapshot`]: fn.snapshot.html
+//! [`Snapshot`]: struct.Snapshot.html
+
+use crate::{gpio, pac, swm, syscon, usart};
+
+/// The number of GPIO ports
+#[cfg(feature = "82x")]
+pub const GPIO_PORTS: usize = 1;
+/// The number of GPIO ports
+#[cfg(feature = "845")]
+pub const GPIO_PORTS: usize = 2;
+
+/// The number of USART peripherals
+#[cfg(feature = "82x")]
+pub const USARTS: usize = 3;
+/// The number of USART peripherals
+#[cfg(feature = "845")]
+pub const USARTS: usize = 5;
+
+/// Take a snapshot of the peripheral registers
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub fn snapshot() -> Snapshot {
+    // Sound, as we only read registers that have no read side effects, and
+    // only if the respective peripheral clock is enabled.
+    let syscon = syscon::snapshot(unsafe { &*pac::SYSCON::ptr() });
+    let clocks = syscon.clocks;
+
+    let swm = clocks
+        .swm
+        .then(|| swm::snapshot(unsafe { &*pac::SWM0::ptr() }));
+
+    let gpio = unsafe { &*pac::GPIO::ptr() };
+    #[cfg(feature = "82x")]
+    let gpio = [clocks.gpio.then(|| gpio::snapshot(gpio, 0))];
+    #[cfg(feature = "845")]
+    let gpio = [
+        clocks.gpio0.then(|| gpio::snapshot(gpio, 0)),
+        clocks.gpio1.then(|| gpio::snapshot(gpio, 1)),
+    ];
+
+    let usart = [
+        clocks
+            .usart0
+            .then(|| usart::snapshot(unsafe { &*pac::USART0::ptr() })),
+        clocks
+            .usart1
+            .then(|| usart::snapshot(unsafe { &*pac::USART1::ptr() })),
+        clocks
+            .usart2
+            .then(|| usart::snapshot(unsafe { &*pac::USART2::ptr() })),
+        #[cfg(feature = "845")]
+        clocks
+            .usart3
+            .then(|| usart::snapshot(unsafe { &*pac::USART3::ptr() })),
+        #[cfg(feature = "845")]
+        clocks
+            .usart4
+            .then(|| usart::snapshot(unsafe { &*pac::USART4::ptr() })),
+    ];
+
+    Snapshot {
+        syscon,
+        swm,
+        gpio,
+        usart,
+    }
+}
+
+/// A snapshot of the peripheral registers
+///
+/// Returned by [`snapshot`]. Peripherals whose clock was disabled when the
+/// snapshot was taken are `None`.
+///
+/// [`snapshot`]: fn.snapshot.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Snapshot {
+    /// The SYSCON clock configuration
+    pub syscon: Syscon,
+
+    /// The switch matrix assignments
+    pub swm: Option<Swm>,
+
+    /// The GPIO ports
+    pub gpio: [Option<GpioPort>; GPIO_PORTS],
+
+    /// The USART peripherals, starting with USART0
+    pub usart: [Option<Usart>; USARTS],
+}
+
+/// The clock configuration registers of SYSCON
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Syscon {
+    /// The decoded contents of SYSAHBCLKCTRL (SYSAHBCLKCTRL0 on the LPC845)
+    pub clocks: syscon::ClockReport,
+
+    /// SYSAHBCLKCTRL (SYSAHBCLKCTRL0 on the LPC845)
+    pub sysahbclkctrl0: u32,
+
+    /// SYSAHBCLKCTRL1
+    #[cfg(feature = "845")]
+    pub sysahbclkctrl1: u32,
+
+    /// SYSAHBCLKDIV
+    pub sysahbclkdiv: u32,
+
+    /// MAINCLKSEL
+    pub mainclksel: u32,
+
+    /// PDRUNCFG
+    pub pdruncfg: u32,
+}
+
+/// The assignment registers of the switch matrix
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Swm {
+    /// PINASSIGN0 and following, for the movable functions
+    pub pinassign: [u32; swm::PINASSIGN_REGISTERS],
+
+    /// PINENABLE0 and following, for the fixed functions
+    pub pinenable: [u32; swm::PINENABLE_REGISTERS],
+}
+
+/// The registers of a GPIO port
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GpioPort {
+    /// DIR, the direction of the pins (`1` means output)
+    pub dir: u32,
+
+    /// PIN, the current state of the pins
+    pub pin: u32,
+}
+
+/// The configuration and status registers of a USART
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Usart {
+    /// CFG
+    pub cfg: u32,
+
+    /// STAT
+    pub stat: u32,
+}
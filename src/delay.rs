@@ -19,11 +19,13 @@
 
 use cortex_m::peripheral::syst::SystClkSource;
 
-use crate::pac::SYST;
-use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use crate::{pac::SYST, syscon};
+use embedded_hal::{
+    blocking::delay::{DelayMs, DelayUs},
+    watchdog::Watchdog,
+};
 
 const SYSTICK_RANGE: u32 = 0x0100_0000;
-const SYSTEM_CLOCK: u32 = 12_000_000;
 
 /// System timer (SysTick) as a delay provider
 #[derive(Clone)]
@@ -33,9 +35,18 @@ pub struct Delay {
 
 impl Delay {
     /// Configures the system timer (SysTick) as a delay provider
+    ///
+    /// The delays are based on [`syscon::system_clock_hz`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the system clock is below 1 MHz.
+    ///
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
     pub fn new(mut syst: SYST) -> Self {
-        assert!(SYSTEM_CLOCK >= 1_000_000);
-        let scale = SYSTEM_CLOCK / 1_000_000;
+        let hz = syscon::system_clock_hz();
+        assert!(hz >= 1_000_000);
+        let scale = hz / 1_000_000;
         syst.set_clock_source(SystClkSource::Core);
 
         syst.set_reload(SYSTICK_RANGE - 1);
@@ -46,6 +57,68 @@ impl Delay {
         // As access to the count register is possible without a reference to the systick, we can
         // safely clone the enabled instance.
     }
+
+    /// Delay for the given duration
+    ///
+    /// This is a convenience wrapper around [`DelayUs`] that takes a typed
+    /// duration, like `5.millis()`.
+    ///
+    /// [`DelayUs`]: #impl-DelayUs%3Cu32%3E
+    pub fn delay(&mut self, duration: fugit::MicrosDurationU32) {
+        self.delay_us(duration.to_micros());
+    }
+
+    /// Update the system clock frequency the delay is based on
+    ///
+    /// [`Delay::new`] reads the system clock frequency once. If you change the
+    /// system clock frequency (see [`syscon::Handle::set_system_clock_divider`]),
+    /// you need to call this method to keep the delays accurate, for example
+    /// with the new value of [`syscon::system_clock_hz`]. Clones of this
+    /// instance are not affected.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `hz` is below 1 MHz.
+    ///
+    /// [`Delay::new`]: #method.new
+    /// [`syscon::Handle::set_system_clock_divider`]: ../syscon/struct.Handle.html#method.set_system_clock_divider
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn set_system_clock(&mut self, hz: u32) {
+        assert!(hz >= 1_000_000);
+        self.scale = hz / 1_000_000;
+    }
+
+    /// Delay for `ms` milliseconds, while feeding a watchdog
+    ///
+    /// Long delays can easily exceed the timeout of a tightly configured
+    /// watchdog. This method splits the delay into chunks of `interval_ms`
+    /// milliseconds and feeds `watchdog` after each of them (and once before
+    /// starting).
+    ///
+    /// If the watchdog is used in windowed mode, make sure `interval_ms` is
+    /// long enough to not feed it too early.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `interval_ms` is zero.
+    pub fn delay_ms_feeding<W>(
+        &mut self,
+        mut ms: u32,
+        interval_ms: u32,
+        watchdog: &mut W,
+    ) where
+        W: Watchdog,
+    {
+        assert!(interval_ms != 0);
+
+        watchdog.feed();
+        while ms != 0 {
+            let current_ms = if ms <= interval_ms { ms } else { interval_ms };
+            self.delay_ms(current_ms);
+            watchdog.feed();
+            ms -= current_ms;
+        }
+    }
 }
 
 impl DelayMs<u32> for Delay {
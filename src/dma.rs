@@ -2,10 +2,20 @@
 //!
 //! The DMA controller is described in the user manual, chapter 12.
 
+use core::fmt;
+use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ptr;
+use core::slice;
 use core::sync::atomic::{compiler_fence, Ordering};
 
+use embedded_dma::{ReadBuffer, WriteBuffer};
 use nb;
+use void::Void;
+
+#[cfg(feature = "bbqueue")]
+pub mod bbqueue;
+pub mod ping_pong;
+pub mod queue;
 
 use crate::{
     init_state,
@@ -13,7 +23,8 @@ use crate::{
         self,
         dma0::{
             channel::{CFG, XFERCFG},
-            ACTIVE0, ENABLESET0, SETTRIG0,
+            ABORT0, ACTIVE0, BUSY0, ENABLECLR0, ENABLESET0, INTA0, INTB0,
+            SETTRIG0,
         },
     },
     reg_proxy::{Reg, RegProxy},
@@ -201,6 +212,7 @@ where
     ty: T,
     _state: S,
     descriptor: &'static mut ChannelDescriptor,
+    priority: Priority,
 
     // This channel's dedicated registers.
     cfg: RegProxy<T::Cfg>,
@@ -208,11 +220,36 @@ where
 
     // Shared registers. We restrict our access to the one bit that is dedicated
     // to this channel, so sharing those with other channels should be safe.
+    abort0: RegProxy<ABORT0>,
     active0: RegProxy<ACTIVE0>,
+    busy0: RegProxy<BUSY0>,
+    enableclr0: RegProxy<ENABLECLR0>,
     enableset0: RegProxy<ENABLESET0>,
     settrig0: RegProxy<SETTRIG0>,
 }
 
+impl<T, S> Channel<T, S>
+where
+    T: ChannelTrait,
+{
+    /// Set the priority of this channel
+    ///
+    /// If multiple channels have pending requests, the DMA controller services
+    /// the one with the highest priority first. By default, all channels have
+    /// the highest priority, which means they are serviced in order of their
+    /// channel number. See user manual, section 12.5.1.
+    ///
+    /// Please refer to [`Priority`] for a set of presets that should work for
+    /// most applications.
+    ///
+    /// The priority takes effect when the next transfer is started.
+    ///
+    /// [`Priority`]: struct.Priority.html
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+}
+
 impl<T> Channel<T, init_state::Disabled>
 where
     T: ChannelTrait,
@@ -226,11 +263,15 @@ where
             ty: self.ty,
             _state: init_state::Enabled(dma),
             descriptor: self.descriptor,
+            priority: self.priority,
 
             cfg: self.cfg,
             xfercfg: self.xfercfg,
 
+            abort0: self.abort0,
             active0: self.active0,
+            busy0: self.busy0,
+            enableclr0: self.enableclr0,
             enableset0: self.enableset0,
             settrig0: self.settrig0,
         }
@@ -243,22 +284,153 @@ where
 {
     /// Starts a DMA transfer
     ///
+    /// Transfers `source` from memory into the destination, which is
+    /// typically a peripheral.
+    ///
+    /// `source` can be any buffer that implements [`ReadBuffer`] from the
+    /// [embedded-dma] crate, like `&'static mut [u8]`, or buffers from
+    /// `heapless` or other crates.
+    ///
+    /// This method is only available, if the `panics` feature is enabled,
+    /// which it is by default. Please refer to [`try_start_transfer`] for a
+    /// variant that returns an error instead of panicking.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the length of `source` is larger than 1024.
+    ///
+    /// [`ReadBuffer`]: https://docs.rs/embedded-dma/0.1.*/embedded_dma/trait.ReadBuffer.html
+    /// [embedded-dma]: https://crates.io/crates/embedded-dma
+    /// [`try_start_transfer`]: #method.try_start_transfer
+    #[cfg(feature = "panics")]
+    pub fn start_transfer<B, D>(
+        self,
+        source: B,
+        dest: D,
+    ) -> Transfer<'dma, T, B, D>
+    where
+        B: ReadBuffer<Word = u8>,
+        D: Dest,
+    {
+        match self.try_start_transfer(source, dest) {
+            Ok(transfer) => transfer,
+            Err((error, _, _, _)) => panic!("{}", error),
+        }
+    }
+
+    /// Starts a DMA transfer, if the source is not too long
+    ///
+    /// Like [`start_transfer`], but returns [`Error::TooLong`] along with all
+    /// arguments, instead of panicking, if the length of `source` is larger
+    /// than 1024.
+    ///
+    /// [`start_transfer`]: #method.start_transfer
+    /// [`Error::TooLong`]: enum.Error.html#variant.TooLong
+    pub fn try_start_transfer<B, D>(
+        self,
+        source: B,
+        dest: D,
+    ) -> Result<Transfer<'dma, T, B, D>, (Error, Self, B, D)>
+    where
+        B: ReadBuffer<Word = u8>,
+        D: Dest,
+    {
+        // Safe, as we're only reading the length.
+        let (_, source_len) = unsafe { source.read_buffer() };
+        if source_len > 1024 {
+            return Err((Error::TooLong, self, source, dest));
+        }
+
+        Ok(self.start_transfer_unchecked(source, dest))
+    }
+
+    /// Starts a DMA transfer, without checking the length of the source
+    ///
+    /// Sources that are longer than 1024 bytes are truncated.
+    pub(crate) fn start_transfer_unchecked<B, D>(
+        self,
+        source: B,
+        mut dest: D,
+    ) -> Transfer<'dma, T, B, D>
+    where
+        B: ReadBuffer<Word = u8>,
+        D: Dest,
+    {
+        compiler_fence(Ordering::SeqCst);
+
+        // Safe, as we're taking ownership of the buffer, which guarantees that
+        // it stays valid for the duration of the transfer.
+        let (source_ptr, source_len) = unsafe { source.read_buffer() };
+
+        // We need to substract 1 from the length below. If the source is empty,
+        // return early to prevent underflow.
+        if source_len == 0 {
+            return Transfer {
+                channel: self,
+                source,
+                dest,
+            };
+        }
+
+        let source_end = unsafe { source_ptr.add(source_len - 1) };
+
+        self.configure(source_len, Increment::Source);
+
+        // Configure channel descriptor
+        // See user manual, sections 12.5.2 and 12.5.3.
+        self.descriptor.source_end = source_end;
+        self.descriptor.dest_end = dest.end_addr();
+
+        self.start();
+
+        Transfer {
+            channel: self,
+            source,
+            dest,
+        }
+    }
+
+    /// Starts a DMA transfer that is paced by a hardware trigger
+    ///
+    /// Like [`start_transfer`], but instead of transferring the data as fast
+    /// as the destination allows, a single word is transferred on every rising
+    /// edge of the channel's hardware trigger. The trigger input is selected
+    /// in the channel's DMA_ITRIG_INMUX register, which is part of INPUTMUX.
+    /// See user manual, section 12.6.16.
+    ///
+    /// The words of `source` can be bytes, half-words, or words, which
+    /// determines the width of the writes to the destination. This allows
+    /// feeding registers that don't support byte access.
+    ///
+    /// This can be used to generate a waveform with a precise timing, as
+    /// done by the [`waveform`] and [`pwm_audio`] modules.
+    ///
     /// # Limitations
     ///
-    /// The length of `source` must be 1024 or less.
-    pub fn start_transfer<D>(
+    /// The length of `source` must be 1024 words or less.
+    ///
+    /// [`start_transfer`]: #method.start_transfer
+    /// [`waveform`]: ../waveform/index.html
+    /// [`pwm_audio`]: ../pwm_audio/index.html
+    pub fn start_paced_transfer<B, D>(
         self,
-        source: &'static mut [u8],
+        source: B,
         mut dest: D,
-    ) -> Transfer<'dma, T, D>
+    ) -> Transfer<'dma, T, B, D>
     where
+        B: ReadBuffer,
+        B::Word: Word,
         D: Dest,
     {
         compiler_fence(Ordering::SeqCst);
 
+        // Safe, as we're taking ownership of the buffer, which guarantees that
+        // it stays valid for the duration of the transfer.
+        let (source_ptr, source_len) = unsafe { source.read_buffer() };
+
         // We need to substract 1 from the length below. If the source is empty,
         // return early to prevent underflow.
-        if source.is_empty() {
+        if source_len == 0 {
             return Transfer {
                 channel: self,
                 source,
@@ -266,13 +438,274 @@ where
             };
         }
 
-        // Configure channel 1 (has request input USART0_TX_DMA)
+        let source_end = unsafe { source_ptr.add(source_len - 1) };
+
+        self.configure(source_len, Increment::Source);
+
+        // `configure` sets up byte transfers. Adjust the width to the word
+        // size of the buffer. See user manual, section 12.6.18.
+        self.xfercfg.modify(|r, w| unsafe {
+            w.bits(
+                r.bits() & !XFERCFG_WIDTH_MASK
+                    | (B::Word::WIDTH as u32) << XFERCFG_WIDTH_SHIFT,
+            )
+        });
+
+        // Transfer a burst of a single word on each rising edge of the
+        // hardware trigger, instead of on peripheral requests. See user
+        // manual, section 12.6.16.
+        self.cfg.write(|w| {
+            w.periphreqen().disabled();
+            w.hwtrigen().enabled();
+            w.trigpol().active_high_rising();
+            w.trigtype().edge();
+            w.trigburst().burst();
+            unsafe {
+                w.burstpower().bits(0);
+                w.chpriority().bits(self.priority.0)
+            }
+        });
+
+        self.descriptor.source_end = source_end as *const u8;
+        self.descriptor.dest_end = dest.end_addr();
+
+        // Enable the channel, but don't trigger it. That is left to the
+        // hardware trigger.
+        self.enableset0.write(|w| unsafe { w.ena().bits(T::FLAG) });
+
+        Transfer {
+            channel: self,
+            source,
+            dest,
+        }
+    }
+
+    /// Starts a DMA transfer from a peripheral into memory
+    ///
+    /// This is the counterpart of [`start_transfer`]. It transfers data from
+    /// the source, which is typically a peripheral, into `dest`, until `dest`
+    /// has been filled.
+    ///
+    /// `dest` can be any buffer that implements [`WriteBuffer`] from the
+    /// [embedded-dma] crate.
+    ///
+    /// # Limitations
+    ///
+    /// The length of `dest` must be 1024 or less.
+    ///
+    /// [`start_transfer`]: #method.start_transfer
+    /// [`WriteBuffer`]: https://docs.rs/embedded-dma/0.1.*/embedded_dma/trait.WriteBuffer.html
+    /// [embedded-dma]: https://crates.io/crates/embedded-dma
+    pub fn start_read<S, B>(
+        self,
+        mut source: S,
+        mut dest: B,
+    ) -> ReadTransfer<'dma, T, S, B>
+    where
+        S: Source,
+        B: WriteBuffer<Word = u8>,
+    {
+        compiler_fence(Ordering::SeqCst);
+
+        // Safe, as we're taking ownership of the buffer, which guarantees that
+        // it stays valid for the duration of the transfer.
+        let (dest_ptr, dest_len) = unsafe { dest.write_buffer() };
+
+        // We need to substract 1 from the length below. If the destination is
+        // empty, return early to prevent underflow.
+        if dest_len == 0 {
+            return ReadTransfer {
+                channel: self,
+                source,
+                dest,
+                len: 0,
+            };
+        }
+
+        let dest_end = unsafe { dest_ptr.add(dest_len - 1) };
+
+        self.configure(dest_len, Increment::Dest);
+
+        // Configure channel descriptor
+        // See user manual, sections 12.5.2 and 12.5.3.
+        self.descriptor.source_end = source.end_addr();
+        self.descriptor.dest_end = dest_end;
+
+        self.start();
+
+        ReadTransfer {
+            channel: self,
+            source,
+            dest,
+            len: dest_len,
+        }
+    }
+
+    /// Starts a DMA transfer from memory to memory
+    ///
+    /// Copies `source` to the beginning of `dest`, as fast as the DMA
+    /// controller allows. Both buffers can be any buffer that implements
+    /// [`ReadBuffer`] or [`WriteBuffer`] respectively, from the [embedded-dma]
+    /// crate.
+    ///
+    /// `dest` is wrapped in a [`Memory`], which can be unwrapped using
+    /// [`Memory::free`], after [`Transfer::wait`] has returned it.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `dest` is shorter than `source`.
+    ///
+    /// # Limitations
+    ///
+    /// The length of `source` must be 1024 or less.
+    ///
+    /// [`ReadBuffer`]: https://docs.rs/embedded-dma/0.1.*/embedded_dma/trait.ReadBuffer.html
+    /// [`WriteBuffer`]: https://docs.rs/embedded-dma/0.1.*/embedded_dma/trait.WriteBuffer.html
+    /// [embedded-dma]: https://crates.io/crates/embedded-dma
+    /// [`Memory`]: struct.Memory.html
+    /// [`Memory::free`]: struct.Memory.html#method.free
+    /// [`Transfer::wait`]: struct.Transfer.html#method.wait
+    pub fn start_copy<B, W>(
+        self,
+        source: B,
+        mut dest: W,
+    ) -> Transfer<'dma, T, B, Memory<W>>
+    where
+        B: ReadBuffer<Word = u8>,
+        W: WriteBuffer<Word = u8>,
+    {
+        compiler_fence(Ordering::SeqCst);
+
+        // Safe, as we're taking ownership of the buffers, which guarantees
+        // that they stay valid for the duration of the transfer.
+        let (source_ptr, source_len) = unsafe { source.read_buffer() };
+        let (dest_ptr, dest_len) = unsafe { dest.write_buffer() };
+
+        assert!(dest_len >= source_len);
+
+        // We need to substract 1 from the length below. If the source is empty,
+        // return early to prevent underflow.
+        if source_len == 0 {
+            return Transfer {
+                channel: self,
+                source,
+                dest: Memory {
+                    buffer: dest,
+                    end: dest_ptr,
+                },
+            };
+        }
+
+        let source_end = unsafe { source_ptr.add(source_len - 1) };
+        let dest_end = unsafe { dest_ptr.add(source_len - 1) };
+
+        self.configure(source_len, Increment::Both);
+
+        // There's no peripheral to request the transfer. Triggering the
+        // channel once transfers everything. See user manual, section 12.5.1.
+        self.cfg.write(|w| {
+            w.periphreqen().disabled();
+            w.hwtrigen().disabled();
+            w.trigburst().single();
+            unsafe { w.chpriority().bits(self.priority.0) }
+        });
+
+        self.descriptor.source_end = source_end;
+        self.descriptor.dest_end = dest_end;
+
+        self.start();
+
+        Transfer {
+            channel: self,
+            source,
+            dest: Memory {
+                buffer: dest,
+                end: dest_end,
+            },
+        }
+    }
+
+    /// Starts a scatter-gather DMA transfer
+    ///
+    /// Transfers all `segments` into the destination, one after the other, as
+    /// if they were a single buffer. This can be used to send a frame that is
+    /// assembled from multiple parts (like a header, a payload, and a
+    /// checksum), without copying those parts into a single buffer first.
+    ///
+    /// The first segment is configured using the channel's own descriptor.
+    /// Each additional segment needs a [`LinkedDescriptor`], which are taken
+    /// from `descriptors`. The interrupt flag A of the channel is set only
+    /// once, after the last segment has been transferred.
+    ///
+    /// The descriptors are kept by the returned [`Transfer`] and returned by
+    /// [`Transfer::wait`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `segments` is empty, if any segment is empty or longer than
+    /// 1024 bytes, or if `descriptors` has fewer than `segments.len() - 1`
+    /// elements.
+    ///
+    /// [`LinkedDescriptor`]: struct.LinkedDescriptor.html
+    /// [`Transfer`]: struct.Transfer.html
+    /// [`Transfer::wait`]: struct.Transfer.html#method.wait
+    pub fn start_chain<D>(
+        self,
+        segments: &[&'static [u8]],
+        descriptors: &'static mut [LinkedDescriptor],
+        mut dest: D,
+    ) -> Transfer<'dma, T, &'static mut [LinkedDescriptor], D>
+    where
+        D: Dest,
+    {
+        assert!(!segments.is_empty());
+        assert!(descriptors.len() >= segments.len() - 1);
+
+        let dest_end = dest.end_addr();
+
+        // Build the chain from the back, so every descriptor can point to the
+        // next one. See user manual, section 12.5.2.
+        let mut next: *const ChannelDescriptor = ptr::null();
+        for (i, segment) in segments.iter().enumerate().skip(1).rev() {
+            let descriptor = &mut descriptors[i - 1].0;
+
+            descriptor.config = xfercfg_bits(segment.len(), !next.is_null());
+            descriptor.source_end = segment_end(segment);
+            descriptor.dest_end = dest_end;
+            descriptor.next_desc = next;
+
+            next = descriptor as *const _;
+        }
+
+        let first = segments[0];
+        let config = xfercfg_bits(first.len(), !next.is_null());
+
+        self.configure(first.len(), Increment::Source);
+        self.xfercfg.write(|w| unsafe { w.bits(config) });
+
+        self.descriptor.source_end = segment_end(first);
+        self.descriptor.dest_end = dest_end;
+        self.descriptor.next_desc = next;
+
+        compiler_fence(Ordering::SeqCst);
+
+        self.start();
+
+        Transfer {
+            channel: self,
+            source: descriptors,
+            dest,
+        }
+    }
+
+    fn configure(&self, len: usize, increment: Increment) {
+        // Configure channel
         // See user manual, section 12.6.16.
         self.cfg.write(|w| {
             w.periphreqen().enabled();
             w.hwtrigen().disabled();
             w.trigburst().single();
-            unsafe { w.chpriority().bits(0) }
+            unsafe { w.chpriority().bits(self.priority.0) }
         });
 
         // Set channel transfer configuration
@@ -285,33 +718,201 @@ where
             w.setinta().no_effect();
             w.setintb().no_effect();
             w.width().bit_8();
-            w.srcinc().width_x_1();
-            w.dstinc().no_increment();
-            unsafe { w.xfercount().bits(source.len() as u16 - 1) }
+            match increment {
+                Increment::Source => {
+                    w.srcinc().width_x_1();
+                    w.dstinc().no_increment();
+                }
+                Increment::Dest => {
+                    w.srcinc().no_increment();
+                    w.dstinc().width_x_1();
+                }
+                Increment::Both => {
+                    w.srcinc().width_x_1();
+                    w.dstinc().width_x_1();
+                }
+            }
+            unsafe { w.xfercount().bits(len as u16 - 1) }
         });
+    }
 
-        let source_end = unsafe { source.as_ptr().add(source.len() - 1) };
+    /// Return the number of transfers that are still outstanding
+    ///
+    /// See user manual, section 12.6.18.
+    fn remaining(&self) -> usize {
+        if self.active0.read().act().bits() & T::FLAG == 0 {
+            return 0;
+        }
 
-        // Configure channel descriptor
-        // See user manual, sections 12.5.2 and 12.5.3.
-        self.descriptor.source_end = source_end;
-        self.descriptor.dest_end = dest.end_addr();
+        self.xfercfg.read().xfercount().bits() as usize + 1
+    }
+
+    /// Stop the channel and wait until it is idle
+    ///
+    /// The descriptor still holds the number of outstanding transfers, until
+    /// [`abort`] is called. See user manual, section 12.5.4.
+    ///
+    /// [`abort`]: #method.abort
+    fn disable(&self) {
+        // Disable the channel, so no further transfers are started, then wait
+        // for the current one to finish.
+        self.enableclr0.write(|w| unsafe { w.clr().bits(T::FLAG) });
+        while self.busy0.read().bsy().bits() & T::FLAG != 0 {}
+    }
 
-        // Enable channel 1
+    /// Abort the transfer of a channel that has been stopped using [`disable`]
+    ///
+    /// [`disable`]: #method.disable
+    fn abort(&self) {
+        self.abort0
+            .write(|w| unsafe { w.abortctrl().bits(T::FLAG) });
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    fn start(&self) {
+        // Enable channel
         // See user manual, section 12.6.4.
         self.enableset0.write(|w| unsafe { w.ena().bits(T::FLAG) });
 
         // Trigger transfer
         self.settrig0.write(|w| unsafe { w.trig().bits(T::FLAG) });
+    }
+}
 
-        Transfer {
-            channel: self,
-            source,
-            dest,
-        }
+/// The priority of a DMA channel
+///
+/// Passed to [`Channel::set_priority`]. There are 8 priority levels, with 0
+/// being the highest and 7 the lowest.
+///
+/// The presets reflect how costly it is, if a transfer is delayed. Use them
+/// to assign consistent priorities across all channels in use, for example
+/// `Priority::SERIAL_RX` for a USART receiver and `Priority::MEMORY` for a
+/// memory-to-memory copy.
+///
+/// [`Channel::set_priority`]: struct.Channel.html#method.set_priority
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Priority(u8);
+
+impl Priority {
+    /// The highest priority
+    pub const HIGHEST: Self = Priority(0);
+
+    /// The lowest priority
+    pub const LOWEST: Self = Priority(7);
+
+    /// For receiving from serial peripherals
+    ///
+    /// Data is lost, if these transfers are delayed too long.
+    pub const SERIAL_RX: Self = Priority(0);
+
+    /// For transmitting to serial peripherals
+    ///
+    /// Delays cause gaps in the transmitted data, which are usually harmless,
+    /// but might violate timing requirements of some protocols.
+    pub const SERIAL_TX: Self = Priority(2);
+
+    /// For other transfers from or to peripherals
+    pub const PERIPHERAL: Self = Priority(4);
+
+    /// For transfers from memory to memory
+    ///
+    /// Delays only slow down the transfer.
+    pub const MEMORY: Self = Priority(7);
+
+    /// Create a priority from a level
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `level` is larger than 7.
+    pub fn new(level: u8) -> Self {
+        assert!(level <= 7);
+        Priority(level)
+    }
+
+    /// Return the priority level
+    pub fn level(&self) -> u8 {
+        self.0
     }
 }
 
+/// A descriptor for a segment of a scatter-gather transfer
+///
+/// Please refer to [`Channel::start_chain`] for more information. These need
+/// to be kept in a `static`, as the DMA controller reads them while the
+/// transfer is ongoing.
+///
+/// [`Channel::start_chain`]: struct.Channel.html#method.start_chain
+#[repr(C, align(16))]
+pub struct LinkedDescriptor(ChannelDescriptor);
+
+impl LinkedDescriptor {
+    /// Create a new linked descriptor
+    pub const fn new() -> Self {
+        LinkedDescriptor(ChannelDescriptor::new())
+    }
+}
+
+/// Compute the XFERCFG value for a segment of a scatter-gather transfer
+///
+/// The segment is transferred from memory, byte by byte. If `reload` is set,
+/// the next descriptor is loaded afterwards. Otherwise, this is the last
+/// segment, so the channel's trigger is cleared and interrupt flag A is set.
+/// See user manual, section 12.6.18.
+fn xfercfg_bits(len: usize, reload: bool) -> u32 {
+    assert!(len > 0 && len <= 1024);
+
+    // The trigger must stay set until the last segment is exhausted, or the
+    // chain would stall. Once it is, the trigger has to be cleared, or it
+    // would still be pending when the channel is configured next.
+    let cfgvalid = 1 << 0;
+    let reload = if reload { 1 << 1 } else { 1 << 3 | 1 << 4 };
+    let srcinc = 1 << 12;
+    let xfercount = (len as u32 - 1) << 16;
+
+    cfgvalid | reload | srcinc | xfercount
+}
+
+fn segment_end(segment: &[u8]) -> *const u8 {
+    // Can't underflow, as `xfercfg_bits` rejects empty segments.
+    unsafe { segment.as_ptr().add(segment.len() - 1) }
+}
+
+/// The position of the WIDTH field of the XFERCFG registers
+const XFERCFG_WIDTH_SHIFT: u32 = 8;
+
+/// The mask of the WIDTH field of the XFERCFG registers
+const XFERCFG_WIDTH_MASK: u32 = 0b11 << XFERCFG_WIDTH_SHIFT;
+
+/// Implemented for the types of words that the DMA controller can transfer
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Word {
+    /// The value of the WIDTH field in XFERCFG for this word size
+    const WIDTH: u8;
+}
+
+impl Word for u8 {
+    const WIDTH: u8 = 0;
+}
+
+impl Word for u16 {
+    const WIDTH: u8 = 1;
+}
+
+impl Word for u32 {
+    const WIDTH: u8 = 2;
+}
+
+/// Which side of a transfer is in memory and needs to be incremented
+enum Increment {
+    Source,
+    Dest,
+    Both,
+}
+
 /// Implemented for each DMA channel
 pub trait ChannelTrait {
     /// The index of the channel
@@ -350,11 +951,15 @@ macro_rules! channels {
                             ty        : $name(()),
                             _state    : init_state::Disabled,
                             descriptor: descriptors.next().unwrap(),
+                            priority  : Priority::HIGHEST,
 
                             cfg    : RegProxy::new(),
                             xfercfg: RegProxy::new(),
 
+                            abort0    : RegProxy::new(),
                             active0   : RegProxy::new(),
+                            busy0     : RegProxy::new(),
+                            enableclr0: RegProxy::new(),
                             enableset0: RegProxy::new(),
                             settrig0  : RegProxy::new(),
                         },
@@ -444,6 +1049,24 @@ channels!(
     channel24, Channel24, 24, CFG24, XFERCFG24;
 );
 
+/// An error that can occur while starting a DMA transfer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The buffer is longer than the 1024 bytes a transfer can handle
+    TooLong,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooLong => {
+                f.write_str("DMA transfer is longer than 1024 bytes")
+            }
+        }
+    }
+}
+
 /// A destination for a DMA transfer
 pub trait Dest {
     /// The error that can occur while waiting for the destination to be idle
@@ -456,17 +1079,133 @@ pub trait Dest {
     fn end_addr(&mut self) -> *mut u8;
 }
 
+/// A source for a DMA transfer
+///
+/// This is the counterpart of [`Dest`], implemented by peripherals that DMA
+/// transfers can read from. See [`Channel::start_read`].
+///
+/// [`Dest`]: trait.Dest.html
+/// [`Channel::start_read`]: struct.Channel.html#method.start_read
+pub trait Source {
+    /// The error that can occur while waiting for the source to be idle
+    type Error;
+
+    /// Wait for the source to be idle
+    ///
+    /// This is called after the DMA transfer has finished, giving the source
+    /// a chance to wrap up whatever it was doing.
+    fn wait(&mut self) -> nb::Result<(), Self::Error>;
+
+    /// The address of the source's data register
+    fn end_addr(&mut self) -> *const u8;
+}
+
+/// The destination of a DMA transfer from memory to memory
+///
+/// Created by [`Channel::start_copy`].
+///
+/// [`Channel::start_copy`]: struct.Channel.html#method.start_copy
+pub struct Memory<B> {
+    buffer: B,
+    end: *mut u8,
+}
+
+impl<B> Memory<B> {
+    /// Return the wrapped buffer
+    pub fn free(self) -> B {
+        self.buffer
+    }
+}
+
+impl<B> Dest for Memory<B> {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // Memory is idle, once the DMA channel is.
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        self.end
+    }
+}
+
+/// An uninitialized buffer that a DMA transfer can read into
+///
+/// Pass this to [`Channel::start_read`], to receive into memory that doesn't
+/// need to be initialized first. Once the transfer has finished, use
+/// [`ReadTransfer::wait_init`] or [`ReadTransfer::abort_init`] to get the
+/// bytes that have been received, as a regular slice.
+///
+/// [`Channel::start_read`]: struct.Channel.html#method.start_read
+/// [`ReadTransfer::wait_init`]: struct.ReadTransfer.html#method.wait_init
+/// [`ReadTransfer::abort_init`]: struct.ReadTransfer.html#method.abort_init
+pub struct Uninit(&'static mut [MaybeUninit<u8>]);
+
+impl Uninit {
+    /// Wrap an uninitialized buffer
+    ///
+    /// Returns [`Error::TooLong`] along with the buffer, if it is longer than
+    /// the 1024 bytes a transfer can fill.
+    ///
+    /// [`Error::TooLong`]: enum.Error.html#variant.TooLong
+    pub fn new(
+        buffer: &'static mut [MaybeUninit<u8>],
+    ) -> Result<Self, (Error, &'static mut [MaybeUninit<u8>])> {
+        if buffer.len() > 1024 {
+            return Err((Error::TooLong, buffer));
+        }
+
+        Ok(Uninit(buffer))
+    }
+
+    /// Return the wrapped buffer
+    ///
+    /// Any bytes written by a DMA transfer are considered uninitialized
+    /// again.
+    pub fn free(self) -> &'static mut [MaybeUninit<u8>] {
+        self.0
+    }
+
+    /// Split the buffer into the first `len` bytes and the rest
+    ///
+    /// # Safety
+    ///
+    /// The first `len` bytes must have been initialized.
+    unsafe fn split_init(
+        self,
+        len: usize,
+    ) -> (&'static mut [u8], &'static mut [MaybeUninit<u8>]) {
+        let (init, rest) = self.0.split_at_mut(len);
+        let init = slice::from_raw_parts_mut(init.as_mut_ptr() as *mut u8, len);
+        (init, rest)
+    }
+}
+
+// Sound, as the buffer is `'static`, which means it stays valid, and a
+// `MaybeUninit<u8>` can hold any byte.
+unsafe impl WriteBuffer for Uninit {
+    type Word = u8;
+
+    unsafe fn write_buffer(&mut self) -> (*mut Self::Word, usize) {
+        (self.0.as_mut_ptr() as *mut u8, self.0.len())
+    }
+}
+
 /// A DMA transfer
-pub struct Transfer<'dma, T, D>
+///
+/// If the transfer is dropped before it has finished, it is aborted, and the
+/// buffers are only dropped once the channel is idle.
+pub struct Transfer<'dma, T, B, D>
 where
     T: ChannelTrait,
 {
     channel: Channel<T, init_state::Enabled<&'dma Handle>>,
-    source: &'static mut [u8],
+    source: B,
     dest: D,
 }
 
-impl<'dma, T, D> Transfer<'dma, T, D>
+impl<'dma, T, B, D> Transfer<'dma, T, B, D>
 where
     T: ChannelTrait,
     D: Dest,
@@ -474,14 +1213,8 @@ where
     /// Waits for the transfer to finish
     pub fn wait(
         mut self,
-    ) -> Result<
-        (
-            Channel<T, init_state::Enabled<&'dma Handle>>,
-            &'static mut [u8],
-            D,
-        ),
-        D::Error,
-    > {
+    ) -> Result<(Channel<T, init_state::Enabled<&'dma Handle>>, B, D), D::Error>
+    {
         // There's an error interrupt status register. Maybe we should check
         // this here, but I have no idea whether that actually makes sense:
         // 1. As of this writing, we're not enabling any interrupts. I don't
@@ -507,10 +1240,210 @@ where
 
         compiler_fence(Ordering::SeqCst);
 
-        Ok((self.channel, self.source, self.dest))
+        Ok(self.into_parts())
+    }
+}
+
+impl<'dma, T, B, D> Transfer<'dma, T, B, D>
+where
+    T: ChannelTrait,
+{
+    fn into_parts(
+        self,
+    ) -> (Channel<T, init_state::Enabled<&'dma Handle>>, B, D) {
+        let transfer = ManuallyDrop::new(self);
+
+        // Sound, as `transfer` is never dropped, so every field is moved out
+        // exactly once.
+        unsafe {
+            (
+                ptr::read(&transfer.channel),
+                ptr::read(&transfer.source),
+                ptr::read(&transfer.dest),
+            )
+        }
+    }
+}
+
+impl<'dma, T, B, D> Drop for Transfer<'dma, T, B, D>
+where
+    T: ChannelTrait,
+{
+    fn drop(&mut self) {
+        // The buffers are dropped right after this, so the DMA controller
+        // must not access them anymore.
+        self.channel.disable();
+        self.channel.abort();
+    }
+}
+
+/// A DMA transfer from a peripheral into memory
+///
+/// Created by [`Channel::start_read`]. If the transfer is dropped before it
+/// has finished, it is aborted, and the buffer is only dropped once the
+/// channel is idle.
+///
+/// [`Channel::start_read`]: struct.Channel.html#method.start_read
+pub struct ReadTransfer<'dma, T, S, B>
+where
+    T: ChannelTrait,
+{
+    channel: Channel<T, init_state::Enabled<&'dma Handle>>,
+    source: S,
+    dest: B,
+    len: usize,
+}
+
+impl<'dma, T, S, B> ReadTransfer<'dma, T, S, B>
+where
+    T: ChannelTrait,
+    S: Source,
+{
+    /// Waits for the transfer to finish
+    pub fn wait(
+        mut self,
+    ) -> Result<(Channel<T, init_state::Enabled<&'dma Handle>>, S, B), S::Error>
+    {
+        while self.channel.active0.read().act().bits() & T::FLAG != 0 {}
+
+        loop {
+            match self.source.wait() {
+                Err(nb::Error::WouldBlock) => continue,
+                Ok(()) => break,
+
+                Err(nb::Error::Other(error)) => {
+                    compiler_fence(Ordering::SeqCst);
+                    return Err(error);
+                }
+            }
+        }
+
+        compiler_fence(Ordering::SeqCst);
+
+        Ok(self.into_parts())
+    }
+
+    /// Return the number of bytes that have been written to the destination
+    ///
+    /// The transfer is ongoing, so the returned value might be outdated by
+    /// the time it is used.
+    pub fn transferred(&self) -> usize {
+        self.len - self.channel.remaining()
+    }
+
+    /// Abort the transfer
+    ///
+    /// Stops the transfer, whether it has finished or not, and returns the
+    /// number of bytes that have been written to the destination, along with
+    /// the resources used. See user manual, section 12.5.4.
+    pub fn abort(
+        self,
+    ) -> (usize, Channel<T, init_state::Enabled<&'dma Handle>>, S, B) {
+        // Wait for the channel to be idle, before reading the remaining
+        // count.
+        self.channel.disable();
+        let transferred = self.transferred();
+        self.channel.abort();
+
+        let (channel, source, dest) = self.into_parts();
+        (transferred, channel, source, dest)
+    }
+}
+
+impl<'dma, T, S, B> ReadTransfer<'dma, T, S, B>
+where
+    T: ChannelTrait,
+{
+    fn into_parts(
+        self,
+    ) -> (Channel<T, init_state::Enabled<&'dma Handle>>, S, B) {
+        let transfer = ManuallyDrop::new(self);
+
+        // Sound, as `transfer` is never dropped, so every field is moved out
+        // exactly once.
+        unsafe {
+            (
+                ptr::read(&transfer.channel),
+                ptr::read(&transfer.source),
+                ptr::read(&transfer.dest),
+            )
+        }
+    }
+}
+
+impl<'dma, T, S, B> Drop for ReadTransfer<'dma, T, S, B>
+where
+    T: ChannelTrait,
+{
+    fn drop(&mut self) {
+        // The buffer is dropped right after this, so the DMA controller must
+        // not access it anymore.
+        self.channel.disable();
+        self.channel.abort();
+    }
+}
+
+impl<'dma, T, S> ReadTransfer<'dma, T, S, Uninit>
+where
+    T: ChannelTrait,
+    S: Source,
+{
+    /// Waits for the transfer to finish and returns the initialized buffer
+    ///
+    /// Like [`wait`], but returns the buffer as a regular slice, as the
+    /// transfer has filled it completely.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn wait_init(
+        self,
+    ) -> Result<
+        (
+            Channel<T, init_state::Enabled<&'dma Handle>>,
+            S,
+            &'static mut [u8],
+        ),
+        S::Error,
+    > {
+        let len = self.len;
+        let (channel, source, dest) = self.wait()?;
+
+        // Sound, as the transfer has written `len` bytes. That's the whole
+        // buffer, as `Uninit::new` rejects buffers that are too long for a
+        // single transfer.
+        let (init, _) = unsafe { dest.split_init(len) };
+
+        Ok((channel, source, init))
+    }
+
+    /// Abort the transfer and return the bytes received so far
+    ///
+    /// Like [`abort`], but splits the buffer into the bytes that have been
+    /// written by the transfer, as a regular slice of exactly that length,
+    /// and the rest of the buffer, which is still uninitialized.
+    ///
+    /// [`abort`]: #method.abort
+    pub fn abort_init(
+        self,
+    ) -> (
+        Channel<T, init_state::Enabled<&'dma Handle>>,
+        S,
+        &'static mut [u8],
+        &'static mut [MaybeUninit<u8>],
+    ) {
+        let (transferred, channel, source, dest) = self.abort();
+
+        // Sound, as the transfer has written to this many bytes.
+        let (init, rest) = unsafe { dest.split_init(transferred) };
+
+        (channel, source, init, rest)
     }
 }
 
+reg!(ABORT0, ABORT0, pac::DMA0, abort0);
 reg!(ACTIVE0, ACTIVE0, pac::DMA0, active0);
+reg!(BUSY0, BUSY0, pac::DMA0, busy0);
+reg!(ENABLECLR0, ENABLECLR0, pac::DMA0, enableclr0);
 reg!(ENABLESET0, ENABLESET0, pac::DMA0, enableset0);
+reg!(INTA0, INTA0, pac::DMA0, inta0);
+reg!(INTB0, INTB0, pac::DMA0, intb0);
 reg!(SETTRIG0, SETTRIG0, pac::DMA0, settrig0);
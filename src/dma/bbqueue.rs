@@ -0,0 +1,151 @@
+//! Adapters for using [bbqueue] grants as DMA buffers
+//!
+//! This allows data to be streamed between a peripheral and a lock-free
+//! queue, without copying it around. Use [`WriteGrant`] to let the DMA
+//! controller write into a queue (for example from a USART receiver), and
+//! [`ReadGrant`] to let it read from one (for example into a USART
+//! transmitter).
+//!
+//! Transfers that use these adapters can be finished using
+//! [`ReadTransfer::wait_and_commit`] and [`Transfer::wait_and_release`], which
+//! commit or release the grant once the transfer has completed, so the data
+//! becomes available on the other side of the queue. A transfer that is
+//! dropped before it has finished is aborted first, so its grant is only
+//! dropped once the DMA controller no longer accesses it.
+//!
+//! This module is only available, if the `bbqueue` feature is enabled.
+//!
+//! [bbqueue]: https://crates.io/crates/bbqueue
+//! [`WriteGrant`]: struct.WriteGrant.html
+//! [`ReadGrant`]: struct.ReadGrant.html
+//! [`ReadTransfer::wait_and_commit`]: ../struct.ReadTransfer.html#method.wait_and_commit
+//! [`Transfer::wait_and_release`]: ../struct.Transfer.html#method.wait_and_release
+
+use bbqueue::{ArrayLength, GrantR, GrantW};
+use embedded_dma::{ReadBuffer, WriteBuffer};
+
+use crate::init_state;
+
+use super::{
+    Channel, ChannelTrait, Dest, Handle, ReadTransfer, Source, Transfer,
+};
+
+/// A bbqueue write grant that can be used as a DMA destination
+///
+/// Pass this to [`Channel::start_read`], or any other API that accepts a DMA
+/// destination buffer. The whole grant is filled by the transfer.
+///
+/// [`Channel::start_read`]: ../struct.Channel.html#method.start_read
+pub struct WriteGrant<N>(GrantW<'static, N>)
+where
+    N: ArrayLength<u8>;
+
+impl<N> WriteGrant<N>
+where
+    N: ArrayLength<u8>,
+{
+    /// Wrap a write grant
+    pub fn new(grant: GrantW<'static, N>) -> Self {
+        Self(grant)
+    }
+
+    /// Return the wrapped write grant
+    pub fn free(self) -> GrantW<'static, N> {
+        self.0
+    }
+}
+
+unsafe impl<N> WriteBuffer for WriteGrant<N>
+where
+    N: ArrayLength<u8>,
+{
+    type Word = u8;
+
+    unsafe fn write_buffer(&mut self) -> (*mut u8, usize) {
+        let buffer: &mut [u8] = &mut self.0;
+        (buffer.as_mut_ptr(), buffer.len())
+    }
+}
+
+/// A bbqueue read grant that can be used as a DMA source
+///
+/// Pass this to [`Channel::start_transfer`], or any other API that accepts a
+/// DMA source buffer. The whole grant is transferred.
+///
+/// [`Channel::start_transfer`]: ../struct.Channel.html#method.start_transfer
+pub struct ReadGrant<N>(GrantR<'static, N>)
+where
+    N: ArrayLength<u8>;
+
+impl<N> ReadGrant<N>
+where
+    N: ArrayLength<u8>,
+{
+    /// Wrap a read grant
+    pub fn new(grant: GrantR<'static, N>) -> Self {
+        Self(grant)
+    }
+
+    /// Return the wrapped read grant
+    pub fn free(self) -> GrantR<'static, N> {
+        self.0
+    }
+}
+
+unsafe impl<N> ReadBuffer for ReadGrant<N>
+where
+    N: ArrayLength<u8>,
+{
+    type Word = u8;
+
+    unsafe fn read_buffer(&self) -> (*const u8, usize) {
+        let buffer: &[u8] = &self.0;
+        (buffer.as_ptr(), buffer.len())
+    }
+}
+
+impl<'dma, T, S, N> ReadTransfer<'dma, T, S, WriteGrant<N>>
+where
+    T: ChannelTrait,
+    S: Source,
+    N: ArrayLength<u8>,
+{
+    /// Waits for the transfer to finish, then commits the grant
+    ///
+    /// After this method returns, the received data is available to the
+    /// consumer side of the queue.
+    pub fn wait_and_commit(
+        self,
+    ) -> Result<(Channel<T, init_state::Enabled<&'dma Handle>>, S), S::Error>
+    {
+        let (channel, source, grant) = self.wait()?;
+
+        let len = grant.0.len();
+        grant.0.commit(len);
+
+        Ok((channel, source))
+    }
+}
+
+impl<'dma, T, N, D> Transfer<'dma, T, ReadGrant<N>, D>
+where
+    T: ChannelTrait,
+    N: ArrayLength<u8>,
+    D: Dest,
+{
+    /// Waits for the transfer to finish, then releases the grant
+    ///
+    /// After this method returns, the transferred part of the queue is
+    /// available to the producer side again.
+    pub fn wait_and_release(
+        self,
+    ) -> Result<(Channel<T, init_state::Enabled<&'dma Handle>>, D), D::Error>
+    {
+        let (channel, grant, dest) = self.wait()?;
+
+        let len = grant.0.len();
+        grant.0.release(len);
+
+        Ok((channel, dest))
+    }
+}
@@ -0,0 +1,249 @@
+//! A continuous DMA transfer that alternates between two buffers
+//!
+//! [`PingPong`] transfers two buffers to a DMA destination, one after the
+//! other, over and over again, until it is stopped. While one buffer is being
+//! transferred, the other one can be replaced using [`PingPong::swap`]. The
+//! replacement takes effect without any gap, which makes this suitable for
+//! streaming samples to a DAC, for example.
+//!
+//! If no buffer is swapped in, the same two buffers are transferred again,
+//! which is useful for generating a periodic signal.
+//!
+//! The two buffers are linked using reload descriptors. See user manual,
+//! section 12.5.2.
+//!
+//! [`PingPong`]: struct.PingPong.html
+//! [`PingPong::swap`]: struct.PingPong.html#method.swap
+
+use core::{
+    mem,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use embedded_dma::ReadBuffer;
+use void::Void;
+
+use crate::{init_state, reg_proxy::RegProxy};
+
+use super::{
+    Channel, ChannelDescriptor, ChannelTrait, Dest, Handle, Increment,
+    LinkedDescriptor, Word, INTA0, INTB0, XFERCFG_WIDTH_SHIFT,
+};
+
+/// A continuous DMA transfer from two alternating buffers
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct PingPong<'dma, T, B, D>
+where
+    T: ChannelTrait,
+{
+    channel: Channel<T, init_state::Enabled<&'dma Handle>>,
+    dest: D,
+
+    buffers: [B; 2],
+    descriptors: &'static mut [LinkedDescriptor; 2],
+
+    // The buffer that is replaced by the next call to `swap`. Buffer 0 sets
+    // interrupt flag A when it has been transferred, buffer 1 flag B.
+    next: usize,
+
+    inta0: RegProxy<INTA0>,
+    intb0: RegProxy<INTB0>,
+}
+
+impl<'dma, T, B, D> PingPong<'dma, T, B, D>
+where
+    T: ChannelTrait,
+    B: ReadBuffer,
+    B::Word: Word,
+    D: Dest,
+{
+    /// Start transferring `buffers` to `dest`, alternately
+    ///
+    /// Each word of the buffers is transferred on a request of the
+    /// destination. `buffers[0]` is transferred first.
+    ///
+    /// `descriptors` need to be kept in a `static`, as the DMA controller
+    /// reads them while the transfer is ongoing.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if either buffer is empty or longer than 1024 words.
+    pub fn new(
+        channel: Channel<T, init_state::Enabled<&'dma Handle>>,
+        mut dest: D,
+        buffers: [B; 2],
+        descriptors: &'static mut [LinkedDescriptor; 2],
+    ) -> Self {
+        let dest_end = dest.end_addr();
+
+        // Link the descriptors into a ring. The channel's own descriptor
+        // starts with a copy of descriptor 0, then hands over to the ring.
+        let next_0 = &descriptors[1].0 as *const ChannelDescriptor;
+        let next_1 = &descriptors[0].0 as *const ChannelDescriptor;
+        for (i, &next) in [next_0, next_1].iter().enumerate() {
+            let descriptor = &mut descriptors[i].0;
+            load(descriptor, &buffers[i], i);
+            descriptor.dest_end = dest_end;
+            descriptor.next_desc = next;
+        }
+
+        let config = descriptors[0].0.config;
+        let (len, _) = buffer_end(&buffers[0]);
+
+        let inta0 = RegProxy::<INTA0>::new();
+        let intb0 = RegProxy::<INTB0>::new();
+
+        // The flags are cleared by writing 1. See user manual, sections
+        // 12.6.11 and 12.6.12.
+        inta0.write(|w| unsafe { w.bits(T::FLAG) });
+        intb0.write(|w| unsafe { w.bits(T::FLAG) });
+
+        channel.configure(len, Increment::Source);
+        channel.xfercfg.write(|w| unsafe { w.bits(config) });
+
+        channel.descriptor.source_end = descriptors[0].0.source_end;
+        channel.descriptor.dest_end = dest_end;
+        channel.descriptor.next_desc = next_0;
+
+        compiler_fence(Ordering::SeqCst);
+
+        channel.start();
+
+        PingPong {
+            channel,
+            dest,
+
+            buffers,
+            descriptors,
+
+            next: 0,
+
+            inta0,
+            intb0,
+        }
+    }
+
+    /// Replace the buffer that has been transferred last
+    ///
+    /// Buffers are replaced alternately, starting with `buffers[0]`. A buffer
+    /// can be replaced, once it has been transferred completely. Then the
+    /// other buffer is being transferred, and `buffer` is transferred after
+    /// it.
+    ///
+    /// Returns the replaced buffer, or `WouldBlock`, if the buffer to replace
+    /// is still being transferred. This method needs to be called, before the
+    /// other buffer has been transferred completely. Otherwise the DMA
+    /// controller might be transferring the buffer that is being replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `buffer` is empty or longer than 1024 words.
+    pub fn swap(&mut self, buffer: B) -> nb::Result<B, Void> {
+        if !self.is_transferred(self.next) {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let i = self.next;
+
+        load(&mut self.descriptors[i].0, &buffer, i);
+        compiler_fence(Ordering::SeqCst);
+
+        self.clear_flag(i);
+
+        self.next = 1 - i;
+
+        Ok(mem::replace(&mut self.buffers[i], buffer))
+    }
+
+    /// Indicates whether the next call to [`swap`] would succeed
+    ///
+    /// [`swap`]: #method.swap
+    pub fn can_swap(&self) -> bool {
+        self.is_transferred(self.next)
+    }
+
+    /// Stop the transfer and release the resources used
+    ///
+    /// The transfer is stopped immediately, which might be in the middle of a
+    /// buffer. See user manual, section 12.5.4.
+    pub fn stop(
+        self,
+    ) -> (
+        Channel<T, init_state::Enabled<&'dma Handle>>,
+        D,
+        [B; 2],
+        &'static mut [LinkedDescriptor; 2],
+    ) {
+        self.channel
+            .enableclr0
+            .write(|w| unsafe { w.clr().bits(T::FLAG) });
+        while self.channel.busy0.read().bsy().bits() & T::FLAG != 0 {}
+
+        self.channel
+            .abort0
+            .write(|w| unsafe { w.abortctrl().bits(T::FLAG) });
+
+        self.clear_flag(0);
+        self.clear_flag(1);
+
+        compiler_fence(Ordering::SeqCst);
+
+        (self.channel, self.dest, self.buffers, self.descriptors)
+    }
+
+    fn is_transferred(&self, i: usize) -> bool {
+        let flags = match i {
+            0 => self.inta0.read().bits(),
+            _ => self.intb0.read().bits(),
+        };
+
+        flags & T::FLAG != 0
+    }
+
+    fn clear_flag(&self, i: usize) {
+        match i {
+            0 => self.inta0.write(|w| unsafe { w.bits(T::FLAG) }),
+            _ => self.intb0.write(|w| unsafe { w.bits(T::FLAG) }),
+        }
+    }
+}
+
+/// Point a descriptor at a buffer
+///
+/// Descriptor 0 sets interrupt flag A when its buffer has been transferred,
+/// descriptor 1 sets flag B. Both reload the next descriptor.
+fn load<B>(descriptor: &mut ChannelDescriptor, buffer: &B, i: usize)
+where
+    B: ReadBuffer,
+    B::Word: Word,
+{
+    let (len, end) = buffer_end(buffer);
+
+    // See user manual, section 12.6.18.
+    let cfgvalid = 1 << 0;
+    let reload = 1 << 1;
+    let setint = if i == 0 { 1 << 4 } else { 1 << 5 };
+    let width = (B::Word::WIDTH as u32) << XFERCFG_WIDTH_SHIFT;
+    let srcinc = 1 << 12;
+    let xfercount = (len as u32 - 1) << 16;
+
+    descriptor.config = cfgvalid | reload | setint | width | srcinc | xfercount;
+    descriptor.source_end = end;
+}
+
+/// Return the length of a buffer and the address of its last word
+fn buffer_end<B>(buffer: &B) -> (usize, *const u8)
+where
+    B: ReadBuffer,
+{
+    // Sound, as the buffer is owned by the `PingPong` instance, or is about
+    // to be, which guarantees that it stays valid during the transfer.
+    let (ptr, len) = unsafe { buffer.read_buffer() };
+    assert!(len > 0 && len <= 1024);
+
+    // Can't underflow, as we just checked that the buffer isn't empty.
+    (len, unsafe { ptr.add(len - 1) } as *const u8)
+}
@@ -0,0 +1,254 @@
+//! A transmit queue that streams data to a peripheral using DMA
+//!
+//! [`TxQueue`] collects data in a pool of buffers and streams it to a DMA
+//! destination, typically a USART transmitter. All buffers that have been
+//! filled while a transfer was ongoing are sent as a single scatter-gather
+//! transfer, once the previous one has finished. This keeps bursts of log
+//! messages from blocking the CPU, without requiring one large buffer.
+//!
+//! Buffers return to the pool, once the transfer that sent them has finished.
+//! This is detected by [`TxQueue::poll`], which needs to be called regularly,
+//! from the main loop or from the DMA interrupt handler.
+//!
+//! [`TxQueue`]: struct.TxQueue.html
+//! [`TxQueue::poll`]: struct.TxQueue.html#method.poll
+
+use core::{
+    fmt, ptr,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use crate::init_state;
+
+use super::{
+    segment_end, xfercfg_bits, Channel, ChannelDescriptor, ChannelTrait, Dest,
+    Handle, Increment, LinkedDescriptor,
+};
+
+/// A DMA transmit queue with `N` buffers of `S` bytes each
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct TxQueue<'dma, T, D, const N: usize, const S: usize>
+where
+    T: ChannelTrait,
+{
+    channel: Channel<T, init_state::Enabled<&'dma Handle>>,
+    dest: D,
+
+    buffers: &'static mut [[u8; S]; N],
+    lens: [usize; N],
+    descriptors: &'static mut [LinkedDescriptor; N],
+
+    // The buffers are used as a ring. Starting with `first`, there are
+    // `in_flight` buffers that are being transferred, followed by `pending`
+    // buffers that are waiting for the next transfer. The rest is free.
+    first: usize,
+    in_flight: usize,
+    pending: usize,
+}
+
+impl<'dma, T, D, const N: usize, const S: usize> TxQueue<'dma, T, D, N, S>
+where
+    T: ChannelTrait,
+    D: Dest,
+{
+    /// Create a transmit queue
+    ///
+    /// `buffers` and `descriptors` need to be kept in a `static`, as the DMA
+    /// controller accesses them while a transfer is ongoing.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `N` is zero, or if `S` is zero or larger than 1024.
+    pub fn new(
+        channel: Channel<T, init_state::Enabled<&'dma Handle>>,
+        dest: D,
+        buffers: &'static mut [[u8; S]; N],
+        descriptors: &'static mut [LinkedDescriptor; N],
+    ) -> Self {
+        assert!(N > 0);
+        assert!(S > 0 && S <= 1024);
+
+        TxQueue {
+            channel,
+            dest,
+
+            buffers,
+            lens: [0; N],
+            descriptors,
+
+            first: 0,
+            in_flight: 0,
+            pending: 0,
+        }
+    }
+
+    /// Add data to the queue
+    ///
+    /// Copies `data` into the free buffers. If a buffer is waiting for the
+    /// next transfer and has room left, that is filled up first. The data is
+    /// sent by the next call to [`TxQueue::poll`] that finds the DMA channel
+    /// idle.
+    ///
+    /// Returns [`Error::Full`] and queues nothing, if there's not enough room
+    /// for all of `data`.
+    ///
+    /// [`TxQueue::poll`]: #method.poll
+    /// [`Error::Full`]: enum.Error.html#variant.Full
+    pub fn enqueue(&mut self, mut data: &[u8]) -> Result<(), Error> {
+        if data.len() > self.free_space() {
+            return Err(Error::Full);
+        }
+
+        if self.pending > 0 {
+            let i = self.index(self.in_flight + self.pending - 1);
+            data = self.fill(i, data);
+        }
+
+        while !data.is_empty() {
+            let i = self.index(self.in_flight + self.pending);
+            self.lens[i] = 0;
+            self.pending += 1;
+
+            data = self.fill(i, data);
+        }
+
+        Ok(())
+    }
+
+    /// Recycle sent buffers and start the next transfer
+    ///
+    /// If the ongoing transfer has finished, its buffers are returned to the
+    /// pool. If the DMA channel is idle afterwards and data is waiting, a new
+    /// transfer is started for all of it.
+    ///
+    /// Call this method regularly, or from the DMA interrupt handler.
+    pub fn poll(&mut self) {
+        if self.in_flight > 0 {
+            if self.channel.active0.read().act().bits() & T::FLAG != 0 {
+                return;
+            }
+
+            compiler_fence(Ordering::SeqCst);
+
+            self.first = self.index(self.in_flight);
+            self.in_flight = 0;
+        }
+
+        if self.pending > 0 {
+            self.start();
+        }
+    }
+
+    /// Return the number of bytes that can currently be enqueued
+    pub fn free_space(&self) -> usize {
+        let used = self.in_flight + self.pending;
+
+        let mut free = (N - used) * S;
+        if self.pending > 0 {
+            free += S - self.lens[self.index(used - 1)];
+        }
+
+        free
+    }
+
+    /// Indicates whether all queued data has been sent
+    ///
+    /// The destination might still be busy sending the last bytes.
+    pub fn is_idle(&self) -> bool {
+        self.in_flight == 0 && self.pending == 0
+    }
+
+    /// Send all queued data and release the resources used by the queue
+    pub fn free(
+        mut self,
+    ) -> Result<
+        (
+            Channel<T, init_state::Enabled<&'dma Handle>>,
+            D,
+            &'static mut [[u8; S]; N],
+            &'static mut [LinkedDescriptor; N],
+        ),
+        D::Error,
+    > {
+        while !self.is_idle() {
+            self.poll();
+        }
+
+        nb::block!(self.dest.wait())?;
+
+        Ok((self.channel, self.dest, self.buffers, self.descriptors))
+    }
+
+    fn start(&mut self) {
+        let dest_end = self.dest.end_addr();
+
+        // Build the chain from the back, so every descriptor can point to the
+        // next one. The first buffer uses the channel's own descriptor, so its
+        // linked descriptor stays unused. See user manual, section 12.5.2.
+        let mut next: *const ChannelDescriptor = ptr::null();
+        for k in (1..self.pending).rev() {
+            let i = self.index(k);
+            let buffer = &self.buffers[i][..self.lens[i]];
+            let descriptor = &mut self.descriptors[i].0;
+
+            descriptor.config = xfercfg_bits(buffer.len(), !next.is_null());
+            descriptor.source_end = segment_end(buffer);
+            descriptor.dest_end = dest_end;
+            descriptor.next_desc = next;
+
+            next = descriptor as *const _;
+        }
+
+        let first = &self.buffers[self.first][..self.lens[self.first]];
+        let config = xfercfg_bits(first.len(), !next.is_null());
+
+        self.channel.configure(first.len(), Increment::Source);
+        self.channel.xfercfg.write(|w| unsafe { w.bits(config) });
+
+        self.channel.descriptor.source_end = segment_end(first);
+        self.channel.descriptor.dest_end = dest_end;
+        self.channel.descriptor.next_desc = next;
+
+        compiler_fence(Ordering::SeqCst);
+
+        self.channel.start();
+
+        self.in_flight = self.pending;
+        self.pending = 0;
+    }
+
+    /// Copy as much of `data` into buffer `i` as fits, return the rest
+    fn fill<'d>(&mut self, i: usize, data: &'d [u8]) -> &'d [u8] {
+        let len = self.lens[i];
+        let n = data.len().min(S - len);
+
+        self.buffers[i][len..len + n].copy_from_slice(&data[..n]);
+        self.lens[i] += n;
+
+        &data[n..]
+    }
+
+    /// Return the index of the buffer `k` positions after the first
+    fn index(&self, k: usize) -> usize {
+        (self.first + k) % N
+    }
+}
+
+/// An error that can occur while enqueueing data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// There's not enough room in the queue
+    Full,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Full => f.write_str("DMA transmit queue full"),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! DMX512 transmitter on top of USART, MRT, and DMA
+//!
+//! The entry point to this API is [`Dmx`]. It generates the break and
+//! mark-after-break that start a DMX512 packet, then sends the packet data
+//! using DMA.
+//!
+//! The USART must be configured for 250000 baud by the user. [`Dmx::new`]
+//! takes care of the rest of the character format (8 data bits, no parity, 2
+//! stop bits).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     dma, dmx::Dmx, init_state, mrt::MrtChannel, pac::USART0,
+//!     usart::USART,
+//! };
+//!
+//! fn run<Pins, T>(
+//!     usart: &mut USART<USART0, init_state::Enabled<Pins>>,
+//!     timer: MrtChannel,
+//!     mut channel: dma::Channel<T, init_state::Enabled<&dma::Handle>>,
+//!     mut packet: &'static mut [u8],
+//! ) -> !
+//! where
+//!     T: dma::ChannelTrait,
+//! {
+//!     let mut dmx = Dmx::new(usart, timer);
+//!
+//!     loop {
+//!         // Start code
+//!         packet[0] = 0;
+//!         // Update channel values in `packet[1..]` here
+//!
+//!         let transfer = dmx.send(packet, channel);
+//!         let (c, p, _) = transfer.wait().unwrap();
+//!
+//!         channel = c;
+//!         packet = p;
+//!     }
+//! }
+//! ```
+
+use embedded_dma::ReadBuffer;
+use embedded_hal::timer::CountDown;
+use nb::block;
+
+use crate::{
+    dma, init_state,
+    mrt::MrtChannel,
+    usart::{Instance, Settings, StopBits, Tx, USART},
+};
+
+/// Length of the break in microseconds. The minimum is 92.
+const BREAK_US: u32 = 100;
+
+/// Length of the mark-after-break in microseconds. The minimum is 12.
+const MARK_AFTER_BREAK_US: u32 = 16;
+
+/// DMX512 transmitter
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Dmx<'usart, I, Pins> {
+    usart: &'usart USART<I, init_state::Enabled<Pins>>,
+    timer: MrtChannel,
+}
+
+impl<'usart, I, Pins> Dmx<'usart, I, Pins>
+where
+    I: Instance,
+{
+    /// Create the DMX512 transmitter
+    ///
+    /// Configures `usart` for 2 stop bits. It must already be configured for
+    /// 250000 baud. `timer` is used to time the break and mark-after-break.
+    pub fn new(
+        usart: &'usart mut USART<I, init_state::Enabled<Pins>>,
+        timer: MrtChannel,
+    ) -> Self {
+        usart.configure(&Settings::default().stop_bits(StopBits::Two));
+
+        Dmx { usart, timer }
+    }
+
+    /// Send a DMX512 packet
+    ///
+    /// `packet` contains the start code, followed by up to 512 channel
+    /// values. Blocks while the break and mark-after-break are being
+    /// generated, then returns the DMA transfer that sends `packet`.
+    pub fn send<'dma, T, B>(
+        &mut self,
+        packet: B,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+    ) -> dma::Transfer<'dma, T, B, Tx<'usart, I>>
+    where
+        T: dma::ChannelTrait,
+        B: ReadBuffer<Word = u8>,
+    {
+        let mut tx = self.usart.tx();
+
+        tx.start_break();
+        self.delay_us(BREAK_US);
+        tx.end_break();
+        self.delay_us(MARK_AFTER_BREAK_US);
+
+        channel.start_transfer_unchecked(packet, tx)
+    }
+
+    /// Release the timer
+    pub fn free(self) -> MrtChannel {
+        self.timer
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        // Can't fail, as the delays are far below the maximum of the timer.
+        let _ = self.timer.start_us(us);
+        // Can't fail, as the error type is `Void`.
+        let _ = block!(self.timer.wait());
+    }
+}
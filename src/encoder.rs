@@ -0,0 +1,230 @@
+//! Helper for rotary encoders, using pin interrupts
+//!
+//! Rotary encoders, as used for front-panel knobs, output two quadrature
+//! signals. The order in which they change indicates the direction of
+//! rotation. Mechanical encoders bounce heavily, which is why this module uses
+//! a state table that only accepts a step, once both signals have gone through
+//! the complete sequence from one detent to the next. Bounces on one signal
+//! just cause the state machine to go back and forth, without producing any
+//! steps.
+//!
+//! The entry point to this API is [`Encoder`]. It is driven by the pin
+//! interrupts of both signals and collects the steps in a small queue, which
+//! can be read from thread context.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{encoder::Encoder, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let a = swm.pins.pio0_16.into_gpio_pin(&p.GPIO).into_input();
+//! let b = swm.pins.pio0_17.into_gpio_pin(&p.GPIO).into_input();
+//!
+//! let mut pinint = p.PINT.enable(&mut syscon.handle);
+//! let mut encoder = Encoder::new(a, b, 0, 1, &mut pinint, &mut syscon.handle);
+//!
+//! // Call this from the PININT0 and PININT1 interrupt handlers.
+//! encoder.handle_interrupt(&mut pinint);
+//!
+//! // Read the steps from thread context.
+//! while let Some(delta) = encoder.next_delta() {
+//!     // Positive values are clockwise steps, negative ones counter-clockwise
+//! }
+//! ```
+//!
+//! Since the encoder is used from interrupt handlers and thread context, it
+//! will usually need to be shared via `cortex_m::interrupt::Mutex`, or a
+//! similar mechanism.
+
+use crate::{
+    gpio::direction,
+    init_state,
+    pinint::{Edge, PININT},
+    swm::{pin_state, Pin, PinTrait},
+    syscon,
+};
+
+/// The number of entries in the queue of steps
+pub const QUEUE_LEN: usize = 8;
+
+/// A rotary encoder
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Encoder<'gpio, A: PinTrait, B: PinTrait> {
+    a: Pin<A, pin_state::Gpio<'gpio, direction::Input>>,
+    b: Pin<B, pin_state::Gpio<'gpio, direction::Input>>,
+    interrupt_a: usize,
+    interrupt_b: usize,
+
+    state: u8,
+
+    queue: [i8; QUEUE_LEN],
+    queue_start: usize,
+    queue_len: usize,
+}
+
+impl<'gpio, A, B> Encoder<'gpio, A, B>
+where
+    A: PinTrait,
+    B: PinTrait,
+{
+    /// Create the encoder and start listening for edges
+    ///
+    /// Configures pin interrupts `interrupt_a` and `interrupt_b` to trigger on
+    /// both edges of `a` and `b` respectively. The interrupts need to be
+    /// unmasked in the NVIC.
+    ///
+    /// The encoder is expected to rest with both signals high, which is the
+    /// case for the usual wiring with pull-up resistors and the common pin
+    /// connected to ground.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if any of the interrupt numbers is not smaller than
+    /// [`pinint::NUM_INTERRUPTS`], or if both are the same.
+    ///
+    /// [`pinint::NUM_INTERRUPTS`]: ../pinint/constant.NUM_INTERRUPTS.html
+    pub fn new(
+        a: Pin<A, pin_state::Gpio<'gpio, direction::Input>>,
+        b: Pin<B, pin_state::Gpio<'gpio, direction::Input>>,
+        interrupt_a: usize,
+        interrupt_b: usize,
+        pinint: &mut PININT<init_state::Enabled>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        assert!(interrupt_a != interrupt_b);
+
+        pinint.configure(interrupt_a, &a, Edge::Both, syscon);
+        pinint.configure(interrupt_b, &b, Edge::Both, syscon);
+
+        Encoder {
+            a,
+            b,
+            interrupt_a,
+            interrupt_b,
+
+            state: R_START,
+
+            queue: [0; QUEUE_LEN],
+            queue_start: 0,
+            queue_len: 0,
+        }
+    }
+
+    /// Process edges of the encoder signals
+    ///
+    /// Call this method from the interrupt handlers of both pin interrupts. It
+    /// clears the interrupts.
+    pub fn handle_interrupt(
+        &mut self,
+        pinint: &mut PININT<init_state::Enabled>,
+    ) {
+        let edge_a = pinint.take_edge(self.interrupt_a);
+        let edge_b = pinint.take_edge(self.interrupt_b);
+
+        if edge_a.is_none() && edge_b.is_none() {
+            return;
+        }
+
+        let inputs =
+            (is_high(&self.b) as usize) << 1 | is_high(&self.a) as usize;
+        self.state = TABLE[(self.state & 0xf) as usize][inputs];
+
+        match self.state & DIR_MASK {
+            DIR_CW => self.push(1),
+            DIR_CCW => self.push(-1),
+            _ => {}
+        }
+    }
+
+    /// Return the next position delta from the queue
+    ///
+    /// Positive values mean clockwise rotation, negative values mean
+    /// counter-clockwise rotation. Usually, every entry is a single step. If
+    /// the queue runs full, steps are added to the last entry instead, so no
+    /// steps are lost.
+    ///
+    /// Returns `None`, if the queue is empty.
+    pub fn next_delta(&mut self) -> Option<i8> {
+        if self.queue_len == 0 {
+            return None;
+        }
+
+        let delta = self.queue[self.queue_start];
+        self.queue_start = (self.queue_start + 1) % QUEUE_LEN;
+        self.queue_len -= 1;
+
+        Some(delta)
+    }
+
+    /// Disable the pin interrupts and release the pins
+    pub fn free(
+        self,
+        pinint: &mut PININT<init_state::Enabled>,
+    ) -> (
+        Pin<A, pin_state::Gpio<'gpio, direction::Input>>,
+        Pin<B, pin_state::Gpio<'gpio, direction::Input>>,
+    ) {
+        pinint.disable_interrupt(self.interrupt_a);
+        pinint.disable_interrupt(self.interrupt_b);
+
+        (self.a, self.b)
+    }
+
+    fn push(&mut self, delta: i8) {
+        if self.queue_len < QUEUE_LEN {
+            let i = (self.queue_start + self.queue_len) % QUEUE_LEN;
+            self.queue[i] = delta;
+            self.queue_len += 1;
+        } else {
+            let i = (self.queue_start + QUEUE_LEN - 1) % QUEUE_LEN;
+            self.queue[i] = self.queue[i].saturating_add(delta);
+        }
+    }
+}
+
+fn is_high<P: PinTrait>(
+    pin: &Pin<P, pin_state::Gpio<direction::Input>>,
+) -> bool {
+    pin.state.registers.pin[P::PORT].read().port().bits() & P::MASK != 0
+}
+
+// The state table for full-step decoding. The state only returns to the start,
+// with a direction flag set, after the signals went through all four phases in
+// one direction. The inputs are indexed as `b << 1 | a`.
+
+const R_START: u8 = 0x0;
+const R_CW_FINAL: u8 = 0x1;
+const R_CW_BEGIN: u8 = 0x2;
+const R_CW_NEXT: u8 = 0x3;
+const R_CCW_BEGIN: u8 = 0x4;
+const R_CCW_FINAL: u8 = 0x5;
+const R_CCW_NEXT: u8 = 0x6;
+
+const DIR_CW: u8 = 0x10;
+const DIR_CCW: u8 = 0x20;
+const DIR_MASK: u8 = 0x30;
+
+const TABLE: [[u8; 4]; 7] = [
+    // R_START
+    [R_START, R_CW_BEGIN, R_CCW_BEGIN, R_START],
+    // R_CW_FINAL
+    [R_CW_NEXT, R_START, R_CW_FINAL, R_START | DIR_CW],
+    // R_CW_BEGIN
+    [R_CW_NEXT, R_CW_BEGIN, R_START, R_START],
+    // R_CW_NEXT
+    [R_CW_NEXT, R_CW_BEGIN, R_CW_FINAL, R_START],
+    // R_CCW_BEGIN
+    [R_CCW_NEXT, R_START, R_CCW_BEGIN, R_START],
+    // R_CCW_FINAL
+    [R_CCW_NEXT, R_CCW_FINAL, R_START, R_START | DIR_CCW],
+    // R_CCW_NEXT
+    [R_CCW_NEXT, R_CCW_FINAL, R_CCW_BEGIN, R_START],
+];
@@ -0,0 +1,199 @@
+//! A crate-wide error type
+//!
+//! The peripheral APIs each have their own error types, which describe exactly
+//! what can go wrong with that API. [`Error`] can hold any of them, which
+//! allows applications to use `?` to propagate errors from multiple APIs,
+//! without defining an error type of their own.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{mrt::MrtChannel, Error};
+//!
+//! fn stop(timer: &mut MrtChannel) -> Result<(), Error> {
+//!     use lpc8xx_hal::prelude::*;
+//!
+//!     timer.cancel()?;
+//!     Ok(())
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::{
+    clock_check, data_logger, dma, fgen, i2c, iap, modbus, mrt, pinint, pmu,
+    scheduler, spi, syscon, usart, xmodem,
+};
+#[cfg(feature = "845")]
+use crate::{ppm, servo, ultrasonic};
+
+/// Any error that can be returned by the APIs in this crate
+///
+/// Every variant wraps the error type of one API. Use `From` or `?` to convert
+/// an API's error into this type.
+///
+/// More variants might be added in the future, as new APIs are added.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    /// An invalid peripheral clock configuration
+    Clock(syscon::clocksource::Error),
+
+    /// An error while measuring the main clock against a reference
+    ClockCheck(clock_check::Error),
+
+    /// An error from the data logger
+    DataLogger(data_logger::Error),
+
+    /// An error while starting a DMA transfer
+    Dma(dma::Error),
+
+    /// An error from the DMA transmit queue
+    DmaQueue(dma::queue::Error),
+
+    /// An error from the frequency generator
+    Fgen(fgen::Error),
+
+    /// An error while clearing the I2C bus
+    I2cBusClear(i2c::BusClearError),
+
+    /// An error during an I2C transaction
+    I2cTransfer(i2c::TransferError),
+
+    /// An error from an IAP command
+    Iap(iap::Error),
+
+    /// An error while changing the main clock
+    MainClock(syscon::main_clock::Error),
+
+    /// An error from the MODBUS RTU receiver
+    Modbus(modbus::Error),
+
+    /// An error from an MRT channel
+    Mrt(mrt::Error),
+
+    /// An error from the pin interrupt registry
+    PinInt(pinint::Error),
+
+    /// An error while entering a power mode
+    Pmu(pmu::Error),
+
+    /// An error from the PPM decoder
+    #[cfg(feature = "845")]
+    Ppm(ppm::Error),
+
+    /// An error from the software timer scheduler
+    Scheduler(scheduler::Error),
+
+    /// An error from the servo helper
+    #[cfg(feature = "845")]
+    Servo(servo::Error),
+
+    /// An error from an SPI peripheral
+    Spi(spi::Error),
+
+    /// A blocking operation timed out
+    Timeout(mrt::Timeout),
+
+    /// An error from the ultrasonic sensor helper
+    #[cfg(feature = "845")]
+    Ultrasonic(ultrasonic::Error),
+
+    /// An error from a USART
+    Usart(usart::Error),
+
+    /// An error while setting up USART delimiter matching
+    UsartDelimiter(usart::DelimiterError),
+
+    /// An error while injecting a fault into a USART
+    #[cfg(feature = "fault_injection")]
+    UsartFault(usart::FaultError),
+
+    /// An error from the USART packet receiver
+    UsartPacket(usart::PacketError),
+
+    /// An error while receiving a firmware image via XMODEM
+    Xmodem(xmodem::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Clock(error) => error.fmt(f),
+            Error::ClockCheck(error) => error.fmt(f),
+            Error::DataLogger(error) => error.fmt(f),
+            Error::Dma(error) => error.fmt(f),
+            Error::DmaQueue(error) => error.fmt(f),
+            Error::Fgen(error) => error.fmt(f),
+            Error::I2cBusClear(error) => error.fmt(f),
+            Error::I2cTransfer(error) => error.fmt(f),
+            Error::Iap(error) => error.fmt(f),
+            Error::MainClock(error) => error.fmt(f),
+            Error::Modbus(error) => error.fmt(f),
+            Error::Mrt(error) => error.fmt(f),
+            Error::PinInt(error) => error.fmt(f),
+            Error::Pmu(error) => error.fmt(f),
+            #[cfg(feature = "845")]
+            Error::Ppm(error) => error.fmt(f),
+            Error::Scheduler(error) => error.fmt(f),
+            #[cfg(feature = "845")]
+            Error::Servo(error) => error.fmt(f),
+            Error::Spi(error) => error.fmt(f),
+            Error::Timeout(error) => error.fmt(f),
+            #[cfg(feature = "845")]
+            Error::Ultrasonic(error) => error.fmt(f),
+            Error::Usart(error) => error.fmt(f),
+            Error::UsartDelimiter(error) => error.fmt(f),
+            #[cfg(feature = "fault_injection")]
+            Error::UsartFault(error) => error.fmt(f),
+            Error::UsartPacket(error) => error.fmt(f),
+            Error::Xmodem(error) => error.fmt(f),
+        }
+    }
+}
+
+macro_rules! from {
+    ($($(#[$attr:meta])* $error:ty => $variant:ident;)*) => {
+        $(
+            $(#[$attr])*
+            impl From<$error> for Error {
+                fn from(error: $error) -> Self {
+                    Error::$variant(error)
+                }
+            }
+        )*
+    };
+}
+
+from!(
+    syscon::clocksource::Error => Clock;
+    clock_check::Error => ClockCheck;
+    data_logger::Error => DataLogger;
+    dma::Error => Dma;
+    dma::queue::Error => DmaQueue;
+    fgen::Error => Fgen;
+    i2c::BusClearError => I2cBusClear;
+    i2c::TransferError => I2cTransfer;
+    iap::Error => Iap;
+    syscon::main_clock::Error => MainClock;
+    modbus::Error => Modbus;
+    mrt::Error => Mrt;
+    pinint::Error => PinInt;
+    pmu::Error => Pmu;
+    #[cfg(feature = "845")]
+    ppm::Error => Ppm;
+    scheduler::Error => Scheduler;
+    #[cfg(feature = "845")]
+    servo::Error => Servo;
+    spi::Error => Spi;
+    mrt::Timeout => Timeout;
+    #[cfg(feature = "845")]
+    ultrasonic::Error => Ultrasonic;
+    usart::Error => Usart;
+    usart::DelimiterError => UsartDelimiter;
+    #[cfg(feature = "fault_injection")]
+    usart::FaultError => UsartFault;
+    usart::PacketError => UsartPacket;
+    xmodem::Error => Xmodem;
+);
@@ -0,0 +1,228 @@
+//! Counting of external pulses, using the SCT
+//!
+//! Sensors like flow meters and anemometers output a pulse per unit of volume
+//! or per revolution. [`EventCounter`] counts the rising edges of such a
+//! signal in hardware, using the SCT as a single 32-bit counter that is
+//! clocked by the signal. No CPU time is spent per pulse.
+//!
+//! Optionally, a flag is set and an interrupt is requested, once a threshold
+//! number of pulses has been counted.
+//!
+//! The input signal is sampled by the system clock, so its frequency must be
+//! less than half the system clock frequency.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{event_counter::EventCounter, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (input, _) = swm
+//!     .movable_functions
+//!     .sct_pin0
+//!     .assign(swm.pins.pio0_17.into_swm_pin(), &mut swm_handle);
+//!
+//! let mut counter = EventCounter::new(p.SCT0, input, &mut syscon.handle);
+//!
+//! // Request an interrupt every 1000 pulses
+//! counter.set_threshold(Some(1000));
+//! counter.enable_interrupt();
+//!
+//! // Call this from the SCT interrupt handler, or periodically.
+//! let pulses = counter.take();
+//! ```
+//!
+//! The SCT is described in the user manual, chapter 10 (LPC82x), or chapter 16
+//! (LPC845).
+//!
+//! [`EventCounter`]: struct.EventCounter.html
+
+use crate::{
+    sct::{self, InputTrait, SCT},
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+/// The event that signals the threshold
+const THRESHOLD_EVENT: u8 = 1 << 0;
+
+/// Counts rising edges of an SCT input
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct EventCounter<I, P> {
+    sct: SCT,
+    input: swm::Function<I, Assigned<P>>,
+    base: u32,
+    threshold: Option<u32>,
+}
+
+impl<I, P> EventCounter<I, P>
+where
+    I: InputTrait,
+{
+    /// Start counting rising edges of `input`
+    ///
+    /// Enables the SCT clock and runs the SCT as a single 32-bit counter.
+    pub fn new(
+        sct: SCT,
+        input: swm::Function<I, Assigned<P>>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        syscon.enable_clock(&sct.sct);
+        sct::halt_all(&sct.sct);
+
+        // Sound, as we own the SCT and the input, and all values are valid
+        // according to the user manual.
+        unsafe {
+            // The counter is clocked by rising edges of the input, which are
+            // sampled by the system clock. CKSEL is `2 * n` for rising edges
+            // on input `n`.
+            sct.sct.config.write(|w| {
+                w.unify().unified_counter();
+                w.clkmode().sct_input_clock_mode();
+                w.cksel().bits(I::INDEX as u8 * 2)
+            });
+            sct.sct.event[0].state.write(|w| w.statemskn().bits(0));
+            sct.sct.evflag.write(|w| w.flag().bits(THRESHOLD_EVENT));
+
+            #[cfg(feature = "845")]
+            sct::select_input::<I>();
+        }
+
+        // Start the counter. This clears the HALT_L bit.
+        sct.sct.ctrl.write(|w| w.clrctr_l().set_bit());
+
+        EventCounter {
+            sct,
+            input,
+            base: 0,
+            threshold: None,
+        }
+    }
+
+    /// Return the number of edges counted since the last call to [`take`]
+    ///
+    /// [`take`]: #method.take
+    pub fn count(&self) -> u32 {
+        self.sct.sct.count.read().bits().wrapping_sub(self.base)
+    }
+
+    /// Return the number of edges counted since the last call and reset it
+    ///
+    /// The hardware counter keeps running, so no edges are missed, unless a
+    /// threshold is set. In that case, the counter is halted for a few clock
+    /// cycles, to update the threshold, and edges occurring during that time
+    /// are missed.
+    ///
+    /// Clears the threshold flag.
+    pub fn take(&mut self) -> u32 {
+        let count = self.sct.sct.count.read().bits();
+        let taken = count.wrapping_sub(self.base);
+        self.base = count;
+
+        self.arm();
+
+        taken
+    }
+
+    /// Set or remove the threshold
+    ///
+    /// Once the number of edges counted since the last call to [`take`]
+    /// reaches `threshold`, the threshold flag is set and, if enabled, the SCT
+    /// interrupt is requested. Both stay active until the next call to
+    /// [`take`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `threshold` is `Some(0)`.
+    ///
+    /// [`take`]: #method.take
+    pub fn set_threshold(&mut self, threshold: Option<u32>) {
+        assert!(threshold != Some(0));
+
+        self.threshold = threshold;
+        self.arm();
+    }
+
+    /// Indicates whether the threshold has been reached
+    pub fn is_threshold_reached(&self) -> bool {
+        match self.threshold {
+            Some(threshold) => {
+                self.sct.sct.evflag.read().flag().bits() & THRESHOLD_EVENT != 0
+                    || self.count() >= threshold
+            }
+            None => false,
+        }
+    }
+
+    /// Request the SCT interrupt, when the threshold is reached
+    ///
+    /// The interrupt needs to be unmasked in the NVIC separately.
+    pub fn enable_interrupt(&mut self) {
+        self.sct
+            .sct
+            .even
+            .write(|w| unsafe { w.ien().bits(THRESHOLD_EVENT) });
+    }
+
+    /// Don't request the SCT interrupt, when the threshold is reached
+    pub fn disable_interrupt(&mut self) {
+        self.sct.sct.even.write(|w| unsafe { w.ien().bits(0) });
+    }
+
+    /// Stop counting and release the resources used
+    ///
+    /// Disables the SCT clock.
+    pub fn free(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (SCT, swm::Function<I, Assigned<P>>) {
+        sct::halt_all(&self.sct.sct);
+
+        // Sound, as we own the SCT, and all values are valid according to the
+        // user manual.
+        unsafe {
+            self.sct.sct.event[0].state.write(|w| w.statemskn().bits(0));
+            self.sct.sct.even.write(|w| w.ien().bits(0));
+        }
+        syscon.disable_clock(&self.sct.sct);
+
+        (self.sct, self.input)
+    }
+
+    /// Point the threshold event at the current threshold
+    fn arm(&mut self) {
+        let sct = &self.sct.sct;
+
+        // Sound, as we own the SCT, and all values are valid according to the
+        // user manual.
+        unsafe {
+            sct.event[0].state.write(|w| w.statemskn().bits(0));
+            sct.evflag.write(|w| w.flag().bits(THRESHOLD_EVENT));
+
+            if let Some(threshold) = self.threshold {
+                // The match register can only be written while the counter is
+                // halted. See the description of the SCT's CTRL register in the
+                // user manual.
+                sct::halt_all(sct);
+                sct.sctmatch0()
+                    .write(|w| w.bits(self.base.wrapping_add(threshold)));
+                sct.ctrl.write(|w| w.bits(0));
+
+                sct.event[0].ctrl.write(|w| w.combmode().match_());
+                sct.event[0].state.write(|w| w.statemskn().bits(1 << 0));
+            }
+        }
+    }
+}
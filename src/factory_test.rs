@@ -0,0 +1,308 @@
+//! Scaffold for board manufacturing tests
+//!
+//! Boards are usually tested right after assembly, on a fixture that connects
+//! to the board's pins and watches what the firmware does. Instead of flashing
+//! a dedicated test firmware, the production firmware can enter a test mode
+//! at boot, for example if a strap pin is pulled low by the fixture (see
+//! [`requested`]).
+//!
+//! [`FactoryTest`] runs the individual tests and reports the results over a
+//! [`Console`], one line per result, in a format that is easy to parse for
+//! the test station:
+//!
+//! ``` text
+//! FACTORY TEST
+//! GPIO led0 TOGGLED
+//! USART usart1 OK
+//! I2C 0x52 FOUND
+//! I2C 0xa0 MISSING
+//! ADC vbat 2048 OK
+//! RESULT FAIL 1
+//! ```
+//!
+//! The tests that are run, and the peripherals they use, are up to the
+//! application. Available tests are:
+//!
+//! - [`gpio_sequence`], which toggles pins one after the other, for the
+//!   fixture to check
+//! - [`usart_echo`], which checks that a USART receives what it sends, through
+//!   a loopback on the fixture
+//! - [`i2c_scan`], which probes all addresses on an I2C bus and compares the
+//!   result with the expected devices
+//! - [`adc_channel`], which reads an ADC channel and checks the result against
+//!   a range
+//!
+//! This module is only available, if the `factory_test` feature is enabled.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     console::Console,
+//!     delay::Delay,
+//!     factory_test::{self, FactoryTest},
+//!     Peripherals,
+//! };
+//!
+//! // `console` is the USART that is connected to the test station
+//! fn run(console: &mut dyn Console) {
+//!     let p = Peripherals::take().unwrap();
+//!
+//!     let swm = p.SWM.split();
+//!     let mut delay = Delay::new(p.SYST);
+//!
+//!     let strap = swm.pins.pio0_4.into_gpio_pin(&p.GPIO).into_input();
+//!     let mut led0 = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_output();
+//!     let mut led1 = swm.pins.pio0_13.into_gpio_pin(&p.GPIO).into_output();
+//!
+//!     if !factory_test::requested(&strap) {
+//!         return;
+//!     }
+//!
+//!     let mut test = FactoryTest::new(console);
+//!
+//!     test.gpio_sequence(
+//!         &mut [("led0", &mut led0), ("led1", &mut led1)],
+//!         &mut delay,
+//!         500,
+//!     );
+//!
+//!     if test.finish() {
+//!         // Store that the board has passed
+//!     }
+//! }
+//! ```
+//!
+//! [`requested`]: fn.requested.html
+//! [`FactoryTest`]: struct.FactoryTest.html
+//! [`Console`]: ../console/trait.Console.html
+//! [`gpio_sequence`]: struct.FactoryTest.html#method.gpio_sequence
+//! [`usart_echo`]: struct.FactoryTest.html#method.usart_echo
+//! [`i2c_scan`]: struct.FactoryTest.html#method.i2c_scan
+//! [`adc_channel`]: struct.FactoryTest.html#method.adc_channel
+
+use core::{fmt::Write as _, ops::RangeInclusive};
+
+use embedded_hal::{
+    blocking::delay::DelayMs,
+    digital::v2::{InputPin, OutputPin},
+    timer::CountDown as _,
+};
+use nb::block;
+use void::{ResultVoidExt as _, Void};
+
+use crate::{
+    adc::{self, ADC},
+    console::Console,
+    i2c::{self, I2C},
+    init_state,
+    mrt::MrtChannel,
+    swm::{self, state::Assigned},
+};
+
+/// The pattern sent by the USART echo test
+///
+/// Contains alternating bit patterns, to catch baud rate mismatches.
+const ECHO_PATTERN: &[u8] = b"\x55\xaaFACTORY\x00\xff";
+
+/// The time to wait for each echoed character
+const ECHO_TIMEOUT_US: u32 = 10_000;
+
+/// The first and last 7-bit addresses that aren't reserved by the I2C spec
+const I2C_ADDRESSES: RangeInclusive<u8> = 0x08..=0x77;
+
+/// Check the strap pin that requests the factory test
+///
+/// Returns `true`, if the pin is low. Enable a pull-up on the pin, so the test
+/// is only requested, if the fixture pulls it low.
+pub fn requested(strap: &dyn InputPin<Error = Void>) -> bool {
+    strap.is_low().void_unwrap()
+}
+
+/// Runs manufacturing tests and reports their results
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct FactoryTest<'c> {
+    console: &'c mut dyn Console,
+    failures: u32,
+}
+
+impl<'c> FactoryTest<'c> {
+    /// Start the factory test
+    ///
+    /// Reports the start of the test over `console`.
+    pub fn new(console: &'c mut dyn Console) -> Self {
+        let mut test = FactoryTest {
+            console,
+            failures: 0,
+        };
+        test.report(format_args!("FACTORY TEST"));
+
+        test
+    }
+
+    /// Toggle pins one after the other
+    ///
+    /// Sets each pin high for `ms` milliseconds, then low again, before moving
+    /// on to the next one, and reports each pin by its name. Whether the pins
+    /// actually toggle can't be checked by the firmware, so this is up to the
+    /// fixture.
+    pub fn gpio_sequence(
+        &mut self,
+        pins: &mut [(&str, &mut dyn OutputPin<Error = Void>)],
+        delay: &mut dyn DelayMs<u16>,
+        ms: u16,
+    ) {
+        for (name, pin) in pins.iter_mut() {
+            pin.set_high().void_unwrap();
+            delay.delay_ms(ms);
+            pin.set_low().void_unwrap();
+
+            self.report(format_args!("GPIO {} TOGGLED", name));
+        }
+    }
+
+    /// Check that a USART receives what it sends
+    ///
+    /// Requires the USART's TX and RX to be connected by the fixture. Sends a
+    /// test pattern, and compares what is received. `timer` limits the time
+    /// spent waiting for each character.
+    ///
+    /// Returns whether the test has passed.
+    pub fn usart_echo(
+        &mut self,
+        name: &str,
+        usart: &mut dyn Console,
+        timer: &mut MrtChannel,
+    ) -> bool {
+        // Discard anything that was received before the test.
+        while usart.read().is_ok() {}
+
+        let mut passed = true;
+        for &word in ECHO_PATTERN {
+            // Can't fail, as the error type is `Void`.
+            let _ = block!(usart.write(word));
+
+            // Can't fail, as the timeout is far below the maximum.
+            let _ = timer.start_us(ECHO_TIMEOUT_US);
+            let echo = loop {
+                match usart.read() {
+                    Ok(echo) => break Some(echo),
+                    Err(nb::Error::WouldBlock) => {}
+                    Err(nb::Error::Other(_)) => break None,
+                }
+
+                if timer.wait().is_ok() {
+                    break None;
+                }
+            };
+
+            if echo != Some(word) {
+                passed = false;
+                break;
+            }
+        }
+
+        self.result(format_args!("USART {}", name), passed)
+    }
+
+    /// Probe all addresses on an I2C bus
+    ///
+    /// Writes an empty message to every address that isn't reserved, and
+    /// reports all devices that acknowledge. Addresses are in the format
+    /// [`I2C::try_write`] expects, with the R/W bit as `0`. Every device in
+    /// `expected` that doesn't acknowledge is reported as missing.
+    ///
+    /// Returns whether all expected devices have been found.
+    ///
+    /// [`I2C::try_write`]: ../i2c/struct.I2C.html#method.try_write
+    pub fn i2c_scan<I, Pins>(
+        &mut self,
+        i2c: &mut I2C<I, init_state::Enabled<Pins>>,
+        expected: &[u8],
+    ) -> bool
+    where
+        I: i2c::Instance,
+    {
+        let mut passed = true;
+
+        for address in I2C_ADDRESSES.map(|address| address << 1) {
+            let found = i2c.try_write(address, &[]).is_ok();
+
+            if found {
+                self.report(format_args!("I2C {:#04x} FOUND", address));
+            } else if expected.contains(&address) {
+                self.report(format_args!("I2C {:#04x} MISSING", address));
+                passed = false;
+            }
+        }
+
+        if !passed {
+            self.failures += 1;
+        }
+
+        passed
+    }
+
+    /// Read an ADC channel, and check the result against a range
+    ///
+    /// Reports the 12-bit result by the channel's name.
+    ///
+    /// Returns whether the result is within `limits`.
+    pub fn adc_channel<C>(
+        &mut self,
+        name: &str,
+        adc: &mut ADC,
+        channel: &swm::Function<C, Assigned<C::Pin>>,
+        limits: RangeInclusive<u16>,
+    ) -> bool
+    where
+        C: adc::Channel,
+    {
+        let (value,) = adc.scan((channel,));
+
+        self.result(
+            format_args!("ADC {} {}", name, value),
+            limits.contains(&value),
+        )
+    }
+
+    /// Return the number of tests that have failed so far
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Report the overall result, and end the factory test
+    ///
+    /// Returns whether all tests have passed.
+    pub fn finish(mut self) -> bool {
+        let failures = self.failures;
+
+        if failures == 0 {
+            self.report(format_args!("RESULT PASS"));
+        } else {
+            self.report(format_args!("RESULT FAIL {}", failures));
+        }
+
+        failures == 0
+    }
+
+    /// Report the result of a test, and count it, if it failed
+    fn result(&mut self, args: core::fmt::Arguments, passed: bool) -> bool {
+        if passed {
+            self.report(format_args!("{} OK", args));
+        } else {
+            self.failures += 1;
+            self.report(format_args!("{} FAIL", args));
+        }
+
+        passed
+    }
+
+    fn report(&mut self, args: core::fmt::Arguments) {
+        // Can't fail, as writing to a console never fails.
+        let _ = write!(self.console, "{}\r\n", args);
+    }
+}
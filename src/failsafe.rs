@@ -0,0 +1,240 @@
+//! Fail-safe teardown of outputs, for use in panic handlers
+//!
+//! Applications that control motors, heaters, or similar loads must not leave
+//! them running, if the firmware panics. This module allows the application to
+//! register, ahead of time, which outputs need to be switched off and which
+//! level every GPIO pin needs to be put into. [`teardown`] then applies that
+//! configuration from a panic or fault handler.
+//!
+//! [`teardown`] writes directly to the peripheral registers, using the masks
+//! registered in the [`Config`]. It doesn't need access to any HAL API, doesn't
+//! allocate, can't panic, and doesn't produce any output. This means it can be
+//! called first thing in a panic handler, before any output is sent over
+//! semihosting or RTT, which can take a long time or even block forever, if no
+//! debugger is attached.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     failsafe::{self, Config, Level},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let gpio = p.GPIO;
+//! #[cfg(feature = "845")]
+//! let gpio = p.GPIO.enable(&mut syscon.handle);
+//!
+//! let motor_enable = swm.pins.pio0_12.into_gpio_pin(&gpio).into_output();
+//!
+//! failsafe::register(Config::new().pin(&motor_enable, Level::Low));
+//!
+//! // In the panic handler:
+//! failsafe::teardown();
+//! ```
+//!
+//! [`teardown`]: fn.teardown.html
+//! [`Config`]: struct.Config.html
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    gpio::direction,
+    pac, sct,
+    swm::{pin_state, Pin, PinTrait},
+};
+
+/// The number of GPIO ports
+#[cfg(feature = "82x")]
+const PORTS: usize = 1;
+/// The number of GPIO ports
+#[cfg(feature = "845")]
+const PORTS: usize = 2;
+
+static CONFIG: Mutex<Cell<Option<Config>>> = Mutex::new(Cell::new(None));
+
+/// Register the configuration that is applied by [`teardown`]
+///
+/// Replaces any configuration that has been registered before.
+///
+/// [`teardown`]: fn.teardown.html
+pub fn register(config: Config) {
+    interrupt::free(|cs| CONFIG.borrow(cs).set(Some(config)));
+}
+
+/// Put all registered outputs into their fail-safe state
+///
+/// Does nothing, if no configuration has been registered using [`register`].
+/// Disables interrupts, so the application can't reenable any of the outputs.
+///
+/// The PWM outputs are switched off first. After that, the registered GPIO
+/// pins are driven to their fail-safe levels.
+///
+/// [`register`]: fn.register.html
+pub fn teardown() {
+    interrupt::disable();
+
+    // Sound, as interrupts are disabled.
+    let cs = unsafe { &interrupt::CriticalSection::new() };
+    let config = match CONFIG.borrow(cs).get() {
+        Some(config) => config,
+        None => return,
+    };
+
+    // Sound, as we're tearing down the whole application. Any driver that
+    // might still have access to these peripherals will never run again.
+    unsafe {
+        if let Some(levels) = config.sct_outputs {
+            let sct = &*pac::SCT0::ptr();
+            sct::halt_all(sct);
+            sct.output.write(|w| w.bits(levels));
+        }
+
+        #[cfg(feature = "845")]
+        stop_ctimer(&config);
+
+        let gpio = &*pac::GPIO::ptr();
+        for port in 0..PORTS {
+            #[cfg(feature = "82x")]
+            let (set, clr, dirset) = (&gpio.set0, &gpio.clr0, &gpio.dirset0);
+            #[cfg(feature = "845")]
+            let (set, clr, dirset) =
+                (&gpio.set[port], &gpio.clr[port], &gpio.dirset[port]);
+
+            set.write(|w| w.bits(config.high[port]));
+            clr.write(|w| w.bits(config.low[port]));
+            dirset.write(|w| w.bits(config.high[port] | config.low[port]));
+        }
+    }
+}
+
+/// Stop the CTimer and drive its PWM outputs low, if configured
+///
+/// # Safety
+///
+/// Must only be called from [`teardown`].
+///
+/// [`teardown`]: fn.teardown.html
+#[cfg(feature = "845")]
+unsafe fn stop_ctimer(config: &Config) {
+    if !config.ctimer_pwm {
+        return;
+    }
+
+    // Disable PWM mode, which hands control of the outputs to the external
+    // match register, then drive them low from there.
+    let ct = &*pac::CTIMER0::ptr();
+    ct.tcr.write(|w| w.cen().clear_bit());
+    ct.pwmc.reset();
+    ct.emr.reset();
+}
+
+/// The outputs that [`teardown`] puts into their fail-safe state
+///
+/// Use the builder methods to add outputs, then pass the configuration to
+/// [`register`].
+///
+/// [`teardown`]: fn.teardown.html
+/// [`register`]: fn.register.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    low: [u32; PORTS],
+    high: [u32; PORTS],
+
+    sct_outputs: Option<u32>,
+
+    #[cfg(feature = "845")]
+    ctimer_pwm: bool,
+}
+
+impl Config {
+    /// Create an empty configuration
+    pub const fn new() -> Self {
+        Config {
+            low: [0; PORTS],
+            high: [0; PORTS],
+
+            sct_outputs: None,
+
+            #[cfg(feature = "845")]
+            ctimer_pwm: false,
+        }
+    }
+
+    /// Add a GPIO output
+    ///
+    /// The pin is driven to `level` by [`teardown`]. If the pin is no longer
+    /// in the GPIO output state by then, it is switched back to it.
+    ///
+    /// [`teardown`]: fn.teardown.html
+    pub fn pin<T>(
+        mut self,
+        _: &Pin<T, pin_state::Gpio<direction::Output>>,
+        level: Level,
+    ) -> Self
+    where
+        T: PinTrait,
+    {
+        match level {
+            Level::Low => {
+                self.low[T::PORT] |= T::MASK;
+                self.high[T::PORT] &= !T::MASK;
+            }
+            Level::High => {
+                self.high[T::PORT] |= T::MASK;
+                self.low[T::PORT] &= !T::MASK;
+            }
+        }
+
+        self
+    }
+
+    /// Halt the SCT and set its outputs
+    ///
+    /// There is no HAL API for the SCT yet, so this works on the raw
+    /// registers. [`teardown`] halts the SCT and writes `levels` to its OUTPUT
+    /// register, where bit n is the level of output n.
+    ///
+    /// [`teardown`]: fn.teardown.html
+    pub fn sct_outputs(mut self, levels: u32) -> Self {
+        self.sct_outputs = Some(levels);
+        self
+    }
+
+    /// Stop the CTimer and drive its PWM outputs low
+    ///
+    /// See [`CTimer::start_pwm`].
+    ///
+    /// [`CTimer::start_pwm`]: ../ctimer/struct.CTimer.html#method.start_pwm
+    #[cfg(feature = "845")]
+    pub fn ctimer_pwm(mut self) -> Self {
+        self.ctimer_pwm = true;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The fail-safe level of a GPIO pin
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Level {
+    /// The pin is driven low
+    Low,
+
+    /// The pin is driven high
+    High,
+}
@@ -0,0 +1,271 @@
+//! Square wave output of a requested frequency
+//!
+//! [`Fgen`] outputs a square wave with a 50 % duty cycle on a pin, for example
+//! to clock an external device, or to drive a piezo buzzer. It computes the
+//! timer's dividers from the requested frequency and reports the frequency
+//! that was actually achieved, which can differ slightly due to rounding.
+//!
+//! The square wave can be generated by either half of the SCT (see [`sct`]),
+//! using any SCT output, or (on LPC845) by the CTimer, using any of its match
+//! outputs.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{fgen::Fgen, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (output, _) = swm
+//!     .movable_functions
+//!     .sct_out0
+//!     .assign(swm.pins.pio0_16.into_swm_pin(), &mut swm_handle);
+//!
+//! let counters = p.SCT0.split(&mut syscon.handle);
+//!
+//! let mut fgen = Fgen::new(counters.low, output, &mut syscon.handle);
+//! let hz = fgen.start(440).unwrap();
+//! ```
+//!
+//! [`Fgen`]: struct.Fgen.html
+//! [`sct`]: ../sct/index.html
+
+use core::fmt;
+
+use crate::{
+    sct,
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+#[cfg(feature = "845")]
+use crate::ctimer::{self, CTimer};
+
+/// Outputs a square wave on a pin
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Fgen<T, F, P> {
+    timer: T,
+    function: swm::Function<F, Assigned<P>>,
+    clock: u32,
+    hz: Option<u32>,
+}
+
+impl<T, F, P> Fgen<T, F, P>
+where
+    T: Timer<F>,
+{
+    /// Create the frequency generator
+    ///
+    /// Enables the timer's clock, if necessary. The output stays low until
+    /// [`start`] is called.
+    ///
+    /// The frequency of the timer's clock is taken from
+    /// [`syscon::system_clock_hz`]. If the system clock is changed, the
+    /// frequency generator needs to be created anew.
+    ///
+    /// [`start`]: #method.start
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn new(
+        mut timer: T,
+        function: swm::Function<F, Assigned<P>>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        timer.enable(syscon);
+
+        Fgen {
+            timer,
+            function,
+            clock: syscon::system_clock_hz(),
+            hz: None,
+        }
+    }
+
+    /// Start outputting a square wave of frequency `hz`
+    ///
+    /// If the square wave is already being output, it is restarted with the
+    /// new frequency. Doesn't require access to SYSCON, so it can be called
+    /// from an interrupt handler.
+    ///
+    /// Returns the achieved frequency, rounded to the nearest Hz.
+    ///
+    /// Returns [`Error::OutOfRange`], if `hz` is `0`, higher than half the
+    /// system clock frequency, or too low for the timer. The output is left
+    /// unchanged in that case.
+    ///
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    pub fn start(&mut self, hz: u32) -> Result<u32, Error> {
+        let (prescaler, period) =
+            dividers(self.clock, hz, T::MAX_PRESCALER, T::MAX_PERIOD)
+                .ok_or(Error::OutOfRange)?;
+
+        self.timer.start(prescaler, period);
+
+        let ticks = 2 * prescaler * period;
+        let achieved = (self.clock + ticks / 2) / ticks;
+        self.hz = Some(achieved);
+
+        Ok(achieved)
+    }
+
+    /// Stop outputting the square wave
+    ///
+    /// The output is left low.
+    pub fn stop(&mut self) {
+        if self.hz.take().is_some() {
+            self.timer.stop();
+        }
+    }
+
+    /// Return the achieved frequency, if a square wave is being output
+    pub fn frequency(&self) -> Option<u32> {
+        self.hz
+    }
+
+    /// Stop outputting the square wave and release the resources used
+    ///
+    /// Disables the timer's clock, if it was enabled by [`new`].
+    ///
+    /// [`new`]: #method.new
+    pub fn free(
+        mut self,
+        syscon: &mut syscon::Handle,
+    ) -> (T, swm::Function<F, Assigned<P>>) {
+        self.stop();
+        self.timer.disable(syscon);
+        (self.timer, self.function)
+    }
+}
+
+/// Compute the dividers for a square wave of frequency `hz`
+///
+/// Returns the division factors of the prescaler and the period, which toggle
+/// the output every `prescaler * period` clock cycles. The prescaler is kept
+/// as small as possible, for the best resolution.
+fn dividers(
+    clock: u32,
+    hz: u32,
+    max_prescaler: u32,
+    max_period: u32,
+) -> Option<(u32, u32)> {
+    if hz == 0 {
+        return None;
+    }
+
+    // The number of clock cycles between two toggles of the output, rounded
+    // to the nearest integer.
+    let ticks = clock.checked_add(hz)? / hz.checked_mul(2)?;
+    if ticks == 0 {
+        return None;
+    }
+
+    let mut prescaler = ticks / max_period;
+    if ticks % max_period != 0 {
+        prescaler += 1;
+    }
+    if prescaler > max_prescaler {
+        return None;
+    }
+
+    let period = (ticks + prescaler / 2) / prescaler;
+
+    Some((prescaler, period))
+}
+
+/// An error that can occur while starting a frequency generator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The requested frequency can't be generated with the timer
+    OutOfRange,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfRange => f.write_str("frequency out of range"),
+        }
+    }
+}
+
+/// Implemented for timers that can toggle output function `F`
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Timer<F> {
+    /// The largest division factor of the prescaler
+    const MAX_PRESCALER: u32;
+
+    /// The largest number of ticks between two toggles of the output
+    const MAX_PERIOD: u32;
+
+    /// Enable the timer's clock, if necessary
+    fn enable(&mut self, syscon: &mut syscon::Handle);
+
+    /// Disable the timer's clock, if it was enabled by `enable`
+    fn disable(&mut self, syscon: &mut syscon::Handle);
+
+    /// Toggle the output every `prescaler * period` clock cycles
+    fn start(&mut self, prescaler: u32, period: u32);
+
+    /// Stop toggling the output and leave it low
+    fn stop(&mut self);
+}
+
+impl<C, O> Timer<O> for sct::Counter<C>
+where
+    C: sct::Half,
+    O: sct::OutputTrait,
+{
+    const MAX_PRESCALER: u32 = 256;
+    const MAX_PERIOD: u32 = 0xffff;
+
+    // The SCT clock is enabled by `SCT::split`.
+    fn enable(&mut self, _: &mut syscon::Handle) {}
+
+    fn disable(&mut self, _: &mut syscon::Handle) {}
+
+    fn start(&mut self, prescaler: u32, period: u32) {
+        self.start_square_wave::<O>((prescaler - 1) as u8, period as u16);
+    }
+
+    fn stop(&mut self) {
+        self.release_output::<O>();
+    }
+}
+
+#[cfg(feature = "845")]
+impl<M> Timer<M> for CTimer
+where
+    M: ctimer::MatchOutput,
+{
+    const MAX_PRESCALER: u32 = u32::MAX;
+    const MAX_PERIOD: u32 = u32::MAX;
+
+    fn enable(&mut self, syscon: &mut syscon::Handle) {
+        self.enable_clock(syscon);
+    }
+
+    fn disable(&mut self, syscon: &mut syscon::Handle) {
+        self.disable_clock(syscon);
+    }
+
+    fn start(&mut self, prescaler: u32, period: u32) {
+        self.start_toggle::<M>(prescaler - 1, period);
+    }
+
+    fn stop(&mut self) {
+        self.stop_toggle();
+    }
+}
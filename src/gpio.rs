@@ -29,12 +29,14 @@
 //! [`swm`]: ../swm/index.html
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
+use core::{marker::PhantomData, ptr};
+
 use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin};
 use void::Void;
 
 use crate::{
-    init_state, pac,
-    swm::{pin_state, Pin, PinTrait},
+    dma, init_state, pac,
+    swm::{pin_state, Pin, PinId, PinTrait},
     syscon,
 };
 
@@ -134,6 +136,60 @@ impl GPIO<init_state::Enabled> {
             _state: init_state::Disabled,
         }
     }
+
+    /// Capture the direction and output level of all GPIO pins
+    ///
+    /// The returned [`SavedState`] can be passed to [`restore_state`] later,
+    /// to undo any temporary reconfiguration of the pins in between, for
+    /// example for a self-test.
+    ///
+    /// Only the GPIO registers are captured. Switch matrix assignments and
+    /// IOCON settings are not.
+    ///
+    /// [`SavedState`]: struct.SavedState.html
+    /// [`restore_state`]: #method.restore_state
+    pub fn save_state(&self) -> SavedState {
+        let mut state = SavedState {
+            direction: [0; target::PORTS],
+            output: [0; target::PORTS],
+        };
+
+        for port in 0..target::PORTS {
+            #[cfg(feature = "82x")]
+            let (dir, set) = (&self.gpio.dir0, &self.gpio.set0);
+            #[cfg(feature = "845")]
+            let (dir, set) = (&self.gpio.dir[port], &self.gpio.set[port]);
+
+            // Reading SET returns the output register, regardless of the
+            // pin's direction. See user manual, section 9.6.
+            state.direction[port] = dir.read().bits();
+            state.output[port] = set.read().bits();
+        }
+
+        state
+    }
+
+    /// Restore the direction and output level of all GPIO pins
+    ///
+    /// Requires a mutable reference, which proves that no GPIO [`Pin`]s exist
+    /// that could be affected. The output levels are restored before the
+    /// directions, so pins that become outputs immediately drive their saved
+    /// level.
+    ///
+    /// [`Pin`]: ../swm/struct.Pin.html
+    pub fn restore_state(&mut self, state: &SavedState) {
+        for port in 0..target::PORTS {
+            #[cfg(feature = "82x")]
+            let (dir, pin) = (&self.gpio.dir0, &self.gpio.pin0);
+            #[cfg(feature = "845")]
+            let (dir, pin) = (&self.gpio.dir[port], &self.gpio.pin[port]);
+
+            // Writing PIN loads the output register. See user manual, section
+            // 9.6.
+            pin.write(|w| unsafe { w.bits(state.output[port]) });
+            dir.write(|w| unsafe { w.bits(state.direction[port]) });
+        }
+    }
 }
 
 impl<State> GPIO<State> {
@@ -154,6 +210,38 @@ impl<State> GPIO<State> {
     }
 }
 
+/// The direction and output level of all GPIO pins
+///
+/// Returned by [`GPIO::save_state`].
+///
+/// [`GPIO::save_state`]: struct.GPIO.html#method.save_state
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SavedState {
+    direction: [u32; target::PORTS],
+    output: [u32; target::PORTS],
+}
+
+impl SavedState {
+    /// Return the direction bits of a port, with `1` meaning output
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `port` doesn't exist on the target.
+    pub fn direction(&self, port: usize) -> u32 {
+        self.direction[port]
+    }
+
+    /// Return the output bits of a port, with `1` meaning high
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `port` doesn't exist on the target.
+    pub fn output(&self, port: usize) -> u32 {
+        self.output[port]
+    }
+}
+
 impl<'gpio, T, D> Pin<T, pin_state::Gpio<'gpio, D>>
 where
     T: PinTrait,
@@ -168,6 +256,10 @@ where
     /// Consumes the pin instance and returns a new instance that is in output
     /// mode, making the methods to set the output level available.
     ///
+    /// The output level is left as it was, which means the pin might briefly
+    /// output the wrong level. Use [`into_output_high`] or [`into_output_low`]
+    /// to prevent this.
+    ///
     /// # Example
     ///
     /// ``` no_run
@@ -187,6 +279,9 @@ where
     /// pin.set_high();
     /// pin.set_low();
     /// ```
+    ///
+    /// [`into_output_high`]: #method.into_output_high
+    /// [`into_output_low`]: #method.into_output_low
     pub fn into_output(
         self,
     ) -> Pin<T, pin_state::Gpio<'gpio, direction::Output>> {
@@ -203,6 +298,38 @@ where
             },
         }
     }
+
+    /// Set pin direction to output, with an initial level of HIGH
+    ///
+    /// Works like [`into_output`], but sets the output level to HIGH before
+    /// changing the pin direction. This makes sure that the pin never outputs
+    /// the wrong level, which is important for chip-select or enable lines.
+    ///
+    /// [`into_output`]: #method.into_output
+    pub fn into_output_high(
+        self,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Output>> {
+        self.state.registers.set[T::PORT]
+            .write(|w| unsafe { w.setp().bits(T::MASK) });
+
+        self.into_output()
+    }
+
+    /// Set pin direction to output, with an initial level of LOW
+    ///
+    /// Works like [`into_output`], but sets the output level to LOW before
+    /// changing the pin direction. This makes sure that the pin never outputs
+    /// the wrong level, which is important for chip-select or enable lines.
+    ///
+    /// [`into_output`]: #method.into_output
+    pub fn into_output_low(
+        self,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Output>> {
+        self.state.registers.clr[T::PORT]
+            .write(|w| unsafe { w.clrp().bits(T::MASK) });
+
+        self.into_output()
+    }
 }
 
 impl<'gpio, T> OutputPin for Pin<T, pin_state::Gpio<'gpio, direction::Output>>
@@ -341,6 +468,127 @@ where
     }
 }
 
+impl<'gpio, T, D> Pin<T, pin_state::Gpio<'gpio, D>>
+where
+    T: PinTrait,
+    D: direction::Direction,
+{
+    /// Set pin direction to input, with the internal pull-up resistor enabled
+    ///
+    /// Configures the pin's IOCON register, then sets the pin direction to
+    /// input. This is the usual configuration for a button that connects the
+    /// pin to ground.
+    ///
+    /// The IOCON clock must be enabled, using [`syscon::Handle::enable_clock`].
+    ///
+    /// # Limitations
+    ///
+    /// On LPC82x, PIO0_10 and PIO0_11 are true open-drain pins without pull
+    /// resistors. Calling this method for them has no effect on the resistors.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{prelude::*, Peripherals};
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut syscon = p.SYSCON.split();
+    /// let swm = p.SWM.split();
+    ///
+    /// syscon.handle.enable_clock(&p.IOCON);
+    ///
+    /// let button = swm.pins.pio0_12
+    ///     .into_gpio_pin(&p.GPIO)
+    ///     .into_pull_up_input(&p.IOCON);
+    ///
+    /// if button.is_low().unwrap() {
+    ///     // The button is pressed
+    /// }
+    /// ```
+    ///
+    /// [`syscon::Handle::enable_clock`]: ../syscon/struct.Handle.html#method.enable_clock
+    pub fn into_pull_up_input(
+        self,
+        iocon: &pac::IOCON,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Input>> {
+        self.into_input_with_pull(iocon, Pull::Up)
+    }
+
+    /// Set pin direction to input, with the internal pull-down resistor
+    /// enabled
+    ///
+    /// Works like [`into_pull_up_input`], but enables the pull-down resistor
+    /// instead.
+    ///
+    /// [`into_pull_up_input`]: #method.into_pull_up_input
+    pub fn into_pull_down_input(
+        self,
+        iocon: &pac::IOCON,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Input>> {
+        self.into_input_with_pull(iocon, Pull::Down)
+    }
+
+    /// Set pin direction to input, with the internal resistors disabled
+    ///
+    /// Works like [`into_pull_up_input`], but disables both resistors. The
+    /// pin's level is undefined, unless it is driven externally.
+    ///
+    /// [`into_pull_up_input`]: #method.into_pull_up_input
+    pub fn into_floating_input(
+        self,
+        iocon: &pac::IOCON,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Input>> {
+        self.into_input_with_pull(iocon, Pull::None)
+    }
+
+    /// Set pin direction to input, with the given resistor configuration
+    ///
+    /// Works like [`into_pull_up_input`], but the resistor configuration is
+    /// selected at runtime.
+    ///
+    /// [`into_pull_up_input`]: #method.into_pull_up_input
+    pub fn into_input_with_pull(
+        self,
+        iocon: &pac::IOCON,
+        pull: Pull,
+    ) -> Pin<T, pin_state::Gpio<'gpio, direction::Input>> {
+        // Enabling the resistor before changing the direction makes sure the
+        // input never floats.
+        set_pull(iocon, PinId::of::<T>(), pull);
+
+        self.state.registers.dirclr[T::PORT]
+            .write(|w| unsafe { w.dirclrp().bits(T::MASK) });
+
+        Pin {
+            ty: self.ty,
+
+            state: pin_state::Gpio {
+                registers: self.state.registers,
+                _direction: direction::Input,
+            },
+        }
+    }
+}
+
+/// The configuration of a pin's internal resistors
+///
+/// Used by [`Pin::into_input_with_pull`].
+///
+/// [`Pin::into_input_with_pull`]: ../swm/struct.Pin.html#method.into_input_with_pull
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Pull {
+    /// Both resistors disabled
+    None,
+
+    /// Pull-down resistor enabled
+    Down,
+
+    /// Pull-up resistor enabled
+    Up,
+}
+
 impl<'gpio, T> InputPin for Pin<T, pin_state::Gpio<'gpio, direction::Input>>
 where
     T: PinTrait,
@@ -384,6 +632,267 @@ where
     }
 }
 
+impl<'gpio, T, D> Pin<T, pin_state::Gpio<'gpio, D>>
+where
+    T: PinTrait,
+    D: direction::Direction,
+{
+    /// Read the levels of all pins in this pin's port
+    ///
+    /// Reads the PIN register of the port this pin belongs to, returning the
+    /// levels of all of that port's pins with a single register access. Use
+    /// [`Pin::mask`] to test the level of specific pins in the returned value.
+    ///
+    /// This is useful for bit-banged protocols, where reading multiple pins
+    /// one after the other would be too slow, or not precise enough.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc82x_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let swm = p.SWM.split();
+    ///
+    /// let clock = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_input();
+    /// let data = swm.pins.pio0_13.into_gpio_pin(&p.GPIO).into_input();
+    ///
+    /// let port = clock.read_port();
+    /// if port & clock.mask() != 0 {
+    ///     let bit = port & data.mask() != 0;
+    /// }
+    /// ```
+    ///
+    /// [`Pin::mask`]: #method.mask
+    #[inline(always)]
+    pub fn read_port(&self) -> u32 {
+        self.state.registers.pin[T::PORT].read().port().bits()
+    }
+
+    /// Return this pin's bit mask within the value returned by [`read_port`]
+    ///
+    /// [`read_port`]: #method.read_port
+    #[inline(always)]
+    pub fn mask(&self) -> u32 {
+        T::MASK
+    }
+}
+
+impl<'gpio, T> Pin<T, pin_state::Gpio<'gpio, direction::Output>>
+where
+    T: PinTrait,
+{
+    /// Return this pin's word pin register
+    ///
+    /// Every GPIO pin has a word pin register (W0_x, W1_x), which reads as
+    /// `0xffff_ffff`, if the pin is high, and as `0`, if it is low. Writing `0`
+    /// sets the pin low, writing any other value sets it high.
+    ///
+    /// This allows setting the pin from a computed value without branching,
+    /// and makes the pin usable as a DMA destination, for example for
+    /// waveform generation.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc82x_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let swm = p.SWM.split();
+    ///
+    /// let mut pin = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_output();
+    ///
+    /// let value: u32 = 0b0100;
+    /// pin.word_register().write(value & 0b0100);
+    /// ```
+    pub fn word_register(&mut self) -> WordRegister {
+        let address = pac::GPIO::ptr() as usize
+            + 0x1000
+            + T::PORT * 0x80
+            + T::ID as usize * 4;
+
+        WordRegister {
+            ptr: address as *mut u32,
+            _pin: PhantomData,
+        }
+    }
+
+    /// Return this pin's byte pin register
+    ///
+    /// Every GPIO pin has a byte pin register (B0_x, B1_x), which reads as `1`,
+    /// if the pin is high, and as `0`, if it is low. Writing `0` sets the pin
+    /// low, writing `1` sets it high.
+    pub fn byte_register(&mut self) -> ByteRegister {
+        let address =
+            pac::GPIO::ptr() as usize + T::PORT * 0x20 + T::ID as usize;
+
+        ByteRegister {
+            ptr: address as *mut u8,
+            _pin: PhantomData,
+        }
+    }
+}
+
+/// A GPIO pin's word pin register
+///
+/// Returned by [`Pin::word_register`].
+///
+/// [`Pin::word_register`]: ../swm/struct.Pin.html#method.word_register
+pub struct WordRegister<'pin> {
+    ptr: *mut u32,
+    _pin: PhantomData<&'pin mut ()>,
+}
+
+impl WordRegister<'_> {
+    /// Set the pin low, if `value` is `0`, high otherwise
+    #[inline(always)]
+    pub fn write(&mut self, value: u32) {
+        // Sound, as the address is valid and we have exclusive access to the
+        // pin.
+        unsafe { ptr::write_volatile(self.ptr, value) }
+    }
+
+    /// Return `0xffff_ffff`, if the pin is high, `0` otherwise
+    #[inline(always)]
+    pub fn read(&self) -> u32 {
+        // Sound, as the address is valid.
+        unsafe { ptr::read_volatile(self.ptr) }
+    }
+
+    /// Return the address of the register
+    ///
+    /// Can be used to access the register from outside of this API, for
+    /// example as a DMA destination.
+    pub fn address(&self) -> *mut u32 {
+        self.ptr
+    }
+}
+
+impl dma::Dest for WordRegister<'_> {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // Writes to the pin register take effect immediately. There's nothing
+        // to wait for.
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        // The DMA controller writes single bytes, which is enough, as any
+        // non-zero value sets the pin high.
+        self.ptr as *mut u8
+    }
+}
+
+/// A GPIO pin's byte pin register
+///
+/// Returned by [`Pin::byte_register`].
+///
+/// [`Pin::byte_register`]: ../swm/struct.Pin.html#method.byte_register
+pub struct ByteRegister<'pin> {
+    ptr: *mut u8,
+    _pin: PhantomData<&'pin mut ()>,
+}
+
+impl ByteRegister<'_> {
+    /// Set the pin low, if `value` is `0`, high otherwise
+    #[inline(always)]
+    pub fn write(&mut self, value: u8) {
+        // Sound, as the address is valid and we have exclusive access to the
+        // pin.
+        unsafe { ptr::write_volatile(self.ptr, value) }
+    }
+
+    /// Return `1`, if the pin is high, `0` otherwise
+    #[inline(always)]
+    pub fn read(&self) -> u8 {
+        // Sound, as the address is valid.
+        unsafe { ptr::read_volatile(self.ptr) }
+    }
+
+    /// Return the address of the register
+    ///
+    /// Can be used to access the register from outside of this API, for
+    /// example as a DMA destination.
+    pub fn address(&self) -> *mut u8 {
+        self.ptr
+    }
+}
+
+impl dma::Dest for ByteRegister<'_> {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // Writes to the pin register take effect immediately. There's nothing
+        // to wait for.
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+}
+
+/// Configure a pin's internal resistors, regardless of its state
+///
+/// Callers must have exclusive access to the pin.
+pub(crate) fn set_pull(_iocon: &pac::IOCON, pin: PinId, pull: Pull) {
+    let offset =
+        target::IOCON_OFFSETS[pin.port as usize][pin.number as usize] as usize;
+    let address = (pac::IOCON::ptr() as usize + offset) as *mut u32;
+
+    // The MODE field, bits 4:3 of the IOCON register. See user manual, section
+    // 8.5.
+    let mode = match pull {
+        Pull::None => 0b00,
+        Pull::Down => 0b01,
+        Pull::Up => 0b10,
+    };
+
+    // Sound, as the address is this pin's IOCON register, and the caller has
+    // exclusive access to the pin.
+    unsafe {
+        let value = ptr::read_volatile(address);
+        ptr::write_volatile(address, value & !(0b11 << 3) | mode << 3);
+    }
+}
+
+/// Set a pin's direction to input, regardless of its state
+///
+/// Callers must have exclusive access to the pin.
+pub(crate) fn set_input(gpio: &GPIO, pin: PinId) {
+    #[cfg(feature = "82x")]
+    let dirclr = &gpio.gpio.dirclr0;
+    #[cfg(feature = "845")]
+    let dirclr = &gpio.gpio.dirclr[pin.port as usize];
+
+    dirclr.write(|w| unsafe { w.dirclrp().bits(1 << pin.number) });
+}
+
+/// Collect the registers of a GPIO port for a [`debug_dump`] snapshot
+///
+/// [`debug_dump`]: ../debug_dump/index.html
+#[cfg(feature = "debug_dump")]
+pub(crate) fn snapshot(
+    gpio: &pac::gpio::RegisterBlock,
+    port: usize,
+) -> crate::debug_dump::GpioPort {
+    #[cfg(feature = "82x")]
+    let (dir, pin) = (
+        core::slice::from_ref(&gpio.dir0),
+        core::slice::from_ref(&gpio.pin0),
+    );
+    #[cfg(feature = "845")]
+    let (dir, pin) = (&gpio.dir, &gpio.pin);
+
+    crate::debug_dump::GpioPort {
+        dir: dir[port].read().bits(),
+        pin: pin[port].read().bits(),
+    }
+}
+
 /// Contains types to indicate the direction of GPIO pins
 ///
 /// Please refer to [`Pin`] for documentation on how these types are used.
@@ -409,6 +918,7 @@ pub mod direction {
     ///
     /// [`Gpio`]: ../../swm/pin_state/struct.Gpio.html
     /// [`Pin`]: ../../swm/struct.Pin.html
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Unknown;
     impl Direction for Unknown {}
 
@@ -420,6 +930,7 @@ pub mod direction {
     ///
     /// [`Gpio`]: ../../swm/pin_state/struct.Gpio.html
     /// [`Pin`]: ../../swm/struct.Pin.html
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Input;
     impl Direction for Input {}
 
@@ -431,6 +942,7 @@ pub mod direction {
     ///
     /// [`Gpio`]: ../../swm/pin_state/struct.Gpio.html
     /// [`Pin`]: ../../swm/struct.Pin.html
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Output;
     impl Direction for Output {}
 
@@ -458,3 +970,41 @@ pub mod direction {
     impl NotInput for Unknown {}
     impl NotInput for Output {}
 }
+
+#[cfg(feature = "82x")]
+mod target {
+    /// The number of GPIO ports
+    pub const PORTS: usize = 1;
+
+    /// The offsets of the pins' IOCON registers, by port and pin number
+    ///
+    /// The registers aren't ordered by pin number. See user manual, section
+    /// 8.5.
+    pub const IOCON_OFFSETS: [&[u8]; 1] = [&[
+        0x44, 0x2c, 0x18, 0x14, 0x10, 0x0c, 0x40, 0x3c, 0x38, 0x34, 0x20, 0x1c,
+        0x08, 0x04, 0x48, 0x28, 0x24, 0x00, 0x78, 0x74, 0x70, 0x6c, 0x68, 0x64,
+        0x60, 0x5c, 0x58, 0x54, 0x50,
+    ]];
+}
+
+#[cfg(feature = "845")]
+mod target {
+    /// The number of GPIO ports
+    pub const PORTS: usize = 2;
+
+    /// The offsets of the pins' IOCON registers, by port and pin number
+    ///
+    /// The registers aren't ordered by pin number. See user manual, section
+    /// 8.5.
+    pub const IOCON_OFFSETS: [&[u8]; 2] = [
+        &[
+            0x44, 0x2c, 0x18, 0x14, 0x10, 0x0c, 0x40, 0x3c, 0x38, 0x34, 0x20,
+            0x1c, 0x08, 0x04, 0x48, 0x28, 0x24, 0x00, 0x78, 0x74, 0x70, 0x6c,
+            0x68, 0x64, 0x60, 0x5c, 0x58, 0x54, 0x50, 0xc8, 0xcc, 0x8c,
+        ],
+        &[
+            0x90, 0x94, 0x98, 0xa4, 0xa8, 0xac, 0xb8, 0xc4, 0x7c, 0x80, 0xdc,
+            0xd8, 0x84, 0x88, 0x9c, 0xa0, 0xb0, 0xb4, 0xbc, 0xc0, 0xd0, 0xd4,
+        ],
+    ];
+}
@@ -40,21 +40,40 @@
 //!     .expect("Failed to write data");
 //! ```
 //!
+//! To share an I2C bus between multiple drivers, please refer to the [`shared`]
+//! module. To retry transactions that failed due to arbitration loss or an
+//! unacknowledged address, please refer to the [`retry`] module. To gather
+//! statistics about the bus, please refer to the [`monitor`] module.
+//!
 //! Please refer to the [examples in the repository] for more example code.
 //!
+//! [`shared`]: shared/index.html
+//! [`retry`]: retry/index.html
+//! [`monitor`]: monitor/index.html
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
-use core::ops::Deref;
-use embedded_hal::blocking::i2c;
+pub mod monitor;
+pub mod retry;
+pub mod shared;
+
+use core::{fmt, ops::Deref};
+use embedded_dma::WriteBuffer;
+use embedded_hal::blocking::{delay::DelayUs, i2c};
 use void::Void;
 
 use crate::{
-    init_state,
+    dma,
+    gpio::direction,
+    init_state, mrt,
     pac::{self, Interrupt},
-    swm::{self},
+    reg_proxy::Reg,
+    swm::{self, pin_state, Pin, PinTrait},
     syscon::{self, clocksource::I2cClock, PeripheralClock},
 };
 
+#[cfg(feature = "timeouts")]
+use crate::mrt::MrtChannel;
+
 /// Interface to an I2C peripheral
 ///
 /// Please refer to the [module documentation] for more information.
@@ -72,7 +91,10 @@ use crate::{
 /// [module documentation]: index.html
 pub struct I2C<I, State = init_state::Enabled> {
     i2c: I,
-    _state: State,
+    state: State,
+
+    #[cfg(feature = "timeouts")]
+    deadman: Option<mrt::Deadman>,
 }
 
 impl<I> I2C<I, init_state::Disabled>
@@ -82,7 +104,10 @@ where
     pub(crate) fn new(i2c: I) -> Self {
         I2C {
             i2c: i2c,
-            _state: init_state::Disabled,
+            state: init_state::Disabled,
+
+            #[cfg(feature = "timeouts")]
+            deadman: None,
         }
     }
 
@@ -95,6 +120,9 @@ where
     /// Consumes this instance of `I2C` and returns another instance that has
     /// its `State` type parameter set to [`Enabled`].
     ///
+    /// The switch matrix functions for SDA and SCL are kept by the returned
+    /// instance. You can get them back by calling [`I2C::disable`].
+    ///
     /// # Limitations
     ///
     /// This method expects the mode for SDA & SCL pins to be set to
@@ -105,13 +133,14 @@ where
     ///
     /// [`Disabled`]: ../init_state/struct.Disabled.html
     /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`I2C::disable`]: #method.disable
     pub fn enable<SdaPin, SclPin, Clock>(
         mut self,
         clock: &I2cClock<Clock>,
         syscon: &mut syscon::Handle,
-        _: swm::Function<I::Sda, swm::state::Assigned<SdaPin>>,
-        _: swm::Function<I::Scl, swm::state::Assigned<SclPin>>,
-    ) -> I2C<I, init_state::Enabled>
+        sda: swm::Function<I::Sda, swm::state::Assigned<SdaPin>>,
+        scl: swm::Function<I::Scl, swm::state::Assigned<SclPin>>,
+    ) -> I2C<I, init_state::Enabled<Functions<I, SdaPin, SclPin>>>
     where
         I2cClock<Clock>: PeripheralClock<I>,
     {
@@ -137,41 +166,258 @@ where
 
         I2C {
             i2c: self.i2c,
-            _state: init_state::Enabled(()),
+            state: init_state::Enabled((sda, scl)),
+
+            #[cfg(feature = "timeouts")]
+            deadman: self.deadman,
         }
     }
 }
 
-impl<I> i2c::Write for I2C<I, init_state::Enabled>
+impl<I, Pins> I2C<I, init_state::Enabled<Pins>>
 where
     I: Instance,
 {
-    type Error = Void;
+    /// Disable the I2C peripheral
+    ///
+    /// This method is only available, if `I2C` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `I2C` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`], together with the
+    /// switch matrix functions that were passed to [`I2C::enable`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`I2C::enable`]: #method.enable
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (I2C<I, init_state::Disabled>, Pins) {
+        syscon.disable_clock(&self.i2c);
 
-    /// Write to the I2C bus
+        let i2c = I2C {
+            i2c: self.i2c,
+            state: init_state::Disabled,
+
+            #[cfg(feature = "timeouts")]
+            deadman: self.deadman,
+        };
+
+        (i2c, self.state.0)
+    }
+
+    /// Time out blocking operations using an MRT channel
     ///
-    /// Please refer to the [embedded-hal documentation] for details.
+    /// Once this has been called, the blocking read and write operations
+    /// return [`TransferError::Timeout`], if the I2C peripheral doesn't make
+    /// progress for `timeout`, for example because a slave is holding SCL low.
+    /// The timer is restarted for every byte, so longer transfers don't need
+    /// a longer timeout. The bus might need to be recovered using
+    /// [`clear_bus`] afterwards.
+    ///
+    /// Timeouts that don't fit into the MRT channel are reduced to its
+    /// maximum, which is about 178 seconds on the LPC82x, and about 1.4
+    /// seconds on the LPC845, at 12 MHz.
+    ///
+    /// The MRT channel is used in one-shot mode. Returns the MRT channel that
+    /// was used before, if any.
+    ///
+    /// Only available, if the `timeouts` feature is enabled.
+    ///
+    /// [`TransferError::Timeout`]: enum.TransferError.html#variant.Timeout
+    /// [`clear_bus`]: fn.clear_bus.html
+    #[cfg(feature = "timeouts")]
+    pub fn set_timeout(
+        &mut self,
+        timer: MrtChannel,
+        timeout: fugit::MicrosDurationU32,
+    ) -> Option<MrtChannel> {
+        let previous = self.clear_timeout();
+        self.deadman = Some(mrt::Deadman::new(timer, timeout));
+        previous
+    }
+
+    /// Stop timing out blocking operations and return the MRT channel
+    ///
+    /// Only available, if the `timeouts` feature is enabled.
+    #[cfg(feature = "timeouts")]
+    pub fn clear_timeout(&mut self) -> Option<MrtChannel> {
+        self.deadman.take().map(|deadman| deadman.free())
+    }
+
+    /// Enable the hardware time-out
+    ///
+    /// Once enabled, the I2C peripheral flags an SCL time-out, if a slave
+    /// holds SCL low for longer than `clocks` I2C function clocks, and an
+    /// event time-out, if no start, stop, or clock edge occurs on the bus for
+    /// that long. `clocks` is rounded up to a multiple of 16, and saturates at
+    /// 65536.
+    ///
+    /// An SCL time-out aborts [`I2C::try_write`] and [`I2C::try_read`] with
+    /// [`TransferError::SclTimeout`]. The event time-out is used by
+    /// [`I2C::is_bus_idle`].
+    ///
+    /// See user manual, section 15.6.7.
+    ///
+    /// [`I2C::try_write`]: #method.try_write
+    /// [`I2C::try_read`]: #method.try_read
+    /// [`TransferError::SclTimeout`]: enum.TransferError.html#variant.SclTimeout
+    /// [`I2C::is_bus_idle`]: #method.is_bus_idle
+    pub fn enable_bus_timeout(&mut self, clocks: u32) {
+        // The time-out is `(TO + 1) * 16` clocks. The value must only be
+        // changed while time-outs are disabled.
+        let to = (clocks.max(1) + 15) / 16 - 1;
+        let to = to.min(0xfff) as u16;
+
+        self.disable_bus_timeout();
+        self.i2c.timeout.write(|w| unsafe { w.to().bits(to) });
+
+        // Start from a clean slate. Both flags are cleared by writing 1.
+        self.i2c.stat.write(|w| {
+            w.eventtimeout().set_bit();
+            w.scltimeout().set_bit()
+        });
+
+        self.i2c.cfg.modify(|_, w| w.timeouten().enabled());
+    }
+
+    /// Disable the hardware time-out
+    pub fn disable_bus_timeout(&mut self) {
+        self.i2c.cfg.modify(|_, w| w.timeouten().disabled());
+    }
+
+    /// Indicates whether the bus is idle
+    ///
+    /// Returns `true`, if this master isn't in the middle of a transaction, and
+    /// no other master is using the bus. The latter can only be detected using
+    /// the event time-out, so this method only considers the state of this
+    /// master, unless the hardware time-out has been enabled using
+    /// [`I2C::enable_bus_timeout`].
     ///
     /// # Limitations
     ///
-    /// Writing multiple bytes should work, but has not been tested.
+    /// The event time-out flag is cleared, whenever this master starts a
+    /// transaction. Once set, it isn't cleared by activity of other masters,
+    /// so a transaction another master starts after the flag is set goes
+    /// unnoticed. Retrying on arbitration loss, using the [`retry`] module,
+    /// covers that case.
     ///
-    /// [embedded-hal documentation]: https://docs.rs/embedded-hal/0.2.1/embedded_hal/blocking/i2c/trait.Write.html#tymethod.write
-    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+    /// [`I2C::enable_bus_timeout`]: #method.enable_bus_timeout
+    /// [`retry`]: retry/index.html
+    pub fn is_bus_idle(&self) -> bool {
+        let stat = self.i2c.stat.read();
+
+        if !stat.mststate().is_idle() {
+            return false;
+        }
+
+        if self.i2c.cfg.read().timeouten().is_enabled() {
+            return stat.eventtimeout().bit_is_set();
+        }
+
+        true
+    }
+
+    /// Enable the bus monitor
+    ///
+    /// Enables the monitor function and its interrupts, and returns a
+    /// [`Monitor`] that gathers statistics about the bus from the I2C
+    /// interrupt handler. The interrupt still needs to be unmasked in the NVIC.
+    /// Only one `Monitor` should exist at a time.
+    ///
+    /// If `clock_stretching` is `true`, the monitor stretches the clock, until
+    /// the interrupt handler has read each byte. No byte is ever missed then,
+    /// but the interrupt latency slows down the bus.
+    ///
+    /// [`Monitor`]: monitor/struct.Monitor.html
+    pub fn enable_monitor(
+        &mut self,
+        clock_stretching: bool,
+    ) -> monitor::Monitor<I> {
+        self.i2c.cfg.modify(|_, w| {
+            w.monen().enabled();
+            if clock_stretching {
+                w.monclkstr().enabled()
+            } else {
+                w.monclkstr().disabled()
+            }
+        });
+
+        monitor::Monitor::new()
+    }
+
+    /// Disable the bus monitor
+    ///
+    /// Disables the monitor function and its interrupts.
+    pub fn disable_monitor(&mut self, monitor: monitor::Monitor<I>) {
+        monitor.disable();
+
+        self.i2c.cfg.modify(|_, w| {
+            w.monclkstr().disabled();
+            w.monen().disabled()
+        });
+    }
+
+    /// Wait until `ready` returns `true`, or the deadman timer expires
+    ///
+    /// The deadman timer is restarted for every wait, so it only expires, if
+    /// the peripheral doesn't make progress.
+    fn wait_until(&mut self, ready: impl Fn(&I) -> bool) -> Result<(), Error> {
+        #[cfg(feature = "timeouts")]
+        if let Some(deadman) = &mut self.deadman {
+            deadman.arm();
+        }
+
+        while !ready(&self.i2c) {
+            #[cfg(feature = "timeouts")]
+            if let Some(deadman) = &mut self.deadman {
+                if deadman.expired() {
+                    return Err(mrt::Timeout);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<I, Pins> I2C<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    /// Write to the I2C bus
+    ///
+    /// Returns an error, if the slave doesn't acknowledge its address or a
+    /// data byte, if another master wins arbitration, or if the bus is stuck
+    /// (see [`TransferError`]). The transaction is aborted in any case. The
+    /// [`Write`] implementation calls this method.
+    ///
+    /// [`TransferError`]: enum.TransferError.html
+    /// [`Write`]: #impl-Write
+    pub fn try_write(
+        &mut self,
+        address: u8,
+        data: &[u8],
+    ) -> Result<(), TransferError> {
         // Wait until peripheral is idle
-        while !self.i2c.stat.read().mststate().is_idle() {}
+        self.wait_until(|i2c| i2c.stat.read().mststate().is_idle())?;
 
         // Write slave address with rw bit set to 0
         self.i2c
             .mstdat
             .write(|w| unsafe { w.data().bits(address & 0xfe) });
 
+        // The event time-out flag is cleared by writing 1. See `is_bus_idle`.
+        self.i2c.stat.write(|w| w.eventtimeout().set_bit());
+
         // Start transmission
         self.i2c.mstctl.write(|w| w.mststart().start());
 
         for &b in data {
             // Wait until peripheral is ready to transmit
-            while self.i2c.stat.read().mstpending().is_in_progress() {}
+            self.wait_for_master()?;
 
             // Write byte
             self.i2c.mstdat.write(|w| unsafe { w.data().bits(b) });
@@ -181,65 +427,267 @@ where
         }
 
         // Wait until peripheral is ready to transmit
-        while self.i2c.stat.read().mstpending().is_in_progress() {}
+        self.wait_for_master()?;
+
+        // Stop transmission
+        self.i2c.mstctl.modify(|_, w| w.mststop().stop());
+
+        Ok(())
+    }
+
+    /// Read from the I2C bus
+    ///
+    /// Returns an error, if the slave doesn't acknowledge its address, if
+    /// another master wins arbitration, or if the bus is stuck (see
+    /// [`TransferError`]). The transaction is aborted in any case. The
+    /// [`Read`] implementation calls this method.
+    ///
+    /// [`TransferError`]: enum.TransferError.html
+    /// [`Read`]: #impl-Read
+    pub fn try_read(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), TransferError> {
+        // Wait until peripheral is idle
+        self.wait_until(|i2c| i2c.stat.read().mststate().is_idle())?;
+
+        // Write slave address with rw bit set to 1
+        self.i2c
+            .mstdat
+            .write(|w| unsafe { w.data().bits(address | 0x01) });
+
+        // The event time-out flag is cleared by writing 1. See `is_bus_idle`.
+        self.i2c.stat.write(|w| w.eventtimeout().set_bit());
+
+        // Start transmission
+        self.i2c.mstctl.write(|w| w.mststart().start());
+
+        // Wait until the address has been acknowledged and the first byte
+        // has been received
+        self.wait_for_master()?;
+
+        for (i, b) in buffer.iter_mut().enumerate() {
+            if i > 0 {
+                // Continue transmission
+                self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+
+                // Wait until peripheral is ready to receive
+                self.wait_for_master()?;
+            }
+
+            // Read received byte
+            *b = self.i2c.mstdat.read().data().bits();
+        }
 
         // Stop transmission
         self.i2c.mstctl.modify(|_, w| w.mststop().stop());
 
         Ok(())
     }
+
+    /// Wait until the master is pending, then check the outcome
+    ///
+    /// Stops the transaction, if the slave didn't acknowledge. See user manual,
+    /// section 15.6.2.
+    fn wait_for_master(&mut self) -> Result<(), TransferError> {
+        self.wait_until(|i2c| {
+            let stat = i2c.stat.read();
+            !stat.mstpending().is_in_progress()
+                || stat.scltimeout().bit_is_set()
+        })?;
+
+        let stat = self.i2c.stat.read();
+
+        if stat.scltimeout().bit_is_set() {
+            // A slave is holding SCL low, so there's no way to send a stop
+            // condition. The flag is cleared by writing 1.
+            self.i2c.stat.write(|w| w.scltimeout().set_bit());
+            return Err(TransferError::SclTimeout);
+        }
+
+        if stat.mstarbloss().bit_is_set() {
+            // The master has already released the bus. The flag is cleared by
+            // writing 1.
+            self.i2c.stat.write(|w| w.mstarbloss().set_bit());
+            return Err(TransferError::ArbitrationLost);
+        }
+
+        let error = if stat.mststate().is_nack_address() {
+            TransferError::AddressNack
+        } else if stat.mststate().is_nack_data() {
+            TransferError::DataNack
+        } else {
+            return Ok(());
+        };
+
+        self.i2c.mstctl.modify(|_, w| w.mststop().stop());
+
+        Err(error)
+    }
 }
 
-impl<I> i2c::Read for I2C<I, init_state::Enabled>
+impl<I, Pins> i2c::Write for I2C<I, init_state::Enabled<Pins>>
 where
     I: Instance,
 {
-    type Error = Void;
+    type Error = TransferError;
+
+    /// Write to the I2C bus
+    ///
+    /// Please refer to the [embedded-hal documentation] for details.
+    ///
+    /// Works like [`I2C::try_write`], which returns the same errors.
+    ///
+    /// # Limitations
+    ///
+    /// Writing multiple bytes should work, but has not been tested.
+    ///
+    /// [embedded-hal documentation]: https://docs.rs/embedded-hal/0.2.1/embedded_hal/blocking/i2c/trait.Write.html#tymethod.write
+    /// [`I2C::try_write`]: struct.I2C.html#method.try_write
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.try_write(address, data)
+    }
+}
+
+impl<I, Pins> i2c::Read for I2C<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    type Error = TransferError;
 
     /// Read from the I2C bus
     ///
     /// Please refer to the [embedded-hal documentation] for details.
     ///
+    /// Works like [`I2C::try_read`], which returns the same errors. If an
+    /// error is returned, the contents of `buffer` are unspecified.
+    ///
     /// # Limitations
     ///
     /// Reading multiple bytes should work, but has not been tested.
     ///
     /// [embedded-hal documentation]: https://docs.rs/embedded-hal/0.2.1/embedded_hal/blocking/i2c/trait.Read.html#tymethod.read
+    /// [`I2C::try_read`]: struct.I2C.html#method.try_read
     fn read(
         &mut self,
         address: u8,
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
+        self.try_read(address, buffer)
+    }
+}
+
+impl<I, Pins> I2C<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    /// Read registers from a slave, using DMA
+    ///
+    /// Writes the register address `register` to the slave at `address`, then
+    /// reads from the slave until `buffer` is full. This is the common access
+    /// pattern for sensors like IMUs. The register address is written by the
+    /// CPU, the data is read using the provided DMA channel.
+    ///
+    /// Returns the DMA transfer. Call [`ReadTransfer::wait`] on it to wait for
+    /// the read to finish. This will also stop the I2C transmission.
+    ///
+    /// Only the DMA channel that is connected to this I2C instance's master
+    /// DMA request can be used.
+    ///
+    /// # Limitations
+    ///
+    /// The length of `buffer` must be 1024 or less.
+    ///
+    /// [`ReadTransfer::wait`]: ../dma/struct.ReadTransfer.html#method.wait
+    pub fn read_regs_dma<'i2c, 'dma, B>(
+        &'i2c mut self,
+        address: u8,
+        register: u8,
+        buffer: B,
+        channel: dma::Channel<
+            I::MstChannel,
+            init_state::Enabled<&'dma dma::Handle>,
+        >,
+    ) -> dma::ReadTransfer<'dma, I::MstChannel, DmaRead<'i2c, I>, B>
+    where
+        B: WriteBuffer<Word = u8>,
+    {
         // Wait until peripheral is idle
         while !self.i2c.stat.read().mststate().is_idle() {}
 
+        // Write slave address with rw bit set to 0
+        self.i2c
+            .mstdat
+            .write(|w| unsafe { w.data().bits(address & 0xfe) });
+
+        // The event time-out flag is cleared by writing 1. See `is_bus_idle`.
+        self.i2c.stat.write(|w| w.eventtimeout().set_bit());
+
+        // Start transmission
+        self.i2c.mstctl.write(|w| w.mststart().start());
+
+        // Wait until peripheral is ready to transmit
+        while self.i2c.stat.read().mstpending().is_in_progress() {}
+
+        // Write register address
+        self.i2c
+            .mstdat
+            .write(|w| unsafe { w.data().bits(register) });
+        self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+
+        // Wait until peripheral is ready to transmit
+        while self.i2c.stat.read().mstpending().is_in_progress() {}
+
         // Write slave address with rw bit set to 1
         self.i2c
             .mstdat
             .write(|w| unsafe { w.data().bits(address | 0x01) });
 
-        // Start transmission
+        // Repeated start
         self.i2c.mstctl.write(|w| w.mststart().start());
 
-        for b in buffer {
-            // Continue transmission
-            self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+        // Wait until the first byte has been received
+        while self.i2c.stat.read().mstpending().is_in_progress() {}
 
-            // Wait until peripheral is ready to receive
-            while self.i2c.stat.read().mstpending().is_in_progress() {}
+        // Hand over the data phase to the DMA controller. See user manual,
+        // section 15.6.5.
+        self.i2c.mstctl.write(|w| w.mstdma().enabled());
 
-            // Read received byte
-            *b = self.i2c.mstdat.read().data().bits();
-        }
+        channel.start_read(DmaRead(&mut self.i2c), buffer)
+    }
+}
+
+/// Source for a DMA read from an I2C slave
+///
+/// Used by [`I2C::read_regs_dma`].
+///
+/// [`I2C::read_regs_dma`]: struct.I2C.html#method.read_regs_dma
+pub struct DmaRead<'i2c, I>(&'i2c mut I);
+
+impl<'i2c, I> dma::Source for DmaRead<'i2c, I>
+where
+    I: Instance,
+{
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // The DMA controller is done. We need to take over again, to stop the
+        // transmission. See user manual, section 15.6.5.
+        self.0.mstctl.write(|w| w.mstdma().disabled());
 
         // Stop transmission
-        self.i2c.mstctl.modify(|_, w| w.mststop().stop());
+        self.0.mstctl.modify(|_, w| w.mststop().stop());
 
         Ok(())
     }
+
+    fn end_addr(&mut self) -> *const u8 {
+        &self.0.mstdat as *const _ as *const u8
+    }
 }
 
-impl<I, State> I2C<I, State>
+impl<I> I2C<I, init_state::Disabled>
 where
     I: Instance,
 {
@@ -254,12 +702,249 @@ where
     /// request already exists, comment on the existing issue, so we can
     /// prioritize it accordingly.
     ///
+    /// If an MRT channel has been passed to [`I2C::set_timeout`], it is
+    /// dropped. Use [`I2C::clear_timeout`] before disabling the peripheral, if
+    /// you need it back.
+    ///
     /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    /// [`I2C::set_timeout`]: #method.set_timeout
+    /// [`I2C::clear_timeout`]: #method.clear_timeout
     pub fn free(self) -> I {
         self.i2c
     }
 }
 
+impl<I, Pins> I2C<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    /// Return the raw peripheral and the switch matrix functions
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// Unlike [`I2C::disable`], this leaves the I2C peripheral enabled. The
+    /// switch matrix functions that were passed to [`I2C::enable`] are
+    /// returned as well, so they can be unassigned and their pins used for
+    /// something else.
+    ///
+    /// If an MRT channel has been passed to [`I2C::set_timeout`], it is
+    /// dropped. Use [`I2C::clear_timeout`] first, if you need it back.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    /// [`I2C::disable`]: #method.disable
+    /// [`I2C::enable`]: #method.enable
+    /// [`I2C::set_timeout`]: #method.set_timeout
+    /// [`I2C::clear_timeout`]: #method.clear_timeout
+    pub fn free(self) -> (I, Pins) {
+        (self.i2c, self.state.0)
+    }
+}
+
+/// The error returned by the blocking I2C operations, when they time out
+///
+/// This can only happen, if an MRT channel has been passed to
+/// [`I2C::set_timeout`], which requires the `timeouts` feature. The type is
+/// the same either way, so enabling the feature doesn't break other crates.
+///
+/// [`I2C::set_timeout`]: struct.I2C.html#method.set_timeout
+pub type Error = mrt::Timeout;
+
+/// An error that can occur during an I2C transaction
+///
+/// Returned by [`I2C::try_write`] and [`I2C::try_read`], as well as the
+/// blocking I2C trait implementations.
+///
+/// [`I2C::try_write`]: struct.I2C.html#method.try_write
+/// [`I2C::try_read`]: struct.I2C.html#method.try_read
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferError {
+    /// Another master won arbitration
+    ArbitrationLost,
+
+    /// No slave acknowledged the address
+    AddressNack,
+
+    /// The slave didn't acknowledge a data byte
+    DataNack,
+
+    /// A slave held SCL low for longer than the hardware time-out
+    ///
+    /// See [`I2C::enable_bus_timeout`]. The bus might need to be recovered
+    /// using [`clear_bus`].
+    ///
+    /// [`I2C::enable_bus_timeout`]: struct.I2C.html#method.enable_bus_timeout
+    /// [`clear_bus`]: fn.clear_bus.html
+    SclTimeout,
+
+    /// The I2C peripheral didn't make progress in time
+    ///
+    /// See [`I2C::set_timeout`].
+    ///
+    /// [`I2C::set_timeout`]: struct.I2C.html#method.set_timeout
+    Timeout,
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransferError::ArbitrationLost => {
+                f.write_str("I2C arbitration lost")
+            }
+            TransferError::AddressNack => f.write_str("I2C address not acked"),
+            TransferError::DataNack => f.write_str("I2C data not acked"),
+            TransferError::SclTimeout => f.write_str("I2C SCL held low"),
+            TransferError::Timeout => f.write_str("I2C operation timed out"),
+        }
+    }
+}
+
+impl From<mrt::Timeout> for TransferError {
+    fn from(_: mrt::Timeout) -> Self {
+        TransferError::Timeout
+    }
+}
+
+/// Recover a bus that is blocked by a slave holding SDA low
+///
+/// If a slave was interrupted in the middle of a transfer (for example by a
+/// reset of the master after a brown-out), it might still be holding SDA low,
+/// waiting for more clock pulses. This blocks the bus for all other devices.
+///
+/// This function checks whether SDA is held low. If so, it generates up to 9
+/// clock pulses on SCL, until the slave releases SDA, followed by a STOP
+/// condition. It should be called before [`I2C::enable`], while the pins are
+/// still under GPIO control. Both pins are only ever driven low or released,
+/// never driven high, so this relies on the bus pull-ups.
+///
+/// The clock pulses are generated at about 100 kHz, using `delay`.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc82x_hal::{delay::Delay, i2c, Peripherals};
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let swm = p.SWM.split();
+/// let mut delay = Delay::new(p.SYST);
+///
+/// let mut sda = swm.pins.pio0_11.into_gpio_pin(&p.GPIO).into_input();
+/// let mut scl = swm.pins.pio0_10.into_gpio_pin(&p.GPIO).into_input();
+///
+/// i2c::clear_bus(&mut sda, &mut scl, &mut delay)
+///     .expect("Failed to clear I2C bus");
+///
+/// // Transition pins to the SWM state and enable I2C
+/// ```
+///
+/// [`I2C::enable`]: struct.I2C.html#method.enable
+pub fn clear_bus<'gpio, Sda, Scl, D>(
+    sda: &mut Pin<Sda, pin_state::Gpio<'gpio, direction::Input>>,
+    scl: &mut Pin<Scl, pin_state::Gpio<'gpio, direction::Input>>,
+    delay: &mut D,
+) -> Result<(), BusClearError>
+where
+    Sda: PinTrait,
+    Scl: PinTrait,
+    D: DelayUs<u8>,
+{
+    const HALF_PERIOD_US: u8 = 5;
+
+    if !is_high(scl) {
+        return Err(BusClearError::SclStuckLow);
+    }
+    if is_high(sda) {
+        return Ok(());
+    }
+
+    for _ in 0..9 {
+        pull_low(scl);
+        delay.delay_us(HALF_PERIOD_US);
+        release(scl);
+        delay.delay_us(HALF_PERIOD_US);
+
+        if is_high(sda) {
+            break;
+        }
+    }
+
+    // Generate a STOP condition: SDA goes high, while SCL is high.
+    pull_low(scl);
+    delay.delay_us(HALF_PERIOD_US);
+    pull_low(sda);
+    delay.delay_us(HALF_PERIOD_US);
+    release(scl);
+    delay.delay_us(HALF_PERIOD_US);
+    release(sda);
+    delay.delay_us(HALF_PERIOD_US);
+
+    if !is_high(sda) {
+        return Err(BusClearError::SdaStuckLow);
+    }
+
+    Ok(())
+}
+
+fn is_high<P: PinTrait>(
+    pin: &Pin<P, pin_state::Gpio<direction::Input>>,
+) -> bool {
+    pin.state.registers.pin[P::PORT].read().port().bits() & P::MASK != 0
+}
+
+fn pull_low<P: PinTrait>(pin: &Pin<P, pin_state::Gpio<direction::Input>>) {
+    let registers = &pin.state.registers;
+    registers.clr[P::PORT].write(|w| unsafe { w.clrp().bits(P::MASK) });
+    registers.dirset[P::PORT].write(|w| unsafe { w.dirsetp().bits(P::MASK) });
+}
+
+fn release<P: PinTrait>(pin: &Pin<P, pin_state::Gpio<direction::Input>>) {
+    pin.state.registers.dirclr[P::PORT]
+        .write(|w| unsafe { w.dirclrp().bits(P::MASK) });
+}
+
+/// An error that can occur while clearing the bus
+///
+/// Returned by [`clear_bus`].
+///
+/// [`clear_bus`]: fn.clear_bus.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusClearError {
+    /// SCL is held low by another device, so no clock can be generated
+    SclStuckLow,
+
+    /// SDA is still held low after 9 clock pulses
+    SdaStuckLow,
+}
+
+impl fmt::Display for BusClearError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BusClearError::SclStuckLow => f.write_str("I2C SCL stuck low"),
+            BusClearError::SdaStuckLow => f.write_str("I2C SDA stuck low"),
+        }
+    }
+}
+
+/// The switch matrix functions held by an enabled [`I2C`] instance
+///
+/// Returned by [`I2C::disable`].
+///
+/// [`I2C`]: struct.I2C.html
+/// [`I2C::disable`]: struct.I2C.html#method.disable
+pub type Functions<I, SdaPin, SclPin> = (
+    swm::Function<<I as Instance>::Sda, swm::state::Assigned<SdaPin>>,
+    swm::Function<<I as Instance>::Scl, swm::state::Assigned<SclPin>>,
+);
+
 /// Internal trait for I2C peripherals
 ///
 /// This trait is an internal implementation detail and should neither be
@@ -269,6 +954,7 @@ pub trait Instance:
     Deref<Target = pac::i2c0::RegisterBlock>
     + syscon::ClockControl
     + syscon::ResetControl
+    + Reg<Target = pac::i2c0::RegisterBlock>
 {
     /// The interrupt that is triggered for this I2C peripheral
     const INTERRUPT: Interrupt;
@@ -278,6 +964,9 @@ pub trait Instance:
 
     /// The movable function that needs to be assigned to this I2C's SCL pin
     type Scl;
+
+    /// The DMA channel connected to this I2C's master DMA request
+    type MstChannel: dma::ChannelTrait;
 }
 
 macro_rules! instances {
@@ -286,23 +975,34 @@ macro_rules! instances {
             $instance:ident,
             $interrupt:ident,
             $rx:ident,
-            $tx:ident;
+            $tx:ident,
+            $mst_channel:ident;
         )*
     ) => {
         $(
+            unsafe impl Reg for pac::$instance {
+                type Target = pac::i2c0::RegisterBlock;
+
+                fn get() -> *const Self::Target {
+                    pac::$instance::ptr()
+                }
+            }
+
             impl Instance for pac::$instance {
                 const INTERRUPT: Interrupt = Interrupt::$interrupt;
 
                 type Sda = swm::$rx;
                 type Scl = swm::$tx;
+
+                type MstChannel = dma::$mst_channel;
             }
         )*
     };
 }
 
 instances!(
-    I2C0, I2C0, I2C0_SDA, I2C0_SCL;
-    I2C1, I2C1, I2C1_SDA, I2C1_SCL;
-    I2C2, I2C2, I2C2_SDA, I2C2_SCL;
-    I2C3, I2C3, I2C3_SDA, I2C3_SCL;
+    I2C0, I2C0, I2C0_SDA, I2C0_SCL, Channel11;
+    I2C1, I2C1, I2C1_SDA, I2C1_SCL, Channel13;
+    I2C2, I2C2, I2C2_SDA, I2C2_SCL, Channel15;
+    I2C3, I2C3, I2C3_SDA, I2C3_SCL, Channel17;
 );
@@ -0,0 +1,271 @@
+//! Bus statistics, using the I2C monitor function
+//!
+//! Besides the master, every I2C peripheral contains a monitor that receives
+//! all traffic on the bus, no matter which master or slave is involved. Each
+//! byte is provided in MONRXDAT, together with whether it was preceded by a
+//! start condition, and whether it was acknowledged. See user manual, section
+//! 15.7.8.
+//!
+//! [`Monitor`] counts what the monitor function sees, as well as the
+//! time-outs configured using [`I2C::enable_bus_timeout`], in a [`Counters`]
+//! struct:
+//!
+//! - SCL time-outs happen, whenever a slave stretches the clock for longer
+//!   than the time-out. Setting the time-out just above the low time of SCL
+//!   counts all clock stretching, a longer time-out counts only excessive
+//!   clock stretching.
+//! - Event time-outs happen, whenever the bus has been idle for longer than
+//!   the time-out.
+//! - The number of bytes, start and stop conditions provides an estimate of
+//!   the bus utilization (see [`Counters::utilization`]).
+//!
+//! The counters are updated by [`Monitor::handle_interrupt`], which needs to
+//! be called from the I2C interrupt handler. The monitor doesn't interfere
+//! with the use of the [`I2C`] instance it was created from.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{i2c::I2C, init_state, pac::I2C0};
+//!
+//! fn run<Pins>(i2c: &mut I2C<I2C0, init_state::Enabled<Pins>>) {
+//!     // Time-out after 16 I2C function clocks, and don't stretch the clock
+//!     // while waiting for the monitor interrupt
+//!     i2c.enable_bus_timeout(16);
+//!     let mut monitor = i2c.enable_monitor(false);
+//!
+//!     // In the I2C0 interrupt handler
+//!     monitor.handle_interrupt();
+//!
+//!     // Later, for example once per second
+//!     let counters = monitor.reset();
+//!     let utilization = counters.utilization(100_000, 1_000_000);
+//! }
+//! ```
+//!
+//! [`Monitor`]: struct.Monitor.html
+//! [`I2C::enable_bus_timeout`]: ../struct.I2C.html#method.enable_bus_timeout
+//! [`Counters`]: struct.Counters.html
+//! [`Counters::utilization`]: struct.Counters.html#method.utilization
+//! [`Monitor::handle_interrupt`]: struct.Monitor.html#method.handle_interrupt
+//! [`I2C`]: ../struct.I2C.html
+
+use crate::reg_proxy::RegProxy;
+
+use super::Instance;
+
+/// The number of clock cycles per byte, including the acknowledge bit
+const CLOCKS_PER_BYTE: u64 = 9;
+
+/// Gathers statistics about the I2C bus
+///
+/// Created by [`I2C::enable_monitor`]. Please refer to the
+/// [module documentation] for more information.
+///
+/// [`I2C::enable_monitor`]: ../struct.I2C.html#method.enable_monitor
+/// [module documentation]: index.html
+pub struct Monitor<I>
+where
+    I: Instance,
+{
+    i2c: RegProxy<I>,
+    counters: Counters,
+
+    // Set, while the interrupt for the respective time-out is disabled, to
+    // leave the flag to the master.
+    event_timeout_masked: bool,
+    scl_timeout_masked: bool,
+}
+
+impl<I> Monitor<I>
+where
+    I: Instance,
+{
+    pub(super) fn new() -> Self {
+        let i2c = RegProxy::<I>::new();
+
+        // Discard anything that was received before. MONRDY is cleared by
+        // reading MONRXDAT, the other flags by writing 1.
+        let _ = i2c.monrxdat.read();
+        i2c.stat.write(|w| {
+            w.monov().set_bit();
+            w.monidle().set_bit()
+        });
+
+        i2c.intenset.write(|w| {
+            w.monrdyen().enabled();
+            w.monoven().enabled();
+            w.monidleen().enabled();
+            w.eventtimeouten().enabled();
+            w.scltimeouten().enabled()
+        });
+
+        Monitor {
+            i2c,
+            counters: Counters::default(),
+
+            event_timeout_masked: false,
+            scl_timeout_masked: false,
+        }
+    }
+
+    /// Handle the I2C interrupt
+    ///
+    /// Updates the counters with everything that has happened since the last
+    /// call. Call this from the I2C interrupt handler.
+    ///
+    /// The time-out flags are also used by the [`I2C`] instance:
+    /// [`I2C::is_bus_idle`] checks the event time-out, and the blocking
+    /// operations check the SCL time-out. Each time-out is only counted once
+    /// therefore, and its interrupt stays disabled until the bus is active
+    /// again, leaving the flag as it is. An SCL time-out during a transaction
+    /// of this master is left to the master, which aborts the transaction.
+    ///
+    /// [`I2C`]: ../struct.I2C.html
+    /// [`I2C::is_bus_idle`]: ../struct.I2C.html#method.is_bus_idle
+    pub fn handle_interrupt(&mut self) {
+        let stat = self.i2c.stat.read();
+
+        if stat.monrdy().bit_is_set() {
+            let data = self.i2c.monrxdat.read();
+
+            increment(&mut self.counters.bytes);
+            if data.monstart().bit_is_set() {
+                increment(&mut self.counters.starts);
+            }
+            if data.monnack().bit_is_set() {
+                increment(&mut self.counters.nacks);
+            }
+
+            // The bus is active again, so the event time-out flag is stale.
+            if self.event_timeout_masked {
+                self.i2c.stat.write(|w| w.eventtimeout().set_bit());
+                self.i2c.intenset.write(|w| w.eventtimeouten().enabled());
+                self.event_timeout_masked = false;
+            }
+        }
+
+        if stat.monov().bit_is_set() {
+            self.i2c.stat.write(|w| w.monov().set_bit());
+            increment(&mut self.counters.overruns);
+        }
+
+        if stat.monidle().bit_is_set() {
+            self.i2c.stat.write(|w| w.monidle().set_bit());
+            increment(&mut self.counters.stops);
+        }
+
+        if stat.eventtimeout().bit_is_set() && !self.event_timeout_masked {
+            self.i2c.intenclr.write(|w| w.eventtimeoutclr().set_bit());
+            self.event_timeout_masked = true;
+            increment(&mut self.counters.event_timeouts);
+        }
+
+        if stat.scltimeout().bit_is_set() {
+            if !self.scl_timeout_masked {
+                increment(&mut self.counters.scl_timeouts);
+            }
+
+            if stat.mstpending().is_in_progress() {
+                self.i2c.intenclr.write(|w| w.scltimeoutclr().set_bit());
+                self.scl_timeout_masked = true;
+            } else {
+                // Clear the flag, so it doesn't fail the next transaction of
+                // this master.
+                self.i2c.stat.write(|w| w.scltimeout().set_bit());
+                self.unmask_scl_timeout();
+            }
+        } else {
+            // The master has cleared the flag in the meantime.
+            self.unmask_scl_timeout();
+        }
+    }
+
+    /// Return the counters
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// Return the counters, and reset them to zero
+    pub fn reset(&mut self) -> Counters {
+        core::mem::take(&mut self.counters)
+    }
+
+    pub(super) fn disable(self) {
+        self.i2c.intenclr.write(|w| {
+            w.monrdyclr().set_bit();
+            w.monovclr().set_bit();
+            w.monidleclr().set_bit();
+            w.eventtimeoutclr().set_bit();
+            w.scltimeoutclr().set_bit()
+        });
+    }
+
+    fn unmask_scl_timeout(&mut self) {
+        if self.scl_timeout_masked {
+            self.i2c.intenset.write(|w| w.scltimeouten().enabled());
+            self.scl_timeout_masked = false;
+        }
+    }
+}
+
+fn increment(counter: &mut u32) {
+    *counter = counter.wrapping_add(1);
+}
+
+/// Statistics about the I2C bus
+///
+/// Returned by [`Monitor::counters`] and [`Monitor::reset`]. All counters
+/// wrap around on overflow.
+///
+/// [`Monitor::counters`]: struct.Monitor.html#method.counters
+/// [`Monitor::reset`]: struct.Monitor.html#method.reset
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Counters {
+    /// The number of bytes on the bus, including addresses
+    pub bytes: u32,
+
+    /// The number of start and repeated start conditions
+    pub starts: u32,
+
+    /// The number of stop conditions
+    pub stops: u32,
+
+    /// The number of bytes that weren't acknowledged
+    pub nacks: u32,
+
+    /// The number of times the monitor missed a byte
+    ///
+    /// Can only happen, if the monitor doesn't stretch the clock, and the
+    /// interrupt handler doesn't keep up with the bus.
+    pub overruns: u32,
+
+    /// The number of times a slave stretched the clock for longer than the
+    /// time-out
+    pub scl_timeouts: u32,
+
+    /// The number of times the bus was idle for longer than the time-out
+    pub event_timeouts: u32,
+}
+
+impl Counters {
+    /// Estimate the bus utilization, in per mille
+    ///
+    /// `bit_rate_hz` is the nominal bit rate of the bus, `elapsed_us` the time
+    /// over which the counters were gathered. Only the clock cycles of bytes,
+    /// start and stop conditions are considered, so clock stretching and
+    /// the time between bytes aren't part of the result.
+    pub fn utilization(&self, bit_rate_hz: u32, elapsed_us: u32) -> u32 {
+        let clocks = self.bytes as u64 * CLOCKS_PER_BYTE
+            + self.starts as u64
+            + self.stops as u64;
+        let available = bit_rate_hz as u64 * elapsed_us as u64;
+
+        if available == 0 {
+            return 0;
+        }
+
+        (clocks * 1_000_000_000 / available).min(1000) as u32
+    }
+}
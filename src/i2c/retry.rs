@@ -0,0 +1,181 @@
+//! Automatic retries of I2C transactions
+//!
+//! If multiple masters share a bus, a transaction can fail, because another
+//! master won arbitration. Slaves may also refuse to acknowledge their address
+//! while busy, like EEPROMs do during a write cycle. Both conditions are
+//! usually temporary.
+//!
+//! [`Retry`] wraps an [`I2C`] instance and retries transactions that failed
+//! for either reason, as configured by a [`Policy`]. It waits between
+//! attempts, doubling the wait time after each one. Only failures that persist
+//! after the last retry are returned.
+//!
+//! A NACK of a data byte or a timeout is never retried, as the slave might
+//! have acted on part of the transaction already.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     delay::Delay,
+//!     i2c::{
+//!         retry::{Policy, Retry},
+//!         I2C,
+//!     },
+//!     init_state,
+//!     pac::I2C0,
+//!     prelude::*,
+//! };
+//!
+//! fn run<Pins>(i2c: I2C<I2C0, init_state::Enabled<Pins>>, delay: Delay) {
+//!     let policy = Policy::new(3, fugit::MicrosDurationU32::micros(100));
+//!     let mut eeprom = Retry::new(i2c, delay, policy);
+//!
+//!     // Retried while the EEPROM is busy with a previous write cycle
+//!     eeprom.write(0x50, &[0x00, 0x10]).unwrap();
+//! }
+//! ```
+//!
+//! [`Retry`]: struct.Retry.html
+//! [`I2C`]: ../struct.I2C.html
+//! [`Policy`]: struct.Policy.html
+
+use embedded_hal::blocking::{delay::DelayUs, i2c};
+
+use crate::init_state;
+
+use super::{Instance, TransferError, I2C};
+
+/// Configures how [`Retry`] retries failed transactions
+///
+/// [`Retry`]: struct.Retry.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Policy {
+    /// The number of retries after the first attempt
+    pub retries: u8,
+
+    /// The wait time before the first retry
+    ///
+    /// Doubles before each further retry.
+    pub backoff: fugit::MicrosDurationU32,
+}
+
+impl Policy {
+    /// Create a policy
+    pub const fn new(retries: u8, backoff: fugit::MicrosDurationU32) -> Self {
+        Policy { retries, backoff }
+    }
+}
+
+impl Default for Policy {
+    /// Retry three times, waiting 100 µs before the first retry
+    fn default() -> Self {
+        Policy::new(3, fugit::MicrosDurationU32::micros(100))
+    }
+}
+
+/// An I2C master that retries failed transactions
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Retry<I, Pins, D> {
+    i2c: I2C<I, init_state::Enabled<Pins>>,
+    delay: D,
+    policy: Policy,
+}
+
+impl<I, Pins, D> Retry<I, Pins, D>
+where
+    I: Instance,
+    D: DelayUs<u32>,
+{
+    /// Wrap an I2C master
+    ///
+    /// `delay` is used to wait between attempts.
+    pub fn new(
+        i2c: I2C<I, init_state::Enabled<Pins>>,
+        delay: D,
+        policy: Policy,
+    ) -> Self {
+        Retry { i2c, delay, policy }
+    }
+
+    /// Return the policy
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Replace the policy
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    /// Release the I2C master and the delay provider
+    pub fn free(self) -> (I2C<I, init_state::Enabled<Pins>>, D) {
+        (self.i2c, self.delay)
+    }
+
+    fn run(
+        &mut self,
+        mut transaction: impl FnMut(
+            &mut I2C<I, init_state::Enabled<Pins>>,
+        ) -> Result<(), TransferError>,
+    ) -> Result<(), TransferError> {
+        let mut retries = self.policy.retries;
+        let mut backoff_us = self.policy.backoff.to_micros();
+
+        loop {
+            match transaction(&mut self.i2c) {
+                Err(TransferError::ArbitrationLost)
+                | Err(TransferError::AddressNack)
+                    if retries > 0 =>
+                {
+                    self.delay.delay_us(backoff_us);
+
+                    retries -= 1;
+                    backoff_us = backoff_us.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<I, Pins, D> i2c::Write for Retry<I, Pins, D>
+where
+    I: Instance,
+    D: DelayUs<u32>,
+{
+    type Error = TransferError;
+
+    /// Write to the I2C bus, retrying as configured
+    ///
+    /// Please refer to the [embedded-hal documentation] for details.
+    ///
+    /// [embedded-hal documentation]: https://docs.rs/embedded-hal/0.2.1/embedded_hal/blocking/i2c/trait.Write.html#tymethod.write
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.try_write(address, data))
+    }
+}
+
+impl<I, Pins, D> i2c::Read for Retry<I, Pins, D>
+where
+    I: Instance,
+    D: DelayUs<u32>,
+{
+    type Error = TransferError;
+
+    /// Read from the I2C bus, retrying as configured
+    ///
+    /// Please refer to the [embedded-hal documentation] for details.
+    ///
+    /// [embedded-hal documentation]: https://docs.rs/embedded-hal/0.2.1/embedded_hal/blocking/i2c/trait.Read.html#tymethod.read
+    fn read(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.try_read(address, buffer))
+    }
+}
@@ -0,0 +1,153 @@
+//! Sharing an I2C bus between multiple drivers
+//!
+//! Drivers for I2C devices usually expect to own the I2C peripheral they're
+//! using. [`I2cBus`] can hand out any number of [`I2cProxy`] handles instead,
+//! one for each driver. Each proxy implements the blocking I2C traits from
+//! embedded-hal and gains exclusive access to the bus for every operation.
+//!
+//! Every operation happens within a critical section, which makes it possible
+//! to use proxies from the main loop and from interrupt handlers. This doesn't
+//! require atomic compare-and-swap operations, which the Cortex-M0+ doesn't
+//! support. As interrupts are disabled for the duration of an operation, keep
+//! operations short, if interrupt latency is a concern.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     i2c::{shared::I2cBus, I2C},
+//!     init_state,
+//!     pac::I2C0,
+//!     prelude::*,
+//! };
+//!
+//! fn run<Pins>(i2c: I2C<I2C0, init_state::Enabled<Pins>>) {
+//!     let bus = I2cBus::new(i2c);
+//!
+//!     // Each of these would be passed to a different driver.
+//!     let mut sensor = bus.proxy();
+//!     let mut eeprom = bus.proxy();
+//!
+//!     let mut buffer = [0; 2];
+//!     sensor.read(0x48, &mut buffer).unwrap();
+//!     eeprom.write(0x50, &[0x00, 0x10]).unwrap();
+//! }
+//! ```
+//!
+//! [`I2cBus`]: struct.I2cBus.html
+//! [`I2cProxy`]: struct.I2cProxy.html
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, Mutex};
+use embedded_hal::blocking::i2c::{Read, Write};
+
+use crate::init_state;
+
+use super::{Instance, TransferError, I2C};
+
+/// An I2C bus that can be shared between multiple drivers
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct I2cBus<I, Pins> {
+    // Always `Some`, until the bus is consumed by `free`.
+    i2c: Mutex<RefCell<Option<I2C<I, init_state::Enabled<Pins>>>>>,
+}
+
+impl<I, Pins> I2cBus<I, Pins>
+where
+    I: Instance,
+{
+    /// Create a shared bus from an enabled I2C peripheral
+    pub fn new(i2c: I2C<I, init_state::Enabled<Pins>>) -> Self {
+        I2cBus {
+            i2c: Mutex::new(RefCell::new(Some(i2c))),
+        }
+    }
+
+    /// Create a handle that provides access to the bus
+    pub fn proxy(&self) -> I2cProxy<I, Pins> {
+        I2cProxy { bus: self }
+    }
+
+    /// Run an operation with exclusive access to the bus
+    ///
+    /// This can be used to perform multiple operations, without another
+    /// proxy accessing the bus in between.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if called from within another operation, for example from an
+    /// interrupt handler that preempted a running operation, or from within
+    /// `f`.
+    pub fn lock<R>(
+        &self,
+        f: impl FnOnce(&mut I2C<I, init_state::Enabled<Pins>>) -> R,
+    ) -> R {
+        interrupt::free(|cs| {
+            let mut i2c = self.i2c.borrow(cs).borrow_mut();
+            // Can't panic. See comment on field.
+            f(i2c.as_mut().unwrap())
+        })
+    }
+
+    /// Return the I2C peripheral
+    ///
+    /// This can only be called, once all proxies have been dropped.
+    pub fn free(self) -> I2C<I, init_state::Enabled<Pins>> {
+        interrupt::free(|cs| self.i2c.borrow(cs).borrow_mut().take())
+            // Can't panic. See comment on field.
+            .unwrap()
+    }
+}
+
+/// A handle to a shared I2C bus
+///
+/// Created by [`I2cBus::proxy`]. Please refer to the [module documentation]
+/// for more information.
+///
+/// # Panics
+///
+/// The I2C trait implementations panic, if called from within
+/// [`I2cBus::lock`].
+///
+/// [`I2cBus::proxy`]: struct.I2cBus.html#method.proxy
+/// [`I2cBus::lock`]: struct.I2cBus.html#method.lock
+/// [module documentation]: index.html
+pub struct I2cProxy<'bus, I, Pins> {
+    bus: &'bus I2cBus<I, Pins>,
+}
+
+impl<'bus, I, Pins> Clone for I2cProxy<'bus, I, Pins> {
+    fn clone(&self) -> Self {
+        I2cProxy { bus: self.bus }
+    }
+}
+
+impl<'bus, I, Pins> Write for I2cProxy<'bus, I, Pins>
+where
+    I: Instance,
+{
+    type Error = TransferError;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.bus.lock(|i2c| i2c.write(address, data))
+    }
+}
+
+impl<'bus, I, Pins> Read for I2cProxy<'bus, I, Pins>
+where
+    I: Instance,
+{
+    type Error = TransferError;
+
+    fn read(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|i2c| i2c.read(address, buffer))
+    }
+}
@@ -0,0 +1,312 @@
+//! API for the In-Application Programming (IAP) functions of the boot ROM
+//!
+//! The boot ROM provides a number of functions that can be called by the
+//! application. This module provides access to the ones that are related to
+//! starting up and updating the firmware, and to storing data in flash.
+//!
+//! The IAP functions are described in the user manual, chapter 24 (LPC82x) or
+//! chapter 5 (LPC845).
+//!
+//! # Examples
+//!
+//! Enter a safe mode, if the ISP pin is held low at startup, and hand control
+//! over to the ROM bootloader, to allow for a firmware update over USART:
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{iap, Peripherals};
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let swm = p.SWM.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let gpio = p.GPIO;
+//! #[cfg(feature = "845")]
+//! let gpio = p.GPIO.enable(&mut syscon.handle);
+//!
+//! if iap::isp_pin_is_low(&gpio, &swm.pins.pio0_12) {
+//!     // Do whatever needs to be done in safe mode, then reinvoke the
+//!     // bootloader.
+//!     iap::reinvoke_isp(iap::IspMode::Usart);
+//! }
+//!
+//! // Continue with regular startup
+//! ```
+
+use core::{fmt, mem, ptr};
+
+use cortex_m::interrupt;
+
+use crate::{
+    gpio::GPIO,
+    swm::{pin_state, Pin, PinTrait, PIO0_12},
+    syscon,
+};
+
+/// The address of the IAP entry point in the boot ROM
+const IAP_ENTRY: usize = 0x1fff_1ff1;
+
+/// IAP command codes
+///
+/// See user manual, section 24.5.5 (LPC82x) or 5.6 (LPC845).
+pub(crate) mod command {
+    pub const PREPARE_SECTORS: u32 = 50;
+    pub const COPY_RAM_TO_FLASH: u32 = 51;
+    pub const REINVOKE_ISP: u32 = 57;
+    pub const ERASE_PAGE: u32 = 59;
+}
+
+/// The size of a flash page in bytes
+///
+/// A page is the smallest unit of flash that can be erased and written.
+pub const PAGE_SIZE: usize = 64;
+
+/// The number of pages per flash sector
+const PAGES_PER_SECTOR: u32 = 16;
+
+/// Call an IAP function
+///
+/// # Safety
+///
+/// The caller must make sure that the command is valid and that calling it
+/// doesn't interfere with the rest of the program.
+pub(crate) unsafe fn call(command: &mut [u32; 5], result: &mut [u32; 4]) {
+    let iap: extern "C" fn(*mut u32, *mut u32) = mem::transmute(IAP_ENTRY);
+    iap(command.as_mut_ptr(), result.as_mut_ptr());
+}
+
+/// The interface the ROM bootloader should use after [`reinvoke_isp`]
+///
+/// [`reinvoke_isp`]: fn.reinvoke_isp.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IspMode {
+    /// ISP via USART0
+    Usart = 1,
+
+    /// ISP via I2C0
+    I2c = 2,
+
+    /// ISP via SPI0
+    Spi = 3,
+}
+
+/// Hand control over to the ROM bootloader
+///
+/// Disables interrupts and calls the "Reinvoke ISP" IAP command. This enters
+/// the bootloader using the interface selected by `mode`, allowing for a
+/// firmware update in the field. The bootloader reconfigures all hardware it
+/// uses, so the state the application left it in doesn't matter.
+///
+/// This function never returns. The bootloader will reset the microcontroller
+/// once it's done.
+pub fn reinvoke_isp(mode: IspMode) -> ! {
+    interrupt::disable();
+
+    let mut command = [command::REINVOKE_ISP, mode as u32, 0, 0, 0];
+    let mut result = [0; 4];
+
+    // Safe, as we don't expect to ever return from this. Nothing else can
+    // interfere, as interrupts are disabled.
+    unsafe { call(&mut command, &mut result) };
+
+    // We should never get here, but if the bootloader returns anyway for some
+    // reason, there's nothing left to do.
+    loop {}
+}
+
+/// Indicates whether the ISP entry pin (PIO0_12) is held low
+///
+/// The ROM bootloader is entered on reset, if PIO0_12 is held low. This
+/// function can be used by the application to detect the same condition, for
+/// example to branch into a safe mode before doing anything else.
+///
+/// Requires the pin to be unused, to make sure that it is still in its reset
+/// state, where it is an input with a pull-up.
+pub fn isp_pin_is_low(
+    gpio: &GPIO,
+    _: &Pin<PIO0_12, pin_state::Unused>,
+) -> bool {
+    #[cfg(feature = "82x")]
+    let pin = gpio.gpio.pin0.read().port().bits();
+    #[cfg(feature = "845")]
+    let pin = gpio.gpio.pin[PIO0_12::PORT].read().port().bits();
+
+    pin & PIO0_12::MASK == 0
+}
+
+/// The contents of a flash page
+///
+/// The IAP functions require the source data to be word-aligned, which this
+/// type guarantees.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C, align(4))]
+pub struct Page(pub [u8; PAGE_SIZE]);
+
+/// Return the current contents of a flash page
+///
+/// `page` is the number of the page, counting from the start of flash. The
+/// page is copied, so later calls to [`write_page`] don't affect the returned
+/// value.
+///
+/// # Panics
+///
+/// Panics, if `page` is beyond the end of flash.
+///
+/// [`write_page`]: fn.write_page.html
+pub fn read_page(page: u32) -> Page {
+    assert!(page < flash_pages());
+
+    // Sound, as flash is always mapped at address 0 and the page number has
+    // been checked. No reference to flash outlives this read.
+    unsafe { ptr::read((page as usize * PAGE_SIZE) as *const Page) }
+}
+
+/// Erase a flash page and write new data to it
+///
+/// `page` is the number of the page, counting from the start of flash. This
+/// can be used to store small amounts of data that need to survive a reset,
+/// like configuration or calibration data. Make sure to use a page that
+/// doesn't contain any code, typically one at the end of flash that has been
+/// excluded from the linker script.
+///
+/// Calls the "Prepare sectors for write operation", "Erase page", and "Copy
+/// RAM to flash" IAP commands. See user manual, section 24.5.5 (LPC82x) or 5.6
+/// (LPC845). Interrupts are disabled while this function runs, as flash can't
+/// be read while it is being written to. The IAP functions use the top 32
+/// bytes of RAM, which are excluded from the `memory.x` provided by this
+/// crate. If you use your own linker script, make sure to do the same.
+///
+/// The flash commands are passed the system clock frequency, as returned by
+/// [`syscon::system_clock_hz`].
+///
+/// # Panics
+///
+/// Panics, if `page` is beyond the end of flash.
+///
+/// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+pub fn write_page(page: u32, data: &Page) -> Result<(), Error> {
+    assert!(page < flash_pages());
+
+    let sector = page / PAGES_PER_SECTOR;
+    let address = page * PAGE_SIZE as u32;
+    let system_clock_khz = syscon::system_clock_hz() / 1000;
+
+    let commands = [
+        [command::PREPARE_SECTORS, sector, sector, 0, 0],
+        [command::ERASE_PAGE, page, page, system_clock_khz, 0],
+        [command::PREPARE_SECTORS, sector, sector, 0, 0],
+        [
+            command::COPY_RAM_TO_FLASH,
+            address,
+            data.0.as_ptr() as u32,
+            PAGE_SIZE as u32,
+            system_clock_khz,
+        ],
+    ];
+
+    interrupt::free(|_| {
+        for command in &commands {
+            let mut command = *command;
+            let mut result = [0; 4];
+
+            // Sound, as the commands have been validated above and only
+            // affect the page that the caller asked us to write.
+            unsafe { call(&mut command, &mut result) };
+
+            Error::check(result[0])?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Return the number of flash pages
+fn flash_pages() -> u32 {
+    // The build script falls back to the LPC822, if no specific part of the
+    // LPC82x family has been selected.
+    #[cfg(all(feature = "82x", not(feature = "824")))]
+    const FLASH_SIZE: u32 = 16 * 1024;
+    #[cfg(feature = "824")]
+    const FLASH_SIZE: u32 = 32 * 1024;
+    #[cfg(feature = "845")]
+    const FLASH_SIZE: u32 = 64 * 1024;
+
+    FLASH_SIZE / PAGE_SIZE as u32
+}
+
+/// An error returned by an IAP command
+///
+/// See user manual, section 24.5.5.17 (LPC82x) or 5.6.13 (LPC845).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The source address is not word-aligned
+    SourceAddress,
+
+    /// The destination address is not aligned correctly
+    DestinationAddress,
+
+    /// The source address is not mapped in the memory map
+    SourceNotMapped,
+
+    /// The destination address is not mapped in the memory map
+    DestinationNotMapped,
+
+    /// The byte count is not a valid value
+    Count,
+
+    /// The sector number is invalid
+    Sector,
+
+    /// The sector has not been prepared for writing
+    SectorNotPrepared,
+
+    /// The flash programming hardware is busy
+    Busy,
+
+    /// Any other status code returned by the boot ROM
+    Other(u32),
+}
+
+impl Error {
+    fn check(status: u32) -> Result<(), Self> {
+        match status {
+            0 => Ok(()),
+            2 => Err(Error::SourceAddress),
+            3 => Err(Error::DestinationAddress),
+            4 => Err(Error::SourceNotMapped),
+            5 => Err(Error::DestinationNotMapped),
+            6 => Err(Error::Count),
+            7 => Err(Error::Sector),
+            9 => Err(Error::SectorNotPrepared),
+            11 => Err(Error::Busy),
+            status => Err(Error::Other(status)),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SourceAddress => f.write_str("IAP source address unaligned"),
+            Error::DestinationAddress => {
+                f.write_str("IAP destination address unaligned")
+            }
+            Error::SourceNotMapped => {
+                f.write_str("IAP source address not mapped")
+            }
+            Error::DestinationNotMapped => {
+                f.write_str("IAP destination address not mapped")
+            }
+            Error::Count => f.write_str("IAP byte count invalid"),
+            Error::Sector => f.write_str("IAP sector number invalid"),
+            Error::SectorNotPrepared => {
+                f.write_str("IAP sector not prepared for writing")
+            }
+            Error::Busy => f.write_str("IAP flash programming busy"),
+            Error::Other(status) => write!(f, "IAP status code {}", status),
+        }
+    }
+}
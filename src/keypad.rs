@@ -0,0 +1,247 @@
+//! Scanner for key matrices, using GPIO and an MRT channel
+//!
+//! Key matrices connect each key between a row and a column line. The rows
+//! are driven low one at a time, while the columns are read back with pull-up
+//! resistors enabled. A column that reads low means the key at the crossing of
+//! that column and the active row is pressed.
+//!
+//! The entry point to this API is [`Keypad`]. It advances the scan by one row
+//! every time its MRT channel expires and debounces every key separately.
+//! Changes of the debounced state are delivered as [`Event`]s.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     prelude::*,
+//!     keypad::{Event, Keypad},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//!
+//! let mut row0 = swm.pins.pio0_10.into_gpio_pin(&p.GPIO).into_output();
+//! let mut row1 = swm.pins.pio0_11.into_gpio_pin(&p.GPIO).into_output();
+//! let col0 = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_input();
+//! let col1 = swm.pins.pio0_13.into_gpio_pin(&p.GPIO).into_input();
+//!
+//! let mut keypad = Keypad::new(
+//!     [&mut row0, &mut row1],
+//!     [&col0, &col1],
+//!     timer,
+//!     1.millis(),
+//! );
+//!
+//! loop {
+//!     // Can also be called from the MRT0 interrupt handler
+//!     keypad.poll();
+//!
+//!     while let Some(event) = keypad.next_event() {
+//!         match event {
+//!             Event::Pressed { row, column } => {}
+//!             Event::Released { row, column } => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use embedded_hal::{
+    digital::v2::{InputPin, OutputPin},
+    timer::CountDown as _,
+};
+use void::{ResultVoidExt as _, Void};
+
+use crate::mrt::MrtChannel;
+
+/// The number of entries in the event queue
+pub const QUEUE_LEN: usize = 8;
+
+/// The number of consecutive scans a key must be stable, to change its state
+const DEBOUNCE_SCANS: u8 = 4;
+
+/// A key matrix with `R` rows and `C` columns
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Keypad<'a, const R: usize, const C: usize> {
+    rows: [&'a mut dyn OutputPin<Error = Void>; R],
+    columns: [&'a dyn InputPin<Error = Void>; C],
+    timer: MrtChannel,
+
+    row: usize,
+    counters: [[u8; C]; R],
+    pressed: [[bool; C]; R],
+
+    queue: [Event; QUEUE_LEN],
+    queue_start: usize,
+    queue_len: usize,
+}
+
+impl<'a, const R: usize, const C: usize> Keypad<'a, R, C> {
+    /// Create the keypad and start scanning
+    ///
+    /// The column inputs need pull-up resistors, either external ones, or the
+    /// internal ones configured through IOCON. `row_interval` is the time
+    /// every row is driven, before the columns are read. Every key is scanned
+    /// once every `R` times `row_interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `R` or `C` is zero, or if `row_interval` is longer than
+    /// `timer` supports (see [`MrtChannel::max_us`]).
+    ///
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new(
+        mut rows: [&'a mut dyn OutputPin<Error = Void>; R],
+        columns: [&'a dyn InputPin<Error = Void>; C],
+        mut timer: MrtChannel,
+        row_interval: fugit::MicrosDurationU32,
+    ) -> Self {
+        assert!(R > 0 && C > 0);
+
+        for row in rows.iter_mut() {
+            row.set_high().void_unwrap();
+        }
+        rows[0].set_low().void_unwrap();
+
+        if let Err(error) = timer.start_duration(row_interval) {
+            panic!("{}", error);
+        }
+
+        Keypad {
+            rows,
+            columns,
+            timer,
+
+            row: 0,
+            counters: [[0; C]; R],
+            pressed: [[false; C]; R],
+
+            queue: [Event::Released { row: 0, column: 0 }; QUEUE_LEN],
+            queue_start: 0,
+            queue_len: 0,
+        }
+    }
+
+    /// Advance the scan, if the timer has expired
+    ///
+    /// Call this method from the main loop, or from the MRT0 interrupt
+    /// handler, after enabling the interrupt using
+    /// [`MrtChannel::enable_interrupt`]. It needs to be called at least once
+    /// per row interval, or the scan will slow down.
+    ///
+    /// The columns are read for the row that has been driven since the last
+    /// call, which gives the lines a full interval to settle.
+    ///
+    /// [`MrtChannel::enable_interrupt`]: ../mrt/struct.MrtChannel.html#method.enable_interrupt
+    pub fn poll(&mut self) {
+        if self.timer.wait().is_err() {
+            return;
+        }
+
+        let row = self.row;
+        for column in 0..C {
+            let active = self.columns[column].is_low().void_unwrap();
+            self.debounce(row, column, active);
+        }
+
+        self.rows[row].set_high().void_unwrap();
+        self.row = (row + 1) % R;
+        self.rows[self.row].set_low().void_unwrap();
+    }
+
+    /// Indicates whether a key is currently pressed, after debouncing
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `row` or `column` is out of range.
+    pub fn is_pressed(&self, row: usize, column: usize) -> bool {
+        self.pressed[row][column]
+    }
+
+    /// Return the next event from the queue
+    ///
+    /// Returns `None`, if the queue is empty. If the queue runs full, new
+    /// events are dropped, but [`Keypad::is_pressed`] still reflects the
+    /// current state.
+    ///
+    /// [`Keypad::is_pressed`]: #method.is_pressed
+    pub fn next_event(&mut self) -> Option<Event> {
+        if self.queue_len == 0 {
+            return None;
+        }
+
+        let event = self.queue[self.queue_start];
+        self.queue_start = (self.queue_start + 1) % QUEUE_LEN;
+        self.queue_len -= 1;
+
+        Some(event)
+    }
+
+    /// Stop scanning and release the resources used by the keypad
+    pub fn free(
+        self,
+    ) -> (
+        [&'a mut dyn OutputPin<Error = Void>; R],
+        [&'a dyn InputPin<Error = Void>; C],
+        MrtChannel,
+    ) {
+        self.rows[self.row].set_high().void_unwrap();
+        (self.rows, self.columns, self.timer)
+    }
+
+    fn debounce(&mut self, row: usize, column: usize, active: bool) {
+        // The counter integrates the readings. The state only changes once it
+        // has reached either end of its range.
+        let counter = &mut self.counters[row][column];
+        if active && *counter < DEBOUNCE_SCANS {
+            *counter += 1;
+        }
+        if !active && *counter > 0 {
+            *counter -= 1;
+        }
+
+        let pressed = &mut self.pressed[row][column];
+        let event = match (*pressed, *counter) {
+            (false, DEBOUNCE_SCANS) => Event::Pressed { row, column },
+            (true, 0) => Event::Released { row, column },
+            _ => return,
+        };
+        *pressed = !*pressed;
+
+        if self.queue_len < QUEUE_LEN {
+            let i = (self.queue_start + self.queue_len) % QUEUE_LEN;
+            self.queue[i] = event;
+            self.queue_len += 1;
+        }
+    }
+}
+
+/// A change of a key's debounced state
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// A key has been pressed
+    Pressed {
+        /// The row of the key
+        row: usize,
+
+        /// The column of the key
+        column: usize,
+    },
+
+    /// A key has been released
+    Released {
+        /// The row of the key
+        row: usize,
+
+        /// The column of the key
+        column: usize,
+    },
+}
@@ -0,0 +1,218 @@
+//! Interrupt latency measurement, using the SCT
+//!
+//! When tasks of different priorities share the CPU, as they do with RTIC, the
+//! latency of an interrupt depends on what else is running at the time. To
+//! find out whether a priority setup meets its deadlines, [`LatencyProbe`]
+//! measures the time between a hardware event and the entry into the
+//! interrupt handler that the event triggers, and keeps track of the worst
+//! case.
+//!
+//! The event needs to be a rising edge on a pin, which is connected to an SCT
+//! input in addition to whatever triggers the interrupt. A half of the SCT
+//! (see [`sct`]) captures its counter on the edge, and the interrupt handler
+//! calls [`LatencyProbe::record`] first thing, which compares the captured
+//! value with the current one. Useful setups are:
+//!
+//! - A pin interrupt (see [`pinint`]), on a pin that is also connected to the
+//!   SCT input, externally.
+//! - The input of a peripheral, like the RX pin of a USART, connected to the
+//!   SCT input. The latency then includes the time the peripheral takes to
+//!   request the interrupt.
+//!
+//! The resolution is one cycle of the system clock, whose frequency is taken
+//! from [`syscon::system_clock_hz`], as it is in the [`sct`] module.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{latency::LatencyProbe, pinint::Edge, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! // PIO0_4 and PIO0_17 are connected to each other, and to the source of
+//! // the event.
+//! let pin = swm.pins.pio0_4.into_gpio_pin(&p.GPIO).into_input();
+//! let (input, _) = swm
+//!     .movable_functions
+//!     .sct_pin0
+//!     .assign(swm.pins.pio0_17.into_swm_pin(), &mut swm_handle);
+//!
+//! let mut pinint = p.PINT.enable(&mut syscon.handle);
+//! pinint.configure(0, &pin, Edge::Rising, &mut syscon.handle);
+//!
+//! let counters = p.SCT0.split(&mut syscon.handle);
+//! let mut probe = LatencyProbe::new(counters.high, input);
+//!
+//! // First thing in the PININT0 interrupt handler
+//! probe.record();
+//!
+//! // Later, after the application has run for a while
+//! let statistics = probe.statistics();
+//! let worst_case_ns = statistics.max_ns;
+//! ```
+//!
+//! [`LatencyProbe`]: struct.LatencyProbe.html
+//! [`sct`]: ../sct/index.html
+//! [`LatencyProbe::record`]: struct.LatencyProbe.html#method.record
+//! [`pinint`]: ../pinint/index.html
+//! [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+
+use crate::{
+    sct::{self, Counter, FrequencyCounter},
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+/// Measures the latency of an interrupt
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct LatencyProbe<C, I, P> {
+    counter: FrequencyCounter<C, I, P>,
+
+    samples: u32,
+    unmatched: u32,
+    min: u16,
+    max: u16,
+    total: u64,
+}
+
+impl<C, I, P> LatencyProbe<C, I, P>
+where
+    C: sct::Half,
+    I: sct::InputTrait,
+{
+    /// Start capturing rising edges of `input`, using an SCT half
+    ///
+    /// The counter runs at the full SCT clock, so it wraps around after
+    /// 65536 ticks, or about 5.4 ms. Latencies longer than that are
+    /// reported as the remainder.
+    pub fn new(
+        counter: Counter<C>,
+        input: swm::Function<I, Assigned<P>>,
+    ) -> Self {
+        let mut probe = LatencyProbe {
+            counter: counter.into_frequency_counter(0, input),
+
+            samples: 0,
+            unmatched: 0,
+            min: u16::MAX,
+            max: 0,
+            total: 0,
+        };
+        probe.reset();
+
+        probe
+    }
+
+    /// Record the entry into the interrupt handler
+    ///
+    /// Call this first thing in the interrupt handler, as everything that
+    /// happens before is counted as latency. Returns the latency in ns, or
+    /// `None`, if no edge has been captured since the last call.
+    ///
+    /// Only the latest edge is captured. If the event triggers the interrupt
+    /// again before the handler runs, the latency is measured from the last
+    /// edge.
+    pub fn record(&mut self) -> Option<u32> {
+        let now = self.counter.count();
+
+        let capture = match self.counter.capture() {
+            Some(capture) => capture,
+            None => {
+                self.unmatched = self.unmatched.wrapping_add(1);
+                return None;
+            }
+        };
+
+        let ticks = now.wrapping_sub(capture);
+
+        self.samples = self.samples.wrapping_add(1);
+        self.min = self.min.min(ticks);
+        self.max = self.max.max(ticks);
+        self.total += ticks as u64;
+
+        Some(ticks_to_ns(ticks as u64))
+    }
+
+    /// Return the statistics of all latencies recorded so far
+    pub fn statistics(&self) -> Statistics {
+        let (min, mean) = if self.samples > 0 {
+            (self.min, self.total / self.samples as u64)
+        } else {
+            (0, 0)
+        };
+
+        Statistics {
+            samples: self.samples,
+            unmatched: self.unmatched,
+            min_ns: ticks_to_ns(min as u64),
+            max_ns: ticks_to_ns(self.max as u64),
+            mean_ns: ticks_to_ns(mean),
+        }
+    }
+
+    /// Return the statistics, and start over
+    ///
+    /// Discards an edge that has been captured, but not recorded yet.
+    pub fn reset(&mut self) -> Statistics {
+        let statistics = self.statistics();
+
+        let _ = self.counter.capture();
+
+        self.samples = 0;
+        self.unmatched = 0;
+        self.min = u16::MAX;
+        self.max = 0;
+        self.total = 0;
+
+        statistics
+    }
+
+    /// Stop measuring and release the resources used
+    pub fn free(self) -> (Counter<C>, swm::Function<I, Assigned<P>>) {
+        self.counter.free()
+    }
+}
+
+/// Statistics of the recorded latencies
+///
+/// Returned by [`LatencyProbe::statistics`] and [`LatencyProbe::reset`]. All
+/// latencies are `0`, if no latency has been recorded.
+///
+/// [`LatencyProbe::statistics`]: struct.LatencyProbe.html#method.statistics
+/// [`LatencyProbe::reset`]: struct.LatencyProbe.html#method.reset
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Statistics {
+    /// The number of latencies recorded
+    pub samples: u32,
+
+    /// The number of times the interrupt handler was entered without an edge
+    ///
+    /// Happens, if the interrupt is triggered by something else, or if the
+    /// handler runs more than once per edge.
+    pub unmatched: u32,
+
+    /// The shortest latency, in ns
+    pub min_ns: u32,
+
+    /// The longest latency, in ns
+    pub max_ns: u32,
+
+    /// The average latency, in ns
+    pub mean_ns: u32,
+}
+
+fn ticks_to_ns(ticks: u64) -> u32 {
+    (ticks * 1_000_000_000 / syscon::system_clock_hz() as u64) as u32
+}
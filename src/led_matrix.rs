@@ -0,0 +1,223 @@
+//! Driver for multiplexed LED matrices, using GPIO and an MRT channel
+//!
+//! Multiplexed LED matrices connect each LED between a row and a column line.
+//! Only one row is lit at a time, and the rows are cycled fast enough for the
+//! eye to see a steady image. The entry point to this API is [`LedMatrix`],
+//! which takes care of that refresh loop. It displays the contents of a frame
+//! buffer and can dim every row separately, by reducing the time the row is
+//! lit.
+//!
+//! The rows are expected to be active-high (sourcing current), and the columns
+//! to be active-low (sinking current).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{prelude::*, led_matrix::LedMatrix, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let [mut timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//! timer.enable_interrupt();
+//!
+//! let mut row0 = swm.pins.pio0_10.into_gpio_pin(&p.GPIO).into_output();
+//! let mut row1 = swm.pins.pio0_11.into_gpio_pin(&p.GPIO).into_output();
+//! let mut col0 = swm.pins.pio0_12.into_gpio_pin(&p.GPIO).into_output();
+//! let mut col1 = swm.pins.pio0_13.into_gpio_pin(&p.GPIO).into_output();
+//!
+//! let mut matrix = LedMatrix::new(
+//!     [&mut row0, &mut row1],
+//!     [&mut col0, &mut col1],
+//!     timer,
+//!     2.millis(),
+//! );
+//!
+//! matrix.set_frame([[true, false], [false, true]]);
+//! matrix.set_brightness(1, 64);
+//!
+//! // Call this from the MRT0 interrupt handler.
+//! matrix.handle_interrupt();
+//! ```
+
+use embedded_hal::{digital::v2::OutputPin, timer::CountDown as _};
+use void::{ResultVoidExt as _, Void};
+
+use crate::{mrt::MrtChannel, syscon};
+
+/// A multiplexed LED matrix with `R` rows and `C` columns
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct LedMatrix<'a, const R: usize, const C: usize> {
+    rows: [&'a mut dyn OutputPin<Error = Void>; R],
+    columns: [&'a mut dyn OutputPin<Error = Void>; C],
+    timer: MrtChannel,
+    row_ticks: u32,
+
+    frame: [[bool; C]; R],
+    brightness: [u8; R],
+
+    row: usize,
+    lit: bool,
+}
+
+impl<'a, const R: usize, const C: usize> LedMatrix<'a, R, C> {
+    /// Create the LED matrix and start refreshing it
+    ///
+    /// Every row is displayed for `row_period`, so the whole matrix is
+    /// refreshed every `R` times `row_period`. Initially, all LEDs are off and
+    /// all rows are set to full brightness.
+    ///
+    /// The refresh is driven by [`LedMatrix::handle_interrupt`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `R` or `C` is zero, or if `row_period` is shorter than
+    /// 256 µs, which is the minimum that allows for 256 brightness levels.
+    /// Also panics, if `row_period` is longer than `timer` supports (see
+    /// [`MrtChannel::max_us`]).
+    ///
+    /// [`LedMatrix::handle_interrupt`]: #method.handle_interrupt
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new(
+        mut rows: [&'a mut dyn OutputPin<Error = Void>; R],
+        mut columns: [&'a mut dyn OutputPin<Error = Void>; C],
+        mut timer: MrtChannel,
+        row_period: fugit::MicrosDurationU32,
+    ) -> Self {
+        assert!(R > 0 && C > 0);
+        assert!(row_period.to_micros() >= 256);
+
+        for row in rows.iter_mut() {
+            row.set_low().void_unwrap();
+        }
+        for column in columns.iter_mut() {
+            column.set_high().void_unwrap();
+        }
+
+        if let Err(error) = timer.start_duration(row_period) {
+            panic!("{}", error);
+        }
+
+        // Can't be `None`, as `start_duration` has checked the period above.
+        let row_ticks =
+            syscon::us_to_ticks(row_period.to_micros()).unwrap_or_default();
+
+        LedMatrix {
+            rows,
+            columns,
+            timer,
+            row_ticks,
+
+            frame: [[false; C]; R],
+            brightness: [u8::MAX; R],
+
+            row: R - 1,
+            lit: false,
+        }
+    }
+
+    /// Advance the refresh, if the timer has expired
+    ///
+    /// Call this method from the MRT0 interrupt handler, after enabling the
+    /// interrupt using [`MrtChannel::enable_interrupt`]. Any delay in calling
+    /// this method will cause the display to flicker, or the brightness to be
+    /// off.
+    ///
+    /// [`MrtChannel::enable_interrupt`]: ../mrt/struct.MrtChannel.html#method.enable_interrupt
+    pub fn handle_interrupt(&mut self) {
+        if self.timer.wait().is_err() {
+            return;
+        }
+
+        if self.lit {
+            // The on-time of the current row is over. Turn it off for the rest
+            // of its period, unless it is displayed at full brightness.
+            self.rows[self.row].set_low().void_unwrap();
+            self.lit = false;
+
+            let off_ticks = self.row_ticks - self.on_ticks(self.row);
+            if off_ticks > 0 {
+                self.timer.start(off_ticks - 1);
+                return;
+            }
+        }
+
+        self.row = (self.row + 1) % R;
+
+        let on_ticks = self.on_ticks(self.row);
+        if on_ticks == 0 {
+            self.timer.start(self.row_ticks - 1);
+            return;
+        }
+
+        for (column, &on) in self.columns.iter_mut().zip(&self.frame[self.row])
+        {
+            if on {
+                column.set_low().void_unwrap();
+            } else {
+                column.set_high().void_unwrap();
+            }
+        }
+
+        self.rows[self.row].set_high().void_unwrap();
+        self.lit = true;
+        self.timer.start(on_ticks - 1);
+    }
+
+    /// Replace the whole frame buffer
+    ///
+    /// `true` means the LED is on. The new frame is displayed, starting with
+    /// the next row.
+    pub fn set_frame(&mut self, frame: [[bool; C]; R]) {
+        self.frame = frame;
+    }
+
+    /// Switch a single LED on or off
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `row` or `column` is out of range.
+    pub fn set_pixel(&mut self, row: usize, column: usize, on: bool) {
+        self.frame[row][column] = on;
+    }
+
+    /// Return the current frame buffer
+    pub fn frame(&self) -> &[[bool; C]; R] {
+        &self.frame
+    }
+
+    /// Set the brightness of a row
+    ///
+    /// The row is lit for `brightness / 255` of its period. `0` means the row
+    /// is always off, `255` means it is lit for its whole period.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `row` is out of range.
+    pub fn set_brightness(&mut self, row: usize, brightness: u8) {
+        self.brightness[row] = brightness;
+    }
+
+    /// Stop refreshing and release the resources used by the LED matrix
+    ///
+    /// All LEDs are switched off.
+    pub fn free(
+        self,
+    ) -> (
+        [&'a mut dyn OutputPin<Error = Void>; R],
+        [&'a mut dyn OutputPin<Error = Void>; C],
+        MrtChannel,
+    ) {
+        self.rows[self.row].set_low().void_unwrap();
+        (self.rows, self.columns, self.timer)
+    }
+
+    fn on_ticks(&self, row: usize) -> u32 {
+        self.row_ticks * self.brightness[row] as u32 / u8::MAX as u32
+    }
+}
@@ -52,6 +52,10 @@
 //!
 //! Again, the available options are listed in [`Cargo.toml`].
 //!
+//! If you are using [defmt] for logging, you can enable the `defmt` feature.
+//! This implements `defmt::Format` for the error types and other public types
+//! of this crate, so you can log them directly.
+//!
 //! Please note that LPC8xx HAL is an implementation of [embedded-hal]. If you
 //! are writing code that is not specific to LPC800, please consider depending
 //! on embedded-hal instead.
@@ -62,6 +66,25 @@
 //! [`Cargo.toml`]: https://github.com/lpc-rs/lpc8xx-hal/blob/master/Cargo.toml
 //!
 //!
+//! ## Code size
+//!
+//! The default features enable convenience layers that cost flash, once they
+//! are used: `fmt` implements `core::fmt::Write` for the USART transmitter,
+//! `panics` provides the methods that panic on invalid arguments. To make sure
+//! an application doesn't pull in the formatting and panicking machinery by
+//! accident, disable the default features:
+//!
+//! ``` toml
+//! [dependencies.lpc8xx-hal]
+//! version          = "0.6"
+//! default-features = false
+//! features         = ["824m201jhi33", "82x-rt"]
+//! ```
+//!
+//! The [tiny example] uses GPIO, USART, and delays this way. `scripts/size.sh`
+//! builds it and checks that it fits into 4 KiB of flash.
+//!
+//!
 //! ## Examples
 //!
 //! There are a number of [examples in the repository]. A good place to start is
@@ -90,8 +113,10 @@
 //! manual, which is [available from NXP].
 //!
 //! [embedded-hal]: https://crates.io/crates/embedded-hal
+//! [defmt]: https://crates.io/crates/defmt
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 //! [GPIO example]: https://github.com/lpc-rs/lpc8xx-hal/blob/master/examples/gpio_delay.rs
+//! [tiny example]: https://github.com/lpc-rs/lpc8xx-hal/blob/master/examples/tiny.rs
 //! [available from NXP]: https://www.nxp.com/docs/en/user-guide/UM10800.pdf
 
 #![no_std]
@@ -104,25 +129,76 @@ pub extern crate cortex_m;
 #[cfg(feature = "rt-selected")]
 pub extern crate cortex_m_rt;
 pub extern crate embedded_hal;
+pub extern crate fugit;
 pub extern crate nb;
 
 #[macro_use]
 pub(crate) mod reg_proxy;
 
+pub mod ab_image;
+pub mod acmp;
+pub mod adc;
+#[cfg(feature = "82x")]
+pub mod calibration;
 pub mod clock;
+pub mod clock_check;
+pub mod console;
+pub mod crc;
 #[cfg(feature = "845")]
 pub mod ctimer;
+#[cfg(feature = "845")]
+pub mod dac;
+pub mod data_logger;
+pub mod debounce;
+#[cfg(feature = "debug_dump")]
+pub mod debug_dump;
 pub mod delay;
 pub mod dma;
+pub mod dmx;
+pub mod encoder;
+pub mod error;
+pub mod event_counter;
+#[cfg(feature = "factory_test")]
+pub mod factory_test;
+pub mod failsafe;
+pub mod fgen;
 pub mod gpio;
 pub mod i2c;
+pub mod iap;
+pub mod keypad;
+pub mod latency;
+pub mod led_matrix;
+pub mod line_editor;
+pub mod melody;
+pub mod modbus;
 pub mod mrt;
+pub mod pinint;
 pub mod pmu;
+#[cfg(feature = "845")]
+pub mod ppm;
+pub mod pwm_audio;
+pub mod retained;
+pub mod route;
+pub mod scheduler;
+pub mod sct;
+#[cfg(feature = "845")]
+pub mod servo;
 pub mod sleep;
+pub mod spi;
+pub mod supervisor;
 pub mod swm;
+pub mod sync;
 pub mod syscon;
+pub mod timestamp;
+#[cfg(feature = "845")]
+pub mod ultrasonic;
+pub mod uptime;
 pub mod usart;
+pub mod vector_table;
+pub mod waveform;
 pub mod wkt;
+pub mod wwdt;
+pub mod xmodem;
 
 /// Re-exports various traits that are required to use lpc8xx-hal
 ///
@@ -137,9 +213,11 @@ pub mod wkt;
 /// The traits in this module have been renamed, to avoid collisions with other
 /// imports.
 pub mod prelude {
+    #[cfg(feature = "fmt")]
     pub use core::fmt::Write as _;
 
     pub use crate::clock::{Enabled as _, Frequency as _};
+    pub use crate::fugit::{ExtU32 as _, RateExtU32 as _};
     pub use crate::hal::{digital::v2::*, prelude::*};
     pub use crate::sleep::Sleep as _;
 }
@@ -149,17 +227,27 @@ pub use lpc82x_pac as pac;
 #[cfg(feature = "845")]
 pub use lpc845_pac as pac;
 
+pub use self::acmp::ACMP;
+pub use self::adc::ADC;
+pub use self::crc::CRC;
 #[cfg(feature = "845")]
 pub use self::ctimer::CTimer;
+#[cfg(feature = "845")]
+pub use self::dac::DAC;
 pub use self::dma::DMA;
+pub use self::error::Error;
 pub use self::gpio::GPIO;
 pub use self::i2c::I2C;
 pub use self::mrt::MRT;
+pub use self::pinint::PININT;
 pub use self::pmu::PMU;
+pub use self::sct::SCT;
+pub use self::spi::SPI;
 pub use self::swm::SWM;
 pub use self::syscon::SYSCON;
 pub use self::usart::USART;
 pub use self::wkt::WKT;
+pub use self::wwdt::WWDT;
 
 use embedded_hal as hal;
 
@@ -195,10 +283,23 @@ use embedded_hal as hal;
 /// use of the hardware.
 #[allow(non_snake_case)]
 pub struct Peripherals {
+    /// Analog comparator
+    pub ACOMP: ACMP<init_state::Disabled>,
+
+    /// Analog-to-Digital Converter (ADC)
+    pub ADC0: ADC<init_state::Disabled>,
+
     /// Standard counter/timer (CTIMER)
     #[cfg(feature = "845")]
     pub CTIMER0: CTimer,
 
+    /// CRC engine
+    pub CRC: CRC<init_state::Disabled>,
+
+    /// Digital-to-Analog Converter 0 (DAC0)
+    #[cfg(feature = "845")]
+    pub DAC0: DAC<init_state::Disabled>,
+
     /// DMA controller
     pub DMA: DMA,
 
@@ -222,9 +323,21 @@ pub struct Peripherals {
     /// Multi-Rate Timer (MRT)
     pub MRT0: MRT,
 
+    /// Pin interrupt and pattern match engine
+    pub PINT: PININT<init_state::Disabled>,
+
     /// Power Management Unit
     pub PMU: PMU,
 
+    /// State Configurable Timer (SCT)
+    pub SCT0: SCT,
+
+    /// SPI0
+    pub SPI0: SPI<pac::SPI0, init_state::Disabled>,
+
+    /// SPI1
+    pub SPI1: SPI<pac::SPI1, init_state::Disabled>,
+
     /// Switch matrix
     ///
     /// By default, the switch matrix is enabled on the LPC82x and disabled on
@@ -274,19 +387,8 @@ pub struct Peripherals {
     /// Self-wake-up timer (WKT)
     pub WKT: WKT<init_state::Disabled>,
 
-    /// Analog comparator
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub ACOMP: pac::ACOMP,
-
-    /// Analog-to-Digital Converter (ADC)
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub ADC0: pac::ADC0,
+    /// Windowed Watchdog Timer (WWDT)
+    pub WWDT: WWDT<init_state::Disabled>,
 
     /// Capacitive Touch (CAPT)
     ///
@@ -296,21 +398,6 @@ pub struct Peripherals {
     #[cfg(feature = "845")]
     pub CAPT: pac::CAPT,
 
-    /// CRC engine
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub CRC: pac::CRC,
-
-    /// Digital-to-Analog Converter 0 (DAC0)
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    #[cfg(feature = "845")]
-    pub DAC0: pac::DAC0,
-
     /// Digital-to-Analog Converter 1 (DAC1)
     ///
     /// A HAL API for this peripheral has not been implemented yet. In the
@@ -361,41 +448,6 @@ pub struct Peripherals {
     /// allow you full, unprotected access to the peripheral.
     pub IOCON: pac::IOCON,
 
-    /// Pin interrupt and pattern match engine
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub PINT: pac::PINT,
-
-    /// State Configurable Timer (SCT)
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub SCT0: pac::SCT0,
-
-    /// SPI0
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub SPI0: pac::SPI0,
-
-    /// SPI1
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub SPI1: pac::SPI1,
-
-    /// Windowed Watchdog Timer (WWDT)
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub WWDT: pac::WWDT,
-
     /// CPUID
     ///
     /// This is a core peripherals that's available on all ARM Cortex-M0+ cores.
@@ -516,8 +568,13 @@ impl Peripherals {
     fn new(p: pac::Peripherals, cp: pac::CorePeripherals) -> Self {
         Peripherals {
             // HAL peripherals
+            ACOMP: ACMP::new(p.ACOMP),
+            ADC0: ADC::new(p.ADC0),
             #[cfg(feature = "845")]
             CTIMER0: CTimer::new(p.CTIMER0),
+            CRC: CRC::new(p.CRC),
+            #[cfg(feature = "845")]
+            DAC0: DAC::new(p.DAC0),
             DMA: DMA::new(p.DMA0),
             // NOTE(unsafe) The init state of the gpio peripheral is enabled,
             // thus it's safe to create an already initialized gpio port
@@ -527,7 +584,11 @@ impl Peripherals {
             GPIO: GPIO::new(p.GPIO),
             I2C0: I2C::new(p.I2C0),
             MRT0: MRT::new(p.MRT0),
+            PINT: PININT::new(p.PINT),
             PMU: PMU::new(p.PMU),
+            SCT0: SCT::new(p.SCT0),
+            SPI0: SPI::new(p.SPI0),
+            SPI1: SPI::new(p.SPI1),
             #[cfg(feature = "82x")]
             SWM: unsafe { SWM::new_enabled(p.SWM0) },
             #[cfg(feature = "845")]
@@ -541,15 +602,11 @@ impl Peripherals {
             #[cfg(feature = "845")]
             USART4: USART::new(p.USART4),
             WKT: WKT::new(p.WKT),
+            WWDT: WWDT::new(p.WWDT),
 
             // Raw peripherals
-            ACOMP: p.ACOMP,
-            ADC0: p.ADC0,
             #[cfg(feature = "845")]
             CAPT: p.CAPT,
-            CRC: p.CRC,
-            #[cfg(feature = "845")]
-            DAC0: p.DAC0,
             #[cfg(feature = "845")]
             DAC1: p.DAC1,
             FLASH_CTRL: p.FLASH_CTRL,
@@ -558,11 +615,6 @@ impl Peripherals {
             I2C3: p.I2C3,
             INPUTMUX: p.INPUTMUX,
             IOCON: p.IOCON,
-            PINT: p.PINT,
-            SCT0: p.SCT0,
-            SPI0: p.SPI0,
-            SPI1: p.SPI1,
-            WWDT: p.WWDT,
 
             // Core peripherals
             CPUID: cp.CPUID,
@@ -587,8 +639,10 @@ pub mod init_state {
     /// This usually indicates that the hardware has been initialized and can be
     /// used for its intended purpose. Contains an optional payload that APIs
     /// can use to keep data that is only available while enabled.
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Enabled<T = ()>(pub T);
 
     /// Indicates that the hardware component is disabled
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Disabled;
 }
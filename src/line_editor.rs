@@ -0,0 +1,167 @@
+//! Line input for a serial console
+//!
+//! Command shells and other bring-up tools need to read lines of text that a
+//! user types in a terminal. [`LineEditor`] collects the characters received
+//! from a [`Console`] into a line buffer, echoes them back, handles
+//! backspace, and delivers the line, once the user presses enter.
+//!
+//! Both CR and LF end a line. CR LF counts as a single line ending, so
+//! terminals that send either work. Characters that don't fit into the
+//! buffer are dropped, and the terminal's bell is rung instead of echoing
+//! them.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{console::Console, line_editor::LineEditor};
+//!
+//! fn shell(console: &mut dyn Console) -> ! {
+//!     let mut editor = LineEditor::<64>::new();
+//!
+//!     loop {
+//!         match editor.poll(console) {
+//!             Ok(b"help") => console.bwrite_all(b"no help available\r\n"),
+//!             Ok(_) => console.bwrite_all(b"unknown command\r\n"),
+//!             Err(nb::Error::WouldBlock) => {}
+//!             Err(nb::Error::Other(_)) => {
+//!                 console.bwrite_all(b"receive error\r\n");
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`LineEditor`]: struct.LineEditor.html
+//! [`Console`]: ../console/trait.Console.html
+
+use crate::{console::Console, usart};
+
+const BACKSPACE: u8 = 0x08;
+const BELL: u8 = 0x07;
+const CR: u8 = b'\r';
+const DELETE: u8 = 0x7f;
+const LF: u8 = b'\n';
+
+/// Collects a line of input from a console
+///
+/// `N` is the maximum length of a line, in bytes. Please refer to the
+/// [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct LineEditor<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    echo: bool,
+
+    // The last line was ended by CR, so an LF right after it is ignored.
+    after_cr: bool,
+
+    // The last call to `poll` returned a line, which is discarded before
+    // receiving the next one.
+    complete: bool,
+}
+
+impl<const N: usize> LineEditor<N> {
+    /// Create a line editor with an empty line buffer
+    ///
+    /// Received characters are echoed by default.
+    pub fn new() -> Self {
+        LineEditor {
+            buffer: [0; N],
+            len: 0,
+            echo: true,
+
+            after_cr: false,
+            complete: false,
+        }
+    }
+
+    /// Enable or disable the echo of received characters
+    ///
+    /// Disable the echo, if the terminal already shows what the user types
+    /// (local echo), or for password entry.
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// Process all characters that have been received
+    ///
+    /// Returns the line, once a line ending has been received. The line
+    /// ending itself is not included. The line is discarded by the next call
+    /// to this method.
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)`, if the line isn't complete yet.
+    /// Call this method from the main loop, or whenever a character has been
+    /// received.
+    ///
+    /// If a receive error occurs, it is returned, and the line received so
+    /// far is kept.
+    pub fn poll(
+        &mut self,
+        console: &mut dyn Console,
+    ) -> nb::Result<&[u8], usart::Error> {
+        if self.complete {
+            self.clear();
+        }
+
+        loop {
+            let word = console.read()?;
+
+            match word {
+                LF if self.after_cr => {
+                    self.after_cr = false;
+                }
+                CR | LF => {
+                    self.after_cr = word == CR;
+                    self.complete = true;
+                    self.echo(console, b"\r\n");
+
+                    return Ok(&self.buffer[..self.len]);
+                }
+                BACKSPACE | DELETE => {
+                    self.after_cr = false;
+
+                    if self.len > 0 {
+                        self.len -= 1;
+                        // Move back, overwrite the character, move back again
+                        self.echo(console, &[BACKSPACE, b' ', BACKSPACE]);
+                    }
+                }
+                _ => {
+                    self.after_cr = false;
+
+                    if self.len < N {
+                        self.buffer[self.len] = word;
+                        self.len += 1;
+                        self.echo(console, &[word]);
+                    } else {
+                        self.echo(console, &[BELL]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the part of the line that has been received so far
+    pub fn partial_line(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Discard the line that has been received so far
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.complete = false;
+    }
+
+    fn echo(&self, console: &mut dyn Console, words: &[u8]) {
+        if self.echo {
+            console.bwrite_all(words);
+        }
+    }
+}
+
+impl<const N: usize> Default for LineEditor<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
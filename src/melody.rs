@@ -0,0 +1,275 @@
+//! Non-blocking playback of tones and melodies on a piezo buzzer
+//!
+//! A melody is a slice of [`Note`]s, each a frequency and a duration. The
+//! [`Player`] outputs the frequency of the current note using a frequency
+//! generator (see [`fgen`]), and uses an MRT channel to time the notes. It is
+//! advanced from the MRT interrupt handler, so the melody plays in the
+//! background.
+//!
+//! All MRT channels share the MRT0 interrupt. Call [`Player::on_interrupt`]
+//! from its handler. If other channels use the interrupt too, they can be
+//! handled in the same handler, as every channel has its own interrupt flag.
+//!
+//! Notes that are longer than the MRT channel can time in one go (see
+//! [`MrtChannel::max_us`]) are timed in multiple steps, each of which ends
+//! with an interrupt.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     fgen::{self, Fgen},
+//!     melody::{Note, Player},
+//!     mrt::MrtChannel,
+//! };
+//!
+//! static MELODY: [Note; 4] = [
+//!     Note::new(440, 200),
+//!     Note::rest(50),
+//!     Note::new(440, 200),
+//!     Note::new(660, 400),
+//! ];
+//!
+//! fn run<T, F, P>(fgen: Fgen<T, F, P>, timer: MrtChannel)
+//! where
+//!     T: fgen::Timer<F>,
+//! {
+//!     let mut player = Player::new(fgen, timer);
+//!     player.play(&MELODY).unwrap();
+//!
+//!     // Move `player` to where the MRT0 interrupt handler can access it,
+//!     // then call this from the handler:
+//!     player.on_interrupt().unwrap();
+//! }
+//! ```
+//!
+//! [`Note`]: struct.Note.html
+//! [`Player`]: struct.Player.html
+//! [`Player::on_interrupt`]: struct.Player.html#method.on_interrupt
+//! [`fgen`]: ../fgen/index.html
+//! [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+
+use embedded_hal::timer::{Cancel as _, CountDown as _};
+
+use crate::{
+    fgen::{self, Fgen},
+    mrt::MrtChannel,
+};
+
+/// A note of a melody
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Note {
+    /// The frequency in Hz, or `0` for a rest
+    pub hz: u32,
+
+    /// The duration in milliseconds
+    pub ms: u32,
+}
+
+impl Note {
+    /// Create a note of frequency `hz` that lasts `ms` milliseconds
+    pub const fn new(hz: u32, ms: u32) -> Self {
+        Note { hz, ms }
+    }
+
+    /// Create a rest that lasts `ms` milliseconds
+    pub const fn rest(ms: u32) -> Self {
+        Note { hz: 0, ms }
+    }
+}
+
+/// The state of a [`Player`]
+///
+/// [`Player`]: struct.Player.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum State {
+    /// No melody is playing
+    Stopped,
+
+    /// A melody is playing
+    Playing,
+
+    /// A melody has been paused, and can be resumed
+    Paused,
+}
+
+/// Plays melodies in the background
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Player<'notes, T, F, P> {
+    fgen: Fgen<T, F, P>,
+    timer: MrtChannel,
+    notes: &'notes [Note],
+
+    // The index of the note that follows the current one
+    next: usize,
+
+    // The part of the current note that remains after the current step of
+    // the timer. While paused, the whole remaining duration of the note.
+    remaining_us: u64,
+
+    state: State,
+}
+
+impl<'notes, T, F, P> Player<'notes, T, F, P>
+where
+    T: fgen::Timer<F>,
+{
+    /// Create a player
+    ///
+    /// Enables the interrupt of `timer`. The MRT0 interrupt needs to be
+    /// unmasked in the NVIC.
+    pub fn new(fgen: Fgen<T, F, P>, mut timer: MrtChannel) -> Self {
+        timer.enable_interrupt();
+
+        Player {
+            fgen,
+            timer,
+            notes: &[],
+            next: 0,
+            remaining_us: 0,
+            state: State::Stopped,
+        }
+    }
+
+    /// Start playing a melody from the beginning
+    ///
+    /// Replaces the melody that is currently playing, if any.
+    ///
+    /// Returns an error, if the frequency of the first note can't be
+    /// generated. Playback is stopped in that case.
+    pub fn play(&mut self, notes: &'notes [Note]) -> Result<(), fgen::Error> {
+        self.stop();
+
+        self.notes = notes;
+        self.next = 0;
+        self.state = State::Playing;
+
+        self.start_next()
+    }
+
+    /// Advance to the next note, if the current one has ended
+    ///
+    /// Call this from the MRT0 interrupt handler. Does nothing, if this
+    /// player's MRT channel hasn't expired.
+    ///
+    /// Returns an error, if the frequency of the next note can't be generated.
+    /// Playback is stopped in that case.
+    pub fn on_interrupt(&mut self) -> Result<(), fgen::Error> {
+        if self.timer.wait().is_err() || self.state != State::Playing {
+            return Ok(());
+        }
+
+        if self.remaining_us > 0 {
+            self.start_timer(self.remaining_us);
+            return Ok(());
+        }
+
+        self.start_next()
+    }
+
+    /// Pause playback
+    ///
+    /// Does nothing, if no melody is playing.
+    pub fn pause(&mut self) {
+        if self.state != State::Playing {
+            return;
+        }
+
+        self.remaining_us += self.timer.remaining_us() as u64;
+        let _ = self.timer.cancel();
+        self.fgen.stop();
+
+        self.state = State::Paused;
+    }
+
+    /// Resume paused playback
+    ///
+    /// The current note is continued for the rest of its duration. Does
+    /// nothing, if playback isn't paused.
+    ///
+    /// Returns an error, if the frequency of the current note can't be
+    /// generated. Playback is stopped in that case.
+    pub fn resume(&mut self) -> Result<(), fgen::Error> {
+        if self.state != State::Paused {
+            return Ok(());
+        }
+
+        self.state = State::Playing;
+
+        // Can't panic, as a note is being played while paused.
+        let note = self.notes[self.next - 1];
+        self.sound(note.hz)?;
+        self.start_timer(self.remaining_us);
+
+        Ok(())
+    }
+
+    /// Stop playback
+    ///
+    /// The output is left low.
+    pub fn stop(&mut self) {
+        let _ = self.timer.cancel();
+        self.fgen.stop();
+
+        self.state = State::Stopped;
+    }
+
+    /// Return the state of the player
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Stop playback and release the resources used
+    ///
+    /// Disables the interrupt of the MRT channel.
+    pub fn free(mut self) -> (Fgen<T, F, P>, MrtChannel) {
+        self.stop();
+        self.timer.disable_interrupt();
+
+        (self.fgen, self.timer)
+    }
+
+    fn start_next(&mut self) -> Result<(), fgen::Error> {
+        let note = match self.notes.get(self.next) {
+            Some(note) => *note,
+            None => {
+                self.stop();
+                return Ok(());
+            }
+        };
+        self.next += 1;
+
+        self.sound(note.hz)?;
+        self.start_timer(note.ms as u64 * 1000);
+
+        Ok(())
+    }
+
+    /// Start the next step of the timer, for the `us` that remain of a note
+    fn start_timer(&mut self, us: u64) {
+        let step_us = us.min(self.timer.max_us() as u64);
+        self.remaining_us = us - step_us;
+
+        // Can't fail, as the step is limited to the maximum.
+        let _ = self.timer.start_us(step_us as u32);
+    }
+
+    fn sound(&mut self, hz: u32) -> Result<(), fgen::Error> {
+        if hz == 0 {
+            self.fgen.stop();
+            return Ok(());
+        }
+
+        if let Err(err) = self.fgen.start(hz) {
+            self.stop();
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! MODBUS RTU framing on top of USART, MRT, and the CRC engine
+//!
+//! The entry point to this API is [`Modbus`]. It takes care of the parts of
+//! MODBUS RTU that depend on the hardware:
+//!
+//! - Frames are delimited by a silent interval of 3.5 character times. This is
+//!   detected using an MRT channel.
+//! - Frames end with a CRC-16/MODBUS checksum. This is calculated and checked
+//!   using the CRC engine.
+//!
+//! Interpreting the contents of the frames (addresses, function codes, etc.)
+//! is left to the user.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     crc::CRC, init_state, modbus::Modbus, mrt::MrtChannel, pac::USART0,
+//!     usart::USART,
+//! };
+//!
+//! fn serve<Pins>(
+//!     usart: &USART<USART0, init_state::Enabled<Pins>>,
+//!     timer: MrtChannel,
+//!     crc: &mut CRC,
+//!     buffer: &'static mut [u8],
+//! ) -> ! {
+//!     // The USART has been configured for 19200 baud.
+//!     let mut modbus = Modbus::new(usart, timer, 19_200, buffer);
+//!
+//!     loop {
+//!         if let Ok(request) = modbus.receive(crc) {
+//!             // Handle request, then send response using `modbus.send`.
+//!         }
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use embedded_hal::{
+    serial::{Read, Write},
+    timer::CountDown,
+};
+use nb::{self, block};
+
+use crate::{
+    crc::{self, CRC},
+    init_state,
+    mrt::MrtChannel,
+    usart::{self, Instance, Rx, Tx, USART},
+};
+
+/// The maximum size of a MODBUS RTU frame, including address and CRC
+pub const MAX_FRAME_LEN: usize = 256;
+
+/// MODBUS RTU framing layer
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Modbus<'usart, I: 'usart> {
+    rx: Rx<'usart, I>,
+    tx: Tx<'usart, I>,
+    timer: MrtChannel,
+    silent_interval_us: u32,
+    buffer: &'static mut [u8],
+    len: usize,
+    state: State,
+}
+
+impl<'usart, I> Modbus<'usart, I>
+where
+    I: Instance,
+{
+    /// Create the MODBUS RTU layer
+    ///
+    /// `baud_rate` must be the baud rate that `usart` has been configured with.
+    /// It is used to calculate the length of the silent interval between
+    /// frames. Received frames are stored in `buffer`, which should be able to
+    /// hold [`MAX_FRAME_LEN`] bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the silent interval is longer than `timer` supports (see
+    /// [`MrtChannel::max_us`]), which only happens at a few baud.
+    ///
+    /// [`MAX_FRAME_LEN`]: constant.MAX_FRAME_LEN.html
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new<Pins>(
+        usart: &'usart USART<I, init_state::Enabled<Pins>>,
+        mut timer: MrtChannel,
+        baud_rate: u32,
+        buffer: &'static mut [u8],
+    ) -> Self {
+        // A character consists of 11 bits (start bit, 8 data bits, parity or
+        // second stop bit, stop bit). For baud rates above 19200, the MODBUS
+        // spec recommends a fixed interval of 1.75 ms.
+        let silent_interval_us = if baud_rate > 19_200 {
+            1750
+        } else {
+            38_500_000 / baud_rate
+        };
+
+        if let Err(error) = timer.start_us(silent_interval_us) {
+            panic!("{}", error);
+        }
+
+        Modbus {
+            rx: usart.rx(),
+            tx: usart.tx(),
+            timer,
+            silent_interval_us,
+            buffer,
+            len: 0,
+            state: State::Initial,
+        }
+    }
+
+    /// Receive a frame
+    ///
+    /// Reads all available characters from the USART. Returns the received
+    /// frame, once a silent interval has been detected after it. The returned
+    /// frame includes the address, but not the CRC, which has already been
+    /// checked.
+    ///
+    /// Call this method often enough to not miss any characters.
+    pub fn receive(&mut self, crc: &mut CRC) -> nb::Result<&[u8], Error> {
+        loop {
+            match self.rx.read() {
+                Ok(b) => {
+                    // Can't fail, as the interval has been checked in `new`.
+                    let _ = self.timer.start_us(self.silent_interval_us);
+
+                    match self.state {
+                        // Characters received before the first silent
+                        // interval might be from the middle of a frame.
+                        State::Initial | State::Discarding => continue,
+                        State::Idle => {
+                            self.len = 0;
+                            self.state = State::Receiving;
+                        }
+                        State::Receiving => {}
+                    }
+
+                    if self.len >= self.buffer.len() {
+                        self.state = State::Discarding;
+                        return Err(nb::Error::Other(Error::Overflow));
+                    }
+
+                    self.buffer[self.len] = b;
+                    self.len += 1;
+                }
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(error)) => {
+                    // Can't fail, as the interval has been checked in `new`.
+                    let _ = self.timer.start_us(self.silent_interval_us);
+                    self.state = State::Discarding;
+                    return Err(nb::Error::Other(Error::Usart(error)));
+                }
+            }
+        }
+
+        if self.timer.wait().is_err() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let frame_complete = self.state == State::Receiving;
+        self.state = State::Idle;
+
+        if !frame_complete {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Address, function code, CRC
+        if self.len < 4 {
+            return Err(nb::Error::Other(Error::TooShort));
+        }
+
+        // Running the CRC over a frame that includes a correct CRC yields 0.
+        let mut hasher = crc.start(crc::Config::MODBUS);
+        hasher.write_bytes(&self.buffer[..self.len]);
+        if hasher.sum() as u16 != 0 {
+            return Err(nb::Error::Other(Error::Crc));
+        }
+
+        Ok(&self.buffer[..self.len - 2])
+    }
+
+    /// Send a frame
+    ///
+    /// `frame` must include the address, but not the CRC, which is calculated
+    /// and appended by this method. Waits for the bus to be silent, before
+    /// sending the frame.
+    ///
+    /// This method blocks until the frame has been sent. It must not be called
+    /// while a frame is being received.
+    pub fn send(&mut self, crc: &mut CRC, frame: &[u8]) {
+        while self.state != State::Idle {
+            if self.timer.wait().is_ok() {
+                self.state = State::Idle;
+            }
+        }
+
+        let mut hasher = crc.start(crc::Config::MODBUS);
+        hasher.write_bytes(frame);
+        let sum = hasher.sum() as u16;
+
+        for &b in frame.iter().chain(&sum.to_le_bytes()) {
+            // Can't fail, as the error type is `Void`.
+            let _ = block!(self.tx.write(b));
+        }
+        self.tx.bdrain();
+
+        // The silent interval after the frame starts now.
+        // Can't fail, as the interval has been checked in `new`.
+        let _ = self.timer.start_us(self.silent_interval_us);
+    }
+
+    /// Release the resources used by this instance
+    pub fn free(self) -> (MrtChannel, &'static mut [u8]) {
+        (self.timer, self.buffer)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum State {
+    /// No silent interval has been observed yet
+    Initial,
+
+    /// The bus is silent, waiting for the next frame
+    Idle,
+
+    /// Receiving a frame
+    Receiving,
+
+    /// An error occured, ignoring the rest of the frame
+    Discarding,
+}
+
+/// An error that can occur while receiving a MODBUS RTU frame
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The USART reported an error
+    Usart(usart::Error),
+
+    /// The frame didn't fit into the buffer
+    Overflow,
+
+    /// The frame was too short to be valid
+    TooShort,
+
+    /// The frame's CRC didn't match
+    Crc,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usart(error) => write!(f, "MODBUS: {}", error),
+            Error::Overflow => f.write_str("MODBUS frame too long for buffer"),
+            Error::TooShort => f.write_str("MODBUS frame too short"),
+            Error::Crc => f.write_str("MODBUS frame CRC mismatch"),
+        }
+    }
+}
+
+impl From<usart::Error> for Error {
+    fn from(error: usart::Error) -> Self {
+        Error::Usart(error)
+    }
+}
@@ -24,16 +24,28 @@
 //! }
 //! ```
 
+use core::fmt;
+
 use crate::{
-    pac::{mrt0::CHANNEL, MRT0},
+    pac::{self, mrt0::CHANNEL, MRT0},
     reg_proxy::RegProxy,
     syscon,
 };
 
-use embedded_hal::timer::{CountDown, Periodic};
-use nb::{Error, Result};
+use embedded_hal::timer::{Cancel, CountDown, Periodic};
 use void::Void;
 
+/// The largest value of a channel's timer
+///
+/// The timer is 31 bits wide on the LPC82x, and 24 bits wide on the LPC845.
+/// [`CountDown::start`] only accepts values smaller than this, as it adds one.
+///
+/// [`CountDown::start`]: struct.MrtChannel.html#method.start
+#[cfg(feature = "82x")]
+pub(crate) const MAX_TICKS: u32 = 0x7fff_ffff;
+#[cfg(feature = "845")]
+pub(crate) const MAX_TICKS: u32 = 0x00ff_ffff;
+
 /// Represent a MRT0 instance
 pub struct MRT {
     mrt: MRT0,
@@ -78,6 +90,40 @@ impl MRT {
         ]
     }
 
+    /// Reassembles the MRT from its four channels
+    ///
+    /// This is the inverse of [`MRT::split`]. Disables the MRT and returns an
+    /// `MRT` instance, which can be split again, or be used to gain access to
+    /// the raw peripheral using [`MRT::free`].
+    ///
+    /// Since [`MRT::split`] can only be called once per `MRT` instance, and
+    /// always returns all four channels, passing the four channels here proves
+    /// that none of them is still in use.
+    ///
+    /// [`MRT::split`]: #method.split
+    /// [`MRT::free`]: #method.free
+    pub fn join(
+        channels: [MrtChannel; 4],
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        for channel in &channels {
+            // Stop the channel, so it doesn't fire after being re-split.
+            channel.channels[channel.channel as usize]
+                .intval
+                .write(|w| {
+                    w.load().set_bit();
+                    unsafe { w.ivalue().bits(0) }
+                });
+        }
+
+        // Safe, as we have all four channels, which means nobody else has
+        // access to the MRT. See comment above.
+        let mrt = unsafe { pac::Peripherals::steal().MRT0 };
+        syscon.disable_clock(&mrt);
+
+        Self { mrt }
+    }
+
     /// Return the raw peripheral
     ///
     /// This method serves as an escape hatch from the HAL API. It returns the
@@ -97,9 +143,12 @@ impl MRT {
 
 impl CountDown for MrtChannel {
     /// The timer operates in clock ticks from the system clock, that means it
-    /// runs at 12_000_000 ticks per second if you haven't changed it.
+    /// runs at [`syscon::system_clock_hz`] ticks per second.
+    ///
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
     ///
-    /// It can also only use values smaller than 0x7FFFFFFF.
+    /// It can also only use values smaller than 0x7FFFFFFF on the LPC82x, or
+    /// 0xFFFFFF on the LPC845.
     type Time = u32;
 
     fn start<T>(&mut self, count: T)
@@ -107,7 +156,7 @@ impl CountDown for MrtChannel {
         T: Into<Self::Time>,
     {
         let reload: Self::Time = count.into();
-        debug_assert!(reload < (1 << 31) - 1);
+        debug_assert!(reload < MAX_TICKS);
         // This stops the timer, to prevent race conditions when resetting the
         // interrupt bit
         self.channels[self.channel as usize].intval.write(|w| {
@@ -122,7 +171,7 @@ impl CountDown for MrtChannel {
             .write(|w| unsafe { w.ivalue().bits(reload + 1) });
     }
 
-    fn wait(&mut self) -> Result<(), Void> {
+    fn wait(&mut self) -> nb::Result<(), Void> {
         if self.channels[self.channel as usize]
             .stat
             .read()
@@ -135,11 +184,230 @@ impl CountDown for MrtChannel {
                 .write(|w| w.intflag().set_bit());
             Ok(())
         } else {
-            Err(Error::WouldBlock)
+            Err(nb::Error::WouldBlock)
         }
     }
 }
 
 impl Periodic for MrtChannel {}
 
+impl Cancel for MrtChannel {
+    type Error = Error;
+
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        if !self.is_running() {
+            return Err(Error::NotRunning);
+        }
+
+        // Writing 0 with LOAD set stops the timer immediately. See user
+        // manual, section 11.6.1.
+        self.channels[self.channel as usize].intval.write(|w| {
+            w.load().set_bit();
+            unsafe { w.ivalue().bits(0) }
+        });
+
+        Ok(())
+    }
+}
+
+impl MrtChannel {
+    /// Start the timer with a timeout in microseconds
+    ///
+    /// This is a convenience wrapper around [`CountDown::start`], which takes
+    /// the timeout in clock ticks. The timeout is converted using
+    /// [`syscon::system_clock_hz`].
+    ///
+    /// Returns [`Error::TooLong`] and leaves the timer as it is, if `us` is
+    /// longer than [`MrtChannel::max_us`]. At 12 MHz, that is about 178
+    /// seconds on the LPC82x, and about 1.4 seconds on the LPC845.
+    ///
+    /// [`CountDown::start`]: #method.start
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    /// [`Error::TooLong`]: enum.Error.html#variant.TooLong
+    /// [`MrtChannel::max_us`]: #method.max_us
+    pub fn start_us(&mut self, us: u32) -> Result<(), Error> {
+        let ticks = syscon::us_to_ticks(us)
+            .filter(|&ticks| ticks < MAX_TICKS)
+            .ok_or(Error::TooLong)?;
+
+        self.start(ticks);
+
+        Ok(())
+    }
+
+    /// Start the timer with a typed timeout, like `5.millis()`
+    ///
+    /// Like [`MrtChannel::start_us`], this returns [`Error::TooLong`], if
+    /// `duration` is too long.
+    ///
+    /// [`MrtChannel::start_us`]: #method.start_us
+    /// [`Error::TooLong`]: enum.Error.html#variant.TooLong
+    pub fn start_duration(
+        &mut self,
+        duration: fugit::MicrosDurationU32,
+    ) -> Result<(), Error> {
+        self.start_us(duration.to_micros())
+    }
+
+    /// Returns the longest timeout that can be passed to
+    /// [`MrtChannel::start_us`]
+    ///
+    /// [`MrtChannel::start_us`]: #method.start_us
+    pub fn max_us(&self) -> u32 {
+        syscon::ticks_to_us((MAX_TICKS - 1) as u64) as u32
+    }
+
+    /// Enable the interrupt for this channel
+    ///
+    /// All channels share a single interrupt, MRT0. The interrupt handler can
+    /// use [`CountDown::wait`] to find out which channel has expired, which
+    /// also clears the interrupt flag. The interrupt needs to be unmasked in
+    /// the NVIC.
+    ///
+    /// [`CountDown::wait`]: #method.wait
+    pub fn enable_interrupt(&mut self) {
+        self.channels[self.channel as usize]
+            .ctrl
+            .modify(|_, w| w.inten().set_bit());
+    }
+
+    /// Disable the interrupt for this channel
+    pub fn disable_interrupt(&mut self) {
+        self.channels[self.channel as usize]
+            .ctrl
+            .modify(|_, w| w.inten().clear_bit());
+    }
+
+    /// Indicates whether the timer is currently running
+    pub fn is_running(&self) -> bool {
+        self.channels[self.channel as usize]
+            .stat
+            .read()
+            .run()
+            .bit_is_set()
+    }
+
+    /// Returns the remaining time until the timer expires, in clock ticks
+    ///
+    /// Returns 0, if the timer is not running.
+    pub fn remaining_ticks(&self) -> u32 {
+        if !self.is_running() {
+            return 0;
+        }
+
+        self.channels[self.channel as usize]
+            .timer
+            .read()
+            .value()
+            .bits()
+    }
+
+    /// Returns the remaining time until the timer expires, in microseconds
+    ///
+    /// Like [`MrtChannel::start_us`], this converts the time using
+    /// [`syscon::system_clock_hz`].
+    ///
+    /// [`MrtChannel::start_us`]: #method.start_us
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn remaining_us(&self) -> u32 {
+        syscon::ticks_to_us(self.remaining_ticks() as u64) as u32
+    }
+}
+
+/// A one-shot deadman timer for blocking operations
+///
+/// Used by drivers to abort a blocking operation, if the hardware doesn't
+/// respond in time. The MRT channel is switched to one-shot mode for as long
+/// as it is used by this timer. See user manual, section 11.5.
+#[cfg(feature = "timeouts")]
+pub(crate) struct Deadman {
+    channel: MrtChannel,
+    ticks: u32,
+}
+
+#[cfg(feature = "timeouts")]
+impl Deadman {
+    /// Timeouts that exceed the channel's timer saturate at its maximum.
+    pub(crate) fn new(
+        channel: MrtChannel,
+        timeout: fugit::MicrosDurationU32,
+    ) -> Self {
+        channel.set_mode(MODE_ONE_SHOT);
+
+        let ticks = syscon::us_to_ticks(timeout.to_micros())
+            .map_or(MAX_TICKS - 1, |ticks| ticks.min(MAX_TICKS - 1));
+
+        Deadman { channel, ticks }
+    }
+
+    /// Start the timer, whenever a blocking operation starts waiting
+    pub(crate) fn arm(&mut self) {
+        self.channel.start(self.ticks);
+    }
+
+    /// Indicate whether the timer has expired since it was armed
+    pub(crate) fn expired(&mut self) -> bool {
+        self.channel.wait().is_ok()
+    }
+
+    /// Stop the timer and return the MRT channel, in repeat mode
+    pub(crate) fn free(mut self) -> MrtChannel {
+        let _ = self.channel.cancel();
+        self.channel.set_mode(MODE_REPEAT);
+        self.channel
+    }
+}
+
+#[cfg(feature = "timeouts")]
+impl MrtChannel {
+    fn set_mode(&self, mode: u8) {
+        self.channels[self.channel as usize]
+            .ctrl
+            .modify(|_, w| unsafe { w.mode().bits(mode) });
+    }
+}
+
+/// The MODE value of a channel in repeat interrupt mode
+#[cfg(feature = "timeouts")]
+const MODE_REPEAT: u8 = 0;
+
+/// The MODE value of a channel in one-shot interrupt mode
+#[cfg(feature = "timeouts")]
+const MODE_ONE_SHOT: u8 = 1;
+
+/// A blocking operation didn't finish in time
+///
+/// Returned by blocking operations of drivers that have been given an MRT
+/// channel to time out with. Giving a channel to a driver requires the
+/// `timeouts` feature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timeout;
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("hardware didn't respond in time")
+    }
+}
+
+/// Error that can occur when starting or cancelling an MRT channel
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The timer was not running, so there was nothing to cancel
+    NotRunning,
+
+    /// The timeout is longer than the timer can count
+    TooLong,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotRunning => f.write_str("MRT channel is not running"),
+            Error::TooLong => f.write_str("MRT timeout too long"),
+        }
+    }
+}
+
 reg!(CHANNEL, [CHANNEL; 4], MRT0, channel);
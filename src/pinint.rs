@@ -0,0 +1,432 @@
+//! API for the pin interrupt peripheral (PININT)
+//!
+//! The entry point to this API is [`PININT`]. It can trigger up to eight
+//! interrupts, PININT0 to PININT7, on edges of GPIO inputs. Which pin triggers
+//! which interrupt is selected in SYSCON.
+//!
+//! For users that don't use a framework like RTIC, [`Registry`] can take care
+//! of dispatching pin interrupts to callbacks, from a single function that is
+//! called from all pin interrupt handlers.
+//!
+//! The pattern match engine is not supported by this API.
+//!
+//! The pin interrupt peripheral is described in the user manual, chapter 10
+//! (LPC82x) or chapter 13 (LPC845).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use core::cell::RefCell;
+//!
+//! use cortex_m::interrupt::{self, Mutex};
+//! use lpc8xx_hal::{
+//!     pinint::{Edge, Registry},
+//!     Peripherals,
+//! };
+//!
+//! static REGISTRY: Mutex<RefCell<Option<Registry>>> =
+//!     Mutex::new(RefCell::new(None));
+//!
+//! fn button_pressed(_: Edge) {
+//!     // React to the button press
+//! }
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let button = swm.pins.pio0_4.into_gpio_pin(&p.GPIO).into_input();
+//!
+//! let mut registry = Registry::new(p.PINT.enable(&mut syscon.handle));
+//! registry
+//!     .register(&button, Edge::Falling, button_pressed, &mut syscon.handle)
+//!     .unwrap();
+//!
+//! interrupt::free(|cs| REGISTRY.borrow(cs).replace(Some(registry)));
+//!
+//! // Call this from the interrupt handlers for PININT0 to PININT7. Remember
+//! // to unmask those interrupts in the NVIC.
+//! interrupt::free(|cs| {
+//!     if let Some(registry) = REGISTRY.borrow(cs).borrow_mut().as_mut() {
+//!         registry.dispatch();
+//!     }
+//! });
+//! ```
+
+use core::fmt;
+
+use crate::{
+    gpio::direction::Direction,
+    init_state, pac,
+    pmu::{Wakeup, WakeupSource},
+    swm::{pin_state, Pin, PinTrait},
+    syscon,
+};
+
+/// The number of pin interrupts
+pub const NUM_INTERRUPTS: usize = 8;
+
+/// Interface to the pin interrupt peripheral
+///
+/// Controls the pin interrupts. Use [`Peripherals`] to gain access to an
+/// instance of this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct PININT<State = init_state::Enabled> {
+    pint: pac::PINT,
+    _state: State,
+}
+
+impl PININT<init_state::Disabled> {
+    pub(crate) fn new(pint: pac::PINT) -> Self {
+        PININT {
+            pint,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the pin interrupt peripheral
+    ///
+    /// This method is only available, if `PININT` is in the [`Disabled`]
+    /// state. Code that attempts to call this method when the peripheral is
+    /// already enabled will not compile.
+    ///
+    /// Consumes this instance of `PININT` and returns another instance that
+    /// has its `State` type parameter set to [`Enabled`].
+    ///
+    /// On the LPC82x, the pin interrupt peripheral shares its clock with GPIO,
+    /// which is enabled by default.
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    pub fn enable(
+        self,
+        _syscon: &mut syscon::Handle,
+    ) -> PININT<init_state::Enabled> {
+        #[cfg(feature = "845")]
+        _syscon.enable_clock(&self.pint);
+
+        PININT {
+            pint: self.pint,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl PININT<init_state::Enabled> {
+    /// Disable the pin interrupt peripheral
+    ///
+    /// This method is only available, if `PININT` is in the [`Enabled`]
+    /// state. Code that attempts to call this method when the peripheral is
+    /// already disabled will not compile.
+    ///
+    /// Consumes this instance of `PININT` and returns another instance that
+    /// has its `State` type parameter set to [`Disabled`].
+    ///
+    /// On the LPC82x, the clock is left enabled, as it is shared with GPIO.
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        self,
+        _syscon: &mut syscon::Handle,
+    ) -> PININT<init_state::Disabled> {
+        #[cfg(feature = "845")]
+        _syscon.disable_clock(&self.pint);
+
+        PININT {
+            pint: self.pint,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Trigger pin interrupt `n` on edges of a GPIO pin
+    ///
+    /// Selects the pin in SYSCON, configures the interrupt to be
+    /// edge-sensitive, and enables it for the given edges. Any edge that has
+    /// been detected before is cleared.
+    ///
+    /// This only enables the interrupt in the peripheral. It is up to the user
+    /// to unmask it in the NVIC, if they want the interrupt handler to run.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is not smaller than [`NUM_INTERRUPTS`].
+    ///
+    /// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+    pub fn configure<T, D>(
+        &mut self,
+        n: usize,
+        _: &Pin<T, pin_state::Gpio<D>>,
+        edge: Edge,
+        syscon: &mut syscon::Handle,
+    ) where
+        T: PinTrait,
+        D: Direction,
+    {
+        assert!(n < NUM_INTERRUPTS);
+
+        let mask = 0x1 << n;
+        let pin = (T::PORT * 32) as u8 + T::ID;
+
+        self.disable_interrupt(n);
+
+        syscon.pintsel[n].write(|w| unsafe { w.intpin().bits(pin) });
+        self.pint
+            .isel
+            .modify(|r, w| unsafe { w.bits(r.bits() & !mask) });
+
+        self.clear(n);
+
+        if edge != Edge::Falling {
+            self.pint.sienr.write(|w| unsafe { w.bits(mask) });
+        }
+        if edge != Edge::Rising {
+            self.pint.sienf.write(|w| unsafe { w.bits(mask) });
+        }
+    }
+
+    /// Disable pin interrupt `n`
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is not smaller than [`NUM_INTERRUPTS`].
+    ///
+    /// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+    pub fn disable_interrupt(&mut self, n: usize) {
+        assert!(n < NUM_INTERRUPTS);
+
+        let mask = 0x1 << n;
+        self.pint.cienr.write(|w| unsafe { w.bits(mask) });
+        self.pint.cienf.write(|w| unsafe { w.bits(mask) });
+    }
+
+    /// Return and clear the edges that pin interrupt `n` has detected
+    ///
+    /// Returns `None`, if no edge has been detected since the last call.
+    /// Returns [`Edge::Both`], if a rising and a falling edge have been
+    /// detected.
+    ///
+    /// Call this from the interrupt handler, to clear the interrupt. An edge
+    /// that occurs while this method runs might be lost.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is not smaller than [`NUM_INTERRUPTS`].
+    ///
+    /// [`Edge::Both`]: enum.Edge.html#variant.Both
+    /// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+    pub fn take_edge(&mut self, n: usize) -> Option<Edge> {
+        assert!(n < NUM_INTERRUPTS);
+
+        let mask = 0x1 << n;
+        let rise = self.pint.rise.read().bits() & mask != 0;
+        let fall = self.pint.fall.read().bits() & mask != 0;
+
+        let edge = match (rise, fall) {
+            (false, false) => return None,
+            (true, false) => Edge::Rising,
+            (false, true) => Edge::Falling,
+            (true, true) => Edge::Both,
+        };
+
+        self.clear(n);
+
+        Some(edge)
+    }
+
+    /// Return pin interrupt `n` as a wake-up source
+    ///
+    /// The returned [`Slot`] can be passed to [`pmu::Handle::deep_sleep`], to
+    /// wake up the microcontroller from deep-sleep mode, when pin interrupt
+    /// `n` triggers.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is not smaller than [`NUM_INTERRUPTS`].
+    ///
+    /// [`Slot`]: struct.Slot.html
+    /// [`pmu::Handle::deep_sleep`]: ../pmu/struct.Handle.html#method.deep_sleep
+    /// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+    pub fn slot(&self, n: usize) -> Slot {
+        assert!(n < NUM_INTERRUPTS);
+        Slot { n: n as u8 }
+    }
+
+    fn clear(&mut self, n: usize) {
+        // In edge-sensitive mode, writing to IST clears the detected rising
+        // and falling edges. See user manual, section 10.6.10/13.6.10.
+        self.pint.ist.write(|w| unsafe { w.bits(0x1 << n) });
+    }
+}
+
+impl<State> PININT<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::PINT {
+        self.pint
+    }
+}
+
+/// One of the pin interrupts, as a wake-up source
+///
+/// Returned by [`PININT::slot`].
+///
+/// [`PININT::slot`]: struct.PININT.html#method.slot
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Slot {
+    n: u8,
+}
+
+impl WakeupSource for Slot {
+    fn wakeup(&self) -> Wakeup {
+        Wakeup::PinInt(self.n)
+    }
+}
+
+/// Dispatches pin interrupts to callbacks
+///
+/// Up to [`NUM_INTERRUPTS`] pins can be registered, each with a callback that
+/// is called with the detected edge. [`Registry::dispatch`] must be called
+/// from the interrupt handlers of all pin interrupts that are in use.
+///
+/// The callbacks are function pointers, which means closures can be used, as
+/// long as they don't capture anything.
+///
+/// Please refer to the [module documentation] for an example.
+///
+/// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+/// [`Registry::dispatch`]: #method.dispatch
+/// [module documentation]: index.html
+pub struct Registry {
+    pinint: PININT<init_state::Enabled>,
+    handlers: [Option<fn(Edge)>; NUM_INTERRUPTS],
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new(pinint: PININT<init_state::Enabled>) -> Self {
+        Registry {
+            pinint,
+            handlers: [None; NUM_INTERRUPTS],
+        }
+    }
+
+    /// Call `handler` on edges of a GPIO pin
+    ///
+    /// Uses the first unused pin interrupt and returns its number. The
+    /// corresponding interrupt needs to be unmasked in the NVIC.
+    ///
+    /// Returns [`Error::Full`], if all pin interrupts are in use.
+    ///
+    /// [`Error::Full`]: enum.Error.html#variant.Full
+    pub fn register<T, D>(
+        &mut self,
+        pin: &Pin<T, pin_state::Gpio<D>>,
+        edge: Edge,
+        handler: fn(Edge),
+        syscon: &mut syscon::Handle,
+    ) -> Result<usize, Error>
+    where
+        T: PinTrait,
+        D: Direction,
+    {
+        let n = self
+            .handlers
+            .iter()
+            .position(Option::is_none)
+            .ok_or(Error::Full)?;
+
+        self.pinint.configure(n, pin, edge, syscon);
+        self.handlers[n] = Some(handler);
+
+        Ok(n)
+    }
+
+    /// Stop calling the handler for pin interrupt `n`
+    ///
+    /// `n` is the number that was returned by [`Registry::register`]. The pin
+    /// interrupt can be reused afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is not smaller than [`NUM_INTERRUPTS`].
+    ///
+    /// [`Registry::register`]: #method.register
+    /// [`NUM_INTERRUPTS`]: constant.NUM_INTERRUPTS.html
+    pub fn unregister(&mut self, n: usize) {
+        self.pinint.disable_interrupt(n);
+        self.pinint.clear(n);
+        self.handlers[n] = None;
+    }
+
+    /// Call the handlers of all pin interrupts that have detected an edge
+    ///
+    /// Call this method from the interrupt handlers of all pin interrupts that
+    /// are in use. It clears the interrupts it handles.
+    pub fn dispatch(&mut self) {
+        for (n, handler) in self.handlers.iter().enumerate() {
+            if let Some(handler) = handler {
+                if let Some(edge) = self.pinint.take_edge(n) {
+                    handler(edge);
+                }
+            }
+        }
+    }
+
+    /// Disable all registered pin interrupts and release the peripheral
+    pub fn free(mut self) -> PININT<init_state::Enabled> {
+        for n in 0..NUM_INTERRUPTS {
+            if self.handlers[n].is_some() {
+                self.unregister(n);
+            }
+        }
+
+        self.pinint
+    }
+}
+
+/// The edges that trigger a pin interrupt
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// Rising edges
+    Rising,
+
+    /// Falling edges
+    Falling,
+
+    /// Both rising and falling edges
+    Both,
+}
+
+/// An error that can occur while registering a pin interrupt handler
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// All pin interrupts are in use
+    Full,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Full => f.write_str("all pin interrupts are in use"),
+        }
+    }
+}
@@ -24,13 +24,42 @@
 //! pmu.handle.enter_sleep_mode(&mut p.SCB);
 //! ```
 //!
+//! Enter deep-sleep mode, until either pin interrupt 0 or the WKT fires:
+//!
+//! ``` no_run
+//! use lpc8xx_hal::Peripherals;
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut pmu = p.PMU.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let pinint = p.PINT.enable(&mut syscon.handle);
+//! let wkt = p.WKT.enable(&mut syscon.handle);
+//!
+//! // Configure the pin interrupt and start the WKT here, and unmask both
+//! // interrupts in the NVIC.
+//!
+//! let pin_int0 = pinint.slot(0);
+//! unsafe {
+//!     pmu.handle.deep_sleep(
+//!         &mut p.SCB,
+//!         &mut syscon.handle,
+//!         &[&pin_int0, &wkt],
+//!     );
+//! }
+//! ```
+//!
 //! Please refer to the [examples in the repository] for more example code.
 //!
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
+use core::fmt;
+
 use cortex_m::{asm, interrupt};
+use void::Void;
 
-use crate::{clock, init_state, pac};
+use crate::{clock, init_state, pac, syscon};
 
 /// Entry point to the PMU API
 ///
@@ -128,8 +157,7 @@ impl Handle {
             // mode. See user manual, section 6.7.4.2.
             scb.clear_sleepdeep();
 
-            asm::dsb();
-            asm::wfi();
+            wait_for_interrupt();
         })
     }
 
@@ -138,6 +166,10 @@ impl Handle {
     /// The microcontroller will wake up from deep-sleep mode, if an
     /// NVIC-enabled interrupt occurs. See user manual, section 6.7.5.3.
     ///
+    /// After waking up, the power mode and the SLEEPDEEP bit are reset, so
+    /// that a later `WFI` (or [`enable_sleep_on_exit`]) will enter regular
+    /// sleep mode, not deep-sleep mode.
+    ///
     /// # Limitations
     ///
     /// According to the user manual, section 6.7.5.2, the IRC must be selected
@@ -158,6 +190,8 @@ impl Handle {
     /// Please make sure that the peripheral states configured in PDAWAKECFG
     /// match the peripheral states as tracked by the API before calling this
     /// method.
+    ///
+    /// [`enable_sleep_on_exit`]: #method.enable_sleep_on_exit
     pub unsafe fn enter_deep_sleep_mode(&mut self, scb: &mut pac::SCB) {
         interrupt::free(|_| {
             self.pmu.pcon.modify(|_, w| w.pm().deep_sleep_mode());
@@ -166,16 +200,57 @@ impl Handle {
             // See user manual, section 6.7.5.2.
             scb.set_sleepdeep();
 
-            asm::dsb();
-            asm::wfi();
+            wait_for_interrupt();
+
+            self.restore_default_mode(scb);
         })
     }
 
+    /// Enter deep-sleep mode, waking up from the given sources
+    ///
+    /// Configures the STARTERP0 and STARTERP1 registers of SYSCON, so that
+    /// exactly the interrupts of `sources` can wake up the microcontroller,
+    /// then enters deep-sleep mode, like [`enter_deep_sleep_mode`]. Any
+    /// wake-up interrupts enabled using
+    /// [`syscon::Handle::enable_interrupt_wakeup`] are overridden.
+    ///
+    /// Each source's interrupt still needs to be enabled in its peripheral,
+    /// and unmasked in the NVIC.
+    ///
+    /// # Safety
+    ///
+    /// Please refer to [`enter_deep_sleep_mode`].
+    ///
+    /// [`enter_deep_sleep_mode`]: #method.enter_deep_sleep_mode
+    /// [`syscon::Handle::enable_interrupt_wakeup`]: ../syscon/struct.Handle.html#method.enable_interrupt_wakeup
+    pub unsafe fn deep_sleep(
+        &mut self,
+        scb: &mut pac::SCB,
+        syscon: &mut syscon::Handle,
+        sources: &[&dyn WakeupSource],
+    ) {
+        let mut starterp0 = 0;
+        let mut starterp1 = 0;
+        for source in sources {
+            match source.wakeup() {
+                Wakeup::PinInt(n) => starterp0 |= 1 << n,
+                Wakeup::Interrupt(n) => starterp1 |= 1 << n,
+            }
+        }
+        syscon.set_wakeup_sources(starterp0, starterp1);
+
+        self.enter_deep_sleep_mode(scb);
+    }
+
     /// Enter power-down mode
     ///
     /// The microcontroller will wake up from power-down mode, if an
     /// NVIC-enabled interrupt occurs. See user manual, section 6.7.6.3.
     ///
+    /// After waking up, the power mode and the SLEEPDEEP bit are reset, so
+    /// that a later `WFI` (or [`enable_sleep_on_exit`]) will enter regular
+    /// sleep mode, not power-down mode.
+    ///
     /// # Limitations
     ///
     /// According to the user manual, section 6.7.6.2, the IRC must be selected
@@ -196,6 +271,8 @@ impl Handle {
     /// Please make sure that the peripheral states configured in PDAWAKECFG
     /// match the peripheral states as tracked by the API before calling this
     /// method.
+    ///
+    /// [`enable_sleep_on_exit`]: #method.enable_sleep_on_exit
     pub unsafe fn enter_power_down_mode(&mut self, scb: &mut pac::SCB) {
         interrupt::free(|_| {
             self.pmu.pcon.modify(|_, w| w.pm().power_down_mode());
@@ -204,10 +281,246 @@ impl Handle {
             // See user manual, section 6.7.5.2.
             scb.set_sleepdeep();
 
-            asm::dsb();
-            asm::wfi();
+            wait_for_interrupt();
+
+            self.restore_default_mode(scb);
+        })
+    }
+
+    /// Enter deep power-down mode
+    ///
+    /// Deep power-down mode removes power from the whole chip, except for the
+    /// PMU. The microcontroller does not return from this method, unless deep
+    /// power-down mode is blocked (see below). Waking up causes a reset
+    /// instead, either by pulling the WAKEUP pin (PIO0_4) low (unless disabled
+    /// using [`set_wakeup_pin`]), or when the self-wake-up timer expires, if it
+    /// runs from the [`LowPowerClock`]. See user manual, section 6.7.7.
+    ///
+    /// Only the contents of the general-purpose registers in the PMU are
+    /// retained.
+    ///
+    /// Returns [`Error::DeepPowerDownBlocked`] without doing anything, if deep
+    /// power-down mode has been blocked by setting the NODPD bit in the PCON
+    /// register. Once set, that bit can only be cleared by a power-on reset.
+    /// See user manual, section 6.6.1.
+    ///
+    /// # Limitations
+    ///
+    /// According to the user manual, section 6.7.7.2, the IRC must be selected
+    /// as the main clock before entering deep power-down mode.
+    ///
+    /// [`set_wakeup_pin`]: #method.set_wakeup_pin
+    /// [`LowPowerClock`]: struct.LowPowerClock.html
+    /// [`Error::DeepPowerDownBlocked`]: enum.Error.html#variant.DeepPowerDownBlocked
+    pub fn enter_deep_power_down_mode(
+        &mut self,
+        scb: &mut pac::SCB,
+    ) -> Result<Void, Error> {
+        if self.pmu.pcon.read().nodpd().bit_is_set() {
+            return Err(Error::DeepPowerDownBlocked);
+        }
+
+        interrupt::disable();
+
+        self.pmu.pcon.modify(|_, w| w.pm().deep_power_down_mode());
+        scb.set_sleepdeep();
+
+        loop {
+            wait_for_interrupt();
+        }
+    }
+
+    /// Configure the WAKEUP pin (PIO0_4) for deep power-down mode
+    ///
+    /// By default, pulling PIO0_4 low wakes the microcontroller up from deep
+    /// power-down mode. If the pin is used for something else, and the
+    /// microcontroller should only be woken up by the self-wake-up timer, the
+    /// wake-up function can be disabled. It only applies to deep power-down
+    /// mode. The pin can be used as GPIO or for other functions otherwise,
+    /// regardless of this setting.
+    ///
+    /// See user manual, section 6.6.3.
+    pub fn set_wakeup_pin(&mut self, config: WakeupPin) {
+        self.pmu.dpdctrl.modify(|_, w| match config {
+            WakeupPin::Disabled => {
+                w.wakeuphys().clear_bit();
+                w.wakepad_disable().set_bit()
+            }
+            WakeupPin::Enabled { hysteresis } => {
+                w.wakeuphys().bit(hysteresis);
+                w.wakepad_disable().clear_bit()
+            }
+        });
+    }
+
+    /// Indicates whether the microcontroller woke up from deep power-down
+    ///
+    /// Waking up from deep power-down mode causes a reset, which is why this
+    /// is the only way to tell it apart from other kinds of reset. The flag
+    /// stays set until [`clear_deep_power_down_flag`] is called. See user
+    /// manual, section 6.6.1.
+    ///
+    /// [`clear_deep_power_down_flag`]: #method.clear_deep_power_down_flag
+    pub fn woke_from_deep_power_down(&self) -> bool {
+        self.pmu.pcon.read().dpdflag().bit_is_set()
+    }
+
+    /// Clear the flag that indicates a wake-up from deep power-down mode
+    pub fn clear_deep_power_down_flag(&mut self) {
+        // Writing 1 clears the flag. Writing 0 to SLEEPFLAG has no effect.
+        self.pmu.pcon.modify(|_, w| {
+            w.sleepflag().clear_bit();
+            w.dpdflag().set_bit()
+        });
+    }
+
+    /// Read one of the general-purpose registers
+    ///
+    /// The PMU has four general-purpose registers (GPREG0 to GPREG3). Their
+    /// contents are retained in deep power-down mode and across all resets,
+    /// except for power-on and brown-out resets. This makes them useful for
+    /// passing information to the application after it restarts, like why it
+    /// entered deep power-down mode. See user manual, section 6.6.2.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is larger than 3.
+    pub fn read_gpreg(&self, n: usize) -> u32 {
+        self.pmu.gpreg[n].read().gpdata().bits()
+    }
+
+    /// Write one of the general-purpose registers
+    ///
+    /// Please refer to [`read_gpreg`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is larger than 3.
+    ///
+    /// [`read_gpreg`]: #method.read_gpreg
+    pub fn write_gpreg(&mut self, n: usize, value: u32) {
+        self.pmu.gpreg[n].write(|w| unsafe { w.gpdata().bits(value) });
+    }
+
+    /// Use a general-purpose register as a boot counter
+    ///
+    /// Increments general-purpose register `n` and returns the new value.
+    /// Calling this once during startup counts the number of restarts since the
+    /// last power-on, as the register is cleared by power-on resets. This can
+    /// be used to detect reset loops, for example.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is larger than 3.
+    pub fn increment_boot_counter(&mut self, n: usize) -> u32 {
+        let count = self.read_gpreg(n).wrapping_add(1);
+        self.write_gpreg(n, count);
+        count
+    }
+
+    /// Enter sleep mode whenever an interrupt handler returns
+    ///
+    /// If sleep-on-exit is enabled, the microcontroller enters sleep mode
+    /// automatically, when returning from an interrupt handler to thread mode.
+    /// This is useful for applications that do all their work in interrupt
+    /// handlers. The main loop only needs to call [`enter_sleep_mode`] once.
+    ///
+    /// This method makes sure that regular sleep mode is entered, not
+    /// deep-sleep or power-down mode. See user manual, section 6.7.4.
+    ///
+    /// [`enter_sleep_mode`]: #method.enter_sleep_mode
+    pub fn enable_sleep_on_exit(&mut self, scb: &mut pac::SCB) {
+        interrupt::free(|_| {
+            self.restore_default_mode(scb);
+            scb.set_sleeponexit();
         })
     }
+
+    /// Stop entering sleep mode whenever an interrupt handler returns
+    ///
+    /// Call this from an interrupt handler, to return to thread mode after
+    /// that handler has finished, instead of going back to sleep.
+    pub fn disable_sleep_on_exit(&mut self, scb: &mut pac::SCB) {
+        scb.clear_sleeponexit();
+    }
+
+    /// Reset the power mode, so that `WFI` enters regular sleep mode
+    fn restore_default_mode(&mut self, scb: &mut pac::SCB) {
+        self.pmu.pcon.modify(|_, w| w.pm().default());
+        scb.clear_sleepdeep();
+    }
+}
+
+/// The configuration of the WAKEUP pin in deep power-down mode
+///
+/// Passed to [`pmu::Handle::set_wakeup_pin`].
+///
+/// [`pmu::Handle::set_wakeup_pin`]: struct.Handle.html#method.set_wakeup_pin
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WakeupPin {
+    /// The pin does not wake up the microcontroller
+    Disabled,
+
+    /// A low level on the pin wakes up the microcontroller
+    Enabled {
+        /// Enable the input hysteresis of the pin in deep power-down mode
+        ///
+        /// This makes the wake-up more robust against noise on slowly changing
+        /// signals.
+        hysteresis: bool,
+    },
+}
+
+/// Implemented for everything that can wake up the microcontroller from
+/// deep-sleep and power-down modes
+///
+/// Passed to [`pmu::Handle::deep_sleep`]. Implemented for:
+///
+/// - Pin interrupts ([`pinint::Slot`])
+/// - The self-wake-up timer ([`WKT`])
+/// - USARTs, for example when a start bit is detected (see
+///   [`USART::enable_start_interrupt`])
+/// - Brown-out detection ([`syscon::BOD`])
+///
+/// [`pmu::Handle::deep_sleep`]: struct.Handle.html#method.deep_sleep
+/// [`pinint::Slot`]: ../pinint/struct.Slot.html
+/// [`WKT`]: ../wkt/struct.WKT.html
+/// [`USART::enable_start_interrupt`]: ../usart/struct.USART.html#method.enable_start_interrupt
+/// [`syscon::BOD`]: ../syscon/struct.BOD.html
+pub trait WakeupSource {
+    /// Return the wake-up interrupt of this source
+    fn wakeup(&self) -> Wakeup;
+}
+
+/// A wake-up interrupt, as configured in SYSCON
+///
+/// See user manual, section 5.6.30/5.6.31 (LPC82x) or 8.6.36/8.6.37 (LPC845).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Wakeup {
+    /// Pin interrupt `n`, enabled by bit `n` of STARTERP0
+    PinInt(u8),
+
+    /// A peripheral interrupt, enabled by bit `n` of STARTERP1
+    Interrupt(u8),
+}
+
+impl WakeupSource for syscon::BOD {
+    fn wakeup(&self) -> Wakeup {
+        Wakeup::Interrupt(13)
+    }
+}
+
+/// Wait for an interrupt, with the barriers required by the architecture
+///
+/// The DSB makes sure all outstanding memory accesses are complete before
+/// sleeping. The ISB makes sure that the effects of the interrupt handler are
+/// visible to the code following the `WFI`.
+fn wait_for_interrupt() {
+    asm::dsb();
+    asm::wfi();
+    asm::isb();
 }
 
 /// The 10 kHz low-power clock
@@ -284,3 +597,21 @@ impl<State> clock::Frequency for LowPowerClock<State> {
 }
 
 impl clock::Enabled for LowPowerClock<init_state::Enabled> {}
+
+/// An error that can occur while entering a power mode
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Deep power-down mode is blocked by the NODPD bit
+    DeepPowerDownBlocked,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::DeepPowerDownBlocked => {
+                f.write_str("deep power-down mode blocked")
+            }
+        }
+    }
+}
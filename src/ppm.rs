@@ -0,0 +1,214 @@
+//! Decoder for PPM signals from RC receivers, using CTimer capture
+//!
+//! Many RC receivers can output all of their channels as a single PPM (pulse
+//! position modulation) signal. Each channel's value is encoded as the time
+//! between two rising edges, usually between 1 and 2 ms. After the last
+//! channel, a longer gap marks the start of the next frame.
+//!
+//! The entry point to this API is [`Decoder`]. It measures the time between
+//! rising edges on the T0_CAP0 input using the CTimer capture function, which
+//! means the measurement is not affected by interrupt latency.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use lpc8xx_hal::{ppm::Decoder, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let pin = swm.pins.pio0_17.into_swm_pin();
+//! let (input, _) =
+//!     swm.movable_functions.t0_cap0.assign(pin, &mut swm.handle);
+//!
+//! let mut decoder = Decoder::new(p.CTIMER0, input, &mut syscon.handle);
+//!
+//! // Call this from the CTIMER0 interrupt handler, or poll it often enough
+//! // to not miss an edge.
+//! decoder.handle_interrupt();
+//!
+//! match decoder.channels() {
+//!     Ok(channels) => {
+//!         // Pulse widths in microseconds
+//!     }
+//!     Err(_) => {
+//!         // Signal lost, enter failsafe mode
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::{
+    ctimer::CTimer,
+    pac::CTIMER0,
+    swm::{self, T0_CAP0},
+    syscon,
+};
+
+/// The maximum number of channels in a PPM frame
+pub const MAX_CHANNELS: usize = 12;
+
+/// Gaps longer than this mark the start of a new frame
+const SYNC_MIN_US: u32 = 3000;
+
+/// If no complete frame has been received for this long, the signal is lost
+const TIMEOUT_US: u32 = 100_000;
+
+/// Divides the 12 MHz CTimer clock down to 1 MHz, for 1 µs per tick
+const PRESCALER: u32 = 11;
+
+/// PPM decoder
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Decoder<P> {
+    ct: CTIMER0,
+    input: swm::Function<T0_CAP0, swm::state::Assigned<P>>,
+
+    last_edge: Option<u32>,
+    last_frame: Option<u32>,
+    next_channel: Option<usize>,
+
+    // The frame that is currently being received, and the last complete one.
+    // Only complete frames are returned, so a frame is never mixed with the
+    // channels of the next one.
+    receiving: [u16; MAX_CHANNELS],
+    channels: [u16; MAX_CHANNELS],
+    num_channels: usize,
+}
+
+impl<P> Decoder<P> {
+    /// Create the decoder and start measuring
+    ///
+    /// Configures the CTimer to run at 1 MHz, assuming it is clocked at
+    /// 12 MHz, and to capture the counter value on every rising edge of
+    /// `input`. The CTimer interrupt is enabled in the peripheral, but it's up
+    /// to the user to unmask it in the NVIC, if they want to use it.
+    pub fn new(
+        ctimer: CTimer,
+        input: swm::Function<T0_CAP0, swm::state::Assigned<P>>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        let ct = ctimer.free();
+
+        syscon.enable_clock(&ct);
+        unsafe { ct.pr.write(|w| w.prval().bits(PRESCALER)) };
+        ct.ccr.write(|w| {
+            w.cap0re().set_bit();
+            w.cap0i().set_bit()
+        });
+        ct.tcr.write(|w| w.cen().set_bit());
+
+        Decoder {
+            ct,
+            input,
+
+            last_edge: None,
+            last_frame: None,
+            next_channel: None,
+
+            receiving: [0; MAX_CHANNELS],
+            channels: [0; MAX_CHANNELS],
+            num_channels: 0,
+        }
+    }
+
+    /// Process a captured edge, if one is available
+    ///
+    /// Call this method from the CTIMER0 interrupt handler, or poll it. Each
+    /// edge must be processed before the next one occurs, or the current frame
+    /// will be decoded incorrectly.
+    pub fn handle_interrupt(&mut self) {
+        if !self.ct.ir.read().cr0int().bit_is_set() {
+            return;
+        }
+        self.ct.ir.write(|w| w.cr0int().set_bit());
+
+        let edge = self.ct.cr[0].read().cap().bits();
+        let last_edge = self.last_edge.replace(edge);
+
+        let width = match last_edge {
+            Some(last_edge) => edge.wrapping_sub(last_edge),
+            None => return,
+        };
+
+        if width >= SYNC_MIN_US {
+            // The previous frame is complete.
+            if let Some(channel) = self.next_channel {
+                self.channels[..channel]
+                    .copy_from_slice(&self.receiving[..channel]);
+                self.num_channels = channel;
+                self.last_frame = Some(edge);
+            }
+
+            self.next_channel = Some(0);
+            return;
+        }
+
+        match self.next_channel {
+            Some(channel) if channel < MAX_CHANNELS => {
+                self.receiving[channel] = width as u16;
+                self.next_channel = Some(channel + 1);
+            }
+            // Either we haven't seen a sync gap yet, or the frame has too many
+            // channels. Ignore the rest of the frame.
+            _ => self.next_channel = None,
+        }
+    }
+
+    /// Return the pulse widths of the last complete frame, in microseconds
+    ///
+    /// Returns [`Error::SignalLost`], if no complete frame has been received
+    /// within the last 100 ms, or none has been received at all.
+    ///
+    /// The channels of a frame are only made available here, once the whole
+    /// frame has been received. If [`handle_interrupt`] is called from an
+    /// interrupt handler, call this method and use its result within the same
+    /// critical section, so the frame can't be replaced while it is read.
+    ///
+    /// [`handle_interrupt`]: #method.handle_interrupt
+    /// [`Error::SignalLost`]: enum.Error.html#variant.SignalLost
+    pub fn channels(&self) -> Result<&[u16], Error> {
+        let last_frame = self.last_frame.ok_or(Error::SignalLost)?;
+
+        let now = self.ct.tc.read().tcval().bits();
+        if now.wrapping_sub(last_frame) > TIMEOUT_US {
+            return Err(Error::SignalLost);
+        }
+
+        Ok(&self.channels[..self.num_channels])
+    }
+
+    /// Stop the decoder and release the resources it uses
+    pub fn free(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (CTimer, swm::Function<T0_CAP0, swm::state::Assigned<P>>) {
+        self.ct.tcr.write(|w| w.crst().set_bit());
+        self.ct.tcr.write(|w| w.cen().clear_bit());
+        self.ct.ccr.reset();
+        syscon.disable_clock(&self.ct);
+
+        (CTimer::new(self.ct), self.input)
+    }
+}
+
+/// An error that can occur while decoding a PPM signal
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// No valid frame has been received recently
+    SignalLost,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SignalLost => f.write_str("PPM signal lost"),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Audio output via PWM, with the duty cycle updated by DMA
+//!
+//! The LPC82x has no DAC. [`PwmAudio`] produces an analog signal anyway, by
+//! streaming samples into the duty cycle of a PWM signal at a fixed sample
+//! rate. Filtered by an external RC low-pass (or just the inertia of a
+//! speaker), this yields audio, or any other slowly changing analog signal.
+//!
+//! One half of the SCT (see [`sct`]) generates the PWM signal, the other one
+//! paces the samples: It generates a DMA request once per sample period,
+//! which is connected to a DMA channel's hardware trigger via INPUTMUX. The
+//! DMA controller then writes the next sample to the PWM's duty cycle
+//! register. No CPU time is spent per sample. A new duty cycle takes effect
+//! at the start of the next PWM period, so there are no glitches.
+//!
+//! The PWM frequency should be well above the sample rate, and above the
+//! audible range. With the default system clock of 12 MHz, a PWM period of 256
+//! ticks (8 bit resolution) results in a PWM frequency of about 47 kHz.
+//!
+//! Samples are the raw values of the duty cycle's match register. Use
+//! [`PwmAudio::sample`] to convert duty cycles into samples, ideally ahead of
+//! time.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{dma::DescriptorTable, pwm_audio::PwmAudio, Peripherals};
+//!
+//! static mut DESCRIPTORS: DescriptorTable = DescriptorTable::new();
+//! static mut SAMPLES: [u16; 256] = [0; 256];
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (output, _) = swm
+//!     .movable_functions
+//!     .sct_out0
+//!     .assign(swm.pins.pio0_16.into_swm_pin(), &mut swm_handle);
+//!
+//! let dma = p.DMA.split(unsafe { &mut DESCRIPTORS });
+//! let dma_handle = dma.handle.enable(&mut syscon.handle);
+//! let channel = dma.channels.channel0.enable(&dma_handle);
+//!
+//! let counters = p.SCT0.split(&mut syscon.handle);
+//! let pwm = counters.low.into_pwm(0, 256, output);
+//!
+//! let audio = PwmAudio::new(pwm, counters.high);
+//!
+//! // A sawtooth wave
+//! let samples = unsafe { &mut SAMPLES };
+//! for (i, sample) in samples.iter_mut().enumerate() {
+//!     *sample = audio.sample(i as u16);
+//! }
+//!
+//! // 1500 ticks of the 12 MHz system clock, for a sample rate of 8 kHz
+//! let playback = audio.play(channel, samples, &p.INPUTMUX, 1500);
+//!
+//! let (audio, channel, _) = playback.wait();
+//! ```
+//!
+//! [`PwmAudio`]: struct.PwmAudio.html
+//! [`sct`]: ../sct/index.html
+//! [`PwmAudio::sample`]: struct.PwmAudio.html#method.sample
+
+use embedded_dma::ReadBuffer;
+use void::{ResultVoidExt as _, Void};
+
+use crate::{
+    dma::{self, ChannelTrait},
+    init_state, pac,
+    route::{route, SctDmaRequest0},
+    sct::{self, Half, OutputTrait},
+};
+
+/// Plays back audio via PWM
+///
+/// `C` is the half of the SCT that generates the PWM signal, `T` the half that
+/// paces the samples.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct PwmAudio<C, O, P, T> {
+    pwm: sct::Pwm<C, O, P>,
+    pacer: sct::Counter<T>,
+}
+
+impl<C, O, P, T> PwmAudio<C, O, P, T>
+where
+    C: Half,
+    O: OutputTrait,
+    T: Half,
+{
+    /// Create the audio helper
+    ///
+    /// `pwm` determines the PWM frequency and the resolution of the samples.
+    /// Its duty cycle is left unchanged, until playback starts.
+    pub fn new(pwm: sct::Pwm<C, O, P>, pacer: sct::Counter<T>) -> Self {
+        PwmAudio { pwm, pacer }
+    }
+
+    /// Convert a duty cycle into a sample
+    ///
+    /// `duty` is the number of ticks per PWM period that the output is high,
+    /// as accepted by [`Pwm::set_duty`].
+    ///
+    /// [`Pwm::set_duty`]: ../sct/struct.Pwm.html#method.set_duty
+    pub fn sample(&self, duty: u16) -> u16 {
+        sct::duty_match(duty, self.pwm.period())
+    }
+
+    /// Start playing back samples
+    ///
+    /// Writes one sample from `samples` to the PWM's duty cycle every
+    /// `sample_period` ticks of the system clock, starting one sample period
+    /// after this method has been called. `inputmux` is used to connect the
+    /// SCT to the DMA channel's hardware trigger.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `sample_period` is `0`.
+    ///
+    /// # Limitations
+    ///
+    /// The length of `samples` must be 1024 or less. Longer sounds need to be
+    /// split up, and played back one part after the other.
+    pub fn play<'dma, Ch, B>(
+        mut self,
+        channel: dma::Channel<Ch, init_state::Enabled<&'dma dma::Handle>>,
+        samples: B,
+        inputmux: &pac::INPUTMUX,
+        sample_period: u16,
+    ) -> Playback<'dma, C, O, P, T, Ch, B>
+    where
+        Ch: ChannelTrait,
+        B: ReadBuffer<Word = u16>,
+    {
+        assert!(sample_period > 0);
+
+        route(inputmux, &SctDmaRequest0, &channel);
+
+        let duty = DutyRegister(self.pwm.duty_register());
+        let transfer = channel.start_paced_transfer(samples, duty);
+
+        self.pacer.start_dma_requests(0, sample_period);
+
+        Playback {
+            transfer,
+            audio: self,
+        }
+    }
+
+    /// Release the PWM and the pacing counter
+    pub fn free(self) -> (sct::Pwm<C, O, P>, sct::Counter<T>) {
+        (self.pwm, self.pacer)
+    }
+}
+
+/// Audio that is being played back
+///
+/// Returned by [`PwmAudio::play`].
+///
+/// [`PwmAudio::play`]: struct.PwmAudio.html#method.play
+pub struct Playback<'dma, C, O, P, T, Ch, B>
+where
+    Ch: ChannelTrait,
+{
+    transfer: dma::Transfer<'dma, Ch, B, DutyRegister>,
+    audio: PwmAudio<C, O, P, T>,
+}
+
+impl<'dma, C, O, P, T, Ch, B> Playback<'dma, C, O, P, T, Ch, B>
+where
+    C: Half,
+    O: OutputTrait,
+    T: Half,
+    Ch: ChannelTrait,
+{
+    /// Wait for the playback to finish
+    ///
+    /// Stops pacing afterwards and returns the resources used. The PWM keeps
+    /// running, with the duty cycle of the last sample.
+    pub fn wait(
+        self,
+    ) -> (
+        PwmAudio<C, O, P, T>,
+        dma::Channel<Ch, init_state::Enabled<&'dma dma::Handle>>,
+        B,
+    ) {
+        let (channel, samples, _) = self.transfer.wait().void_unwrap();
+
+        let mut audio = self.audio;
+        audio.pacer.stop_dma_requests();
+
+        (audio, channel, samples)
+    }
+}
+
+/// The duty cycle register of a PWM, as the destination of a DMA transfer
+struct DutyRegister(*mut u8);
+
+impl dma::Dest for DutyRegister {
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *mut u8 {
+        self.0
+    }
+}
@@ -0,0 +1,181 @@
+//! Data that is retained in RAM across resets
+//!
+//! RAM keeps its contents through most resets, like those caused by the
+//! watchdog, the RESET pin, or a software reset request. This can be used to
+//! keep crash logs, or state that allows for a faster restart. [`Retained`]
+//! wraps such data and validates it using a magic number and a CRC, to detect
+//! whether it has been written before the reset.
+//!
+//! A `Retained` needs to be placed into RAM that isn't initialized on startup.
+//! Any `static` in a section whose name starts with `.uninit` qualifies (this
+//! section is provided by the linker script of `cortex-m-rt`).
+//!
+//! After a power-on or brown-out reset, the contents of RAM are random and
+//! might pass validation by accident. [`Retained::get_or_init`] uses the
+//! [`ResetReason`] to reinitialize the data in that case.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{retained::Retained, Peripherals};
+//!
+//! #[link_section = ".uninit.BOOT_COUNT"]
+//! static mut BOOT_COUNT: Retained<u32> = Retained::new();
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let reset_reason = syscon.handle.reset_reason();
+//!
+//! // Sound, as this is the only place that accesses `BOOT_COUNT`.
+//! let boot_count = unsafe { &mut BOOT_COUNT };
+//!
+//! let count = boot_count.get_or_init(&reset_reason, || 0);
+//! boot_count.set(count + 1);
+//! ```
+//!
+//! [`Retained`]: struct.Retained.html
+//! [`Retained::get_or_init`]: struct.Retained.html#method.get_or_init
+//! [`ResetReason`]: ../syscon/struct.ResetReason.html
+
+use core::{
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+use crate::syscon::ResetReason;
+
+/// Marks a [`Retained`] as containing a value ("RTND")
+///
+/// [`Retained`]: struct.Retained.html
+const MAGIC: u32 = 0x444e_5452;
+
+/// Data that is retained in RAM across resets
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+#[repr(C)]
+pub struct Retained<T> {
+    magic: u32,
+    crc: u32,
+    value: MaybeUninit<T>,
+}
+
+impl<T> Retained<T> {
+    /// Create an empty instance
+    ///
+    /// The initializer of a `static` in an `.uninit` section is ignored, so
+    /// this is only needed to satisfy the compiler.
+    pub const fn new() -> Self {
+        Retained {
+            magic: 0,
+            crc: 0,
+            value: MaybeUninit::uninit(),
+        }
+    }
+}
+
+impl<T> Retained<T>
+where
+    T: Retainable,
+{
+    /// Return the retained value, if it is valid
+    ///
+    /// Returns `None`, if no value has been written using [`Retained::set`],
+    /// or if the data has been corrupted.
+    ///
+    /// [`Retained::set`]: #method.set
+    pub fn get(&self) -> Option<T> {
+        if self.magic != MAGIC || self.crc != self.value_crc() {
+            return None;
+        }
+
+        // Sound, as the CRC matches, which means the value has been written by
+        // `set`.
+        Some(unsafe { self.value.as_ptr().read() })
+    }
+
+    /// Return the retained value, or initialize it
+    ///
+    /// If the reset reason indicates that RAM has not been retained, or if
+    /// there is no valid value, `init` is called and its return value is
+    /// stored and returned.
+    pub fn get_or_init(
+        &mut self,
+        reset_reason: &ResetReason,
+        init: impl FnOnce() -> T,
+    ) -> T {
+        if reset_reason.ram_retained() {
+            if let Some(value) = self.get() {
+                return value;
+            }
+        }
+
+        let value = init();
+        self.set(value);
+        value
+    }
+
+    /// Store a value
+    pub fn set(&mut self, value: T) {
+        self.value = MaybeUninit::new(value);
+        self.crc = self.value_crc();
+        self.magic = MAGIC;
+    }
+
+    /// Invalidate the stored value
+    ///
+    /// [`Retained::get`] returns `None` afterwards.
+    ///
+    /// [`Retained::get`]: #method.get
+    pub fn invalidate(&mut self) {
+        self.magic = 0;
+    }
+
+    /// Compute the CRC-32 of the value
+    ///
+    /// This is done in software, so `Retained` can be used during early
+    /// startup, without the CRC engine.
+    fn value_crc(&self) -> u32 {
+        let bytes = self.value.as_ptr() as *const u8;
+
+        let mut crc = !0u32;
+        for i in 0..mem::size_of::<T>() {
+            // Sound, as `Retainable` guarantees that `T` has no padding bytes.
+            // If the value has never been written, the bytes are whatever RAM
+            // contained on startup, which is why they're read using volatile
+            // reads. The CRC check will reject them.
+            let byte = unsafe { ptr::read_volatile(bytes.add(i)) };
+
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+        !crc
+    }
+}
+
+/// Implemented for types that can be stored in [`Retained`]
+///
+/// # Safety
+///
+/// Implementing types must not contain any padding bytes, as those are read
+/// to compute the CRC. This is the case for `#[repr(C)]` structs whose fields
+/// are all `Retainable` and have no gaps between them.
+///
+/// [`Retained`]: struct.Retained.html
+pub unsafe trait Retainable: Copy {}
+
+unsafe impl Retainable for u8 {}
+unsafe impl Retainable for u16 {}
+unsafe impl Retainable for u32 {}
+unsafe impl Retainable for u64 {}
+unsafe impl Retainable for i8 {}
+unsafe impl Retainable for i16 {}
+unsafe impl Retainable for i32 {}
+unsafe impl Retainable for i64 {}
+unsafe impl<T, const N: usize> Retainable for [T; N] where T: Retainable {}
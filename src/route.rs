@@ -0,0 +1,275 @@
+//! Typed routing of hardware signals between peripherals
+//!
+//! Many peripherals can react to signals from other peripherals, without any
+//! involvement of the CPU: A DMA channel can be triggered by the ADC, the SCT,
+//! or a pin interrupt, and (on LPC845) the SCT can count comparator edges.
+//! These connections are made in INPUTMUX, by writing a number that selects
+//! the signal into the consumer's register. Which numbers are valid differs
+//! for every consumer.
+//!
+//! [`route`] makes those connections using types instead of numbers. A
+//! signal can only be routed to a consumer that accepts it; any other
+//! combination fails to compile.
+//!
+//! Signals:
+//!
+//! - [`AdcSequenceA`] and [`AdcSequenceB`], the ADC's sequence interrupts
+//! - [`SctDmaRequest0`] and [`SctDmaRequest1`], the SCT's DMA requests
+//! - [`AcmpOutput`], the analog comparator's output
+//! - [`PinInterrupt`], pin interrupts 4 to 7 (see [`pinint`])
+//! - On LPC845 only: [`SctPin`] (the SCT_PINn switch matrix functions),
+//!   [`AdcThreshold`], [`ArmTxev`], and [`DebugHalted`]
+//!
+//! Consumers:
+//!
+//! - [`dma::Channel`], whose hardware trigger is used by
+//!   [`Channel::start_paced_transfer`]
+//! - On LPC845 only: [`SctInput`], the inputs of the SCT
+//!
+//! The ADC's hardware triggers are not covered, as the [`ADC`] API only
+//! supports software-started conversions and burst mode.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     dma::DescriptorTable,
+//!     route::{route, AdcSequenceA},
+//!     Peripherals,
+//! };
+//!
+//! static mut DESCRIPTORS: DescriptorTable = DescriptorTable::new();
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let dma = p.DMA.split(unsafe { &mut DESCRIPTORS });
+//! let dma_handle = dma.handle.enable(&mut syscon.handle);
+//! let channel = dma.channels.channel0.enable(&dma_handle);
+//!
+//! // Transfer one word every time ADC sequence A completes
+//! route(&p.INPUTMUX, &AdcSequenceA, &channel);
+//! ```
+//!
+//! [`route`]: fn.route.html
+//! [`AdcSequenceA`]: struct.AdcSequenceA.html
+//! [`AdcSequenceB`]: struct.AdcSequenceB.html
+//! [`SctDmaRequest0`]: struct.SctDmaRequest0.html
+//! [`SctDmaRequest1`]: struct.SctDmaRequest1.html
+//! [`AcmpOutput`]: struct.AcmpOutput.html
+//! [`PinInterrupt`]: struct.PinInterrupt.html
+//! [`pinint`]: ../pinint/index.html
+//! [`SctPin`]: struct.SctPin.html
+//! [`AdcThreshold`]: struct.AdcThreshold.html
+//! [`ArmTxev`]: struct.ArmTxev.html
+//! [`DebugHalted`]: struct.DebugHalted.html
+//! [`dma::Channel`]: ../dma/struct.Channel.html
+//! [`Channel::start_paced_transfer`]: ../dma/struct.Channel.html#method.start_paced_transfer
+//! [`SctInput`]: struct.SctInput.html
+//! [`ADC`]: ../adc/struct.ADC.html
+
+use crate::{
+    dma::{self, ChannelTrait},
+    pac,
+};
+
+/// Connect a signal to a consumer
+///
+/// Writes the consumer's INPUTMUX register. Any previous connection of the
+/// consumer is replaced. Only compiles, if `consumer` accepts `signal`.
+pub fn route<S, C>(inputmux: &pac::INPUTMUX, _signal: &S, consumer: &C)
+where
+    C: Consumer,
+    S: Signal<C::Kind>,
+{
+    consumer.select(inputmux, S::SELECT);
+}
+
+/// A peripheral input that can be connected to a signal
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Consumer {
+    /// The kind of consumer, which determines the valid signals
+    type Kind;
+
+    /// Write the number that selects a signal into the INPUTMUX register
+    fn select(&self, inputmux: &pac::INPUTMUX, value: u32);
+}
+
+/// A signal that can be routed to a kind of consumer
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Signal<K> {
+    /// The number that selects this signal in a consumer's register
+    const SELECT: u32;
+}
+
+/// The kind of consumer that DMA channel triggers are
+///
+/// Used in the bounds of [`route`].
+///
+/// [`route`]: fn.route.html
+pub struct DmaTrigger(());
+
+/// The kind of consumer that SCT inputs are
+///
+/// Used in the bounds of [`route`].
+///
+/// [`route`]: fn.route.html
+#[cfg(feature = "845")]
+pub struct SctInputs(());
+
+impl<T, State> Consumer for dma::Channel<T, State>
+where
+    T: ChannelTrait,
+{
+    type Kind = DmaTrigger;
+
+    fn select(&self, inputmux: &pac::INPUTMUX, value: u32) {
+        // Sound, as all values are valid according to the user manual.
+        inputmux.dma_itrig_inmux[T::INDEX].write(|w| unsafe { w.bits(value) });
+    }
+}
+
+/// SCT input `N`, where `N` is 0 to 3
+///
+/// The SCT's inputs 4 to 7 can't be routed.
+#[cfg(feature = "845")]
+pub struct SctInput<const N: usize>;
+
+#[cfg(feature = "845")]
+macro_rules! sct_inputs {
+    ($($n:literal,)*) => {
+        $(
+            impl Consumer for SctInput<$n> {
+                type Kind = SctInputs;
+
+                fn select(&self, inputmux: &pac::INPUTMUX, value: u32) {
+                    // Sound, as all values are valid according to the user
+                    // manual.
+                    inputmux.sct_inmux[$n].write(|w| unsafe { w.bits(value) });
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "845")]
+sct_inputs!(0, 1, 2, 3,);
+
+macro_rules! signals {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident {
+            $($(#[$kind_attr:meta])* $kind:ident => $select:expr,)*
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub struct $name;
+
+            $(
+                $(#[$kind_attr])*
+                impl Signal<$kind> for $name {
+                    const SELECT: u32 = $select;
+                }
+            )*
+        )*
+    };
+}
+
+// See the description of the DMA_ITRIG_INMUX and SCT0_INMUX registers in the
+// INPUTMUX chapter of the user manual.
+signals!(
+    /// The interrupt of ADC sequence A
+    AdcSequenceA {
+        DmaTrigger => 0,
+    }
+
+    /// The interrupt of ADC sequence B
+    AdcSequenceB {
+        DmaTrigger => 1,
+    }
+
+    /// SCT DMA request 0
+    SctDmaRequest0 {
+        DmaTrigger => 2,
+    }
+
+    /// SCT DMA request 1
+    SctDmaRequest1 {
+        DmaTrigger => 3,
+    }
+
+    /// The output of the analog comparator
+    AcmpOutput {
+        DmaTrigger => 4,
+        #[cfg(feature = "845")]
+        SctInputs => 5,
+    }
+);
+
+#[cfg(feature = "845")]
+signals!(
+    /// The ADC threshold compare interrupt
+    AdcThreshold {
+        SctInputs => 4,
+    }
+
+    /// The ARM core's TXEV event, which is triggered by the SEV instruction
+    ArmTxev {
+        SctInputs => 6,
+    }
+
+    /// Indicates that the core has been halted by a debugger
+    DebugHalted {
+        SctInputs => 7,
+    }
+);
+
+/// Pin interrupt `N`, where `N` is 4 to 7
+///
+/// Only the upper four pin interrupts can trigger DMA transfers.
+pub struct PinInterrupt<const N: usize>;
+
+macro_rules! pin_interrupts {
+    ($($n:literal => $select:expr,)*) => {
+        $(
+            impl Signal<DmaTrigger> for PinInterrupt<$n> {
+                const SELECT: u32 = $select;
+            }
+        )*
+    };
+}
+
+pin_interrupts!(
+    4 => 5,
+    5 => 6,
+    6 => 7,
+    7 => 8,
+);
+
+/// The switch matrix function SCT_PINn, where `N` is 0 to 3
+///
+/// Assign the function to a pin using the switch matrix first.
+#[cfg(feature = "845")]
+pub struct SctPin<const N: usize>;
+
+#[cfg(feature = "845")]
+macro_rules! sct_pins {
+    ($($n:literal,)*) => {
+        $(
+            impl Signal<SctInputs> for SctPin<$n> {
+                const SELECT: u32 = $n;
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "845")]
+sct_pins!(0, 1, 2, 3,);
@@ -0,0 +1,270 @@
+//! Software timers, driven by a single MRT channel
+//!
+//! Many applications need to do things periodically, or once after a delay,
+//! like blinking an LED, polling a sensor, or timing out a protocol. Using a
+//! hardware timer for each of those quickly runs out of timers.
+//!
+//! [`Scheduler`] runs up to `N` software timers with millisecond resolution,
+//! from a single free-running MRT channel. Each timer can call a handler when
+//! it expires, and sets a flag that can be checked using
+//! [`Scheduler::take_expired`]. Handlers are called from [`Scheduler::poll`],
+//! which means they run in thread context, if `poll` is called from the main
+//! loop.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     scheduler::{Mode, Scheduler},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//!
+//! let mut scheduler = Scheduler::<4>::new(timer);
+//!
+//! let blink = scheduler
+//!     .start(500, Mode::Periodic, Some(|| { /* toggle LED */ }))
+//!     .unwrap();
+//! let timeout = scheduler.start(2000, Mode::OneShot, None).unwrap();
+//!
+//! loop {
+//!     scheduler.poll();
+//!
+//!     if scheduler.take_expired(timeout) {
+//!         scheduler.stop(blink).unwrap();
+//!     }
+//! }
+//! ```
+//!
+//! [`Scheduler`]: struct.Scheduler.html
+//! [`Scheduler::take_expired`]: struct.Scheduler.html#method.take_expired
+//! [`Scheduler::poll`]: struct.Scheduler.html#method.poll
+
+use core::fmt;
+
+use embedded_hal::timer::CountDown as _;
+
+use crate::{mrt::MrtChannel, syscon};
+
+/// The period of the free-running MRT channel, in milliseconds
+///
+/// Short enough to fit into the 24-bit timer of the LPC845, at the maximum
+/// system clock frequency of 30 MHz.
+const PERIOD_MS: u32 = 500;
+
+/// Runs software timers
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Scheduler<const N: usize> {
+    timer: MrtChannel,
+    timers: [Option<Timer>; N],
+
+    // The number of timer ticks per millisecond, and per period of the timer
+    ticks_per_ms: u32,
+    period_ticks: u32,
+
+    // The timer value at the last call to `poll`, and the ticks since then
+    // that haven't added up to a full millisecond yet.
+    last_ticks: u32,
+    pending_ticks: u32,
+}
+
+impl<const N: usize> Scheduler<N> {
+    /// Create a scheduler without any timers
+    ///
+    /// Starts `timer`, which runs freely from now on, restarting every 500
+    /// ms. The timer's frequency is taken from [`syscon::system_clock_hz`],
+    /// so the system clock must not be changed, while the scheduler is in
+    /// use.
+    ///
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn new(mut timer: MrtChannel) -> Self {
+        let ticks_per_ms = syscon::system_clock_hz() / 1000;
+        let period_ticks = PERIOD_MS * ticks_per_ms;
+
+        timer.start(period_ticks - 1);
+        let last_ticks = timer.remaining_ticks();
+
+        Scheduler {
+            timer,
+            timers: [None; N],
+
+            ticks_per_ms,
+            period_ticks,
+
+            last_ticks,
+            pending_ticks: 0,
+        }
+    }
+
+    /// Start a software timer that expires after `period_ms` milliseconds
+    ///
+    /// Uses the first unused timer and returns its number. A periodic timer
+    /// restarts when it expires. A one-shot timer stays stopped, but remains
+    /// in use until it is stopped using [`Scheduler::stop`], so its flag can
+    /// still be checked. `handler` is called by [`Scheduler::poll`], every
+    /// time the timer expires.
+    ///
+    /// Returns [`Error::Full`], if all `N` timers are in use.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `period_ms` is zero.
+    ///
+    /// [`Scheduler::stop`]: #method.stop
+    /// [`Scheduler::poll`]: #method.poll
+    /// [`Error::Full`]: enum.Error.html#variant.Full
+    pub fn start(
+        &mut self,
+        period_ms: u32,
+        mode: Mode,
+        handler: Option<fn()>,
+    ) -> Result<usize, Error> {
+        assert!(period_ms > 0);
+
+        let n = self
+            .timers
+            .iter()
+            .position(Option::is_none)
+            .ok_or(Error::Full)?;
+
+        self.timers[n] = Some(Timer {
+            period_ms,
+            remaining_ms: period_ms,
+            mode,
+            handler,
+            expired: false,
+        });
+
+        Ok(n)
+    }
+
+    /// Stop software timer `n`
+    ///
+    /// `n` is the number that was returned by [`Scheduler::start`]. The timer
+    /// can be reused afterwards.
+    ///
+    /// Returns [`Error::InvalidTimer`], if `n` is not smaller than `N`.
+    ///
+    /// [`Scheduler::start`]: #method.start
+    /// [`Error::InvalidTimer`]: enum.Error.html#variant.InvalidTimer
+    pub fn stop(&mut self, n: usize) -> Result<(), Error> {
+        *self.timers.get_mut(n).ok_or(Error::InvalidTimer)? = None;
+        Ok(())
+    }
+
+    /// Indicates whether software timer `n` has expired, and clears its flag
+    ///
+    /// Returns `false`, if the timer is not in use, or if `n` is not smaller
+    /// than `N`.
+    pub fn take_expired(&mut self, n: usize) -> bool {
+        match self.timers.get_mut(n) {
+            Some(Some(timer)) => core::mem::replace(&mut timer.expired, false),
+            _ => false,
+        }
+    }
+
+    /// Advance all software timers by the time that has passed
+    ///
+    /// Calls the handlers of all timers that expire. Call this method from
+    /// the main loop, or from any interrupt handler that runs regularly. The
+    /// time since the last call is taken from the free-running MRT channel,
+    /// so no milliseconds are lost, no matter how often this method is
+    /// called. It needs to be called at least every 500 ms though, or the
+    /// software timers will slow down.
+    pub fn poll(&mut self) {
+        // The timer counts down and restarts at the end of its period.
+        let now = self.timer.remaining_ticks();
+        let elapsed = if now <= self.last_ticks {
+            self.last_ticks - now
+        } else {
+            self.last_ticks + self.period_ticks - now
+        };
+        self.last_ticks = now;
+
+        self.pending_ticks += elapsed;
+        let elapsed_ms = self.pending_ticks / self.ticks_per_ms;
+        self.pending_ticks %= self.ticks_per_ms;
+
+        if elapsed_ms == 0 {
+            return;
+        }
+
+        for timer in self.timers.iter_mut().flatten() {
+            if timer.remaining_ms == 0 {
+                // An expired one-shot timer
+                continue;
+            }
+
+            if timer.remaining_ms > elapsed_ms {
+                timer.remaining_ms -= elapsed_ms;
+                continue;
+            }
+
+            timer.expired = true;
+            match timer.mode {
+                Mode::OneShot => timer.remaining_ms = 0,
+                Mode::Periodic => {
+                    // Keep the period stable, even if this call is late.
+                    let overdue =
+                        (elapsed_ms - timer.remaining_ms) % timer.period_ms;
+                    timer.remaining_ms = timer.period_ms - overdue;
+                }
+            }
+            if let Some(handler) = timer.handler {
+                handler();
+            }
+        }
+    }
+
+    /// Stop all software timers and release the MRT channel
+    pub fn free(self) -> MrtChannel {
+        self.timer
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Timer {
+    period_ms: u32,
+    remaining_ms: u32,
+    mode: Mode,
+    handler: Option<fn()>,
+    expired: bool,
+}
+
+/// Whether a software timer restarts after expiring
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mode {
+    /// The timer expires once
+    OneShot,
+
+    /// The timer restarts every time it expires
+    Periodic,
+}
+
+/// An error that can occur while starting or stopping a software timer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// All software timers are in use
+    Full,
+
+    /// The timer number is not smaller than the number of timers
+    InvalidTimer,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Full => f.write_str("all software timers are in use"),
+            Error::InvalidTimer => f.write_str("no such software timer"),
+        }
+    }
+}
@@ -0,0 +1,832 @@
+//! API for the State Configurable Timer (SCT)
+//!
+//! The SCT can operate as one 32-bit counter, or as two independent 16-bit
+//! counters. This API currently supports the latter: [`SCT::split`] puts the
+//! SCT into the two-counter mode and returns the two halves, [`Counter<Low>`]
+//! and [`Counter<High>`]. Each half can then be used independently, for
+//! example one to generate a PWM signal, the other to measure the frequency of
+//! an input signal.
+//!
+//! Events and match/capture registers are shared between the halves in
+//! hardware. To keep them from interfering with each other, each half has a
+//! fixed set of events assigned (events 0 and 1 for the low half, events 2
+//! and 3 for the high half). Each half only accesses its own half of the
+//! match/capture registers. SCT outputs and inputs are tied to the switch
+//! matrix functions that are passed to a half, so they can't be used by both.
+//!
+//! To count external pulses with the SCT as a single 32-bit counter, see the
+//! [`event_counter`] module. To output audio via PWM, see the [`pwm_audio`]
+//! module.
+//!
+//! The SCT is described in the user manual, chapter 10 (LPC82x), or chapter 16
+//! (LPC845).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::Peripherals;
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (out, _) = swm
+//!     .movable_functions
+//!     .sct_out0
+//!     .assign(swm.pins.pio0_16.into_swm_pin(), &mut swm_handle);
+//! let (input, _) = swm
+//!     .movable_functions
+//!     .sct_pin0
+//!     .assign(swm.pins.pio0_17.into_swm_pin(), &mut swm_handle);
+//!
+//! let counters = p.SCT0.split(&mut syscon.handle);
+//!
+//! // 1 kHz PWM with 25 % duty cycle, on the low half
+//! let mut pwm = counters.low.into_pwm(0, 12_000, out);
+//! pwm.set_duty(3_000);
+//!
+//! // Measure the input frequency with 1 µs resolution, on the high half
+//! let mut frequency_counter = counters.high.into_frequency_counter(11, input);
+//! if let Ok(period) = frequency_counter.period() {
+//!     let hz = frequency_counter.frequency_hz(period);
+//! }
+//! ```
+//!
+//! [`event_counter`]: ../event_counter/index.html
+//! [`pwm_audio`]: ../pwm_audio/index.html
+//! [`SCT::split`]: struct.SCT.html#method.split
+//! [`Counter<Low>`]: struct.Counter.html
+//! [`Counter<High>`]: struct.Counter.html
+
+use core::ptr;
+
+use cortex_m::interrupt;
+use void::Void;
+
+#[cfg(feature = "845")]
+use crate::route;
+use crate::{
+    pac::{self, sct0::RegisterBlock},
+    swm::{self, state::Assigned},
+    syscon,
+};
+
+// Bits of the halves of CTRL. The halves are written separately, so the
+// constants are used instead of the PAC's fields. See `write_half`.
+const CTRL_HALT: u16 = 1 << 2;
+const CTRL_CLRCTR: u16 = 1 << 3;
+const CTRL_PRE_SHIFT: u16 = 5;
+
+/// The value of an output's field in RES that clears it on conflict
+const RES_CLEAR: u32 = 2;
+
+/// The value of an output's field in RES that toggles it on conflict
+const RES_TOGGLE: u32 = 3;
+
+/// Interface to the State Configurable Timer (SCT)
+///
+/// Use [`Peripherals`] to gain access to an instance of this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct SCT {
+    pub(crate) sct: pac::SCT0,
+}
+
+impl SCT {
+    pub(crate) fn new(sct: pac::SCT0) -> Self {
+        Self { sct }
+    }
+
+    /// Put the SCT into two-counter mode and return the two halves
+    ///
+    /// Enables the SCT clock. Both halves are halted initially.
+    pub fn split(self, syscon: &mut syscon::Handle) -> Counters {
+        syscon.enable_clock(&self.sct);
+
+        self.sct.config.write(|w| w.unify().dual_counter());
+        halt_all(&self.sct);
+
+        Counters {
+            low: Counter { _half: Low(()) },
+            high: Counter { _half: High(()) },
+        }
+    }
+
+    /// Reassemble the SCT from its two halves
+    ///
+    /// This is the inverse of [`SCT::split`]. Halts both halves, disables the
+    /// SCT clock, and returns an `SCT` instance, which can be split again, or
+    /// be used to gain access to the raw peripheral using [`SCT::free`].
+    ///
+    /// [`SCT::split`]: #method.split
+    /// [`SCT::free`]: #method.free
+    pub fn join(counters: Counters, syscon: &mut syscon::Handle) -> Self {
+        let Counters { low, high } = counters;
+        low.halt();
+        high.halt();
+
+        // Safe, as we have both halves, which means nobody else has access to
+        // the SCT.
+        let sct = unsafe { pac::Peripherals::steal().SCT0 };
+        syscon.disable_clock(&sct);
+
+        Self { sct }
+    }
+
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::SCT0 {
+        self.sct
+    }
+}
+
+/// The two halves of the SCT
+///
+/// Returned by [`SCT::split`].
+///
+/// [`SCT::split`]: struct.SCT.html#method.split
+pub struct Counters {
+    /// The low half (the L counter)
+    pub low: Counter<Low>,
+
+    /// The high half (the H counter)
+    pub high: Counter<High>,
+}
+
+/// One 16-bit half of the SCT
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Counter<C> {
+    _half: C,
+}
+
+impl<C> Counter<C>
+where
+    C: Half,
+{
+    /// Generate a PWM signal
+    ///
+    /// The counter counts from `0` to `period - 1`, with one tick every
+    /// `prescaler + 1` SCT clock cycles. The output is set at the start of
+    /// each period, and cleared when the counter reaches the duty cycle. The
+    /// duty cycle is initially `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `period` is `0`.
+    pub fn into_pwm<O, P>(
+        self,
+        prescaler: u8,
+        period: u16,
+        output: swm::Function<O, Assigned<P>>,
+    ) -> Pwm<C, O, P>
+    where
+        O: OutputTrait,
+    {
+        assert!(period > 0);
+
+        let period_event = C::EVENTS[0];
+        let duty_event = C::EVENTS[1];
+
+        self.halt();
+
+        let sct = registers();
+
+        // Sound, as we only access the registers that belong to this half,
+        // its events, and the output we own. Shared registers are modified
+        // within critical sections.
+        unsafe {
+            interrupt::free(|_| {
+                set_autolimit::<C>(sct, true);
+                set_resolution::<O>(sct, RES_CLEAR);
+                clear_output::<O>(sct);
+            });
+            write_half::<C, _>(&sct.regmode, 0);
+
+            write_half::<C, _>(sct.sctmatch0(), period - 1);
+            write_half::<C, _>(sct.sctmatchrel0(), period - 1);
+            write_half::<C, _>(sct.sctmatch1(), duty_match(0, period));
+            write_half::<C, _>(sct.sctmatchrel1(), duty_match(0, period));
+
+            configure_match_event::<C>(sct, period_event, 0);
+            configure_match_event::<C>(sct, duty_event, 1);
+
+            sct.out[O::INDEX]
+                .set
+                .write(|w| w.set().bits(mask(period_event)));
+            sct.out[O::INDEX]
+                .clr
+                .write(|w| w.clr().bits(mask(duty_event)));
+
+            self.start(prescaler);
+        }
+
+        Pwm {
+            counter: self,
+            output,
+            period,
+        }
+    }
+
+    /// Measure the frequency of an input signal
+    ///
+    /// The counter runs freely, with one tick every `prescaler + 1` SCT clock
+    /// cycles, and its value is captured on every rising edge of the input.
+    /// The prescaler needs to be chosen such that a period of the input
+    /// signal is shorter than 65536 ticks.
+    pub fn into_frequency_counter<I, P>(
+        self,
+        prescaler: u8,
+        input: swm::Function<I, Assigned<P>>,
+    ) -> FrequencyCounter<C, I, P>
+    where
+        I: InputTrait,
+    {
+        let event = C::EVENTS[0];
+
+        self.halt();
+
+        let sct = registers();
+
+        // Sound, as we only access the registers that belong to this half,
+        // its events, and the input we own.
+        unsafe {
+            interrupt::free(|_| set_autolimit::<C>(sct, false));
+
+            // Use register 0 as a capture register, and capture the counter
+            // into it on the event.
+            write_half::<C, _>(&sct.regmode, 1 << 0);
+            write_half::<C, _>(sct.sctcapctrl0(), 1 << event);
+
+            sct.event[event].ctrl.write(|w| {
+                w.hevent().bit(C::INDEX == 1);
+                w.iosel().bits(I::INDEX as u8);
+                w.iocond().rise();
+                w.combmode().io()
+            });
+            sct.event[event].state.write(|w| w.statemskn().bits(1 << 0));
+
+            #[cfg(feature = "845")]
+            select_input::<I>();
+
+            sct.evflag.write(|w| w.flag().bits(mask(event)));
+
+            self.start(prescaler);
+        }
+
+        FrequencyCounter {
+            counter: self,
+            input,
+            prescaler,
+            last: None,
+        }
+    }
+
+    /// Toggle an output every `period` ticks
+    ///
+    /// Used by the [`fgen`] module. Call [`release_output`] to stop.
+    ///
+    /// [`fgen`]: ../fgen/index.html
+    /// [`release_output`]: #method.release_output
+    pub(crate) fn start_square_wave<O>(&mut self, prescaler: u8, period: u16)
+    where
+        O: OutputTrait,
+    {
+        let event = C::EVENTS[0];
+
+        self.halt();
+
+        let sct = registers();
+
+        // Sound, as we only access the registers that belong to this half,
+        // its events, and the output that the caller owns. Shared registers
+        // are modified within critical sections.
+        unsafe {
+            // Setting and clearing the output on the same event toggles it, if
+            // the conflict resolution is configured accordingly.
+            interrupt::free(|_| {
+                set_autolimit::<C>(sct, true);
+                set_resolution::<O>(sct, RES_TOGGLE);
+                clear_output::<O>(sct);
+            });
+            write_half::<C, _>(&sct.regmode, 0);
+
+            write_half::<C, _>(sct.sctmatch0(), period - 1);
+            write_half::<C, _>(sct.sctmatchrel0(), period - 1);
+
+            configure_match_event::<C>(sct, event, 0);
+
+            sct.out[O::INDEX].set.write(|w| w.set().bits(mask(event)));
+            sct.out[O::INDEX].clr.write(|w| w.clr().bits(mask(event)));
+
+            self.start(prescaler);
+        }
+    }
+
+    /// Request a DMA transfer every `period` ticks
+    ///
+    /// Uses the SCT's DMA request 0, which needs to be selected as the hardware
+    /// trigger of a DMA channel. Used by the [`pwm_audio`] module. Call
+    /// [`stop_dma_requests`] to stop.
+    ///
+    /// [`pwm_audio`]: ../pwm_audio/index.html
+    /// [`stop_dma_requests`]: #method.stop_dma_requests
+    pub(crate) fn start_dma_requests(&mut self, prescaler: u8, period: u16) {
+        let event = C::EVENTS[0];
+
+        self.halt();
+
+        let sct = registers();
+
+        // Sound, as we only access the registers that belong to this half,
+        // and its events. DMA request 0 is reserved for the caller. Shared
+        // registers are modified within critical sections.
+        unsafe {
+            interrupt::free(|_| set_autolimit::<C>(sct, true));
+            write_half::<C, _>(&sct.regmode, 0);
+
+            write_half::<C, _>(sct.sctmatch0(), period - 1);
+            write_half::<C, _>(sct.sctmatchrel0(), period - 1);
+
+            configure_match_event::<C>(sct, event, 0);
+            sct.dma0request.write(|w| w.dev_0().bits(mask(event)));
+
+            self.start(prescaler);
+        }
+    }
+
+    /// Halt the counter and stop requesting DMA transfers
+    pub(crate) fn stop_dma_requests(&mut self) {
+        self.halt();
+
+        // Sound, as DMA request 0 is reserved for the caller.
+        registers()
+            .dma0request
+            .write(|w| unsafe { w.dev_0().bits(0) });
+    }
+
+    /// Halt the counter, disconnect an output from it, and leave it low
+    pub(crate) fn release_output<O>(&mut self)
+    where
+        O: OutputTrait,
+    {
+        self.halt();
+
+        let sct = registers();
+
+        // Sound, as the caller owns the output.
+        unsafe {
+            sct.out[O::INDEX].set.write(|w| w.set().bits(0));
+            sct.out[O::INDEX].clr.write(|w| w.clr().bits(0));
+            interrupt::free(|_| clear_output::<O>(sct));
+        }
+    }
+
+    /// Halt the counter and disable its events
+    fn halt(&self) {
+        let sct = registers();
+
+        // Sound, as we only access the registers that belong to this half,
+        // and its events.
+        unsafe {
+            write_half::<C, _>(&sct.ctrl, CTRL_HALT);
+            write_half::<C, _>(&sct.limit, 0);
+            for &event in C::EVENTS.iter() {
+                sct.event[event].state.write(|w| w.statemskn().bits(0));
+            }
+        }
+    }
+
+    /// Clear the counter and start it
+    ///
+    /// # Safety
+    ///
+    /// Must only be called after the counter has been configured.
+    unsafe fn start(&self, prescaler: u8) {
+        write_half::<C, _>(
+            &registers().ctrl,
+            ((prescaler as u16) << CTRL_PRE_SHIFT) | CTRL_CLRCTR,
+        );
+    }
+}
+
+/// A half of the SCT, generating a PWM signal
+///
+/// Created by [`Counter::into_pwm`].
+///
+/// [`Counter::into_pwm`]: struct.Counter.html#method.into_pwm
+pub struct Pwm<C, O, P> {
+    counter: Counter<C>,
+    output: swm::Function<O, Assigned<P>>,
+    period: u16,
+}
+
+impl<C, O, P> Pwm<C, O, P>
+where
+    C: Half,
+    O: OutputTrait,
+{
+    /// Set the duty cycle
+    ///
+    /// `duty` is the number of ticks per period that the output is high. Takes
+    /// effect at the start of the next period. Values equal to or larger than
+    /// the period keep the output high.
+    pub fn set_duty(&mut self, duty: u16) {
+        // Sound, as we only access the half of the register that belongs to
+        // this counter.
+        unsafe {
+            write_half::<C, _>(
+                registers().sctmatchrel1(),
+                duty_match(duty, self.period),
+            )
+        };
+    }
+
+    /// Return the period, in ticks
+    pub fn period(&self) -> u16 {
+        self.period
+    }
+
+    /// Return the address of the register that [`set_duty`] writes to
+    ///
+    /// Used by the [`pwm_audio`] module, to update the duty cycle via DMA.
+    /// The register holds 16 bits and doesn't support byte access. Values
+    /// written to it need to be converted using `duty_match`.
+    ///
+    /// [`set_duty`]: #method.set_duty
+    /// [`pwm_audio`]: ../pwm_audio/index.html
+    pub(crate) fn duty_register(&self) -> *mut u8 {
+        half_address::<C, _>(registers().sctmatchrel1()) as *mut u8
+    }
+
+    /// Stop generating the PWM signal and release the resources used
+    ///
+    /// The output is left low.
+    pub fn free(mut self) -> (Counter<C>, swm::Function<O, Assigned<P>>) {
+        self.counter.release_output::<O>();
+        (self.counter, self.output)
+    }
+}
+
+/// A half of the SCT, measuring the frequency of an input signal
+///
+/// Created by [`Counter::into_frequency_counter`].
+///
+/// [`Counter::into_frequency_counter`]: struct.Counter.html#method.into_frequency_counter
+pub struct FrequencyCounter<C, I, P> {
+    counter: Counter<C>,
+    input: swm::Function<I, Assigned<P>>,
+    prescaler: u8,
+    last: Option<u16>,
+}
+
+impl<C, I, P> FrequencyCounter<C, I, P>
+where
+    C: Half,
+    I: InputTrait,
+{
+    /// Return the period of the input signal, in ticks
+    ///
+    /// Returns the time between the last two rising edges, once a new edge
+    /// has been captured since the last call. Edges that occur between calls
+    /// are missed, which doesn't matter for a periodic signal.
+    pub fn period(&mut self) -> nb::Result<u16, Void> {
+        let capture = self.capture().ok_or(nb::Error::WouldBlock)?;
+
+        match self.last.replace(capture) {
+            Some(last) => Ok(capture.wrapping_sub(last)),
+            None => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    /// Return the counter value at the last rising edge
+    ///
+    /// Returns `None`, unless a new edge has been captured since the last
+    /// call. Used by the [`latency`] module.
+    ///
+    /// [`latency`]: ../latency/index.html
+    pub(crate) fn capture(&mut self) -> Option<u16> {
+        let event = C::EVENTS[0];
+
+        let sct = registers();
+
+        // Sound, as we only access our own event flag and the half of the
+        // capture register that belongs to this counter.
+        if sct.evflag.read().flag().bits() & mask(event) == 0 {
+            return None;
+        }
+        sct.evflag.write(|w| unsafe { w.flag().bits(mask(event)) });
+
+        Some(read_half::<C, _>(sct.sctcap0()))
+    }
+
+    /// Return the current counter value
+    pub(crate) fn count(&self) -> u16 {
+        read_half::<C, _>(&registers().count)
+    }
+
+    /// Convert a period, as returned by [`period`], into a frequency in Hz
+    ///
+    /// The SCT runs from the system clock, whose frequency is taken from
+    /// [`syscon::system_clock_hz`].
+    ///
+    /// [`period`]: #method.period
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn frequency_hz(&self, period: u16) -> u32 {
+        if period == 0 {
+            return 0;
+        }
+
+        syscon::system_clock_hz() / (self.prescaler as u32 + 1) / period as u32
+    }
+
+    /// Stop measuring and release the resources used
+    pub fn free(self) -> (Counter<C>, swm::Function<I, Assigned<P>>) {
+        self.counter.halt();
+        (self.counter, self.input)
+    }
+}
+
+/// Implemented for the two halves of the SCT
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Half {
+    /// `0` for the low half, `1` for the high half
+    const INDEX: usize;
+
+    /// The events that are reserved for this half
+    const EVENTS: [usize; 2];
+}
+
+/// The low half of the SCT
+pub struct Low(());
+
+impl Half for Low {
+    const INDEX: usize = 0;
+    const EVENTS: [usize; 2] = [0, 1];
+}
+
+/// The high half of the SCT
+pub struct High(());
+
+impl Half for High {
+    const INDEX: usize = 1;
+    const EVENTS: [usize; 2] = [2, 3];
+}
+
+/// Implemented for the SCT output functions
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait OutputTrait {
+    /// The number of the output
+    const INDEX: usize;
+}
+
+/// Implemented for the SCT input functions
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait InputTrait {
+    /// The number of the input
+    const INDEX: u32;
+
+    /// Connect SCT input `INDEX` to this function, using INPUTMUX
+    #[cfg(feature = "845")]
+    fn route(inputmux: &pac::INPUTMUX);
+}
+
+macro_rules! functions {
+    ($trait:ident, $ty:ty; $($function:ident, $index:expr;)*) => {
+        $(
+            impl $trait for swm::$function {
+                const INDEX: $ty = $index;
+            }
+        )*
+    };
+}
+
+functions!(OutputTrait, usize;
+    SCT_OUT0, 0;
+    SCT_OUT1, 1;
+    SCT_OUT2, 2;
+    SCT_OUT3, 3;
+    SCT_OUT4, 4;
+    SCT_OUT5, 5;
+);
+
+#[cfg(feature = "845")]
+functions!(OutputTrait, usize;
+    SCT_OUT6, 6;
+);
+
+macro_rules! inputs {
+    ($($function:ident, $index:literal;)*) => {
+        $(
+            impl InputTrait for swm::$function {
+                const INDEX: u32 = $index;
+
+                #[cfg(feature = "845")]
+                fn route(inputmux: &pac::INPUTMUX) {
+                    route::route(
+                        inputmux,
+                        &route::SctPin::<$index>,
+                        &route::SctInput::<$index>,
+                    );
+                }
+            }
+        )*
+    };
+}
+
+inputs!(
+    SCT_PIN0, 0;
+    SCT_PIN1, 1;
+    SCT_PIN2, 2;
+    SCT_PIN3, 3;
+);
+
+/// Return the match value that clears a PWM output after `duty` ticks
+///
+/// The output is set when the counter reaches `period - 1`, which means it's
+/// high while the counter counts from there to the match value. A duty cycle
+/// of `0` lets the set and clear events coincide, which is resolved in favor of
+/// the clear event. The counter never reaches `0xffff`, as the period can be
+/// at most `0xffff` ticks.
+pub(crate) fn duty_match(duty: u16, period: u16) -> u16 {
+    match duty {
+        0 => period - 1,
+        duty if duty >= period => 0xffff,
+        duty => duty - 1,
+    }
+}
+
+/// Return the SCT's registers
+///
+/// Every user of the registers must only access the registers, or halves of
+/// registers, that it owns.
+fn registers() -> &'static RegisterBlock {
+    // Sound, as the pointer is valid for the duration of the program.
+    unsafe { &*pac::SCT0::ptr() }
+}
+
+/// Enable a match event in state 0 of a half's counter
+///
+/// The event occurs when the counter matches the half of match register
+/// `register` that belongs to it.
+///
+/// # Safety
+///
+/// The caller must own the event.
+unsafe fn configure_match_event<C>(
+    sct: &RegisterBlock,
+    event: usize,
+    register: u8,
+) where
+    C: Half,
+{
+    sct.event[event].ctrl.write(|w| {
+        w.matchsel().bits(register);
+        w.hevent().bit(C::INDEX == 1);
+        w.combmode().match_()
+    });
+    sct.event[event].state.write(|w| w.statemskn().bits(1 << 0));
+}
+
+/// Enable or disable the automatic limit of a half's counter
+///
+/// If enabled, the counter is reset when it matches match register 0. Must be
+/// called within a critical section, as CONFIG is shared between the halves.
+fn set_autolimit<C>(sct: &RegisterBlock, enabled: bool)
+where
+    C: Half,
+{
+    sct.config.modify(|_, w| match C::INDEX {
+        0 => w.autolimit_l().bit(enabled),
+        _ => w.autolimit_h().bit(enabled),
+    });
+}
+
+/// Set the conflict resolution of an output
+///
+/// Must be called within a critical section, as RES is shared between the
+/// outputs.
+///
+/// # Safety
+///
+/// The caller must own the output. `value` must be a valid resolution.
+unsafe fn set_resolution<O>(sct: &RegisterBlock, value: u32)
+where
+    O: OutputTrait,
+{
+    let shift = O::INDEX * 2;
+    sct.res.modify(|r, w| {
+        w.bits((r.bits() & !(0b11 << shift)) | (value << shift))
+    });
+}
+
+/// Set an output low
+///
+/// Must be called within a critical section, as OUTPUT is shared between the
+/// outputs.
+///
+/// # Safety
+///
+/// The caller must own the output.
+unsafe fn clear_output<O>(sct: &RegisterBlock)
+where
+    O: OutputTrait,
+{
+    sct.output
+        .modify(|r, w| w.out().bits(r.out().bits() & !(1 << O::INDEX)));
+}
+
+/// Return the mask of an event in the registers that have a bit per event
+fn mask(event: usize) -> u8 {
+    1 << event
+}
+
+/// Halt both halves of the SCT, or the unified counter
+pub(crate) fn halt_all(sct: &RegisterBlock) {
+    sct.ctrl.write(|w| w.halt_l().set_bit().halt_h().set_bit());
+}
+
+/// Connect the SCT input of `I` to the switch matrix function `I`
+///
+/// On the LPC845, the SCT inputs are selected using INPUTMUX. See user manual,
+/// section 8.6.2.
+///
+/// # Safety
+///
+/// The caller must own the switch matrix function. INPUTMUX is accessed
+/// without owning it, which is fine, as only the SCT input's register is
+/// written.
+#[cfg(feature = "845")]
+pub(crate) unsafe fn select_input<I>()
+where
+    I: InputTrait,
+{
+    I::route(&pac::Peripherals::steal().INPUTMUX);
+}
+
+// Registers that are split between the two halves, like CTRL or the match
+// registers, are accessed in halves, using 16-bit accesses. A read-modify-write
+// of the whole register would write the other half's value back, which isn't
+// possible while that half is running, and could overwrite a value that the
+// DMA controller wrote in the meantime. The PAC doesn't provide 16-bit access,
+// so the halves are accessed through pointers to the PAC's registers.
+
+/// Return the address of a half's part of a split register
+fn half_address<C, R>(register: &R) -> *mut u16
+where
+    C: Half,
+{
+    (register as *const R as *mut u16).wrapping_add(C::INDEX)
+}
+
+/// Read a half's part of a split register
+fn read_half<C, R>(register: &R) -> u16
+where
+    C: Half,
+{
+    // Sound, as the address is valid, and reading the registers this function
+    // is used for has no side effects.
+    unsafe { ptr::read_volatile(half_address::<C, _>(register)) }
+}
+
+/// Write a half's part of a split register
+///
+/// # Safety
+///
+/// The caller must own the half, and `value` must be valid for the register.
+unsafe fn write_half<C, R>(register: &R, value: u16)
+where
+    C: Half,
+{
+    ptr::write_volatile(half_address::<C, _>(register), value)
+}
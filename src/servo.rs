@@ -0,0 +1,167 @@
+//! Helper for driving RC servos using the CTimer PWM outputs
+//!
+//! RC servos expect a pulse every 20 ms. The length of the pulse, usually
+//! between 1 and 2 ms, determines the position of the servo. This module
+//! configures the CTimer for that period and allows for setting the pulse
+//! width in microseconds, instead of dealing with raw match values.
+//!
+//! The entry point to this API is [`start`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use lpc8xx_hal::{servo::{self, Servo}, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let (channel, _, _) = servo::start(p.CTIMER0, &mut syscon.handle);
+//!
+//! let pin = swm.pins.pio1_2.into_swm_pin();
+//! let (output, _) =
+//!     swm.movable_functions.t0_mat0.assign(pin, &mut swm.handle);
+//!
+//! let mut servo = Servo::new(channel.attach(output), 1000, 2000);
+//!
+//! // Move to the center position
+//! servo.set_pulse_us(1500).unwrap();
+//! ```
+//!
+//! [`start`]: fn.start.html
+
+use core::fmt;
+
+use embedded_hal::PwmPin as _;
+
+use crate::{
+    ctimer::{CTimer, CTimerPwmPin, DetachedPwmPin},
+    swm::{T0_MAT0, T0_MAT1, T0_MAT2},
+    syscon,
+};
+
+/// The servo period in microseconds
+pub const PERIOD_US: u32 = 20_000;
+
+/// Divides the 12 MHz CTimer clock down to 1 MHz, for 1 µs per tick
+const PRESCALER: u32 = 11;
+
+/// Start the CTimer for servo control
+///
+/// Configures the CTimer for a period of 20 ms, with a resolution of 1 µs,
+/// assuming the CTimer is clocked at 12 MHz. Returns the PWM channels, which
+/// can be attached to outputs and passed to [`Servo::new`].
+///
+/// Use [`CTimer::stop_pwm`] to stop the timer again.
+///
+/// [`Servo::new`]: struct.Servo.html#method.new
+/// [`CTimer::stop_pwm`]: ../ctimer/struct.CTimer.html#method.stop_pwm
+pub fn start(
+    ctimer: CTimer,
+    syscon: &mut syscon::Handle,
+) -> (
+    DetachedPwmPin<T0_MAT0>,
+    DetachedPwmPin<T0_MAT1>,
+    DetachedPwmPin<T0_MAT2>,
+) {
+    // The counter is reset when it reaches the period value, so subtract one
+    // to get exactly `PERIOD_US` ticks per period.
+    ctimer.start_pwm(PERIOD_US - 1, PRESCALER, syscon)
+}
+
+/// An RC servo, attached to a CTimer PWM output
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Servo<CTOutput, PWM> {
+    pwm: CTimerPwmPin<CTOutput, PWM>,
+    min_us: u32,
+    max_us: u32,
+}
+
+impl<CTOutput, PWM> Servo<CTOutput, PWM> {
+    /// Create a servo
+    ///
+    /// `pwm` must come from a CTimer that has been started using [`start`].
+    /// `min_us` and `max_us` define the range of pulse widths that the servo
+    /// accepts. [`set_pulse_us`] will refuse values outside of that range.
+    ///
+    /// The output stays low, until a pulse width has been set.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `min_us` is larger than `max_us`, or `max_us` is not shorter
+    /// than the period.
+    ///
+    /// [`start`]: fn.start.html
+    /// [`set_pulse_us`]: #method.set_pulse_us
+    pub fn new(
+        mut pwm: CTimerPwmPin<CTOutput, PWM>,
+        min_us: u32,
+        max_us: u32,
+    ) -> Self {
+        assert!(min_us <= max_us);
+        assert!(max_us < PERIOD_US);
+
+        // The output goes high when the counter reaches the match value, and
+        // low at the end of the period. Matching on the period value keeps
+        // the output low.
+        pwm.set_duty(PERIOD_US);
+
+        Servo {
+            pwm,
+            min_us,
+            max_us,
+        }
+    }
+
+    /// Set the pulse width in microseconds
+    ///
+    /// The new pulse width takes effect at the start of the next period.
+    ///
+    /// Returns [`Error::OutOfRange`], if `us` is outside of the range that has
+    /// been passed to [`Servo::new`]. The pulse width is left unchanged in
+    /// that case.
+    ///
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    /// [`Servo::new`]: #method.new
+    pub fn set_pulse_us(&mut self, us: u32) -> Result<(), Error> {
+        if us < self.min_us || us > self.max_us {
+            return Err(Error::OutOfRange);
+        }
+
+        self.pwm.set_duty(PERIOD_US - us);
+
+        Ok(())
+    }
+
+    /// Return the current pulse width in microseconds
+    ///
+    /// Returns `0`, if no pulse width has been set yet.
+    pub fn pulse_us(&self) -> u32 {
+        PERIOD_US - self.pwm.get_duty()
+    }
+
+    /// Release the PWM output
+    pub fn free(self) -> CTimerPwmPin<CTOutput, PWM> {
+        self.pwm
+    }
+}
+
+/// An error that can occur while controlling a servo
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The requested pulse width is outside of the configured range
+    OutOfRange,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfRange => f.write_str("servo pulse width out of range"),
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! API for the SPI peripherals
+//!
+//! The entry point to this API is [`SPI`]. Currently, only master mode with
+//! 8-bit frames is supported. Slave select is not controlled by this API. Use
+//! a GPIO pin for that, or share the bus between multiple devices using the
+//! [`shared`] module, which controls slave select per device.
+//!
+//! The SPI peripherals are described in the user manual, chapter 17.
+//!
+//! # Examples
+//!
+//! ``` no_run
+//! use lpc8xx_hal::prelude::*;
+//! use lpc8xx_hal::{
+//!     Peripherals,
+//!     spi,
+//!     syscon::clocksource::SpiClock,
+//! };
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut swm    = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (spi0_sck, _) = swm.movable_functions.spi0_sck.assign(
+//!     swm.pins.pio0_13.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//! let (spi0_mosi, _) = swm.movable_functions.spi0_mosi.assign(
+//!     swm.pins.pio0_14.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//! let (spi0_miso, _) = swm.movable_functions.spi0_miso.assign(
+//!     swm.pins.pio0_15.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! #[cfg(feature = "82x")]
+//! let clock = SpiClock::new();
+//! #[cfg(feature = "845")]
+//! let clock = SpiClock::new(&syscon.iosc);
+//!
+//! let mut spi = p.SPI0.enable(
+//!     &clock,
+//!     &mut syscon.handle,
+//!     spi::MODE_0,
+//!     spi0_sck,
+//!     spi0_mosi,
+//!     spi0_miso,
+//! );
+//!
+//! spi.set_frequency(1_000_000);
+//! spi.write(&[0x01, 0x02, 0x03])
+//!     .expect("Failed to write data");
+//! ```
+//!
+//! [`shared`]: shared/index.html
+
+pub mod shared;
+
+use core::{fmt, ops::Deref};
+
+use embedded_hal::{
+    blocking::spi::{transfer, write},
+    spi::{FullDuplex, Phase, Polarity},
+};
+use nb::{self, block};
+use void::Void;
+
+use crate::{
+    init_state,
+    pac::{self, Interrupt},
+    swm::{self, FunctionTrait, PinTrait},
+    syscon::{self, clocksource::SpiClock, PeripheralClock},
+};
+
+pub use embedded_hal::spi::{Mode, MODE_0, MODE_1, MODE_2, MODE_3};
+
+/// Interface to an SPI peripheral
+///
+/// Controls the SPI. Use [`Peripherals`] to gain access to an instance of this
+/// struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct SPI<I, State = init_state::Enabled> {
+    spi: I,
+    state: State,
+}
+
+impl<I> SPI<I, init_state::Disabled>
+where
+    I: Instance,
+{
+    pub(crate) fn new(spi: I) -> Self {
+        SPI {
+            spi,
+            state: init_state::Disabled,
+        }
+    }
+
+    /// Enable the SPI peripheral in master mode
+    ///
+    /// This method is only available, if `SPI` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `SPI` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// The SPI clock is initially set to the lowest possible frequency. Use
+    /// [`SPI::set_frequency`] to change that.
+    ///
+    /// The switch matrix functions for SCK, MOSI, and MISO are kept by the
+    /// returned instance. You can get them back by calling [`SPI::disable`].
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`SPI::set_frequency`]: #method.set_frequency
+    /// [`SPI::disable`]: #method.disable
+    pub fn enable<SckPin, MosiPin, MisoPin, CLOCK>(
+        self,
+        clock: &SpiClock<CLOCK>,
+        syscon: &mut syscon::Handle,
+        mode: Mode,
+        sck: swm::Function<I::Sck, swm::state::Assigned<SckPin>>,
+        mosi: swm::Function<I::Mosi, swm::state::Assigned<MosiPin>>,
+        miso: swm::Function<I::Miso, swm::state::Assigned<MisoPin>>,
+    ) -> SPI<I, init_state::Enabled<Functions<I, SckPin, MosiPin, MisoPin>>>
+    where
+        SckPin: PinTrait,
+        MosiPin: PinTrait,
+        MisoPin: PinTrait,
+        I::Sck: FunctionTrait<SckPin>,
+        I::Mosi: FunctionTrait<MosiPin>,
+        I::Miso: FunctionTrait<MisoPin>,
+        SpiClock<CLOCK>: PeripheralClock<I>,
+    {
+        syscon.enable_clock(&self.spi);
+        clock.select_clock(syscon);
+
+        self.spi.div.write(|w| unsafe { w.divval().bits(0xffff) });
+
+        // 8-bit frames, don't assert any slave select. See user manual,
+        // section 17.6.4.
+        self.spi.txctl.write(|w| {
+            w.txssel0_n().set_bit();
+            w.txssel1_n().set_bit();
+            w.txssel2_n().set_bit();
+            w.txssel3_n().set_bit();
+            unsafe { w.len().bits(7) }
+        });
+
+        // See user manual, section 17.6.1.
+        self.spi.cfg.write(|w| {
+            w.enable().set_bit();
+            w.master().set_bit();
+            w.lsbf().clear_bit();
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition);
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh)
+        });
+
+        SPI {
+            spi: self.spi,
+            state: init_state::Enabled((sck, mosi, miso)),
+        }
+    }
+}
+
+impl<I, Pins> SPI<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    /// Disable the SPI peripheral
+    ///
+    /// This method is only available, if `SPI` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `SPI` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`], together with the
+    /// switch matrix functions that were passed to [`SPI::enable`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`SPI::enable`]: #method.enable
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (SPI<I, init_state::Disabled>, Pins) {
+        self.spi.cfg.modify(|_, w| w.enable().clear_bit());
+        syscon.disable_clock(&self.spi);
+
+        let spi = SPI {
+            spi: self.spi,
+            state: init_state::Disabled,
+        };
+
+        (spi, self.state.0)
+    }
+
+    /// Set the SPI clock frequency
+    ///
+    /// Selects the highest frequency that is not above `hz`. This can be
+    /// called at any time, for example to switch from the slow clock required
+    /// during SD card initialization to a faster one. Waits until the current
+    /// transfer has finished, before changing the frequency.
+    ///
+    /// The SPI peripheral is clocked by the system clock on the LPC82x (see
+    /// [`syscon::system_clock_hz`]), and by the clock passed to
+    /// [`SPI::enable`] on the LPC845.
+    ///
+    /// Returns [`Error::InvalidFrequency`] and leaves the frequency unchanged,
+    /// if `hz` is `0`.
+    ///
+    /// [`Error::InvalidFrequency`]: enum.Error.html#variant.InvalidFrequency
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    /// [`SPI::enable`]: #method.enable
+    pub fn set_frequency(&mut self, hz: u32) -> Result<(), Error> {
+        let divval = divval::<I>(hz)?;
+
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+
+        self.spi.div.write(|w| unsafe { w.divval().bits(divval) });
+
+        Ok(())
+    }
+
+    /// Set the SPI clock frequency, using a typed rate, like `400.kHz()`
+    ///
+    /// Please refer to [`set_frequency`] for details.
+    ///
+    /// [`set_frequency`]: #method.set_frequency
+    pub fn set_rate(&mut self, rate: fugit::HertzU32) -> Result<(), Error> {
+        self.set_frequency(rate.to_Hz())
+    }
+
+    /// Change mode, bit order, and clock frequency
+    ///
+    /// Allows switching between devices on the same bus that require
+    /// different settings, without disabling the SPI peripheral and giving up
+    /// its pin assignments. `hz` is interpreted like by [`set_frequency`].
+    ///
+    /// Unlike [`set_frequency`], this method doesn't wait for the current
+    /// transfer to finish. It returns [`Error::Busy`] and leaves the settings
+    /// unchanged instead. Like [`set_frequency`], it returns
+    /// [`Error::InvalidFrequency`], if `hz` is `0`.
+    ///
+    /// [`set_frequency`]: #method.set_frequency
+    /// [`Error::Busy`]: enum.Error.html#variant.Busy
+    /// [`Error::InvalidFrequency`]: enum.Error.html#variant.InvalidFrequency
+    pub fn reconfigure(
+        &mut self,
+        mode: Mode,
+        bit_order: BitOrder,
+        hz: u32,
+    ) -> Result<(), Error> {
+        let divval = divval::<I>(hz)?;
+
+        if self.spi.stat.read().mstidle().bit_is_clear() {
+            return Err(Error::Busy);
+        }
+
+        self.spi.div.write(|w| unsafe { w.divval().bits(divval) });
+
+        // The peripheral is disabled while the configuration is changed, so
+        // the clock line doesn't glitch, if the polarity changes. See user
+        // manual, section 17.6.1.
+        self.spi.cfg.modify(|_, w| w.enable().clear_bit());
+        self.spi.cfg.modify(|_, w| {
+            w.lsbf().bit(bit_order == BitOrder::LsbFirst);
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition);
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh)
+        });
+        self.spi.cfg.modify(|_, w| w.enable().set_bit());
+
+        Ok(())
+    }
+
+    /// Send clock pulses without selecting a slave
+    ///
+    /// Sends `bytes` bytes of `0xff`, which results in 8 clock pulses each,
+    /// while MOSI is kept high. Since this API doesn't control slave select,
+    /// this happens with slave select deasserted, as long as the user doesn't
+    /// assert it.
+    ///
+    /// This is required to initialize SD cards in SPI mode, which need at
+    /// least 74 clock pulses after power-up.
+    pub fn send_idle_clocks(&mut self, bytes: usize) {
+        self.send_dummy_bytes(bytes);
+    }
+
+    /// Send dummy bytes, discarding whatever is received
+    ///
+    /// Sends `bytes` bytes of `0xff`. Serial NOR flash memories require dummy
+    /// cycles between the address and the data of some commands, like the
+    /// fast read command (`0x0b`) of 25-series flash, which expects 8 dummy
+    /// cycles. Call this between writing the command and reading the data,
+    /// while the device is selected.
+    pub fn send_dummy_bytes(&mut self, bytes: usize) {
+        for _ in 0..bytes {
+            // Can't fail, as the error type is `Void`.
+            let _ = block!(self.send(0xff));
+            let _ = block!(self.read());
+        }
+    }
+
+    /// Set the delays around the hardware slave selects
+    ///
+    /// The delays are inserted by the SPI peripheral, so they only apply to
+    /// its own slave select outputs, not to GPIO pins that are used as chip
+    /// selects. Waits until the current transfer has finished, before
+    /// changing the delays. See user manual, section 17.6.3.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if any of the delays is larger than 15.
+    pub fn set_delays(&mut self, delays: Delays) {
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+
+        // Sound, as all values are valid according to the user manual.
+        self.spi.dly.write(|w| unsafe { w.bits(delays.bits()) });
+    }
+
+    /// Enable the SPI interrupts
+    ///
+    /// Enable the interrupts for this SPI peripheral. This only enables the
+    /// interrupts via the NVIC. It doesn't enable any specific interrupt.
+    pub fn enable_interrupts(&mut self) {
+        // Safe, because there's no critical section here that this could
+        // interfere with.
+        unsafe { pac::NVIC::unmask(I::INTERRUPT) };
+    }
+}
+
+impl<I, Pins> FullDuplex<u8> for SPI<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    type Error = Void;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        if self.spi.stat.read().rxrdy().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        Ok(self.spi.rxdat.read().rxdat().bits() as u8)
+    }
+
+    fn send(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.spi.stat.read().txrdy().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.spi
+            .txdat
+            .write(|w| unsafe { w.data().bits(word as u16) });
+
+        Ok(())
+    }
+}
+
+impl<I, Pins> transfer::Default<u8> for SPI<I, init_state::Enabled<Pins>> where
+    I: Instance
+{
+}
+
+impl<I, Pins> write::Default<u8> for SPI<I, init_state::Enabled<Pins>> where
+    I: Instance
+{
+}
+
+impl<I> SPI<I, init_state::Disabled> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> I {
+        self.spi
+    }
+}
+
+impl<I, Pins> SPI<I, init_state::Enabled<Pins>> {
+    /// Return the raw peripheral and the switch matrix functions
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// Unlike [`SPI::disable`], this leaves the SPI peripheral enabled. The
+    /// switch matrix functions that were passed to [`SPI::enable`] are
+    /// returned as well, so they can be unassigned and their pins used for
+    /// something else.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    /// [`SPI::disable`]: #method.disable
+    /// [`SPI::enable`]: #method.enable
+    pub fn free(self) -> (I, Pins) {
+        (self.spi, self.state.0)
+    }
+}
+
+/// Compute the DIVVAL for the highest frequency that is not above `hz`
+fn divval<I: Instance>(hz: u32) -> Result<u16, Error> {
+    if hz == 0 {
+        return Err(Error::InvalidFrequency);
+    }
+
+    #[cfg(feature = "82x")]
+    let input_clock_hz = syscon::system_clock_hz();
+    #[cfg(feature = "845")]
+    let input_clock_hz = syscon::clocksource::function_clock_hz(I::FCLKSEL);
+
+    // Rounds up, without overflowing for large values of `hz`, or
+    // underflowing if the LPC845 has no function clock selected.
+    let div = input_clock_hz.saturating_sub(1) / hz + 1;
+    let div = if div > 0x1_0000 { 0x1_0000 } else { div };
+
+    Ok((div - 1) as u16)
+}
+
+/// The order in which the bits of a frame are transferred
+///
+/// Passed to [`SPI::reconfigure`].
+///
+/// [`SPI::reconfigure`]: struct.SPI.html#method.reconfigure
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitOrder {
+    /// The most significant bit is transferred first
+    MsbFirst,
+
+    /// The least significant bit is transferred first
+    LsbFirst,
+}
+
+impl Default for BitOrder {
+    fn default() -> Self {
+        BitOrder::MsbFirst
+    }
+}
+
+/// Delays around the hardware slave selects, in SPI clock cycles
+///
+/// Passed to [`SPI::set_delays`]. Every delay can be between 0 and 15 clock
+/// cycles. All delays are 0 after reset.
+///
+/// [`SPI::set_delays`]: struct.SPI.html#method.set_delays
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Delays {
+    /// Between asserting slave select and the start of the first frame
+    pub pre: u8,
+
+    /// Between the end of the last frame and deasserting slave select
+    ///
+    /// Serial NOR flash memories usually specify this as the chip select hold
+    /// time.
+    pub post: u8,
+
+    /// Between two frames of the same transfer
+    pub frame: u8,
+
+    /// The minimum time slave select is deasserted between two transfers
+    ///
+    /// Serial NOR flash memories usually specify this as the chip select high
+    /// time, which needs to be met for a command to be accepted.
+    pub transfer: u8,
+}
+
+impl Delays {
+    /// Return the value of the DLY register
+    fn bits(&self) -> u32 {
+        assert!(self.pre < 16);
+        assert!(self.post < 16);
+        assert!(self.frame < 16);
+        assert!(self.transfer < 16);
+
+        self.pre as u32
+            | (self.post as u32) << 4
+            | (self.frame as u32) << 8
+            | (self.transfer as u32) << 12
+    }
+}
+
+/// An SPI error
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A transfer was in progress
+    Busy,
+
+    /// The requested clock frequency was `0`
+    InvalidFrequency,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Busy => f.write_str("SPI transfer in progress"),
+            Error::InvalidFrequency => f.write_str("invalid SPI frequency"),
+        }
+    }
+}
+
+/// The switch matrix functions held by an enabled [`SPI`] instance
+///
+/// Returned by [`SPI::disable`].
+///
+/// [`SPI`]: struct.SPI.html
+/// [`SPI::disable`]: struct.SPI.html#method.disable
+pub type Functions<I, SckPin, MosiPin, MisoPin> = (
+    swm::Function<<I as Instance>::Sck, swm::state::Assigned<SckPin>>,
+    swm::Function<<I as Instance>::Mosi, swm::state::Assigned<MosiPin>>,
+    swm::Function<<I as Instance>::Miso, swm::state::Assigned<MisoPin>>,
+);
+
+/// Internal trait for SPI peripherals
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait Instance:
+    Deref<Target = pac::spi0::RegisterBlock>
+    + syscon::ClockControl
+    + syscon::ResetControl
+{
+    /// The interrupt that is triggered for this SPI peripheral
+    const INTERRUPT: Interrupt;
+
+    /// The movable function that needs to be assigned to this SPI's SCK pin
+    type Sck;
+
+    /// The movable function that needs to be assigned to this SPI's MOSI pin
+    type Mosi;
+
+    /// The movable function that needs to be assigned to this SPI's MISO pin
+    type Miso;
+
+    /// The index of this SPI's FCLKSEL register
+    #[cfg(feature = "845")]
+    const FCLKSEL: usize;
+}
+
+macro_rules! instances {
+    (
+        $(
+            $instance:ident,
+            $interrupt:ident,
+            $sck:ident,
+            $mosi:ident,
+            $miso:ident;
+        )*
+    ) => {
+        $(
+            impl Instance for pac::$instance {
+                const INTERRUPT: Interrupt = Interrupt::$interrupt;
+
+                type Sck = swm::$sck;
+                type Mosi = swm::$mosi;
+                type Miso = swm::$miso;
+
+                #[cfg(feature = "845")]
+                const FCLKSEL: usize = <pac::$instance as
+                    syscon::clocksource::PeripheralClockSelector>::REGISTER_NUM;
+            }
+        )*
+    };
+}
+
+instances!(
+    SPI0, SPI0, SPI0_SCK, SPI0_MOSI, SPI0_MISO;
+    SPI1, SPI1, SPI1_SCK, SPI1_MOSI, SPI1_MISO;
+);
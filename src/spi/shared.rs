@@ -0,0 +1,405 @@
+//! Sharing an SPI bus between multiple devices
+//!
+//! Drivers for SPI devices usually expect to own the SPI peripheral they're
+//! using. If multiple devices are connected to the same bus, [`SpiBus`] can
+//! hand out an [`SpiDevice`] per device instead, which can be passed to the
+//! respective driver.
+//!
+//! Each `SpiDevice` owns the chip select of its device, which can either be a
+//! GPIO pin or one of the SPI's hardware slave select outputs (see [`Ssel`]),
+//! as well as the settings its device requires. For every transfer, the
+//! `SpiDevice` gains exclusive access to the bus, applies its settings using
+//! [`SPI::reconfigure`], and selects its device.
+//!
+//! Transfers happen within a critical section, which makes it possible to use
+//! devices on the same bus from the main loop and from interrupt handlers. As
+//! interrupts are disabled for the duration of a transfer, keep transfers
+//! short, if interrupt latency is a concern.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     init_state,
+//!     pac::SPI0,
+//!     prelude::*,
+//!     spi::{
+//!         self,
+//!         shared::{Config, SpiBus},
+//!         BitOrder, SPI,
+//!     },
+//!     Peripherals,
+//! };
+//!
+//! fn run<Pins>(spi: SPI<SPI0, init_state::Enabled<Pins>>) {
+//!     let p = Peripherals::take().unwrap();
+//!
+//!     let swm = p.SWM.split();
+//!     let mut syscon = p.SYSCON.split();
+//!
+//!     #[cfg(feature = "82x")]
+//!     let gpio = p.GPIO;
+//!     #[cfg(feature = "845")]
+//!     let gpio = p.GPIO.enable(&mut syscon.handle);
+//!
+//!     let mut cs_a = swm.pins.pio0_16.into_gpio_pin(&gpio).into_output();
+//!     let mut cs_b = swm.pins.pio0_17.into_gpio_pin(&gpio).into_output();
+//!     cs_a.set_high().unwrap();
+//!     cs_b.set_high().unwrap();
+//!
+//!     let bus = SpiBus::new(spi);
+//!
+//!     let mut device_a = bus.device(
+//!         cs_a,
+//!         Config::new(spi::MODE_0, BitOrder::MsbFirst, 1_000_000),
+//!     );
+//!     let mut device_b = bus.device(
+//!         cs_b,
+//!         Config::new(spi::MODE_3, BitOrder::MsbFirst, 4_000_000),
+//!     );
+//!
+//!     device_a.write(&[0x01, 0x02]).unwrap();
+//!     device_b.write(&[0x03, 0x04]).unwrap();
+//! }
+//! ```
+//!
+//! [`SpiBus`]: struct.SpiBus.html
+//! [`SpiDevice`]: struct.SpiDevice.html
+//! [`Ssel`]: struct.Ssel.html
+//! [`SPI::reconfigure`]: ../struct.SPI.html#method.reconfigure
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, Mutex};
+use embedded_hal::{
+    blocking::spi::{Transfer, Write},
+    digital::v2::OutputPin as _,
+};
+use void::ResultVoidExt as _;
+
+use crate::{
+    gpio::direction,
+    init_state, pac,
+    swm::{self, pin_state, Pin, PinTrait},
+};
+
+use super::{BitOrder, Delays, Error, Instance, Mode, SPI};
+
+/// An SPI bus that can be shared between multiple devices
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct SpiBus<I, Pins> {
+    // Always `Some`, until the bus is consumed by `free`.
+    spi: Mutex<RefCell<Option<SPI<I, init_state::Enabled<Pins>>>>>,
+}
+
+impl<I, Pins> SpiBus<I, Pins>
+where
+    I: Instance,
+{
+    /// Create a shared bus from an enabled SPI peripheral
+    pub fn new(spi: SPI<I, init_state::Enabled<Pins>>) -> Self {
+        SpiBus {
+            spi: Mutex::new(RefCell::new(Some(spi))),
+        }
+    }
+
+    /// Create a handle for a device on this bus
+    ///
+    /// `cs` is deselected right away. `config` is applied before every
+    /// transfer to the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if called during a transfer, from an interrupt handler.
+    pub fn device<Cs>(
+        &self,
+        mut cs: Cs,
+        config: Config,
+    ) -> SpiDevice<I, Pins, Cs>
+    where
+        Cs: ChipSelect<I>,
+    {
+        interrupt::free(|critical_section| {
+            let mut spi = self.spi.borrow(critical_section).borrow_mut();
+            // Can't panic. See comment on field.
+            cs.deselect(&spi.as_mut().unwrap().spi);
+        });
+
+        SpiDevice {
+            bus: self,
+            cs,
+            config,
+        }
+    }
+
+    /// Return the SPI peripheral
+    ///
+    /// This can only be called, once all device handles have been dropped.
+    pub fn free(self) -> SPI<I, init_state::Enabled<Pins>> {
+        interrupt::free(|cs| self.spi.borrow(cs).borrow_mut().take())
+            // Can't panic. See comment on field.
+            .unwrap()
+    }
+}
+
+/// A device on a shared SPI bus
+///
+/// Created by [`SpiBus::device`]. Implements the blocking SPI traits from
+/// embedded-hal, so it can be passed to drivers.
+///
+/// [`SpiBus::device`]: struct.SpiBus.html#method.device
+pub struct SpiDevice<'bus, I, Pins, Cs> {
+    bus: &'bus SpiBus<I, Pins>,
+    cs: Cs,
+    config: Config,
+}
+
+impl<'bus, I, Pins, Cs> SpiDevice<'bus, I, Pins, Cs>
+where
+    I: Instance,
+    Cs: ChipSelect<I>,
+{
+    /// Run a transaction with this device
+    ///
+    /// Gains exclusive access to the bus, applies this device's settings, and
+    /// selects the device, before calling `f`. Once `f` returns, the current
+    /// frame is finished and the device deselected.
+    ///
+    /// Returns [`Error::Busy`], if the bus is already in use, which can only
+    /// happen, if this method is called from within another transaction.
+    /// Returns [`Error::InvalidFrequency`], if the frequency in the [`Config`]
+    /// is `0`.
+    ///
+    /// [`Error::Busy`]: ../enum.Error.html#variant.Busy
+    /// [`Error::InvalidFrequency`]: ../enum.Error.html#variant.InvalidFrequency
+    /// [`Config`]: struct.Config.html
+    pub fn transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut SPI<I, init_state::Enabled<Pins>>) -> R,
+    ) -> Result<R, Error> {
+        let bus = self.bus;
+        let cs = &mut self.cs;
+        let config = &self.config;
+
+        interrupt::free(|critical_section| {
+            let mut spi = bus
+                .spi
+                .borrow(critical_section)
+                .try_borrow_mut()
+                .map_err(|_| Error::Busy)?;
+            // Can't panic. See comment on field.
+            let spi = spi.as_mut().unwrap();
+
+            spi.reconfigure(config.mode, config.bit_order, config.hz)?;
+            spi.set_delays(config.delays);
+
+            cs.select(&spi.spi);
+            let result = f(spi);
+            while spi.spi.stat.read().mstidle().bit_is_clear() {}
+            cs.deselect(&spi.spi);
+
+            Ok(result)
+        })
+    }
+
+    /// Change the settings that are applied before every transfer
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Release the chip select
+    pub fn free(self) -> Cs {
+        self.cs
+    }
+}
+
+impl<'bus, I, Pins, Cs> Transfer<u8> for SpiDevice<'bus, I, Pins, Cs>
+where
+    I: Instance,
+    Cs: ChipSelect<I>,
+{
+    type Error = Error;
+
+    fn transfer<'w>(
+        &mut self,
+        words: &'w mut [u8],
+    ) -> Result<&'w [u8], Self::Error> {
+        self.transaction(|spi| {
+            spi.transfer(words).void_unwrap();
+        })?;
+
+        Ok(words)
+    }
+}
+
+impl<'bus, I, Pins, Cs> Write<u8> for SpiDevice<'bus, I, Pins, Cs>
+where
+    I: Instance,
+    Cs: ChipSelect<I>,
+{
+    type Error = Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.transaction(|spi| spi.write(words).void_unwrap())
+    }
+}
+
+/// The settings of a device on a shared SPI bus
+///
+/// Passed to [`SpiBus::device`]. See [`SPI::reconfigure`] for the meaning of
+/// the settings.
+///
+/// [`SpiBus::device`]: struct.SpiBus.html#method.device
+/// [`SPI::reconfigure`]: ../struct.SPI.html#method.reconfigure
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Config {
+    /// The clock polarity and phase
+    pub mode: Mode,
+
+    /// The bit order
+    pub bit_order: BitOrder,
+
+    /// The clock frequency in Hz
+    pub hz: u32,
+
+    /// The delays around the slave select
+    ///
+    /// Only apply to [`Ssel`], not to GPIO pins. See [`SPI::set_delays`].
+    ///
+    /// [`Ssel`]: struct.Ssel.html
+    /// [`SPI::set_delays`]: ../struct.SPI.html#method.set_delays
+    pub delays: Delays,
+}
+
+impl Config {
+    /// Create device settings
+    ///
+    /// All delays are 0. Use [`with_delays`] to change that.
+    ///
+    /// [`with_delays`]: #method.with_delays
+    pub fn new(mode: Mode, bit_order: BitOrder, hz: u32) -> Self {
+        Config {
+            mode,
+            bit_order,
+            hz,
+            delays: Delays::default(),
+        }
+    }
+
+    /// Change the delays around the slave select
+    ///
+    /// # Panics
+    ///
+    /// Panics, if any of the delays is larger than 15.
+    pub fn with_delays(mut self, delays: Delays) -> Self {
+        // Validate the delays now, instead of on the next transfer.
+        let _ = delays.bits();
+
+        self.delays = delays;
+        self
+    }
+}
+
+/// A chip select for a device on a shared SPI bus
+///
+/// Implemented for GPIO output pins, and for [`Ssel`].
+///
+/// [`Ssel`]: struct.Ssel.html
+pub trait ChipSelect<I> {
+    /// Select the device
+    fn select(&mut self, spi: &I);
+
+    /// Deselect the device
+    ///
+    /// Called after the SPI peripheral has become idle.
+    fn deselect(&mut self, spi: &I);
+}
+
+impl<'gpio, I, T> ChipSelect<I>
+    for Pin<T, pin_state::Gpio<'gpio, direction::Output>>
+where
+    T: PinTrait,
+{
+    fn select(&mut self, _: &I) {
+        self.set_low().void_unwrap();
+    }
+
+    fn deselect(&mut self, _: &I) {
+        self.set_high().void_unwrap();
+    }
+}
+
+/// A hardware slave select output of an SPI peripheral
+///
+/// Can be used as a chip select for a device on a shared SPI bus, instead of
+/// a GPIO pin. The slave select is active low.
+pub struct Ssel<F, P> {
+    function: swm::Function<F, swm::state::Assigned<P>>,
+}
+
+impl<F, P> Ssel<F, P> {
+    /// Use an assigned slave select function as a chip select
+    pub fn new(function: swm::Function<F, swm::state::Assigned<P>>) -> Self {
+        Ssel { function }
+    }
+
+    /// Return the slave select function
+    pub fn free(self) -> swm::Function<F, swm::state::Assigned<P>> {
+        self.function
+    }
+}
+
+impl<I, F, P> ChipSelect<I> for Ssel<F, P>
+where
+    I: Instance,
+    F: SselFunction<I>,
+{
+    fn select(&mut self, spi: &I) {
+        // Assert this slave select for all following frames, by clearing its
+        // TXSSELn_N bit. See user manual, section 17.6.4.
+        spi.txctl
+            .modify(|r, w| unsafe { w.bits(r.bits() & !F::TXSSEL) });
+    }
+
+    fn deselect(&mut self, spi: &I) {
+        // End the transfer, which deasserts the slave select. See user manual,
+        // section 17.6.3.
+        spi.stat.write(|w| w.endtransfer().set_bit());
+        while spi.stat.read().mstidle().bit_is_clear() {}
+
+        spi.txctl
+            .modify(|r, w| unsafe { w.bits(r.bits() | F::TXSSEL) });
+    }
+}
+
+/// Implemented for the slave select functions of SPI peripheral `I`
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+pub trait SselFunction<I> {
+    /// The TXSSELn_N bit of the TXCTL register for this slave select
+    const TXSSEL: u32;
+}
+
+macro_rules! ssel_functions {
+    ($($function:ident, $instance:ident, $index:expr;)*) => {
+        $(
+            impl SselFunction<pac::$instance> for swm::$function {
+                const TXSSEL: u32 = 1 << (16 + $index);
+            }
+        )*
+    };
+}
+
+ssel_functions!(
+    SPI0_SSEL0, SPI0, 0;
+    SPI0_SSEL1, SPI0, 1;
+    SPI0_SSEL2, SPI0, 2;
+    SPI0_SSEL3, SPI0, 3;
+    SPI1_SSEL0, SPI1, 0;
+    SPI1_SSEL1, SPI1, 1;
+);
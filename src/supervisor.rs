@@ -0,0 +1,239 @@
+//! Supply voltage supervision, using the BOD and the comparator
+//!
+//! A device that loses power without warning can't save its state, and might
+//! corrupt flash, if it keeps running while the supply voltage decays.
+//! [`Supervisor`] sets up two thresholds:
+//!
+//! - The brown-out detector (BOD) resets the microcontroller, once the supply
+//!   voltage falls below the reset level. This keeps it from running at a
+//!   voltage it's not specified for.
+//! - The analog comparator compares the supply voltage, divided by its voltage
+//!   ladder, against the internal bandgap reference. This provides an early
+//!   warning at a higher threshold, which leaves the application time to save
+//!   its state, before the BOD resets the microcontroller.
+//!
+//! The warning sets a flag that can be checked using
+//! [`Supervisor::take_warning`], and calls an optional hook. Both happen in
+//! [`Supervisor::handle_interrupt`], which needs to be called from the
+//! comparator interrupt handler (or regularly, from the main loop).
+//!
+//! The bandgap reference is nominally 900 mV, but varies between parts. The
+//! warning threshold is only as accurate as that. Please refer to the data
+//! sheet for the reference voltage and the BOD reset levels.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     supervisor::{ResetLevel, Supervisor},
+//!     Peripherals,
+//! };
+//!
+//! fn save_state() {
+//!     // Write the application's state to flash
+//! }
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let acmp = p.ACOMP.enable(&mut syscon.handle);
+//!
+//! // Reset at about 2.6 V, warn at about 2.8 V
+//! let mut supervisor = Supervisor::new(
+//!     acmp,
+//!     &mut syscon.handle,
+//!     &mut syscon.bod,
+//!     ResetLevel::Level3,
+//!     2800,
+//! );
+//! supervisor.set_hook(Some(save_state));
+//!
+//! // In the comparator interrupt handler
+//! supervisor.handle_interrupt();
+//! ```
+//!
+//! [`Supervisor`]: struct.Supervisor.html
+//! [`Supervisor::take_warning`]: struct.Supervisor.html#method.take_warning
+//! [`Supervisor::handle_interrupt`]: struct.Supervisor.html#method.handle_interrupt
+
+use crate::{
+    acmp::{self, ACMP},
+    pac::syscon::BODCTRL,
+    reg_proxy::RegProxy,
+    syscon,
+};
+
+/// The nominal voltage of the internal bandgap reference, in mV
+const BANDGAP_MV: u32 = 900;
+
+/// The number of steps of the comparator's voltage ladder
+const LADDER_STEPS: u32 = 31;
+
+/// The mask of the BODRSTLEV field of the BODCTRL register
+const BODCTRL_BODRSTLEV: u32 = 0b11;
+
+/// The BODRSTENA bit of the BODCTRL register
+const BODCTRL_BODRSTENA: u32 = 1 << 4;
+
+/// Supervises the supply voltage
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Supervisor {
+    acmp: ACMP,
+    bodctrl: RegProxy<BODCTRL>,
+
+    ladder: u8,
+    hook: Option<fn()>,
+    warning: bool,
+}
+
+impl Supervisor {
+    /// Set up the BOD reset and the early warning
+    ///
+    /// Powers up the BOD and makes it reset the microcontroller below
+    /// `reset_level`. Configures the comparator to detect the supply voltage
+    /// falling below `warning_mv`, rounded to the nearest step of the voltage
+    /// ladder. The actual threshold can be queried using
+    /// [`warning_threshold_mv`].
+    ///
+    /// The comparator uses 20 mV of hysteresis and a synchronized output, so
+    /// noise on the supply doesn't cause repeated warnings. On LPC845, its
+    /// interrupt is enabled. On both families, it needs to be unmasked in the
+    /// NVIC separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `warning_mv` is below 900 mV, the voltage of the bandgap
+    /// reference.
+    ///
+    /// [`warning_threshold_mv`]: #method.warning_threshold_mv
+    pub fn new(
+        mut acmp: ACMP,
+        handle: &mut syscon::Handle,
+        bod: &mut syscon::BOD,
+        reset_level: ResetLevel,
+        warning_mv: u32,
+    ) -> Self {
+        assert!(warning_mv >= BANDGAP_MV);
+
+        let bodctrl = RegProxy::<BODCTRL>::new();
+
+        handle.power_up(bod);
+        bodctrl.modify(|r, w| unsafe {
+            w.bits(
+                r.bits() & !BODCTRL_BODRSTLEV
+                    | reset_level as u32
+                    | BODCTRL_BODRSTENA,
+            )
+        });
+
+        // The ladder outputs `ladder / 31` times the supply voltage, which
+        // equals the bandgap voltage, when the supply voltage is at the
+        // threshold.
+        let ladder = (BANDGAP_MV * LADDER_STEPS + warning_mv / 2) / warning_mv;
+        let ladder = ladder.max(1).min(LADDER_STEPS) as u8;
+
+        // The output is high, while the supply voltage is above the
+        // threshold. Its falling edge is the warning.
+        acmp.set_ladder(ladder, acmp::LadderReference::Supply);
+        acmp.set_hysteresis(acmp::Hysteresis::Mv20);
+        acmp.set_synchronized(true);
+        acmp.configure(&acmp::Ladder, &acmp::Bandgap, acmp::Edge::Falling);
+
+        #[cfg(feature = "845")]
+        acmp.enable_interrupt();
+
+        Supervisor {
+            acmp,
+            bodctrl,
+
+            ladder,
+            hook: None,
+            warning: false,
+        }
+    }
+
+    /// Set the hook that is called on a warning
+    ///
+    /// The hook is called by [`handle_interrupt`], which usually means in
+    /// interrupt context. It should save whatever state needs to survive the
+    /// loss of power, and not much else.
+    ///
+    /// [`handle_interrupt`]: #method.handle_interrupt
+    pub fn set_hook(&mut self, hook: Option<fn()>) {
+        self.hook = hook;
+    }
+
+    /// Handle the comparator interrupt
+    ///
+    /// If the supply voltage has fallen below the warning threshold, sets the
+    /// warning flag and calls the hook. Call this from the comparator
+    /// interrupt handler, or regularly from the main loop.
+    pub fn handle_interrupt(&mut self) {
+        if !self.acmp.edge_detected() {
+            return;
+        }
+
+        self.acmp.clear_edge();
+        self.warning = true;
+
+        if let Some(hook) = self.hook {
+            hook();
+        }
+    }
+
+    /// Indicates whether a warning has occurred, and clears the flag
+    pub fn take_warning(&mut self) -> bool {
+        core::mem::replace(&mut self.warning, false)
+    }
+
+    /// Indicates whether the supply voltage is currently below the warning
+    /// threshold
+    ///
+    /// This can be used during startup, to find out whether it's safe to
+    /// write to flash, for example.
+    pub fn is_below_threshold(&self) -> bool {
+        !self.acmp.output()
+    }
+
+    /// Return the warning threshold, in mV
+    ///
+    /// This is based on the nominal bandgap voltage.
+    pub fn warning_threshold_mv(&self) -> u32 {
+        BANDGAP_MV * LADDER_STEPS / self.ladder as u32
+    }
+
+    /// Disable the BOD reset and release the comparator
+    ///
+    /// The BOD stays powered, as other code might rely on it.
+    // `self` is only mutated on LPC845
+    #[allow(unused_mut)]
+    pub fn free(mut self) -> ACMP {
+        #[cfg(feature = "845")]
+        self.acmp.disable_interrupt();
+
+        self.bodctrl
+            .modify(|r, w| unsafe { w.bits(r.bits() & !BODCTRL_BODRSTENA) });
+
+        self.acmp
+    }
+}
+
+/// The supply voltage, below which the BOD resets the microcontroller
+///
+/// The exact voltages are listed in the data sheet. They differ slightly
+/// between LPC82x and LPC845.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ResetLevel {
+    /// Reset level 1, the lowest (about 2.0 V)
+    Level1 = 1,
+
+    /// Reset level 2 (about 2.3 V)
+    Level2 = 2,
+
+    /// Reset level 3, the highest (about 2.6 V)
+    Level3 = 3,
+}
@@ -5,7 +5,9 @@
 //!
 //! The switch matrix is described in the user manual, chapter 7.
 
-use core::marker::PhantomData;
+use core::{cell::Cell, fmt, marker::PhantomData};
+
+use cortex_m::interrupt::{self, Mutex};
 
 use crate::{
     gpio::{self, GPIO},
@@ -126,6 +128,30 @@ pub struct Parts<STATE> {
     pub fixed_functions: FixedFunctions,
 }
 
+impl<STATE> Parts<STATE> {
+    /// Reassemble the SWM API from its component parts
+    ///
+    /// This is the inverse of [`SWM::split`]. It is only possible to call
+    /// this method, if all pins and functions are back in their initial state,
+    /// which means any function that was assigned needs to be unassigned
+    /// again, and all pins need to be returned from the drivers that were
+    /// using them (for example using [`USART::disable`]).
+    ///
+    /// All fields of `Parts` are public, so if you moved parts out of it, you
+    /// can put them back by constructing a new instance.
+    ///
+    /// The returned [`SWM`] instance can be split again, or be used to get
+    /// access to the raw peripheral via [`SWM::free`].
+    ///
+    /// [`USART::disable`]: ../usart/struct.USART.html#method.disable
+    pub fn free(self) -> SWM<STATE> {
+        SWM {
+            swm: self.handle.swm,
+            state: self.handle._state,
+        }
+    }
+}
+
 /// Handle to the SWM peripheral
 ///
 /// Can be used to enable and disable the switch matrix. It is also required by
@@ -138,12 +164,17 @@ pub struct Parts<STATE> {
 /// [module documentation]: index.html
 pub struct Handle<State = init_state::Enabled> {
     swm: pac::SWM0,
+    batch: Option<Batch>,
     _state: State,
 }
 
 impl<STATE> Handle<STATE> {
     pub(crate) fn new(swm: pac::SWM0, state: STATE) -> Self {
-        Handle { swm, _state: state }
+        Handle {
+            swm,
+            batch: None,
+            _state: state,
+        }
     }
 }
 
@@ -167,6 +198,7 @@ impl Handle<init_state::Disabled> {
 
         Handle {
             swm: self.swm,
+            batch: None,
             _state: init_state::Enabled(()),
         }
     }
@@ -195,9 +227,249 @@ impl Handle<init_state::Enabled> {
 
         Handle {
             swm: self.swm,
+            batch: None,
             _state: init_state::Disabled,
         }
     }
+
+    /// Apply multiple fixed function changes in one register write
+    ///
+    /// Calls `f` with this handle. All fixed functions that are assigned or
+    /// unassigned within `f` are collected, then written to the PINENABLE
+    /// register(s) at once, after `f` returns. This avoids glitches on pins
+    /// that would otherwise be in an intermediate state between the changes.
+    /// Movable functions are not affected and are still assigned immediately.
+    ///
+    /// Returns the return value of `f`.
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let swm = p.SWM.split();
+    /// let mut syscon = p.SYSCON.split();
+    ///
+    /// #[cfg(feature = "82x")]
+    /// let mut swm_handle = swm.handle;
+    /// #[cfg(feature = "845")]
+    /// let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+    ///
+    /// let sda = swm.pins.pio0_11.into_swm_pin();
+    /// let scl = swm.pins.pio0_10.into_swm_pin();
+    ///
+    /// let i2c0_sda = swm.fixed_functions.i2c0_sda;
+    /// let i2c0_scl = swm.fixed_functions.i2c0_scl;
+    ///
+    /// let ((sda, _), (scl, _)) = swm_handle.apply(|handle| {
+    ///     (i2c0_sda.assign(sda, handle), i2c0_scl.assign(scl, handle))
+    /// });
+    /// ```
+    pub fn apply<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let outer = self.batch.replace(Batch::new());
+        let result = f(self);
+
+        if let Some(batch) = self.batch.take() {
+            self.commit(&batch);
+        }
+        self.batch = outer;
+
+        result
+    }
+
+    /// Return an iterator over the current movable function assignments
+    ///
+    /// Reads the PINASSIGN registers and yields an [`Assignment`] for every
+    /// movable function that is currently assigned to a pin. As this reflects
+    /// the actual state of the hardware, it is useful for printing the pin
+    /// mapping for diagnostic purposes.
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let swm = p.SWM.split();
+    /// let mut syscon = p.SYSCON.split();
+    ///
+    /// #[cfg(feature = "82x")]
+    /// let swm_handle = swm.handle;
+    /// #[cfg(feature = "845")]
+    /// let swm_handle = swm.handle.enable(&mut syscon.handle);
+    ///
+    /// for assignment in swm_handle.assignments() {
+    ///     // Formats as, for example, "U0_TXD: PIO0_4"
+    ///     let _ = (assignment.function, assignment.pin);
+    /// }
+    /// ```
+    ///
+    /// [`Assignment`]: struct.Assignment.html
+    pub fn assignments(&self) -> impl Iterator<Item = Assignment> + '_ {
+        MOVABLE_FUNCTIONS
+            .iter()
+            .filter_map(move |&(function, read)| {
+                let pin = PinId::from_bits(read(&*self.swm))?;
+                Some(Assignment { function, pin })
+            })
+    }
+
+    /// Set bit `bit` of PINENABLE0 to `value`
+    ///
+    /// A cleared bit enables the fixed function.
+    pub(crate) fn pinenable0(&mut self, bit: u8, value: bool) {
+        match &mut self.batch {
+            Some(batch) => batch.pinenable0.set(bit, value),
+            None => {
+                let mut batch = Batch::new();
+                batch.pinenable0.set(bit, value);
+                self.commit(&batch);
+            }
+        }
+    }
+
+    /// Set bit `bit` of PINENABLE1 to `value`
+    ///
+    /// A cleared bit enables the fixed function.
+    #[cfg(feature = "845")]
+    pub(crate) fn pinenable1(&mut self, bit: u8, value: bool) {
+        match &mut self.batch {
+            Some(batch) => batch.pinenable1.set(bit, value),
+            None => {
+                let mut batch = Batch::new();
+                batch.pinenable1.set(bit, value);
+                self.commit(&batch);
+            }
+        }
+    }
+
+    fn commit(&mut self, batch: &Batch) {
+        // Every register is written using read-modify-write. The critical
+        // section makes sure no interrupt handler can change the register in
+        // between, with whatever access it might have.
+        //
+        // Writing raw bits is sound, as only the bits of fixed functions are
+        // changed. All other bits keep the value that was read.
+        interrupt::free(|_| {
+            if !batch.pinenable0.is_empty() {
+                self.swm.pinenable0.modify(|r, w| unsafe {
+                    w.bits(batch.pinenable0.apply(r.bits()))
+                });
+            }
+            #[cfg(feature = "845")]
+            if !batch.pinenable1.is_empty() {
+                self.swm.pinenable1.modify(|r, w| unsafe {
+                    w.bits(batch.pinenable1.apply(r.bits()))
+                });
+            }
+        });
+    }
+}
+
+/// The changes collected by [`Handle::apply`]
+///
+/// [`Handle::apply`]: struct.Handle.html#method.apply
+struct Batch {
+    pinenable0: Changes,
+    #[cfg(feature = "845")]
+    pinenable1: Changes,
+}
+
+impl Batch {
+    fn new() -> Self {
+        Batch {
+            pinenable0: Changes::new(),
+            #[cfg(feature = "845")]
+            pinenable1: Changes::new(),
+        }
+    }
+}
+
+/// The pending changes to a single PINENABLE register
+///
+/// Only the latest value of every bit is kept, so a batch can contain any
+/// number of changes.
+struct Changes {
+    /// The bits that have been changed
+    mask: u32,
+
+    /// The new values of the bits in `mask`
+    values: u32,
+}
+
+impl Changes {
+    fn new() -> Self {
+        Changes { mask: 0, values: 0 }
+    }
+
+    fn set(&mut self, bit: u8, value: bool) {
+        let bit = 1 << bit;
+
+        self.mask |= bit;
+        if value {
+            self.values |= bit;
+        } else {
+            self.values &= !bit;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    /// Apply the changes to the current value of the register
+    fn apply(&self, bits: u32) -> u32 {
+        bits & !self.mask | self.values
+    }
+}
+
+/// The number of PINASSIGN registers
+#[cfg(all(feature = "debug_dump", feature = "82x"))]
+pub(crate) const PINASSIGN_REGISTERS: usize = 12;
+/// The number of PINASSIGN registers
+#[cfg(all(feature = "debug_dump", feature = "845"))]
+pub(crate) const PINASSIGN_REGISTERS: usize = 15;
+
+/// The number of PINENABLE registers
+#[cfg(all(feature = "debug_dump", feature = "82x"))]
+pub(crate) const PINENABLE_REGISTERS: usize = 1;
+/// The number of PINENABLE registers
+#[cfg(all(feature = "debug_dump", feature = "845"))]
+pub(crate) const PINENABLE_REGISTERS: usize = 2;
+
+/// Collect the switch matrix registers for a [`debug_dump`] snapshot
+///
+/// [`debug_dump`]: ../debug_dump/index.html
+#[cfg(feature = "debug_dump")]
+pub(crate) fn snapshot(
+    swm: &pac::swm0::RegisterBlock,
+) -> crate::debug_dump::Swm {
+    crate::debug_dump::Swm {
+        pinassign: [
+            swm.pinassign0.read().bits(),
+            swm.pinassign1.read().bits(),
+            swm.pinassign2.read().bits(),
+            swm.pinassign3.read().bits(),
+            swm.pinassign4.read().bits(),
+            swm.pinassign5.read().bits(),
+            swm.pinassign6.read().bits(),
+            swm.pinassign7.read().bits(),
+            swm.pinassign8.read().bits(),
+            swm.pinassign9.read().bits(),
+            swm.pinassign10.read().bits(),
+            swm.pinassign11.read().bits(),
+            #[cfg(feature = "845")]
+            swm.pinassign12.read().bits(),
+            #[cfg(feature = "845")]
+            swm.pinassign13.read().bits(),
+            #[cfg(feature = "845")]
+            swm.pinassign14.read().bits(),
+        ],
+        pinenable: [
+            swm.pinenable0.read().bits(),
+            #[cfg(feature = "845")]
+            swm.pinenable1.read().bits(),
+        ],
+    }
 }
 
 /// Implemented by types that identify pins
@@ -224,8 +496,244 @@ pub trait PinTrait {
     const MASK: u32;
 }
 
+/// Identifies a pin by port and number
+///
+/// Formats as the pin's name, for example `PIO0_4`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinId {
+    /// The port, `0` for [`PIO0_0`] and `1` for [`PIO1_0`]
+    pub port: u8,
+
+    /// The number of the pin within the port
+    pub number: u8,
+}
+
+impl PinId {
+    /// Return the identifier of pin `P`
+    pub fn of<P: PinTrait>() -> Self {
+        PinId {
+            port: P::PORT as u8,
+            number: P::ID,
+        }
+    }
+
+    /// Decode the value of a PINASSIGN register field
+    ///
+    /// `0xff` means that the function is not assigned. See user manual,
+    /// section 7.6.1.
+    fn from_bits(bits: u8) -> Option<Self> {
+        if bits == 0xff {
+            return None;
+        }
+
+        Some(PinId {
+            port: bits >> 5,
+            number: bits & 0x1f,
+        })
+    }
+}
+
+impl fmt::Display for PinId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PIO{}_{}", self.port, self.number)
+    }
+}
+
+/// The pins that are currently in the unused state
+///
+/// Bit `n` of element `p` is set, if pin `PIOp_n` is available in the selected
+/// package and is in [`pin_state::Unused`]. Pins can be moved out of [`Pins`]
+/// without the HAL noticing, so instead, this is updated whenever a pin enters
+/// or leaves the unused state.
+///
+/// [`pin_state::Unused`]: pin_state/struct.Unused.html
+/// [`Pins`]: struct.Pins.html
+static UNUSED: Mutex<Cell<[u32; 2]>> = Mutex::new(Cell::new([0; 2]));
+
+/// Record that pin `T` has entered (`true`) or left (`false`) the unused state
+fn set_unused<T: PinTrait>(unused: bool) {
+    interrupt::free(|cs| {
+        let cell = UNUSED.borrow(cs);
+        let mut pins = cell.get();
+        if unused {
+            pins[T::PORT] |= T::MASK;
+        } else {
+            pins[T::PORT] &= !T::MASK;
+        }
+        cell.set(pins);
+    });
+}
+
+/// Return an iterator over the pins in a set of pins, as used by `UNUSED`
+fn pin_ids(pins: [u32; 2]) -> impl Iterator<Item = PinId> {
+    (0..2u8).flat_map(move |port| {
+        (0..32u8)
+            .filter(move |&number| pins[port as usize] & (1 << number) != 0)
+            .map(move |number| PinId { port, number })
+    })
+}
+
+/// Return an iterator over the pins that are currently unused
+///
+/// Yields every pin that is in [`pin_state::Unused`], regardless of whether it
+/// is still held by [`Pins`] or has been moved out of it. Pins that have been
+/// transitioned into another state, and pins whose reset state is another
+/// state (like the SWD and reset pins), are not included. Pins are yielded in
+/// order of port and number.
+///
+/// The result is a snapshot. Pins that change their state while iterating
+/// don't affect it.
+///
+/// [`pin_state::Unused`]: pin_state/struct.Unused.html
+/// [`Pins`]: struct.Pins.html
+pub fn unused_pins() -> impl Iterator<Item = PinId> {
+    pin_ids(interrupt::free(|cs| UNUSED.borrow(cs).get()))
+}
+
+/// Put all unused pins into a defined input state
+///
+/// Floating inputs can draw significant current, which matters in sleep
+/// modes. This function sets the direction of every pin in
+/// [`pin_state::Unused`] to input, and configures its internal resistors
+/// according to `pull`. See [`unused_pins`] for which pins that includes.
+/// Pins that are in use are left alone, even if they have been moved out of
+/// [`Pins`] already.
+///
+/// The type states are unchanged, so the pins can still be configured for
+/// other uses afterwards.
+///
+/// The IOCON clock must be enabled, using [`syscon::Handle::enable_clock`].
+///
+/// # Limitations
+///
+/// Pins can't be switched to analog mode this way, as that requires assigning
+/// their fixed analog function.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{gpio::Pull, swm, Peripherals};
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let mut syscon = p.SYSCON.split();
+/// let swm = p.SWM.split();
+///
+/// #[cfg(feature = "82x")]
+/// let gpio = p.GPIO;
+/// #[cfg(feature = "845")]
+/// let gpio = p.GPIO.enable(&mut syscon.handle);
+///
+/// let led = swm.pins.pio0_12.into_gpio_pin(&gpio).into_output();
+///
+/// // Leaves the LED pin alone
+/// syscon.handle.enable_clock(&p.IOCON);
+/// swm::set_unused_to_input(&gpio, &p.IOCON, Pull::Up);
+/// ```
+///
+/// [`pin_state::Unused`]: pin_state/struct.Unused.html
+/// [`unused_pins`]: fn.unused_pins.html
+/// [`Pins`]: struct.Pins.html
+/// [`syscon::Handle::enable_clock`]: ../syscon/struct.Handle.html#method.enable_clock
+pub fn set_unused_to_input(gpio: &GPIO, iocon: &pac::IOCON, pull: gpio::Pull) {
+    // The critical section keeps pins from leaving the unused state, while
+    // they are being configured. This makes it sound to configure pins that
+    // are owned by someone else, as nobody relies on the configuration of an
+    // unused pin.
+    interrupt::free(|cs| {
+        for pin in pin_ids(UNUSED.borrow(cs).get()) {
+            gpio::set_pull(iocon, pin, pull);
+            gpio::set_input(gpio, pin);
+        }
+    });
+}
+
+/// Provides the state a pin is in after the HAL has been initialized
+///
+/// This is used by [`board_pins!`] to determine the type of the pins it takes
+/// from [`Pins`]. The user should not need to implement this trait.
+///
+/// [`board_pins!`]: ../macro.board_pins.html
+/// [`Pins`]: struct.Pins.html
+pub trait ResetState: PinTrait {
+    /// The initial state of the pin
+    type State: PinState;
+}
+
+/// Gives names to the pins used by a board
+///
+/// Generates a struct that contains the pins used by a board, under the names
+/// used on that board (like `d13` or `led_green`), as well as a `new` method
+/// that takes those pins out of [`swm::Pins`]. Every field is declared by its
+/// name, its type, and the field of [`swm::Pins`] it is taken from.
+///
+/// This is intended for board support crates, to reduce the boilerplate
+/// required to map the microcontroller's pins to the board's names for them.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{board_pins, Peripherals};
+///
+/// board_pins! {
+///     /// The pins of my board
+///     pub struct BoardPins {
+///         /// The green LED
+///         pub led_green: PIO0_12 = pio0_12,
+///
+///         /// Digital pin 13
+///         pub d13: PIO0_15 = pio0_15,
+///     }
+/// }
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let swm = p.SWM.split();
+/// let pins = BoardPins::new(swm.pins);
+///
+/// let led = pins.led_green.into_gpio_pin(&p.GPIO).into_output_high();
+/// ```
+///
+/// [`swm::Pins`]: swm/struct.Pins.html
+#[macro_export]
+macro_rules! board_pins {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident: $pin:ident = $source:ident,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $crate::swm::Pin<
+                    $crate::swm::$pin,
+                    <$crate::swm::$pin as $crate::swm::ResetState>::State,
+                >,
+            )*
+        }
+
+        impl $name {
+            /// Take the board's pins from the HAL's pins
+            ///
+            /// Any pins not used by the board are dropped.
+            $vis fn new(pins: $crate::swm::Pins) -> Self {
+                $name {
+                    $($field: pins.$source,)*
+                }
+            }
+        }
+    };
+}
+
 macro_rules! pins {
     ($(
+        $(#[$attr:meta])*
         $field:ident,
         $type:ident,
         $port:expr,
@@ -237,29 +745,47 @@ macro_rules! pins {
         ///
         /// This struct is a part of [`swm::Parts`].
         ///
-        /// # Limitations
+        /// # Packages
         ///
-        /// This struct currently provides access to all pins that can be
-        /// available on an LPC8xx part. Please make sure that you are aware of
-        /// which pins are actually available on your specific part, and only
-        /// use those.
+        /// If a specific part has been selected using its Cargo feature (for
+        /// example `824m201jdh20`), this struct only provides access to the
+        /// pins that are available in that part's package. Using any other pin
+        /// will fail to compile.
+        ///
+        /// If only a family has been selected (`82x` or `845`), this struct
+        /// provides access to all pins that can be available in that family.
+        /// Please make sure that you are aware of which pins are actually
+        /// available on your specific part, and only use those.
         ///
         /// [`swm::Parts`]: struct.Parts.html
         #[allow(missing_docs)]
         pub struct Pins {
-            $(pub $field: Pin<$type, $default_state_ty>,)*
+            $(
+                $(#[$attr])*
+                pub $field: Pin<$type, $default_state_ty>,
+            )*
         }
 
         impl Pins {
             pub(crate) fn new() -> Self {
-                Pins {
+                let pins = Pins {
                     $(
+                        $(#[$attr])*
                         $field: Pin {
                             ty   : $type(()),
                             state: $default_state_val,
                         },
                     )*
-                }
+                };
+
+                let mut unused = [0; 2];
+                $(
+                    $(#[$attr])*
+                    pins.$field.add_if_unused(&mut unused);
+                )*
+                interrupt::free(|cs| UNUSED.borrow(cs).set(unused));
+
+                pins
             }
         }
 
@@ -278,6 +804,10 @@ macro_rules! pins {
                 const ID  : u8    = $id;
                 const MASK: u32   = 0x1 << $id;
             }
+
+            impl ResetState for $type {
+                type State = $default_state_ty;
+            }
         )*
     }
 }
@@ -302,16 +832,26 @@ pins!(
     pio0_15, PIO0_15, 0, 0x0f, pin_state::Unused        , pin_state::Unused;
     pio0_16, PIO0_16, 0, 0x10, pin_state::Unused        , pin_state::Unused;
     pio0_17, PIO0_17, 0, 0x11, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_18, PIO0_18, 0, 0x12, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_19, PIO0_19, 0, 0x13, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_20, PIO0_20, 0, 0x14, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_21, PIO0_21, 0, 0x15, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_22, PIO0_22, 0, 0x16, pin_state::Unused        , pin_state::Unused;
     pio0_23, PIO0_23, 0, 0x17, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_24, PIO0_24, 0, 0x18, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_25, PIO0_25, 0, 0x19, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_26, PIO0_26, 0, 0x1a, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_27, PIO0_27, 0, 0x1b, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "20"))]
     pio0_28, PIO0_28, 0, 0x1c, pin_state::Unused        , pin_state::Unused;
 );
 
@@ -346,30 +886,55 @@ pins!(
     pio0_26, PIO0_26, 0, 0x1a, pin_state::Unused        , pin_state::Unused;
     pio0_27, PIO0_27, 0, 0x1b, pin_state::Unused        , pin_state::Unused;
     pio0_28, PIO0_28, 0, 0x1c, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio0_29, PIO0_29, 0, 0x1d, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio0_30, PIO0_30, 0, 0x1e, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio0_31, PIO0_31, 0, 0x1f, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_0 , PIO1_0 , 1, 0x00, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_1 , PIO1_1 , 1, 0x01, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_2 , PIO1_2 , 1, 0x02, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_3 , PIO1_3 , 1, 0x03, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_4 , PIO1_4 , 1, 0x04, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_5 , PIO1_5 , 1, 0x05, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_6 , PIO1_6 , 1, 0x06, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_7 , PIO1_7 , 1, 0x07, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_8 , PIO1_8 , 1, 0x08, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(feature = "33"))]
     pio1_9 , PIO1_9 , 1, 0x09, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_10, PIO1_10, 1, 0x0a, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_11, PIO1_11, 1, 0x0b, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_12, PIO1_12, 1, 0x0c, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_13, PIO1_13, 1, 0x0d, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_14, PIO1_14, 1, 0x0e, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_15, PIO1_15, 1, 0x0f, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_16, PIO1_16, 1, 0x10, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_17, PIO1_17, 1, 0x11, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_18, PIO1_18, 1, 0x12, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_19, PIO1_19, 1, 0x13, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_20, PIO1_20, 1, 0x14, pin_state::Unused        , pin_state::Unused;
+    #[cfg(not(any(feature = "33", feature = "48")))]
     pio1_21, PIO1_21, 1, 0x15, pin_state::Unused        , pin_state::Unused;
 );
 
@@ -569,6 +1134,11 @@ impl<T> Pin<T, pin_state::Unused>
 where
     T: PinTrait,
 {
+    /// Used by `Pins::new`, to initialize the set of unused pins
+    fn add_if_unused(&self, unused: &mut [u32; 2]) {
+        unused[T::PORT] |= T::MASK;
+    }
+
     /// Transition pin to GPIO state
     ///
     /// This method is only available while the pin is in the unused state. Code
@@ -622,6 +1192,7 @@ where
             set: &gpio.gpio.set,
             clr: &gpio.gpio.clr,
         };
+        set_unused::<T>(false);
         Pin {
             ty: self.ty,
             state: pin_state::Gpio {
@@ -660,6 +1231,7 @@ where
     ///
     /// [State Management]: #state-management
     pub fn into_swm_pin(self) -> Pin<T, pin_state::Swm<(), ()>> {
+        set_unused::<T>(false);
         Pin {
             ty: self.ty,
             state: pin_state::Swm::new(),
@@ -667,6 +1239,14 @@ where
     }
 }
 
+impl<T, Outputs, Inputs> Pin<T, pin_state::Swm<Outputs, Inputs>>
+where
+    T: PinTrait,
+{
+    /// Used by `Pins::new`. Pins in the SWM state are not unused.
+    fn add_if_unused(&self, _: &mut [u32; 2]) {}
+}
+
 impl<T> Pin<T, pin_state::Swm<(), ()>>
 where
     T: PinTrait,
@@ -687,6 +1267,7 @@ where
     ///
     /// [State Management]: #state-management
     pub fn into_unused_pin(self) -> Pin<T, pin_state::Unused> {
+        set_unused::<T>(true);
         Pin {
             ty: self.ty,
             state: pin_state::Unused,
@@ -944,6 +1525,74 @@ impl<T> Function<T, state::Unassigned> {
     }
 }
 
+impl<T, State> Function<T, State>
+where
+    T: MovableFunctionTrait,
+{
+    /// Return the pin this function is currently assigned to, if any
+    ///
+    /// Reads the function's PINASSIGN register field, so the result reflects
+    /// the actual state of the hardware, regardless of this `Function`'s type
+    /// state.
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{swm::PinId, Peripherals};
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let swm = p.SWM.split();
+    /// let mut syscon = p.SYSCON.split();
+    ///
+    /// #[cfg(feature = "82x")]
+    /// let mut swm_handle = swm.handle;
+    /// #[cfg(feature = "845")]
+    /// let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+    ///
+    /// let u0_txd = swm.movable_functions.u0_txd;
+    /// assert_eq!(u0_txd.assigned_pin(&swm_handle), None);
+    ///
+    /// let (u0_txd, _) = u0_txd
+    ///     .assign(swm.pins.pio0_4.into_swm_pin(), &mut swm_handle);
+    /// assert_eq!(
+    ///     u0_txd.assigned_pin(&swm_handle),
+    ///     Some(PinId { port: 0, number: 4 }),
+    /// );
+    /// ```
+    pub fn assigned_pin(&self, swm: &Handle) -> Option<PinId> {
+        PinId::from_bits(T::read(&swm.swm))
+    }
+
+    /// Indicates whether this function is currently assigned to a pin
+    ///
+    /// Like [`assigned_pin`], this reads the hardware.
+    ///
+    /// [`assigned_pin`]: #method.assigned_pin
+    pub fn is_assigned(&self, swm: &Handle) -> bool {
+        self.assigned_pin(swm).is_some()
+    }
+
+    /// Return the name of this function, as used in the user manual
+    pub fn name(&self) -> &'static str {
+        T::NAME
+    }
+}
+
+impl<T, P> Function<T, state::Assigned<P>>
+where
+    P: PinTrait,
+{
+    /// Return the pin this function has been assigned to
+    ///
+    /// Unlike [`assigned_pin`], this doesn't read the hardware, but is derived
+    /// from this `Function`'s type state. It is also available for fixed
+    /// functions.
+    ///
+    /// [`assigned_pin`]: #method.assigned_pin
+    pub fn pin(&self) -> PinId {
+        PinId::of::<P>()
+    }
+}
+
 impl<T, P> Function<T, state::Assigned<P>> {
     /// Unassign this function from a pin
     ///
@@ -1014,6 +1663,126 @@ impl<T, P> Function<T, state::Assigned<P>> {
 
         (function, pin.unassign())
     }
+
+    /// Temporarily switch the pin this function is assigned to to GPIO
+    ///
+    /// This method is only available, if this function is the only function
+    /// assigned to the pin. Code attempting to call this method otherwise will
+    /// not compile.
+    ///
+    /// Unassigns this function from the pin and transitions the pin into the
+    /// GPIO state. This can be used to briefly take control of a pin that is
+    /// otherwise used by a peripheral, for example to bit-bang a break
+    /// condition on a USART TX line, or to read the level of a bootstrap pin.
+    ///
+    /// Consumes this `Function` and the [`Pin`], and returns the pin in the
+    /// GPIO state, as well as a [`SwitchedToGpio`] token. The token is the only
+    /// way to get this `Function` back. Pass it and the pin to
+    /// [`SwitchedToGpio::switch_back`], to restore the original configuration.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc82x_hal::prelude::*;
+    /// use lpc82x_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut swm = p.SWM.split();
+    ///
+    /// let (u0_txd, pio0_0) = swm.movable_functions.u0_txd.assign(
+    ///     swm.pins.pio0_0.into_swm_pin(),
+    ///     &mut swm.handle,
+    /// );
+    ///
+    /// // Take control of the pin and drive it low
+    /// let (u0_txd, pio0_0) =
+    ///     u0_txd.switch_to_gpio(pio0_0, &mut swm.handle, &p.GPIO);
+    /// let mut pio0_0 = pio0_0.into_output();
+    /// pio0_0.set_low();
+    ///
+    /// // Hand the pin back to U0_TXD
+    /// let (u0_txd, pio0_0) = u0_txd.switch_back(pio0_0, &mut swm.handle);
+    /// ```
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`SwitchedToGpio`]: struct.SwitchedToGpio.html
+    /// [`SwitchedToGpio::switch_back`]: struct.SwitchedToGpio.html#method.switch_back
+    pub fn switch_to_gpio<'gpio, S>(
+        self,
+        pin: Pin<P, S>,
+        swm: &mut Handle,
+        gpio: &'gpio GPIO,
+    ) -> (
+        SwitchedToGpio<T, P, S>,
+        Pin<P, pin_state::Gpio<'gpio, gpio::direction::Unknown>>,
+    )
+    where
+        T: FunctionTrait<P>,
+        P: PinTrait,
+        S: PinState,
+        Pin<P, S>: UnassignFunction<
+            T,
+            T::Kind,
+            Unassigned = Pin<P, pin_state::Swm<(), ()>>,
+        >,
+    {
+        let (function, pin) = self.unassign(pin, swm);
+        let pin = pin.into_unused_pin().into_gpio_pin(gpio);
+
+        let switched = SwitchedToGpio {
+            function,
+            _pin: PhantomData,
+        };
+
+        (switched, pin)
+    }
+}
+
+/// A function whose pin has been temporarily switched to GPIO
+///
+/// Returned by [`Function::switch_to_gpio`]. Holds on to the unassigned
+/// function and remembers the state the pin was in, so it can only be assigned
+/// back to the same pin, restoring the original configuration.
+///
+/// [`Function::switch_to_gpio`]: struct.Function.html#method.switch_to_gpio
+pub struct SwitchedToGpio<T, P, S> {
+    function: Function<T, state::Unassigned>,
+    _pin: PhantomData<(P, S)>,
+}
+
+impl<T, P, S> SwitchedToGpio<T, P, S> {
+    /// Switch the pin back from GPIO to its original function
+    ///
+    /// Sets the pin's direction back to input, so it doesn't interfere with
+    /// the function, then reassigns the function to the pin. Returns the
+    /// function and the pin in the same states that were passed to
+    /// [`Function::switch_to_gpio`].
+    ///
+    /// [`Function::switch_to_gpio`]: struct.Function.html#method.switch_to_gpio
+    pub fn switch_back<'gpio, D>(
+        self,
+        pin: Pin<P, pin_state::Gpio<'gpio, D>>,
+        swm: &mut Handle,
+    ) -> (Function<T, state::Assigned<P>>, Pin<P, S>)
+    where
+        T: FunctionTrait<P>,
+        P: PinTrait,
+        S: PinState,
+        D: gpio::direction::Direction,
+        Pin<P, pin_state::Swm<(), ()>>:
+            AssignFunction<T, T::Kind, Assigned = Pin<P, S>>,
+    {
+        pin.state.registers.dirclr[P::PORT]
+            .write(|w| unsafe { w.dirclrp().bits(P::MASK) });
+
+        let pin = Pin {
+            ty: pin.ty,
+            state: pin_state::Swm::new(),
+        };
+
+        self.function.assign(pin, swm)
+    }
 }
 
 /// Implemented for all fixed and movable functions
@@ -1038,6 +1807,46 @@ pub trait FunctionTrait<P: PinTrait> {
     fn unassign(&mut self, pin: &mut P, swm: &mut Handle);
 }
 
+/// Implemented for all movable functions
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+///
+/// Please refer to [`Function::assigned_pin`] for the public API that uses this
+/// trait.
+pub trait MovableFunctionTrait {
+    /// The name of the function, as used in the user manual
+    const NAME: &'static str;
+
+    /// Internal method to read the function's PINASSIGN register field
+    fn read(swm: &pac::swm0::RegisterBlock) -> u8;
+}
+
+/// A movable function assignment, as read from the hardware
+///
+/// Formats as the function's name, followed by the pin, for example
+/// `U0_TXD: PIO0_4`.
+///
+/// Returned by [`Handle::assignments`].
+///
+/// [`Handle::assignments`]: struct.Handle.html#method.assignments
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Assignment {
+    /// The name of the function, as used in the user manual
+    pub function: &'static str,
+
+    /// The pin the function is assigned to
+    pub pin: PinId,
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.function, self.pin)
+    }
+}
+
 /// Implemented for types that designate whether a function is input or output
 ///
 /// This trait is an internal implementation detail and should neither be
@@ -1109,6 +1918,17 @@ macro_rules! movable_functions {
             $(pub $field: Function<$type, state::Unassigned>,)*
         }
 
+        /// The name and PINASSIGN reader of every movable function
+        const MOVABLE_FUNCTIONS: &[(
+            &str,
+            fn(&pac::swm0::RegisterBlock) -> u8,
+        )] = &[
+            $((
+                $type::NAME,
+                $type::read as fn(&pac::swm0::RegisterBlock) -> u8,
+            ),)*
+        ];
+
         impl MovableFunctions {
             fn new() -> Self {
                 MovableFunctions {
@@ -1131,6 +1951,14 @@ macro_rules! movable_functions {
             #[allow(non_camel_case_types)]
             pub struct $type(());
 
+            impl MovableFunctionTrait for $type {
+                const NAME: &'static str = stringify!($type);
+
+                fn read(swm: &pac::swm0::RegisterBlock) -> u8 {
+                    swm.$reg_name.read().$reg_field().bits()
+                }
+            }
+
             impl_function!($type, $kind, $reg_name, $reg_field, PIO0_0 );
             impl_function!($type, $kind, $reg_name, $reg_field, PIO0_1 );
             impl_function!($type, $kind, $reg_name, $reg_field, PIO0_2 );
@@ -1326,17 +2154,19 @@ movable_functions!(
     t0_mat1      , T0_MAT1      , Output, pinassign13, t0_mat1;
     t0_mat2      , T0_MAT2      , Output, pinassign13, t0_mat2;
     t0_mat3      , T0_MAT3      , Output, pinassign14, t0_mat3;
-    t0_cap0      , T0_CAP0      , Output, pinassign14, t0_cap0;
-    t0_cap1      , T0_CAP1      , Output, pinassign14, t0_cap1;
-    t0_cap2      , T0_CAP2      , Output, pinassign14, t0_cap2;
+    t0_cap0      , T0_CAP0      , Input , pinassign14, t0_cap0;
+    t0_cap1      , T0_CAP1      , Input , pinassign14, t0_cap1;
+    t0_cap2      , T0_CAP2      , Input , pinassign14, t0_cap2;
 );
 
+// `$bit` is the position of the function's field in its PINENABLE register.
 macro_rules! fixed_functions {
     ($(
         $type:ident,
         $kind:ident,
         $register:ident,
         $field:ident,
+        $bit:expr,
         $pin:ident,
         $default_state:ty;
     )*) => {
@@ -1377,12 +2207,12 @@ macro_rules! fixed_functions {
 
 
                 fn assign(&mut self, _: &mut $pin, swm : &mut Handle) {
-                    swm.swm.$register.modify(|_, w| w.$field().clear_bit());
+                    swm.$register($bit, false);
                 }
 
                 fn unassign(&mut self, _: &mut $pin, swm : &mut Handle)
                 {
-                    swm.swm.$register.modify(|_, w| w.$field().set_bit());
+                    swm.$register($bit, true);
                 }
             }
         )*
@@ -1391,73 +2221,74 @@ macro_rules! fixed_functions {
 
 #[cfg(feature = "82x")]
 fixed_functions!(
-    ACMP_I1 , Input , pinenable0, acmp_i1 , PIO0_0 , state::Unassigned;
-    ACMP_I2 , Input , pinenable0, acmp_i2 , PIO0_1 , state::Unassigned;
-    ACMP_I3 , Input , pinenable0, acmp_i3 , PIO0_14, state::Unassigned;
-    ACMP_I4 , Input , pinenable0, acmp_i4 , PIO0_23, state::Unassigned;
-    SWCLK   , Output, pinenable0, swclk   , PIO0_3 , state::Assigned<PIO0_3>;
-    SWDIO   , Output, pinenable0, swdio   , PIO0_2 , state::Assigned<PIO0_2>;
-    XTALIN  , Input , pinenable0, xtalin  , PIO0_8 , state::Unassigned;
-    XTALOUT , Output, pinenable0, xtalout , PIO0_9 , state::Unassigned;
-    RESETN  , Input , pinenable0, resetn  , PIO0_5 , state::Assigned<PIO0_5>;
-    CLKIN   , Input , pinenable0, clkin   , PIO0_1 , state::Unassigned;
-    VDDCMP  , Input , pinenable0, vddcmp  , PIO0_6 , state::Unassigned;
-    I2C0_SDA, Output, pinenable0, i2c0_sda, PIO0_11, state::Unassigned;
-    I2C0_SCL, Output, pinenable0, i2c0_scl, PIO0_10, state::Unassigned;
-    ADC_0   , Analog, pinenable0, adc_0   , PIO0_7 , state::Unassigned;
-    ADC_1   , Analog, pinenable0, adc_1   , PIO0_6 , state::Unassigned;
-    ADC_2   , Analog, pinenable0, adc_2   , PIO0_14, state::Unassigned;
-    ADC_3   , Analog, pinenable0, adc_3   , PIO0_23, state::Unassigned;
-    ADC_4   , Analog, pinenable0, adc_4   , PIO0_22, state::Unassigned;
-    ADC_5   , Analog, pinenable0, adc_5   , PIO0_21, state::Unassigned;
-    ADC_6   , Analog, pinenable0, adc_6   , PIO0_20, state::Unassigned;
-    ADC_7   , Analog, pinenable0, adc_7   , PIO0_19, state::Unassigned;
-    ADC_8   , Analog, pinenable0, adc_8   , PIO0_18, state::Unassigned;
-    ADC_9   , Analog, pinenable0, adc_9   , PIO0_17, state::Unassigned;
-    ADC_10  , Analog, pinenable0, adc_10  , PIO0_13, state::Unassigned;
-    ADC_11  , Analog, pinenable0, adc_11  , PIO0_4 , state::Unassigned;
+    ACMP_I1 , Input , pinenable0, acmp_i1 , 0 , PIO0_0 , state::Unassigned;
+    ACMP_I2 , Input , pinenable0, acmp_i2 , 1 , PIO0_1 , state::Unassigned;
+    ACMP_I3 , Input , pinenable0, acmp_i3 , 2 , PIO0_14, state::Unassigned;
+    ACMP_I4 , Input , pinenable0, acmp_i4 , 3 , PIO0_23, state::Unassigned;
+    SWCLK   , Output, pinenable0, swclk   , 4 , PIO0_3 , state::Assigned<PIO0_3>;
+    SWDIO   , Output, pinenable0, swdio   , 5 , PIO0_2 , state::Assigned<PIO0_2>;
+    XTALIN  , Input , pinenable0, xtalin  , 6 , PIO0_8 , state::Unassigned;
+    XTALOUT , Output, pinenable0, xtalout , 7 , PIO0_9 , state::Unassigned;
+    RESETN  , Input , pinenable0, resetn  , 8 , PIO0_5 , state::Assigned<PIO0_5>;
+    CLKIN   , Input , pinenable0, clkin   , 9 , PIO0_1 , state::Unassigned;
+    VDDCMP  , Input , pinenable0, vddcmp  , 10, PIO0_6 , state::Unassigned;
+    I2C0_SDA, Output, pinenable0, i2c0_sda, 11, PIO0_11, state::Unassigned;
+    I2C0_SCL, Output, pinenable0, i2c0_scl, 12, PIO0_10, state::Unassigned;
+    ADC_0   , Analog, pinenable0, adc_0   , 13, PIO0_7 , state::Unassigned;
+    ADC_1   , Analog, pinenable0, adc_1   , 14, PIO0_6 , state::Unassigned;
+    ADC_2   , Analog, pinenable0, adc_2   , 15, PIO0_14, state::Unassigned;
+    ADC_3   , Analog, pinenable0, adc_3   , 16, PIO0_23, state::Unassigned;
+    ADC_4   , Analog, pinenable0, adc_4   , 17, PIO0_22, state::Unassigned;
+    ADC_5   , Analog, pinenable0, adc_5   , 18, PIO0_21, state::Unassigned;
+    ADC_6   , Analog, pinenable0, adc_6   , 19, PIO0_20, state::Unassigned;
+    ADC_7   , Analog, pinenable0, adc_7   , 20, PIO0_19, state::Unassigned;
+    ADC_8   , Analog, pinenable0, adc_8   , 21, PIO0_18, state::Unassigned;
+    ADC_9   , Analog, pinenable0, adc_9   , 22, PIO0_17, state::Unassigned;
+    ADC_10  , Analog, pinenable0, adc_10  , 23, PIO0_13, state::Unassigned;
+    ADC_11  , Analog, pinenable0, adc_11  , 24, PIO0_4 , state::Unassigned;
 );
 
+// Bit 4 of PINENABLE0 belongs to ACMP_I5, which is not provided yet.
 #[cfg(feature = "845")]
 fixed_functions!(
-    ACMP_I1 , Input , pinenable0, acmp_i1 , PIO0_0 , state::Unassigned;
-    ACMP_I2 , Input , pinenable0, acmp_i2 , PIO0_1 , state::Unassigned;
-    ACMP_I3 , Input , pinenable0, acmp_i3 , PIO0_14, state::Unassigned;
-    ACMP_I4 , Input , pinenable0, acmp_i4 , PIO0_23, state::Unassigned;
-    SWCLK   , Output, pinenable0, swclk   , PIO0_3 , state::Assigned<PIO0_3>;
-    SWDIO   , Output, pinenable0, swdio   , PIO0_2 , state::Assigned<PIO0_2>;
-    XTALIN  , Input , pinenable0, xtalin  , PIO0_8 , state::Unassigned;
-    XTALOUT , Output, pinenable0, xtalout , PIO0_9 , state::Unassigned;
-    RESETN  , Input , pinenable0, resetn  , PIO0_5 , state::Assigned<PIO0_5>;
-    CLKIN   , Input , pinenable0, clkin   , PIO0_1 , state::Unassigned;
-    VDDCMP  , Input , pinenable0, vddcmp  , PIO0_6 , state::Unassigned;
-    I2C0_SDA, Output, pinenable0, i2c0_sda, PIO0_11, state::Unassigned;
-    I2C0_SCL, Output, pinenable0, i2c0_scl, PIO0_10, state::Unassigned;
-    ADC_0   , Analog, pinenable0, adc_0   , PIO0_7 , state::Unassigned;
-    ADC_1   , Analog, pinenable0, adc_1   , PIO0_6 , state::Unassigned;
-    ADC_2   , Analog, pinenable0, adc_2   , PIO0_14, state::Unassigned;
-    ADC_3   , Analog, pinenable0, adc_3   , PIO0_23, state::Unassigned;
-    ADC_4   , Analog, pinenable0, adc_4   , PIO0_22, state::Unassigned;
-    ADC_5   , Analog, pinenable0, adc_5   , PIO0_21, state::Unassigned;
-    ADC_6   , Analog, pinenable0, adc_6   , PIO0_20, state::Unassigned;
-    ADC_7   , Analog, pinenable0, adc_7   , PIO0_19, state::Unassigned;
-    ADC_8   , Analog, pinenable0, adc_8   , PIO0_18, state::Unassigned;
-    ADC_9   , Analog, pinenable0, adc_9   , PIO0_17, state::Unassigned;
-    ADC_10  , Analog, pinenable0, adc_10  , PIO0_13, state::Unassigned;
-    ADC_11  , Analog, pinenable0, adc_11  , PIO0_4 , state::Unassigned;
-    DACOUT0 , Analog, pinenable0, dacout0 , PIO0_17, state::Unassigned;
-    DACOUT1 , Analog, pinenable0, dacout1 , PIO0_29, state::Unassigned;
-    CAPT_X0 , Analog, pinenable0, capt_x0 , PIO0_31, state::Unassigned;
-    CAPT_X1 , Analog, pinenable0, capt_x1 , PIO1_0 , state::Unassigned;
-    CAPT_X2 , Analog, pinenable0, capt_x2 , PIO1_1 , state::Unassigned;
-    CAPT_X3 , Analog, pinenable0, capt_x3 , PIO1_2 , state::Unassigned;
-    CAPT_X4 , Analog, pinenable1, capt_x4 , PIO1_3 , state::Unassigned;
-    CAPT_X5 , Analog, pinenable1, capt_x5 , PIO1_4 , state::Unassigned;
-    CAPT_X6 , Analog, pinenable1, capt_x6 , PIO1_5 , state::Unassigned;
-    CAPT_X7 , Analog, pinenable1, capt_x7 , PIO1_6 , state::Unassigned;
-    CAPT_X8 , Analog, pinenable1, capt_x8 , PIO1_7 , state::Unassigned;
-    CAPT_YL , Analog, pinenable1, capt_yl , PIO1_8 , state::Unassigned;
-    CAPT_YH , Analog, pinenable1, capt_yh , PIO1_8 , state::Unassigned;
+    ACMP_I1 , Input , pinenable0, acmp_i1 , 0 , PIO0_0 , state::Unassigned;
+    ACMP_I2 , Input , pinenable0, acmp_i2 , 1 , PIO0_1 , state::Unassigned;
+    ACMP_I3 , Input , pinenable0, acmp_i3 , 2 , PIO0_14, state::Unassigned;
+    ACMP_I4 , Input , pinenable0, acmp_i4 , 3 , PIO0_23, state::Unassigned;
+    SWCLK   , Output, pinenable0, swclk   , 5 , PIO0_3 , state::Assigned<PIO0_3>;
+    SWDIO   , Output, pinenable0, swdio   , 6 , PIO0_2 , state::Assigned<PIO0_2>;
+    XTALIN  , Input , pinenable0, xtalin  , 7 , PIO0_8 , state::Unassigned;
+    XTALOUT , Output, pinenable0, xtalout , 8 , PIO0_9 , state::Unassigned;
+    RESETN  , Input , pinenable0, resetn  , 9 , PIO0_5 , state::Assigned<PIO0_5>;
+    CLKIN   , Input , pinenable0, clkin   , 10, PIO0_1 , state::Unassigned;
+    VDDCMP  , Input , pinenable0, vddcmp  , 11, PIO0_6 , state::Unassigned;
+    I2C0_SDA, Output, pinenable0, i2c0_sda, 12, PIO0_11, state::Unassigned;
+    I2C0_SCL, Output, pinenable0, i2c0_scl, 13, PIO0_10, state::Unassigned;
+    ADC_0   , Analog, pinenable0, adc_0   , 14, PIO0_7 , state::Unassigned;
+    ADC_1   , Analog, pinenable0, adc_1   , 15, PIO0_6 , state::Unassigned;
+    ADC_2   , Analog, pinenable0, adc_2   , 16, PIO0_14, state::Unassigned;
+    ADC_3   , Analog, pinenable0, adc_3   , 17, PIO0_23, state::Unassigned;
+    ADC_4   , Analog, pinenable0, adc_4   , 18, PIO0_22, state::Unassigned;
+    ADC_5   , Analog, pinenable0, adc_5   , 19, PIO0_21, state::Unassigned;
+    ADC_6   , Analog, pinenable0, adc_6   , 20, PIO0_20, state::Unassigned;
+    ADC_7   , Analog, pinenable0, adc_7   , 21, PIO0_19, state::Unassigned;
+    ADC_8   , Analog, pinenable0, adc_8   , 22, PIO0_18, state::Unassigned;
+    ADC_9   , Analog, pinenable0, adc_9   , 23, PIO0_17, state::Unassigned;
+    ADC_10  , Analog, pinenable0, adc_10  , 24, PIO0_13, state::Unassigned;
+    ADC_11  , Analog, pinenable0, adc_11  , 25, PIO0_4 , state::Unassigned;
+    DACOUT0 , Analog, pinenable0, dacout0 , 26, PIO0_17, state::Unassigned;
+    DACOUT1 , Analog, pinenable0, dacout1 , 27, PIO0_29, state::Unassigned;
+    CAPT_X0 , Analog, pinenable0, capt_x0 , 28, PIO0_31, state::Unassigned;
+    CAPT_X1 , Analog, pinenable0, capt_x1 , 29, PIO1_0 , state::Unassigned;
+    CAPT_X2 , Analog, pinenable0, capt_x2 , 30, PIO1_1 , state::Unassigned;
+    CAPT_X3 , Analog, pinenable0, capt_x3 , 31, PIO1_2 , state::Unassigned;
+    CAPT_X4 , Analog, pinenable1, capt_x4 , 0 , PIO1_3 , state::Unassigned;
+    CAPT_X5 , Analog, pinenable1, capt_x5 , 1 , PIO1_4 , state::Unassigned;
+    CAPT_X6 , Analog, pinenable1, capt_x6 , 2 , PIO1_5 , state::Unassigned;
+    CAPT_X7 , Analog, pinenable1, capt_x7 , 3 , PIO1_6 , state::Unassigned;
+    CAPT_X8 , Analog, pinenable1, capt_x8 , 4 , PIO1_7 , state::Unassigned;
+    CAPT_YL , Analog, pinenable1, capt_yl , 5 , PIO1_8 , state::Unassigned;
+    CAPT_YH , Analog, pinenable1, capt_yh , 6 , PIO1_8 , state::Unassigned;
 );
 
 /// Contains types that indicate the state of fixed or movable functions
@@ -0,0 +1,176 @@
+//! Primitives for sharing data with interrupt handlers
+//!
+//! The Cortex-M0+ doesn't support atomic read-modify-write operations. Atomic
+//! types can be loaded and stored, but operations like `swap`, `fetch_add`, or
+//! `compare_exchange` are not available on `thumbv6m-none-eabi`. Code that
+//! relies on them, like many lock-free queues, doesn't compile for the
+//! LPC800 series.
+//!
+//! The primitives in this module use short critical sections instead, in which
+//! interrupts are disabled. This is how all state that is shared with
+//! interrupt handlers is protected within this crate, and these primitives are
+//! meant to be used by interrupt-driven code, for example to pass received
+//! data or completion notifications from an interrupt handler to the main
+//! loop.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::sync::{Flag, Queue};
+//!
+//! static RECEIVED: Queue<u8, 32> = Queue::new();
+//! static OVERFLOW: Flag = Flag::new();
+//!
+//! // In the interrupt handler:
+//! if RECEIVED.enqueue(0x2a).is_err() {
+//!     OVERFLOW.set();
+//! }
+//!
+//! // In the main loop:
+//! while let Some(b) = RECEIVED.dequeue() {
+//!     // Process `b`
+//! }
+//! if OVERFLOW.take() {
+//!     // Data has been lost
+//! }
+//! ```
+
+use core::{
+    cell::{Cell, RefCell},
+    mem::MaybeUninit,
+    ptr,
+};
+
+use cortex_m::interrupt::{self, Mutex};
+
+/// A flag that can be set by one context and taken by another
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Flag {
+    inner: Mutex<Cell<bool>>,
+}
+
+impl Flag {
+    /// Create a flag that is not set
+    pub const fn new() -> Self {
+        Flag {
+            inner: Mutex::new(Cell::new(false)),
+        }
+    }
+
+    /// Set the flag
+    pub fn set(&self) {
+        interrupt::free(|cs| self.inner.borrow(cs).set(true));
+    }
+
+    /// Clear the flag and return whether it was set
+    pub fn take(&self) -> bool {
+        interrupt::free(|cs| self.inner.borrow(cs).replace(false))
+    }
+
+    /// Return whether the flag is set, without clearing it
+    pub fn is_set(&self) -> bool {
+        interrupt::free(|cs| self.inner.borrow(cs).get())
+    }
+}
+
+/// A first-in, first-out queue with room for `N` items
+///
+/// Any number of contexts can enqueue and dequeue items. Each operation
+/// happens in a critical section, which is only held for the duration of a
+/// copy of one item.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Queue<T, const N: usize> {
+    inner: Mutex<RefCell<Ring<T, N>>>,
+}
+
+impl<T, const N: usize> Queue<T, N> {
+    /// Create an empty queue
+    pub const fn new() -> Self {
+        Queue {
+            inner: Mutex::new(RefCell::new(Ring {
+                buffer: MaybeUninit::uninit(),
+                first: 0,
+                len: 0,
+            })),
+        }
+    }
+
+    /// Add an item to the back of the queue
+    ///
+    /// Returns the item, if the queue is full.
+    pub fn enqueue(&self, item: T) -> Result<(), T> {
+        interrupt::free(|cs| self.inner.borrow(cs).borrow_mut().push(item))
+    }
+
+    /// Remove the item at the front of the queue
+    ///
+    /// Returns `None`, if the queue is empty.
+    pub fn dequeue(&self) -> Option<T> {
+        interrupt::free(|cs| self.inner.borrow(cs).borrow_mut().pop())
+    }
+
+    /// Return the number of items in the queue
+    pub fn len(&self) -> usize {
+        interrupt::free(|cs| self.inner.borrow(cs).borrow().len)
+    }
+
+    /// Indicate whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+struct Ring<T, const N: usize> {
+    // The items are used as a ring. Starting with `first`, `len` items are
+    // initialized.
+    buffer: MaybeUninit<[T; N]>,
+    first: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    fn push(&mut self, item: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(item);
+        }
+
+        let i = (self.first + self.len) % N;
+        // Sound, as `i` is in bounds, and the slot isn't initialized, so
+        // nothing is leaked by overwriting it.
+        unsafe { ptr::write(self.slot(i), item) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // Sound, as `first` is in bounds and initialized. The slot is treated
+        // as uninitialized afterwards.
+        let item = unsafe { ptr::read(self.slot(self.first)) };
+        self.first = (self.first + 1) % N;
+        self.len -= 1;
+
+        Some(item)
+    }
+
+    fn slot(&mut self, i: usize) -> *mut T {
+        // Sound, as all callers pass an index that is smaller than `N`.
+        unsafe { (self.buffer.as_mut_ptr() as *mut T).add(i) }
+    }
+}
+
+impl<T, const N: usize> Drop for Ring<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
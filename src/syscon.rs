@@ -11,9 +11,11 @@
 
 #[cfg(feature = "845")]
 pub mod frg;
+pub mod main_clock;
 
 #[cfg(feature = "845")]
 pub use self::frg::FRG;
+pub use self::main_clock::MainClock;
 
 #[cfg(feature = "82x")]
 /// Clock configuration for peripherals
@@ -26,24 +28,94 @@ pub mod clocksource_845;
 #[cfg(feature = "845")]
 pub use clocksource_845 as clocksource;
 
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 #[cfg(feature = "82x")]
 use crate::pac::syscon::{
-    pdruncfg, presetctrl as presetctrl0, starterp1,
-    sysahbclkctrl as sysahbclkctrl0, PDRUNCFG, PRESETCTRL as PRESETCTRL0,
-    STARTERP1, SYSAHBCLKCTRL as SYSAHBCLKCTRL0, UARTCLKDIV, UARTFRGDIV,
-    UARTFRGMULT,
+    pdruncfg, pdsleepcfg, presetctrl as presetctrl0, starterp1,
+    sysahbclkctrl as sysahbclkctrl0, IRCCTRL, PDRUNCFG, PDSLEEPCFG, PINTSEL,
+    PRESETCTRL as PRESETCTRL0, STARTERP0, STARTERP1,
+    SYSAHBCLKCTRL as SYSAHBCLKCTRL0, SYSAHBCLKDIV, SYSMEMREMAP, SYSRSTSTAT,
+    UARTCLKDIV, UARTFRGDIV, UARTFRGMULT,
 };
 
 #[cfg(feature = "845")]
 use crate::pac::syscon::{
-    pdruncfg, presetctrl0, starterp1, sysahbclkctrl0, FCLKSEL, PDRUNCFG,
-    PRESETCTRL0, STARTERP1, SYSAHBCLKCTRL0,
+    pdruncfg, pdsleepcfg, presetctrl0, starterp1, sysahbclkctrl0, ADCCLKDIV,
+    ADCCLKSEL, FCLKSEL, PDRUNCFG, PDSLEEPCFG, PINTSEL, PRESETCTRL0, STARTERP0,
+    STARTERP1, SYSAHBCLKCTRL0, SYSAHBCLKDIV, SYSMEMREMAP, SYSRSTSTAT,
 };
 
 use crate::{clock, init_state, pac, reg_proxy::RegProxy};
 
+/// The frequency of the IRC (LPC82x) or FRO (LPC845), in Hz
+///
+/// The main clock and the system clock run from this oscillator after reset.
+pub(crate) const IOSC_HZ: u32 = 12_000_000;
+
+// The current frequencies of the main clock and the system clock. Only
+// written with a `&mut Handle`, so plain loads and stores are enough.
+static MAIN_CLOCK_HZ: AtomicU32 = AtomicU32::new(IOSC_HZ);
+static SYSTEM_CLOCK_HZ: AtomicU32 = AtomicU32::new(IOSC_HZ);
+
+/// Return the frequency of the main clock, in Hz
+///
+/// This is 12 MHz after reset, and is updated by [`MainClock::set`]. On the
+/// LPC82x, the USART fractional baud rate generator runs from the main clock
+/// (see [`UARTFRG`]).
+///
+/// [`MainClock::set`]: main_clock/struct.MainClock.html#method.set
+/// [`UARTFRG`]: struct.UARTFRG.html
+pub fn main_clock_hz() -> u32 {
+    MAIN_CLOCK_HZ.load(Ordering::Relaxed)
+}
+
+/// Return the frequency of the system clock, in Hz
+///
+/// The system clock runs the core and the memories, as well as peripherals
+/// like the MRT and the SCT. All parts of the HAL that convert between time
+/// and system clock ticks use this frequency, like the microsecond-based
+/// methods of [`MrtChannel`], or the IAP functions.
+///
+/// This is 12 MHz after reset, and is updated by [`MainClock::set`] and
+/// [`Handle::set_system_clock_divider`].
+///
+/// [`MrtChannel`]: ../mrt/struct.MrtChannel.html
+/// [`MainClock::set`]: main_clock/struct.MainClock.html#method.set
+/// [`Handle::set_system_clock_divider`]: struct.Handle.html#method.set_system_clock_divider
+pub fn system_clock_hz() -> u32 {
+    SYSTEM_CLOCK_HZ.load(Ordering::Relaxed)
+}
+
+/// Record the main clock frequency and system clock divider
+fn set_clock_hz(main_hz: u32, div: u8) {
+    MAIN_CLOCK_HZ.store(main_hz, Ordering::Relaxed);
+    SYSTEM_CLOCK_HZ.store(main_hz / div as u32, Ordering::Relaxed);
+}
+
+/// Convert microseconds into system clock ticks
+///
+/// Returns `None`, if the result doesn't fit into a `u32`.
+pub(crate) fn us_to_ticks(us: u32) -> Option<u32> {
+    let ticks = us as u64 * system_clock_hz() as u64 / 1_000_000;
+
+    if ticks > u32::MAX as u64 {
+        return None;
+    }
+
+    Some(ticks as u32)
+}
+
+/// Convert system clock ticks into microseconds, rounding down
+pub(crate) fn ticks_to_us(ticks: u64) -> u64 {
+    // Split up, so the multiplication can't overflow.
+    let hz = system_clock_hz() as u64;
+    ticks / hz * 1_000_000 + ticks % hz * 1_000_000 / hz
+}
+
 /// Entry point to the SYSCON API
 ///
 /// The SYSCON API is split into multiple parts, which are all available through
@@ -79,15 +151,29 @@ impl SYSCON {
         Parts {
             handle: Handle {
                 pdruncfg: RegProxy::new(),
+                pdsleepcfg: RegProxy::new(),
                 presetctrl0: RegProxy::new(),
+                starterp0: RegProxy::new(),
                 starterp1: RegProxy::new(),
                 sysahbclkctrl: RegProxy::new(),
+                sysahbclkdiv: RegProxy::new(),
                 #[cfg(feature = "845")]
                 fclksel: RegProxy::new(),
+                #[cfg(feature = "845")]
+                adcclksel: RegProxy::new(),
+                #[cfg(feature = "845")]
+                adcclkdiv: RegProxy::new(),
+                pintsel: RegProxy::new(),
+                #[cfg(feature = "82x")]
+                ircctrl: RegProxy::new(),
+                sysrststat: RegProxy::new(),
+                sysmemremap: RegProxy::new(),
+                deep_sleep_users: [0; 2],
             },
 
             bod: BOD(PhantomData),
             flash: FLASH(PhantomData),
+            main_clock: MainClock::new(),
             iosc: IOSC(PhantomData),
             ioscout: IOSCOUT(PhantomData),
             mtb: MTB(PhantomData),
@@ -144,6 +230,9 @@ pub struct Parts {
     /// Flash memory
     pub flash: FLASH,
 
+    /// The main clock
+    pub main_clock: MainClock,
+
     /// IRC/FRO
     pub iosc: IOSC,
 
@@ -193,11 +282,26 @@ pub struct Parts {
 /// [module documentation]: index.html
 pub struct Handle {
     pdruncfg: RegProxy<PDRUNCFG>,
+    pdsleepcfg: RegProxy<PDSLEEPCFG>,
     presetctrl0: RegProxy<PRESETCTRL0>,
+    starterp0: RegProxy<STARTERP0>,
     starterp1: RegProxy<STARTERP1>,
     sysahbclkctrl: RegProxy<SYSAHBCLKCTRL0>,
+    sysahbclkdiv: RegProxy<SYSAHBCLKDIV>,
     #[cfg(feature = "845")]
     pub(crate) fclksel: RegProxy<FCLKSEL>,
+    #[cfg(feature = "845")]
+    pub(crate) adcclksel: RegProxy<ADCCLKSEL>,
+    #[cfg(feature = "845")]
+    pub(crate) adcclkdiv: RegProxy<ADCCLKDIV>,
+    pub(crate) pintsel: RegProxy<PINTSEL>,
+    #[cfg(feature = "82x")]
+    ircctrl: RegProxy<IRCCTRL>,
+    sysrststat: RegProxy<SYSRSTSTAT>,
+    sysmemremap: RegProxy<SYSMEMREMAP>,
+
+    // The number of users of every `DeepSleepBlock`, by index
+    deep_sleep_users: [u8; 2],
 }
 
 impl Handle {
@@ -243,6 +347,53 @@ impl Handle {
         self.pdruncfg.modify(|_, w| peripheral.power_down(w));
     }
 
+    /// Keep an analog block powered in deep-sleep and power-down mode
+    ///
+    /// By default, the brown-out detector ([`BOD`]) and the watchdog
+    /// oscillator (represented by [`pac::WWDT`]) are powered down in those
+    /// modes, which stops any peripheral they are required by. These are the
+    /// only blocks that can be kept powered. All other oscillators, except
+    /// the PMU's [`LowPowerClock`], are always stopped.
+    ///
+    /// Calls are counted, so multiple peripherals can share a block. It is
+    /// powered down in those modes again, once [`release_in_deep_sleep`] has
+    /// been called as often as this method.
+    ///
+    /// [`BOD`]: struct.BOD.html
+    /// [`pac::WWDT`]: ../pac/struct.WWDT.html
+    /// [`LowPowerClock`]: ../pmu/struct.LowPowerClock.html
+    /// [`release_in_deep_sleep`]: #method.release_in_deep_sleep
+    pub fn keep_powered_in_deep_sleep<P: DeepSleepBlock>(&mut self, block: &P) {
+        let users = &mut self.deep_sleep_users[P::INDEX];
+        *users = users.saturating_add(1);
+
+        self.pdsleepcfg.modify(|_, w| block.keep_powered(w));
+    }
+
+    /// Release an analog block kept powered in deep-sleep and power-down mode
+    ///
+    /// Undoes one call to [`keep_powered_in_deep_sleep`]. The block is powered
+    /// down in those modes, once no users are left.
+    ///
+    /// [`keep_powered_in_deep_sleep`]: #method.keep_powered_in_deep_sleep
+    pub fn release_in_deep_sleep<P: DeepSleepBlock>(&mut self, block: &P) {
+        let users = &mut self.deep_sleep_users[P::INDEX];
+        *users = users.saturating_sub(1);
+
+        if *users == 0 {
+            self.pdsleepcfg.modify(|_, w| block.power_down(w));
+        }
+    }
+
+    /// Indicates whether an analog block is kept powered in deep-sleep mode
+    ///
+    /// Please refer to [`keep_powered_in_deep_sleep`] for more information.
+    ///
+    /// [`keep_powered_in_deep_sleep`]: #method.keep_powered_in_deep_sleep
+    pub fn is_powered_in_deep_sleep<P: DeepSleepBlock>(&self, _: &P) -> bool {
+        self.deep_sleep_users[P::INDEX] > 0
+    }
+
     /// Enable interrupt wake-up from deep-sleep and power-down modes
     ///
     /// To use an interrupt for waking up the system from the deep-sleep and
@@ -264,6 +415,256 @@ impl Handle {
     {
         self.starterp1.modify(|_, w| I::disable(w));
     }
+
+    /// Enable exactly the given wake-up sources
+    ///
+    /// Overwrites the STARTERP0 and STARTERP1 registers. Used by
+    /// [`pmu::Handle::deep_sleep`].
+    ///
+    /// [`pmu::Handle::deep_sleep`]: ../pmu/struct.Handle.html#method.deep_sleep
+    pub(crate) fn set_wakeup_sources(
+        &mut self,
+        starterp0: u32,
+        starterp1: u32,
+    ) {
+        // Sound, as all bits that can be set by `WakeupSource` implementations
+        // are valid. See user manual, section 5.6.30/5.6.31 (LPC82x) or
+        // 8.6.36/8.6.37 (LPC845).
+        self.starterp0.write(|w| unsafe { w.bits(starterp0) });
+        self.starterp1.write(|w| unsafe { w.bits(starterp1) });
+    }
+
+    /// Change the system clock divider
+    ///
+    /// The system clock, which runs the core, memories, and many peripherals,
+    /// is derived from the main clock by dividing it by this value. Increasing
+    /// it at runtime reduces power consumption. See user manual, section
+    /// 5.6.16 (LPC82x) or 8.6.21 (LPC845).
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `div` is zero, as that would disable the system clock.
+    ///
+    /// The new frequency is recorded, and returned by [`system_clock_hz`].
+    /// Everything that converts between time and system clock ticks when it
+    /// is used follows along, like the microsecond-based methods of
+    /// [`MrtChannel`], the IAP functions, [`FrequencyCounter::frequency_hz`],
+    /// and [`SPI::set_frequency`] (LPC82x).
+    ///
+    /// # Safety
+    ///
+    /// Settings that were derived from the system clock frequency, before it
+    /// was changed, are invalidated. The following need to be updated or
+    /// recreated, before they're used again:
+    ///
+    /// - [`Delay`], using [`Delay::set_system_clock`]
+    /// - MRT channels that are running. The change must not happen while a
+    ///   [`Scheduler`], [`Timestamps`], [`Uptime`], or [`LedMatrix`] is in
+    ///   use, as these convert their ticks using the frequency they were
+    ///   created with, or the current one.
+    /// - [`Fgen`], which reads the frequency when it is created
+    /// - The SPI clock (LPC82x), whose frequency changes along with the system
+    ///   clock. Call [`SPI::set_frequency`] again.
+    /// - Baud rates of USART (LPC82x, relative to [`main_clock_hz`]) and clock
+    ///   rates of I2C, if they are clocked from the main or system clock.
+    ///
+    /// It is up to the caller to make sure all of these are accounted for.
+    ///
+    /// [`system_clock_hz`]: fn.system_clock_hz.html
+    /// [`main_clock_hz`]: fn.main_clock_hz.html
+    /// [`Delay`]: ../delay/struct.Delay.html
+    /// [`Delay::set_system_clock`]: ../delay/struct.Delay.html#method.set_system_clock
+    /// [`MrtChannel`]: ../mrt/struct.MrtChannel.html
+    /// [`FrequencyCounter::frequency_hz`]: ../sct/struct.FrequencyCounter.html#method.frequency_hz
+    /// [`SPI::set_frequency`]: ../spi/struct.SPI.html#method.set_frequency
+    /// [`Scheduler`]: ../scheduler/struct.Scheduler.html
+    /// [`Timestamps`]: ../timestamp/struct.Timestamps.html
+    /// [`Uptime`]: ../uptime/struct.Uptime.html
+    /// [`LedMatrix`]: ../led_matrix/struct.LedMatrix.html
+    /// [`Fgen`]: ../fgen/struct.Fgen.html
+    pub unsafe fn set_system_clock_divider(&mut self, div: u8) {
+        assert!(div != 0);
+        self.sysahbclkdiv.write(|w| w.div().bits(div));
+        set_clock_hz(main_clock_hz(), div);
+    }
+
+    /// Return the current system clock divider
+    ///
+    /// Please refer to [`set_system_clock_divider`] for more information.
+    ///
+    /// [`set_system_clock_divider`]: #method.set_system_clock_divider
+    pub fn system_clock_divider(&self) -> u8 {
+        self.sysahbclkdiv.read().div().bits()
+    }
+
+    /// Report which peripheral clocks are currently enabled
+    ///
+    /// Decodes the SYSAHBCLKCTRL register (SYSAHBCLKCTRL0 on the LPC845).
+    /// This can be used to verify that no unnecessary clocks are running,
+    /// before entering a low-power mode.
+    pub fn clock_report(&self) -> ClockReport {
+        ClockReport::new(&self.sysahbclkctrl.read())
+    }
+
+    /// Return the reason for the last reset, then clear it
+    ///
+    /// Reads and clears the SYSRSTSTAT register. The register accumulates
+    /// reset sources until it is cleared, so this should be called once during
+    /// startup, to get accurate results after the next reset.
+    pub fn reset_reason(&mut self) -> ResetReason {
+        let r = self.sysrststat.read();
+        let reason = ResetReason {
+            power_on: r.por().bit_is_set(),
+            external: r.extrst().bit_is_set(),
+            watchdog: r.wdt().bit_is_set(),
+            brown_out: r.bod().bit_is_set(),
+            system: r.sysrst().bit_is_set(),
+        };
+
+        // The bits are cleared by writing ones to them.
+        self.sysrststat.write(|w| unsafe { w.bits(r.bits()) });
+
+        reason
+    }
+
+    /// Return what is currently mapped to address 0
+    ///
+    /// Please refer to [`set_memory_map`] for more information.
+    ///
+    /// [`set_memory_map`]: #method.set_memory_map
+    pub fn memory_map(&self) -> MemoryMap {
+        match self.sysmemremap.read().bits() & 0b11 {
+            0 => MemoryMap::BootRom,
+            1 => MemoryMap::Ram,
+            _ => MemoryMap::Flash,
+        }
+    }
+
+    /// Map flash to address 0
+    ///
+    /// This is the default, after the boot ROM has started the application.
+    /// It undoes any previous call to [`set_memory_map`].
+    ///
+    /// [`set_memory_map`]: #method.set_memory_map
+    pub fn map_flash(&mut self) {
+        // Sound, as flash holds the application's vector table.
+        unsafe { self.set_memory_map(MemoryMap::Flash) }
+    }
+
+    /// Select what is mapped to address 0
+    ///
+    /// Writes the SYSMEMREMAP register. The first 192 bytes of the selected
+    /// memory appear at address 0, which is where the interrupt vectors are
+    /// read from, unless VTOR has been changed. See user manual, section 5.6.1
+    /// (LPC82x) or 8.6.1 (LPC845).
+    ///
+    /// Bootloaders use this to hand the vector table over to the application,
+    /// and flash self-programming code can map RAM to keep interrupts
+    /// working, while flash can't be read. If you only need to change
+    /// interrupt handlers, the [`vector_table`] module is the safer option.
+    ///
+    /// # Safety
+    ///
+    /// Every interrupt and exception that happens afterwards uses the vector
+    /// table in the selected memory, so it must contain a valid one:
+    ///
+    /// - [`MemoryMap::Ram`] requires a vector table at the start of RAM
+    ///   (0x1000_0000), which is usually where the stack or `.data` are
+    ///   placed by the linker script. It needs to be placed there explicitly.
+    /// - [`MemoryMap::BootRom`] uses the boot ROM's handlers, which don't
+    ///   know about the application. Any enabled interrupt, including a
+    ///   fault, ends up in the boot ROM.
+    ///
+    /// Interrupts should be disabled, while the map is changed.
+    ///
+    /// [`vector_table`]: ../vector_table/index.html
+    /// [`MemoryMap::Ram`]: enum.MemoryMap.html#variant.Ram
+    /// [`MemoryMap::BootRom`]: enum.MemoryMap.html#variant.BootRom
+    pub unsafe fn set_memory_map(&mut self, map: MemoryMap) {
+        self.sysmemremap.write(|w| w.bits(map as u32));
+    }
+
+    /// Return the current trim value of the IRC
+    ///
+    /// The trim value is set to a factory-calibrated value on reset. See user
+    /// manual, section 5.6.7.
+    #[cfg(feature = "82x")]
+    pub fn irc_trim(&self) -> u8 {
+        self.ircctrl.read().trim().bits()
+    }
+
+    /// Change the trim value of the IRC
+    ///
+    /// This can be used to improve the accuracy of the IRC beyond the factory
+    /// calibration, for example for a specific operating temperature. The
+    /// [`calibration`] module provides a way to store the trim value in flash.
+    ///
+    /// The effect of a trim step on the frequency is not specified in the user
+    /// manual. The right trim value needs to be determined by measuring the
+    /// IRC frequency against a more accurate reference.
+    ///
+    /// Only available on LPC82x. The trim of the LPC845's FRO is not
+    /// accessible to the application.
+    ///
+    /// [`calibration`]: ../calibration/index.html
+    #[cfg(feature = "82x")]
+    pub fn set_irc_trim(&mut self, trim: u8) {
+        self.ircctrl.write(|w| unsafe { w.trim().bits(trim) });
+    }
+}
+
+/// The sources of the last reset
+///
+/// Returned by [`syscon::Handle::reset_reason`]. Every field is `true`, if
+/// that source has caused a reset since the reset reason was last cleared.
+///
+/// [`syscon::Handle::reset_reason`]: struct.Handle.html#method.reset_reason
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResetReason {
+    /// Power-on reset
+    pub power_on: bool,
+
+    /// Reset via the RESET pin
+    pub external: bool,
+
+    /// Watchdog reset
+    pub watchdog: bool,
+
+    /// Brown-out detection reset
+    pub brown_out: bool,
+
+    /// System reset requested by software, via the SYSRESETREQ bit
+    pub system: bool,
+}
+
+impl ResetReason {
+    /// Indicates whether the contents of RAM have survived the reset
+    ///
+    /// This is the case, unless the reset was caused by a power-on or
+    /// brown-out. If the supply voltage has dropped, RAM contents can't be
+    /// relied upon.
+    pub fn ram_retained(&self) -> bool {
+        !self.power_on && !self.brown_out
+    }
+}
+
+/// The memory that is mapped to address 0
+///
+/// Used by [`syscon::Handle::set_memory_map`].
+///
+/// [`syscon::Handle::set_memory_map`]: struct.Handle.html#method.set_memory_map
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MemoryMap {
+    /// The boot ROM, as during boot loading
+    BootRom = 0,
+
+    /// The start of RAM
+    Ram = 1,
+
+    /// Flash, as when the application runs
+    Flash = 2,
 }
 
 /// Brown-out detection
@@ -354,7 +755,10 @@ pub struct UARTFRG {
 impl UARTFRG {
     /// Set UART clock divider value (UARTCLKDIV)
     ///
-    /// See user manual, section 5.6.15.
+    /// Divides the main clock, whose frequency is returned by
+    /// [`main_clock_hz`]. See user manual, section 5.6.15.
+    ///
+    /// [`main_clock_hz`]: fn.main_clock_hz.html
     pub fn set_clkdiv(&mut self, value: u8) {
         self.uartclkdiv.write(|w| unsafe { w.div().bits(value) });
     }
@@ -374,6 +778,142 @@ impl UARTFRG {
     }
 }
 
+macro_rules! clock_report {
+    (
+        $(
+            $(#[doc = $doc:literal])*
+            $(#[cfg($cfg:meta)])?
+            $field:ident, $bit:ident;
+        )*
+    ) => {
+        /// The peripheral clocks that are enabled
+        ///
+        /// Returned by [`syscon::Handle::clock_report`]. Every field is `true`,
+        /// if the clock is enabled.
+        ///
+        /// [`syscon::Handle::clock_report`]: struct.Handle.html#method.clock_report
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub struct ClockReport {
+            $(
+                $(#[doc = $doc])*
+                $(#[cfg($cfg)])?
+                pub $field: bool,
+            )*
+        }
+
+        impl ClockReport {
+            fn new(r: &sysahbclkctrl0::R) -> Self {
+                ClockReport {
+                    $(
+                        $(#[cfg($cfg)])?
+                        $field: r.$bit().bit_is_set(),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+clock_report!(
+    /// ROM
+    rom, rom;
+    /// SRAM0 and SRAM1
+    ram0_1, ram0_1;
+    /// Flash register interface
+    #[cfg(feature = "82x")]
+    flash_registers, flashreg;
+    /// Flash
+    flash, flash;
+    /// I2C0
+    i2c0, i2c0;
+    /// GPIO
+    #[cfg(feature = "82x")]
+    gpio, gpio;
+    /// GPIO port 0
+    #[cfg(feature = "845")]
+    gpio0, gpio0;
+    /// GPIO port 1
+    #[cfg(feature = "845")]
+    gpio1, gpio1;
+    /// Pin interrupts and pattern match engine
+    #[cfg(feature = "845")]
+    pinint, gpio_int;
+    /// Switch matrix
+    swm, swm;
+    /// State configurable timer
+    sct, sct;
+    /// Self-wake-up timer
+    wkt, wkt;
+    /// Multi-rate timer
+    mrt, mrt;
+    /// Standard counter/timer
+    #[cfg(feature = "845")]
+    ctimer, ctimer;
+    /// SPI0
+    spi0, spi0;
+    /// SPI1
+    spi1, spi1;
+    /// CRC engine
+    crc, crc;
+    /// USART0
+    usart0, uart0;
+    /// USART1
+    usart1, uart1;
+    /// USART2
+    usart2, uart2;
+    /// USART3
+    #[cfg(feature = "845")]
+    usart3, uart3;
+    /// USART4
+    #[cfg(feature = "845")]
+    usart4, uart4;
+    /// Windowed watchdog timer
+    wwdt, wwdt;
+    /// I/O configuration
+    iocon, iocon;
+    /// Analog comparator
+    acmp, acmp;
+    /// I2C1
+    i2c1, i2c1;
+    /// I2C2
+    i2c2, i2c2;
+    /// I2C3
+    i2c3, i2c3;
+    /// ADC
+    adc, adc;
+    /// DAC0
+    #[cfg(feature = "845")]
+    dac0, dac0;
+    /// Micro trace buffer
+    mtb, mtb;
+    /// DMA controller
+    dma, dma;
+);
+
+/// Collect the SYSCON registers for a [`debug_dump`] snapshot
+///
+/// [`debug_dump`]: ../debug_dump/index.html
+#[cfg(feature = "debug_dump")]
+pub(crate) fn snapshot(
+    syscon: &pac::syscon::RegisterBlock,
+) -> crate::debug_dump::Syscon {
+    #[cfg(feature = "82x")]
+    let sysahbclkctrl0 = syscon.sysahbclkctrl.read();
+    #[cfg(feature = "845")]
+    let sysahbclkctrl0 = syscon.sysahbclkctrl0.read();
+
+    crate::debug_dump::Syscon {
+        clocks: ClockReport::new(&sysahbclkctrl0),
+        sysahbclkctrl0: sysahbclkctrl0.bits(),
+        #[cfg(feature = "845")]
+        sysahbclkctrl1: syscon.sysahbclkctrl1.read().bits(),
+        sysahbclkdiv: syscon.sysahbclkdiv.read().bits(),
+        mainclksel: syscon.mainclksel.read().bits(),
+        pdruncfg: syscon.pdruncfg.read().bits(),
+    }
+}
+
 /// Internal trait for controlling peripheral clocks
 ///
 /// This trait is an internal implementation detail and should neither be
@@ -452,8 +992,13 @@ impl_clock_control!(pac::I2C1, i2c1);
 impl_clock_control!(pac::I2C2, i2c2);
 impl_clock_control!(pac::I2C3, i2c3);
 impl_clock_control!(pac::ADC0, adc);
+#[cfg(feature = "845")]
+impl_clock_control!(pac::DAC0, dac0);
 impl_clock_control!(MTB, mtb);
 impl_clock_control!(pac::DMA0, dma);
+#[cfg(feature = "845")]
+impl_clock_control!(pac::PINT, gpio_int);
+
 #[cfg(feature = "845")]
 impl ClockControl for pac::GPIO {
     fn enable_clock<'w>(
@@ -616,6 +1161,58 @@ impl_analog_block!(SYSOSC, sysosc_pd);
 impl_analog_block!(pac::WWDT, wdtosc_pd);
 impl_analog_block!(SYSPLL, syspll_pd);
 impl_analog_block!(pac::ACOMP, acmp);
+#[cfg(feature = "845")]
+impl_analog_block!(pac::DAC0, dac0);
+
+/// Internal trait for analog blocks that can stay powered in deep-sleep mode
+///
+/// This trait is an internal implementation detail and should neither be
+/// implemented nor used outside of LPC8xx HAL. Any changes to this trait won't
+/// be considered breaking changes.
+///
+/// Please refer to [`syscon::Handle::keep_powered_in_deep_sleep`] for the
+/// public API that uses this trait.
+///
+/// [`syscon::Handle::keep_powered_in_deep_sleep`]: struct.Handle.html#method.keep_powered_in_deep_sleep
+pub trait DeepSleepBlock {
+    /// Internal index into the users tracked by `Handle`
+    const INDEX: usize;
+
+    /// Internal method to keep an analog block powered
+    fn keep_powered<'w>(
+        &self,
+        w: &'w mut pdsleepcfg::W,
+    ) -> &'w mut pdsleepcfg::W;
+
+    /// Internal method to power down an analog block
+    fn power_down<'w>(&self, w: &'w mut pdsleepcfg::W)
+        -> &'w mut pdsleepcfg::W;
+}
+
+macro_rules! impl_deep_sleep_block {
+    ($block:ty, $field:ident, $index:expr) => {
+        impl DeepSleepBlock for $block {
+            const INDEX: usize = $index;
+
+            fn keep_powered<'w>(
+                &self,
+                w: &'w mut pdsleepcfg::W,
+            ) -> &'w mut pdsleepcfg::W {
+                w.$field().clear_bit()
+            }
+
+            fn power_down<'w>(
+                &self,
+                w: &'w mut pdsleepcfg::W,
+            ) -> &'w mut pdsleepcfg::W {
+                w.$field().set_bit()
+            }
+        }
+    };
+}
+
+impl_deep_sleep_block!(BOD, bod_pd, 0);
+impl_deep_sleep_block!(pac::WWDT, wdtosc_pd, 1);
 
 /// The 750 kHz IRC/FRO-derived clock
 ///
@@ -742,17 +1339,29 @@ pub trait PeripheralClock<PERIPH> {
 }
 
 reg!(PDRUNCFG, PDRUNCFG, pac::SYSCON, pdruncfg);
+reg!(PDSLEEPCFG, PDSLEEPCFG, pac::SYSCON, pdsleepcfg);
 #[cfg(feature = "82x")]
 reg!(PRESETCTRL0, PRESETCTRL0, pac::SYSCON, presetctrl);
 #[cfg(feature = "845")]
 reg!(PRESETCTRL0, PRESETCTRL0, pac::SYSCON, presetctrl0);
+reg!(STARTERP0, STARTERP0, pac::SYSCON, starterp0);
 reg!(STARTERP1, STARTERP1, pac::SYSCON, starterp1);
 #[cfg(feature = "82x")]
 reg!(SYSAHBCLKCTRL0, SYSAHBCLKCTRL0, pac::SYSCON, sysahbclkctrl);
 #[cfg(feature = "845")]
 reg!(SYSAHBCLKCTRL0, SYSAHBCLKCTRL0, pac::SYSCON, sysahbclkctrl0);
+reg!(SYSAHBCLKDIV, SYSAHBCLKDIV, pac::SYSCON, sysahbclkdiv);
 #[cfg(feature = "845")]
 reg!(FCLKSEL, [FCLKSEL; 11], pac::SYSCON, fclksel);
+#[cfg(feature = "845")]
+reg!(ADCCLKSEL, ADCCLKSEL, pac::SYSCON, adcclksel);
+#[cfg(feature = "845")]
+reg!(ADCCLKDIV, ADCCLKDIV, pac::SYSCON, adcclkdiv);
+reg!(PINTSEL, [PINTSEL; 8], pac::SYSCON, pintsel);
+#[cfg(feature = "82x")]
+reg!(IRCCTRL, IRCCTRL, pac::SYSCON, ircctrl);
+reg!(SYSRSTSTAT, SYSRSTSTAT, pac::SYSCON, sysrststat);
+reg!(SYSMEMREMAP, SYSMEMREMAP, pac::SYSCON, sysmemremap);
 
 #[cfg(feature = "82x")]
 reg!(UARTCLKDIV, UARTCLKDIV, pac::SYSCON, uartclkdiv);
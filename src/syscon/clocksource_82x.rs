@@ -1,5 +1,8 @@
-use crate::syscon::{self, PeripheralClock, UARTFRG};
-use core::marker::PhantomData;
+use crate::{
+    pac,
+    syscon::{self, PeripheralClock, UARTFRG},
+};
+use core::{fmt, marker::PhantomData};
 
 /// Defines the clock configuration for a usart
 pub struct UsartClock<PeriphClock> {
@@ -12,15 +15,45 @@ impl<PERIPH: crate::usart::Instance> UsartClock<PERIPH> {
     /// Create the clock config for the uart
     ///
     /// `osrval` has to be between 5-16
-    pub fn new(_: &UARTFRG, psc: u16, osrval: u8) -> Self {
-        let osrval = osrval - 1;
-        assert!(osrval > 3 && osrval < 0x10);
+    ///
+    /// `osrval` is the oversampling value, the number of peripheral clock
+    /// cycles per bit. A higher value places the samples within each bit more
+    /// precisely, which increases the tolerance for baud rate mismatches.
+    ///
+    /// This method is only available, if the `panics` feature is enabled,
+    /// which it is by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `osrval` is not between 5 and 16. Please refer to
+    /// [`try_new`] for a variant that returns an error instead.
+    ///
+    /// [`try_new`]: #method.try_new
+    #[cfg(feature = "panics")]
+    pub fn new(frg: &UARTFRG, psc: u16, osrval: u8) -> Self {
+        match Self::try_new(frg, psc, osrval) {
+            Ok(clock) => clock,
+            Err(error) => panic!("{}", error),
+        }
+    }
 
-        Self {
+    /// Create the clock config for the uart, if the arguments are valid
+    ///
+    /// Like [`new`], but returns [`Error::InvalidOversampling`] instead of
+    /// panicking, if `osrval` is not between 5 and 16.
+    ///
+    /// [`new`]: #method.new
+    /// [`Error::InvalidOversampling`]: enum.Error.html#variant.InvalidOversampling
+    pub fn try_new(_: &UARTFRG, psc: u16, osrval: u8) -> Result<Self, Error> {
+        if !(5..=16).contains(&osrval) {
+            return Err(Error::InvalidOversampling);
+        }
+
+        Ok(Self {
             psc,
-            osrval,
+            osrval: osrval - 1,
             _periphclock: PhantomData,
-        }
+        })
     }
 }
 
@@ -44,15 +77,48 @@ impl<PERIPH: crate::i2c::Instance> I2cClock<PERIPH> {
     /// Create the clock config for the i2c peripheral
     ///
     /// mstclhigh & mstcllow have to be between 2-9
+    ///
+    /// This method is only available, if the `panics` feature is enabled,
+    /// which it is by default. Please refer to [`try_new`] for a variant that
+    /// returns an error instead of panicking.
+    ///
+    /// [`try_new`]: #method.try_new
+    #[cfg(feature = "panics")]
     pub fn new(divval: u16, mstsclhigh: u8, mstscllow: u8) -> Self {
-        assert!(mstsclhigh > 1 && mstsclhigh < 10);
-        assert!(mstscllow > 1 && mstscllow < 10);
-        Self {
+        match Self::try_new(divval, mstsclhigh, mstscllow) {
+            Ok(clock) => clock,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Create the clock config for the i2c peripheral, if the arguments are
+    /// valid
+    ///
+    /// Like [`new`], but returns [`Error::InvalidSclHigh`] or
+    /// [`Error::InvalidSclLow`] instead of panicking, if `mstsclhigh` or
+    /// `mstscllow` are not between 2 and 9.
+    ///
+    /// [`new`]: #method.new
+    /// [`Error::InvalidSclHigh`]: enum.Error.html#variant.InvalidSclHigh
+    /// [`Error::InvalidSclLow`]: enum.Error.html#variant.InvalidSclLow
+    pub fn try_new(
+        divval: u16,
+        mstsclhigh: u8,
+        mstscllow: u8,
+    ) -> Result<Self, Error> {
+        if !(2..=9).contains(&mstsclhigh) {
+            return Err(Error::InvalidSclHigh);
+        }
+        if !(2..=9).contains(&mstscllow) {
+            return Err(Error::InvalidSclLow);
+        }
+
+        Ok(Self {
             divval,
             mstsclhigh: mstsclhigh - 2,
             mstscllow: mstscllow - 2,
             _periphclock: PhantomData,
-        }
+        })
     }
 
     /// Create a new i2c clock config for 400 kHz
@@ -75,3 +141,87 @@ impl<PERIPH: crate::i2c::Instance> PeripheralClock<PERIPH>
         // NOOP, selected by default
     }
 }
+
+/// A struct containing the clock configuration for an SPI peripheral
+pub struct SpiClock<PeriphClock> {
+    _periphclock: PhantomData<PeriphClock>,
+}
+
+impl<PERIPH: crate::spi::Instance> SpiClock<PERIPH> {
+    /// Create the clock config for the SPI peripheral
+    ///
+    /// The SPI peripherals are always clocked by the system clock. The SPI
+    /// clock frequency is derived from that. See [`SPI::set_frequency`].
+    ///
+    /// [`SPI::set_frequency`]: ../../spi/struct.SPI.html#method.set_frequency
+    pub fn new() -> Self {
+        Self {
+            _periphclock: PhantomData,
+        }
+    }
+}
+
+impl<PERIPH: crate::spi::Instance> PeripheralClock<PERIPH>
+    for SpiClock<PERIPH>
+{
+    fn select_clock(&self, _: &mut syscon::Handle) {
+        // NOOP, selected by default
+    }
+}
+
+/// The clock configuration for the ADC
+///
+/// The ADC is always clocked by the system clock, which it divides as
+/// required for calibration and conversions.
+pub struct AdcClock {
+    _private: (),
+}
+
+impl AdcClock {
+    /// Create the clock config for the ADC
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl Default for AdcClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PeripheralClock<pac::ADC0> for AdcClock {
+    fn select_clock(&self, _: &mut syscon::Handle) {
+        // NOOP, selected by default
+    }
+}
+
+/// An error that can occur while creating a clock configuration
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The USART oversampling value is not between 5 and 16
+    InvalidOversampling,
+
+    /// The I2C SCL high time is not between 2 and 9 clock cycles
+    InvalidSclHigh,
+
+    /// The I2C SCL low time is not between 2 and 9 clock cycles
+    InvalidSclLow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidOversampling => {
+                f.write_str("oversampling value must be between 5 and 16")
+            }
+            Error::InvalidSclHigh => {
+                f.write_str("SCL high time must be between 2 and 9 cycles")
+            }
+            Error::InvalidSclLow => {
+                f.write_str("SCL low time must be between 2 and 9 cycles")
+            }
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use crate::pac;
 use crate::{
     pac::syscon::fclksel::SEL_A,
-    syscon::{self, frg, PeripheralClock, IOSC},
+    syscon::{self, frg, PeripheralClock, IOSC, IOSC_HZ},
 };
 
-use core::marker::PhantomData;
+use core::{fmt, marker::PhantomData};
 
 /// Internal trait used for defining the fclksel index for a peripheral
 ///
@@ -33,6 +33,44 @@ periph_clock_selector!(I2C0, 5);
 periph_clock_selector!(I2C1, 6);
 periph_clock_selector!(I2C2, 7);
 periph_clock_selector!(I2C3, 8);
+periph_clock_selector!(SPI0, 9);
+periph_clock_selector!(SPI1, 10);
+
+/// Return the frequency of a peripheral's function clock, in Hz
+///
+/// Reads the clock that is currently selected in FCLKSEL register `index`
+/// (see [`PeripheralClockSelector`]). Returns `0`, if no clock is selected.
+///
+/// [`PeripheralClockSelector`]: trait.PeripheralClockSelector.html
+pub(crate) fn function_clock_hz(index: usize) -> u32 {
+    // Sound, as we're only reading from the registers.
+    let syscon = unsafe { &*pac::SYSCON::ptr() };
+
+    match syscon.fclksel[index].read().sel().variant() {
+        SEL_A::FRO => IOSC_HZ,
+        SEL_A::MAIN_CLK => syscon::main_clock_hz(),
+        SEL_A::FRG0CLK => frg_hz(&syscon.frg0),
+        SEL_A::FRG1CLK => frg_hz(&syscon.frg1),
+        SEL_A::FRO_DIV => IOSC_HZ / 2,
+        SEL_A::NONE | SEL_A::NONE1 | SEL_A::NONE2 => 0,
+    }
+}
+
+/// Return the output frequency of a fractional generator, in Hz
+///
+/// The output frequency is `input / (1 + MULT / (DIV + 1))`.
+fn frg_hz(frg: &pac::syscon::FRG) -> u32 {
+    let input_hz = match frg.frgclksel.read().sel().variant() {
+        frg::Clock::FRO => IOSC_HZ,
+        frg::Clock::MAIN_CLK => syscon::main_clock_hz(),
+        frg::Clock::SYS_PLL | frg::Clock::NONE => 0,
+    };
+
+    let div = frg.frgdiv.read().div().bits() as u64 + 1;
+    let mult = frg.frgmult.read().mult().bits() as u64;
+
+    (input_hz as u64 * div / (div + mult)) as u32
+}
 
 /// Internal trait used for defining valid peripheal clock sources
 ///
@@ -69,15 +107,45 @@ impl<PERIPH: crate::usart::Instance, CLOCK: PeripheralClockSource>
     /// Create the clock config for the uart
     ///
     /// `osrval` has to be between 5-16
-    pub fn new(_: &CLOCK, psc: u16, osrval: u8) -> Self {
-        let osrval = osrval - 1;
-        assert!(osrval > 3 && osrval < 0x10);
+    ///
+    /// `osrval` is the oversampling value, the number of peripheral clock
+    /// cycles per bit. A higher value places the samples within each bit more
+    /// precisely, which increases the tolerance for baud rate mismatches.
+    ///
+    /// This method is only available, if the `panics` feature is enabled,
+    /// which it is by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `osrval` is not between 5 and 16. Please refer to
+    /// [`try_new`] for a variant that returns an error instead.
+    ///
+    /// [`try_new`]: #method.try_new
+    #[cfg(feature = "panics")]
+    pub fn new(clock: &CLOCK, psc: u16, osrval: u8) -> Self {
+        match Self::try_new(clock, psc, osrval) {
+            Ok(clock) => clock,
+            Err(error) => panic!("{}", error),
+        }
+    }
 
-        Self {
+    /// Create the clock config for the uart, if the arguments are valid
+    ///
+    /// Like [`new`], but returns [`Error::InvalidOversampling`] instead of
+    /// panicking, if `osrval` is not between 5 and 16.
+    ///
+    /// [`new`]: #method.new
+    /// [`Error::InvalidOversampling`]: enum.Error.html#variant.InvalidOversampling
+    pub fn try_new(_: &CLOCK, psc: u16, osrval: u8) -> Result<Self, Error> {
+        if !(5..=16).contains(&osrval) {
+            return Err(Error::InvalidOversampling);
+        }
+
+        Ok(Self {
             psc,
-            osrval,
+            osrval: osrval - 1,
             _periphclock: PhantomData,
-        }
+        })
     }
 }
 
@@ -92,11 +160,11 @@ impl<PERIPH: crate::usart::Instance + PeripheralClockSelector>
         let calc = baudrate * 20;
         let mut osrval = 5;
         for i in (5..=16).rev() {
-            if calc * (i as u32) < 12_000_000 {
+            if calc * (i as u32) < IOSC_HZ {
                 osrval = i;
             }
         }
-        let psc = (12_000_000 / (baudrate * osrval as u32) - 1) as u16;
+        let psc = (IOSC_HZ / (baudrate * osrval as u32) - 1) as u16;
         let osrval = osrval - 1;
         Self {
             psc,
@@ -129,15 +197,54 @@ impl<PERIPH: PeripheralClockSelector, CLOCK: PeripheralClockSource>
     /// Create the clock config for the i2c peripheral
     ///
     /// mstclhigh & mstcllow have to be between 2-9
-    pub fn new(_: &CLOCK, divval: u16, mstsclhigh: u8, mstscllow: u8) -> Self {
-        assert!(mstsclhigh > 1 && mstsclhigh < 10);
-        assert!(mstscllow > 1 && mstscllow < 10);
-        Self {
+    ///
+    /// This method is only available, if the `panics` feature is enabled,
+    /// which it is by default. Please refer to [`try_new`] for a variant that
+    /// returns an error instead of panicking.
+    ///
+    /// [`try_new`]: #method.try_new
+    #[cfg(feature = "panics")]
+    pub fn new(
+        clock: &CLOCK,
+        divval: u16,
+        mstsclhigh: u8,
+        mstscllow: u8,
+    ) -> Self {
+        match Self::try_new(clock, divval, mstsclhigh, mstscllow) {
+            Ok(clock) => clock,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Create the clock config for the i2c peripheral, if the arguments are
+    /// valid
+    ///
+    /// Like [`new`], but returns [`Error::InvalidSclHigh`] or
+    /// [`Error::InvalidSclLow`] instead of panicking, if `mstsclhigh` or
+    /// `mstscllow` are not between 2 and 9.
+    ///
+    /// [`new`]: #method.new
+    /// [`Error::InvalidSclHigh`]: enum.Error.html#variant.InvalidSclHigh
+    /// [`Error::InvalidSclLow`]: enum.Error.html#variant.InvalidSclLow
+    pub fn try_new(
+        _: &CLOCK,
+        divval: u16,
+        mstsclhigh: u8,
+        mstscllow: u8,
+    ) -> Result<Self, Error> {
+        if !(2..=9).contains(&mstsclhigh) {
+            return Err(Error::InvalidSclHigh);
+        }
+        if !(2..=9).contains(&mstscllow) {
+            return Err(Error::InvalidSclLow);
+        }
+
+        Ok(Self {
             divval,
             mstsclhigh: mstsclhigh - 2,
             mstscllow: mstscllow - 2,
             _periphclock: PhantomData,
-        }
+        })
     }
 }
 
@@ -163,3 +270,88 @@ impl<PERIPH: PeripheralClockSelector, CLOCK: PeripheralClockSource>
             .write(|w| w.sel().variant(CLOCK::CLOCK));
     }
 }
+
+/// A struct containing the clock configuration for an SPI peripheral
+pub struct SpiClock<PeriphClock> {
+    _periphclock: PhantomData<PeriphClock>,
+}
+
+impl<PERIPH: PeripheralClockSelector, CLOCK: PeripheralClockSource>
+    SpiClock<(PERIPH, CLOCK)>
+{
+    /// Create the clock config for the SPI peripheral
+    ///
+    /// The SPI clock frequency is derived from this clock. See
+    /// [`SPI::set_frequency`].
+    ///
+    /// [`SPI::set_frequency`]: ../../spi/struct.SPI.html#method.set_frequency
+    pub fn new(_: &CLOCK) -> Self {
+        Self {
+            _periphclock: PhantomData,
+        }
+    }
+}
+
+impl<PERIPH: PeripheralClockSelector, CLOCK: PeripheralClockSource>
+    PeripheralClock<PERIPH> for SpiClock<(PERIPH, CLOCK)>
+{
+    fn select_clock(&self, syscon: &mut syscon::Handle) {
+        syscon.fclksel[PERIPH::REGISTER_NUM]
+            .write(|w| w.sel().variant(CLOCK::CLOCK));
+    }
+}
+
+/// The clock configuration for the ADC
+///
+/// The ADC is clocked by the internal oscillator, undivided. The ADC divides
+/// this clock further, as required for calibration and conversions.
+pub struct AdcClock {
+    _private: (),
+}
+
+impl AdcClock {
+    /// Create the clock config for the ADC
+    pub fn new(_: &IOSC) -> Self {
+        Self { _private: () }
+    }
+}
+
+impl PeripheralClock<pac::ADC0> for AdcClock {
+    fn select_clock(&self, syscon: &mut syscon::Handle) {
+        syscon.adcclksel.write(|w| w.sel().fro());
+
+        // A divider of `0` would disable the clock. Sound, as all values are
+        // valid according to the user manual.
+        syscon.adcclkdiv.write(|w| unsafe { w.div().bits(1) });
+    }
+}
+
+/// An error that can occur while creating a clock configuration
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The USART oversampling value is not between 5 and 16
+    InvalidOversampling,
+
+    /// The I2C SCL high time is not between 2 and 9 clock cycles
+    InvalidSclHigh,
+
+    /// The I2C SCL low time is not between 2 and 9 clock cycles
+    InvalidSclLow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidOversampling => {
+                f.write_str("oversampling value must be between 5 and 16")
+            }
+            Error::InvalidSclHigh => {
+                f.write_str("SCL high time must be between 2 and 9 cycles")
+            }
+            Error::InvalidSclLow => {
+                f.write_str("SCL low time must be between 2 and 9 cycles")
+            }
+        }
+    }
+}
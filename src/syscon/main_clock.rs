@@ -0,0 +1,444 @@
+//! Guarded changes of the main clock
+//!
+//! After reset, the main clock runs from the 12 MHz IRC (LPC82x) or FRO
+//! (LPC845). [`MainClock::set`] can switch it to the system PLL, which
+//! multiplies that frequency, or back. Getting the sequence wrong can lock up
+//! the microcontroller in ways that are hard to diagnose, so this method
+//! takes care of all steps:
+//!
+//! 1. Validate the configuration, before touching any register.
+//! 2. Check that the brown-out detector resets the microcontroller, if the
+//!    system clock is going to be faster than after reset.
+//! 3. Increase the flash wait states, so flash access is safe at any of the
+//!    intermediate frequencies.
+//! 4. Run the main clock from the IRC/FRO, while the PLL is reconfigured.
+//! 5. Configure the PLL, and wait until it has locked.
+//! 6. Set the system clock divider, then switch the main clock to the PLL.
+//! 7. Decrease the flash wait states, if the new frequency allows it.
+//!
+//! The main clock and PLL are described in the user manual, section 5.6
+//! (LPC82x) or 8.6 (LPC845).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     syscon::main_clock::{Config, Source},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//!
+//! syscon
+//!     .main_clock
+//!     .enable_brown_out_reset(&mut syscon.handle, &mut syscon.bod);
+//!
+//! // 12 MHz * 5 / 2 = 30 MHz
+//! let config = Config {
+//!     source: Source::Pll { multiplier: 5 },
+//!     divider: 2,
+//! };
+//!
+//! let hz = unsafe {
+//!     syscon.main_clock.set(
+//!         config,
+//!         &mut syscon.handle,
+//!         &mut syscon.syspll,
+//!         &p.FLASH_CTRL,
+//!     )
+//! }
+//! .expect("Invalid clock configuration");
+//! ```
+//!
+//! [`MainClock::set`]: struct.MainClock.html#method.set
+
+use core::fmt;
+
+#[cfg(feature = "845")]
+use crate::pac::syscon::{MAINCLKPLLSEL, MAINCLKPLLUEN};
+#[cfg(feature = "82x")]
+use crate::pac::syscon::{MAINCLKSEL, MAINCLKUEN};
+use crate::{
+    pac::{
+        self,
+        syscon::{BODCTRL, SYSPLLCLKSEL, SYSPLLCLKUEN, SYSPLLCTRL, SYSPLLSTAT},
+    },
+    reg_proxy::RegProxy,
+};
+
+use super::{set_clock_hz, Handle, BOD, IOSC_HZ, SYSPLL};
+
+/// The maximum system clock frequency
+const MAX_SYSTEM_CLOCK_HZ: u32 = 30_000_000;
+
+/// The maximum system clock frequency for one flash wait state
+///
+/// See user manual, section 4.6.1 (LPC82x) or 5.6.1 (LPC845).
+const MAX_SINGLE_CYCLE_FLASH_HZ: u32 = 20_000_000;
+
+/// The maximum PLL output frequency
+const MAX_PLL_HZ: u32 = 100_000_000;
+
+/// The range of the PLL's current-controlled oscillator
+const CCO_HZ: (u32, u32) = (156_000_000, 320_000_000);
+
+/// How often the PLL lock status is polled, before giving up
+///
+/// The PLL locks within a few hundred microseconds. This is a generous bound
+/// at 12 MHz.
+const PLL_LOCK_ATTEMPTS: u32 = 100_000;
+
+/// The source of the main clock
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Source {
+    /// The 12 MHz IRC (LPC82x) or FRO (LPC845)
+    ///
+    /// Powers down the PLL, as it is no longer needed.
+    Iosc,
+
+    /// The system PLL, fed by the IRC/FRO
+    ///
+    /// Its output frequency is 12 MHz times `multiplier`, which must be in the
+    /// range `1..=8`.
+    Pll {
+        /// The factor the IRC/FRO frequency is multiplied by
+        multiplier: u8,
+    },
+}
+
+/// A main clock configuration
+///
+/// Passed to [`MainClock::set`].
+///
+/// [`MainClock::set`]: struct.MainClock.html#method.set
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// The source of the main clock
+    pub source: Source,
+
+    /// The system clock divider
+    ///
+    /// The system clock is the main clock divided by this value. Must not be
+    /// zero.
+    pub divider: u8,
+}
+
+impl Config {
+    /// Return the system clock frequency in Hz, if the configuration is valid
+    pub fn system_clock_hz(&self) -> Result<u32, Error> {
+        if self.divider == 0 {
+            return Err(Error::InvalidDivider);
+        }
+
+        let main_hz = match self.source {
+            Source::Iosc => IOSC_HZ,
+            Source::Pll { multiplier } => Pll::new(multiplier)?.hz(),
+        };
+
+        let hz = main_hz / self.divider as u32;
+        if hz > MAX_SYSTEM_CLOCK_HZ {
+            return Err(Error::TooFast);
+        }
+
+        Ok(hz)
+    }
+}
+
+/// The PLL settings for a given multiplier
+struct Pll {
+    msel: u8,
+    psel: u8,
+    multiplier: u8,
+}
+
+impl Pll {
+    fn new(multiplier: u8) -> Result<Self, Error> {
+        if multiplier == 0 || IOSC_HZ * multiplier as u32 > MAX_PLL_HZ {
+            return Err(Error::InvalidMultiplier);
+        }
+
+        // The CCO runs at 2 * P times the output frequency, where P is 1, 2,
+        // 4, or 8. Pick the smallest P that keeps it in range.
+        let out_hz = IOSC_HZ * multiplier as u32;
+        let psel = (0..4)
+            .find(|psel| {
+                let cco_hz = 2 * (1 << *psel) * out_hz;
+                (CCO_HZ.0..=CCO_HZ.1).contains(&cco_hz)
+            })
+            .ok_or(Error::InvalidMultiplier)?;
+
+        Ok(Pll {
+            msel: multiplier - 1,
+            psel,
+            multiplier,
+        })
+    }
+
+    fn hz(&self) -> u32 {
+        IOSC_HZ * self.multiplier as u32
+    }
+}
+
+/// The main clock
+///
+/// This struct is part of [`syscon::Parts`].
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`syscon::Parts`]: ../struct.Parts.html
+/// [module documentation]: index.html
+pub struct MainClock {
+    #[cfg(feature = "82x")]
+    mainclksel: RegProxy<MAINCLKSEL>,
+    #[cfg(feature = "82x")]
+    mainclkuen: RegProxy<MAINCLKUEN>,
+    #[cfg(feature = "845")]
+    mainclkpllsel: RegProxy<MAINCLKPLLSEL>,
+    #[cfg(feature = "845")]
+    mainclkplluen: RegProxy<MAINCLKPLLUEN>,
+    syspllclksel: RegProxy<SYSPLLCLKSEL>,
+    syspllclkuen: RegProxy<SYSPLLCLKUEN>,
+    syspllctrl: RegProxy<SYSPLLCTRL>,
+    syspllstat: RegProxy<SYSPLLSTAT>,
+    bodctrl: RegProxy<BODCTRL>,
+}
+
+impl MainClock {
+    pub(crate) fn new() -> Self {
+        MainClock {
+            #[cfg(feature = "82x")]
+            mainclksel: RegProxy::new(),
+            #[cfg(feature = "82x")]
+            mainclkuen: RegProxy::new(),
+            #[cfg(feature = "845")]
+            mainclkpllsel: RegProxy::new(),
+            #[cfg(feature = "845")]
+            mainclkplluen: RegProxy::new(),
+            syspllclksel: RegProxy::new(),
+            syspllclkuen: RegProxy::new(),
+            syspllctrl: RegProxy::new(),
+            syspllstat: RegProxy::new(),
+            bodctrl: RegProxy::new(),
+        }
+    }
+
+    /// Change the main clock and system clock divider
+    ///
+    /// Follows the sequence outlined in the [module documentation]. Returns
+    /// the new system clock frequency in Hz. The new frequencies of the main
+    /// and system clocks are recorded, and returned by [`main_clock_hz`] and
+    /// [`system_clock_hz`] from then on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without changing anything, if the configuration is
+    /// invalid, or if the system clock would be faster than 12 MHz while the
+    /// brown-out reset is disabled. Enable it using
+    /// [`enable_brown_out_reset`] first.
+    ///
+    /// Returns [`Error::PllLockTimeout`], if the PLL doesn't lock. The main
+    /// clock is left running from the IRC/FRO in that case.
+    ///
+    /// # Limitations
+    ///
+    /// On LPC845, this method assumes that the main clock before the PLL is
+    /// the FRO, and that the FRO runs at 12 MHz. Both are the case after
+    /// reset.
+    ///
+    /// # Safety
+    ///
+    /// Changing the system clock invalidates all settings that were derived
+    /// from its frequency. Please refer to
+    /// [`Handle::set_system_clock_divider`] for a list.
+    ///
+    /// [module documentation]: index.html
+    /// [`main_clock_hz`]: ../fn.main_clock_hz.html
+    /// [`system_clock_hz`]: ../fn.system_clock_hz.html
+    /// [`enable_brown_out_reset`]: #method.enable_brown_out_reset
+    /// [`Error::PllLockTimeout`]: enum.Error.html#variant.PllLockTimeout
+    /// [`Handle::set_system_clock_divider`]: ../struct.Handle.html#method.set_system_clock_divider
+    pub unsafe fn set(
+        &mut self,
+        config: Config,
+        handle: &mut Handle,
+        syspll: &mut SYSPLL,
+        flash: &pac::FLASH_CTRL,
+    ) -> Result<u32, Error> {
+        let hz = config.system_clock_hz()?;
+        let pll = match config.source {
+            Source::Iosc => None,
+            Source::Pll { multiplier } => Some(Pll::new(multiplier)?),
+        };
+
+        if hz > IOSC_HZ && !self.brown_out_reset_enabled(handle) {
+            return Err(Error::BrownOutResetDisabled);
+        }
+
+        // Two wait states are safe at any frequency.
+        set_flash_wait_states(flash, 2);
+
+        // The PLL must not be reconfigured while it runs the main clock.
+        self.select_pll(false);
+        set_clock_hz(IOSC_HZ, handle.system_clock_divider().max(1));
+        handle.power_down(syspll);
+
+        if let Some(pll) = &pll {
+            // The IRC/FRO feeds the PLL. The new selection only takes effect,
+            // once the update enable register is toggled.
+            self.syspllclksel.write(|w| w.sel().bits(0));
+            self.syspllclkuen.write(|w| w.ena().clear_bit());
+            self.syspllclkuen.write(|w| w.ena().set_bit());
+
+            self.syspllctrl.write(|w| {
+                w.msel().bits(pll.msel);
+                w.psel().bits(pll.psel)
+            });
+
+            handle.power_up(syspll);
+
+            let mut attempts = 0;
+            while self.syspllstat.read().lock().bit_is_clear() {
+                attempts += 1;
+                if attempts >= PLL_LOCK_ATTEMPTS {
+                    handle.power_down(syspll);
+                    return Err(Error::PllLockTimeout);
+                }
+            }
+        }
+
+        // Setting the divider first makes sure the system clock never exceeds
+        // the target frequency.
+        handle.set_system_clock_divider(config.divider);
+
+        if let Some(pll) = pll {
+            self.select_pll(true);
+            set_clock_hz(pll.hz(), config.divider);
+        }
+
+        if hz <= MAX_SINGLE_CYCLE_FLASH_HZ {
+            set_flash_wait_states(flash, 1);
+        }
+
+        Ok(hz)
+    }
+
+    /// Enable the brown-out reset
+    ///
+    /// Powers up the brown-out detector, and makes it reset the
+    /// microcontroller, if the supply voltage drops below the configured reset
+    /// level. Running faster than after reset at a low supply voltage could
+    /// corrupt flash reads, so [`set`] requires this.
+    ///
+    /// [`set`]: #method.set
+    pub fn enable_brown_out_reset(
+        &mut self,
+        handle: &mut Handle,
+        bod: &mut BOD,
+    ) {
+        handle.power_up(bod);
+        self.bodctrl.modify(|_, w| w.bodrstena().set_bit());
+    }
+
+    /// Indicates whether the main clock currently runs from the PLL
+    pub fn is_pll_selected(&self) -> bool {
+        #[cfg(feature = "82x")]
+        let selected = self.mainclksel.read().sel().bits() == 3;
+        #[cfg(feature = "845")]
+        let selected = self.mainclkpllsel.read().sel().bits() == 1;
+
+        selected
+    }
+
+    fn brown_out_reset_enabled(&self, handle: &Handle) -> bool {
+        handle.pdruncfg.read().bod_pd().bit_is_clear()
+            && self.bodctrl.read().bodrstena().bit_is_set()
+    }
+
+    /// Switch the main clock between the PLL and the IRC/FRO
+    ///
+    /// The new selection only takes effect, once the update enable register is
+    /// toggled.
+    fn select_pll(&mut self, pll: bool) {
+        #[cfg(feature = "82x")]
+        {
+            // 0 selects the IRC, 3 the PLL output.
+            let sel = if pll { 3 } else { 0 };
+            self.mainclksel.write(|w| w.sel().bits(sel));
+            self.mainclkuen.write(|w| w.ena().clear_bit());
+            self.mainclkuen.write(|w| w.ena().set_bit());
+        }
+
+        #[cfg(feature = "845")]
+        {
+            // 0 selects the main clock before the PLL, 1 the PLL output.
+            let sel = if pll { 1 } else { 0 };
+            self.mainclkpllsel.write(|w| w.sel().bits(sel));
+            self.mainclkplluen.write(|w| w.ena().clear_bit());
+            self.mainclkplluen.write(|w| w.ena().set_bit());
+        }
+    }
+}
+
+/// Set the number of system clocks per flash access
+fn set_flash_wait_states(flash: &pac::FLASH_CTRL, clocks: u8) {
+    // FLASHTIM is the number of clocks minus one. The other bits of the
+    // register are reserved and must be preserved.
+    flash
+        .flashcfg
+        .modify(|_, w| unsafe { w.flashtim().bits(clocks - 1) });
+}
+
+/// An error that can occur while changing the main clock
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The system clock divider is zero
+    InvalidDivider,
+
+    /// The PLL can't generate the requested frequency
+    InvalidMultiplier,
+
+    /// The system clock would be faster than 30 MHz
+    TooFast,
+
+    /// The system clock would be faster than after reset, while the
+    /// brown-out reset is disabled
+    BrownOutResetDisabled,
+
+    /// The PLL didn't lock
+    PllLockTimeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidDivider => f.write_str("system clock divider is 0"),
+            Error::InvalidMultiplier => {
+                f.write_str("PLL multiplier out of range")
+            }
+            Error::TooFast => f.write_str("system clock too fast"),
+            Error::BrownOutResetDisabled => {
+                f.write_str("brown-out reset disabled")
+            }
+            Error::PllLockTimeout => f.write_str("PLL didn't lock"),
+        }
+    }
+}
+
+#[cfg(feature = "82x")]
+reg!(MAINCLKSEL, MAINCLKSEL, pac::SYSCON, mainclksel);
+#[cfg(feature = "82x")]
+reg!(MAINCLKUEN, MAINCLKUEN, pac::SYSCON, mainclkuen);
+#[cfg(feature = "845")]
+reg!(MAINCLKPLLSEL, MAINCLKPLLSEL, pac::SYSCON, mainclkpllsel);
+#[cfg(feature = "845")]
+reg!(MAINCLKPLLUEN, MAINCLKPLLUEN, pac::SYSCON, mainclkplluen);
+reg!(SYSPLLCLKSEL, SYSPLLCLKSEL, pac::SYSCON, syspllclksel);
+reg!(SYSPLLCLKUEN, SYSPLLCLKUEN, pac::SYSCON, syspllclkuen);
+reg!(SYSPLLCTRL, SYSPLLCTRL, pac::SYSCON, syspllctrl);
+reg!(SYSPLLSTAT, SYSPLLSTAT, pac::SYSCON, syspllstat);
+reg!(BODCTRL, BODCTRL, pac::SYSCON, bodctrl);
@@ -0,0 +1,137 @@
+//! A time base for timestamping events
+//!
+//! The entry point to this API is [`Timestamps`]. It turns an MRT channel into
+//! a free-running counter, which can be used to measure the time between
+//! events, without any interrupt load.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{timestamp::Timestamps, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//!
+//! let timestamps = Timestamps::new(timer);
+//!
+//! let start = timestamps.now();
+//! // Do something
+//! let elapsed = timestamps.now().duration_since(start);
+//!
+//! let us = elapsed.as_us();
+//! ```
+
+use embedded_hal::timer::CountDown as _;
+
+// `MAX_TICKS` is the largest value the MRT can count down from. It is one less
+// than a power of two on both targets, so wrapping arithmetic can be done using
+// a simple mask.
+use crate::mrt::{MrtChannel, MAX_TICKS};
+
+use crate::syscon;
+
+/// A free-running time base
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Timestamps {
+    timer: MrtChannel,
+}
+
+impl Timestamps {
+    /// Create a time base from an MRT channel
+    ///
+    /// The counter runs at the system clock frequency, and wraps around after
+    /// about 178 seconds on the LPC82x (31-bit timer), or about 1.4 seconds on
+    /// the LPC845 (24-bit timer), assuming a 12 MHz system clock. Durations
+    /// longer than that can't be measured.
+    pub fn new(mut timer: MrtChannel) -> Self {
+        // The channel is in repeat mode, so it reloads automatically. `start`
+        // adds one to the value, so this counts down from `MAX_TICKS`.
+        timer.start(MAX_TICKS - 1);
+
+        Timestamps { timer }
+    }
+
+    /// Return the current point in time
+    pub fn now(&self) -> Instant {
+        Instant {
+            ticks: MAX_TICKS - self.timer.remaining_ticks(),
+        }
+    }
+
+    /// Release the MRT channel
+    pub fn free(self) -> MrtChannel {
+        self.timer
+    }
+}
+
+/// A point in time
+///
+/// Returned by [`Timestamps::now`].
+///
+/// [`Timestamps::now`]: struct.Timestamps.html#method.now
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Instant {
+    ticks: u32,
+}
+
+impl Instant {
+    /// Return the raw counter value
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Return the time that has passed since an earlier point in time
+    ///
+    /// Handles a single wrap-around of the counter correctly. If more time has
+    /// passed than the counter can represent, the result is wrong.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        Duration {
+            ticks: self.ticks.wrapping_sub(earlier.ticks) & MAX_TICKS,
+        }
+    }
+}
+
+/// An amount of time
+///
+/// Returned by [`Instant::duration_since`].
+///
+/// [`Instant::duration_since`]: struct.Instant.html#method.duration_since
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Duration {
+    ticks: u32,
+}
+
+impl Duration {
+    /// Return the duration in clock ticks
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Return the duration in microseconds
+    ///
+    /// Converts the ticks using [`syscon::system_clock_hz`].
+    ///
+    /// [`syscon::system_clock_hz`]: ../syscon/fn.system_clock_hz.html
+    pub fn as_us(&self) -> u32 {
+        syscon::ticks_to_us(self.ticks as u64) as u32
+    }
+}
+
+/// Converts the ticks as they are
+///
+/// Only correct, if the system clock runs at 12 MHz, which is the case after
+/// reset. Use [`Duration::as_us`] otherwise.
+///
+/// [`Duration::as_us`]: struct.Duration.html#method.as_us
+impl From<Duration> for fugit::TimerDurationU32<12_000_000> {
+    fn from(duration: Duration) -> Self {
+        Self::from_ticks(duration.ticks)
+    }
+}
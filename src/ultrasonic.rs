@@ -0,0 +1,217 @@
+//! Helper for ultrasonic distance sensors, using CTimer capture
+//!
+//! Sensors like the HC-SR04 start a measurement, when their trigger input is
+//! pulsed high for at least 10 µs. They then output a pulse on their echo
+//! output, whose width is the time of flight of the ultrasonic burst, to the
+//! obstacle and back.
+//!
+//! The entry point to this API is [`Sensor`]. It generates the trigger pulse
+//! using the CTimer as a time base, and measures the echo pulse using the
+//! CTimer capture function on the T0_CAP0 input. This means the measurement is
+//! not affected by interrupt latency.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use lpc8xx_hal::{prelude::*, ultrasonic::Sensor, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//! let gpio = p.GPIO.enable(&mut syscon.handle);
+//!
+//! let trigger = swm.pins.pio0_16.into_gpio_pin(&gpio).into_output();
+//!
+//! let echo = swm.pins.pio0_17.into_swm_pin();
+//! let (echo, _) =
+//!     swm.movable_functions.t0_cap0.assign(echo, &mut swm_handle);
+//!
+//! let mut sensor = Sensor::new(p.CTIMER0, trigger, echo, &mut syscon.handle);
+//!
+//! match sensor.measure(30.millis()) {
+//!     Ok(measurement) => {
+//!         let distance_mm = measurement.distance_mm();
+//!     }
+//!     Err(_) => {
+//!         // No echo received
+//!     }
+//! }
+//! ```
+//!
+//! Please refer to the [examples in the repository] for more example code.
+//!
+//! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
+
+use core::fmt;
+
+use embedded_hal::digital::v2::OutputPin;
+use void::{ResultVoidExt as _, Void};
+
+use crate::{
+    ctimer::CTimer,
+    pac::CTIMER0,
+    swm::{self, T0_CAP0},
+    syscon,
+};
+
+/// The width of the trigger pulse in microseconds
+const TRIGGER_US: u32 = 10;
+
+/// Divides the 12 MHz CTimer clock down to 1 MHz, for 1 µs per tick
+const PRESCALER: u32 = 11;
+
+/// An ultrasonic distance sensor
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Sensor<Trigger, P> {
+    ct: CTIMER0,
+    trigger: Trigger,
+    echo: swm::Function<T0_CAP0, swm::state::Assigned<P>>,
+}
+
+impl<Trigger, P> Sensor<Trigger, P>
+where
+    Trigger: OutputPin<Error = Void>,
+{
+    /// Create the sensor helper
+    ///
+    /// Configures the CTimer to run at 1 MHz, assuming it is clocked at
+    /// 12 MHz, and to capture the counter value on both edges of `echo`.
+    pub fn new(
+        ctimer: CTimer,
+        mut trigger: Trigger,
+        echo: swm::Function<T0_CAP0, swm::state::Assigned<P>>,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        let ct = ctimer.free();
+
+        trigger.set_low().void_unwrap();
+
+        syscon.enable_clock(&ct);
+        unsafe { ct.pr.write(|w| w.prval().bits(PRESCALER)) };
+        ct.ccr.write(|w| {
+            w.cap0re().set_bit();
+            w.cap0fe().set_bit()
+        });
+        ct.tcr.write(|w| w.cen().set_bit());
+
+        Sensor { ct, trigger, echo }
+    }
+
+    /// Trigger a measurement and wait for the echo
+    ///
+    /// Blocks until the echo pulse has been received, or `timeout` has passed
+    /// since the end of the trigger pulse. HC-SR04-class sensors output a
+    /// pulse of about 38 ms, if there's no obstacle in range, so a timeout of
+    /// 30 ms or less means that case is reported as [`Error::Timeout`].
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn measure(
+        &mut self,
+        timeout: fugit::MicrosDurationU32,
+    ) -> Result<Measurement, Error> {
+        // Discard any edge that has been captured since the last measurement.
+        self.ct.ir.write(|w| w.cr0int().set_bit());
+
+        let start = self.now();
+        self.trigger.set_high().void_unwrap();
+        while self.now().wrapping_sub(start) < TRIGGER_US {}
+        self.trigger.set_low().void_unwrap();
+
+        let start = self.now();
+        let rising = self.wait_for_edge(start, timeout.to_micros())?;
+        let falling = self.wait_for_edge(start, timeout.to_micros())?;
+
+        Ok(Measurement {
+            echo_us: falling.wrapping_sub(rising),
+        })
+    }
+
+    /// Stop the CTimer and release the resources used by the sensor
+    pub fn free(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (
+        CTimer,
+        Trigger,
+        swm::Function<T0_CAP0, swm::state::Assigned<P>>,
+    ) {
+        self.ct.tcr.write(|w| w.crst().set_bit());
+        self.ct.tcr.write(|w| w.cen().clear_bit());
+        self.ct.ccr.reset();
+        syscon.disable_clock(&self.ct);
+
+        (CTimer::new(self.ct), self.trigger, self.echo)
+    }
+
+    fn now(&self) -> u32 {
+        self.ct.tc.read().tcval().bits()
+    }
+
+    /// Wait for the next captured edge and return its timestamp
+    fn wait_for_edge(
+        &mut self,
+        start: u32,
+        timeout: u32,
+    ) -> Result<u32, Error> {
+        while !self.ct.ir.read().cr0int().bit_is_set() {
+            if self.now().wrapping_sub(start) > timeout {
+                return Err(Error::Timeout);
+            }
+        }
+        self.ct.ir.write(|w| w.cr0int().set_bit());
+
+        Ok(self.ct.cr[0].read().cap().bits())
+    }
+}
+
+/// The result of a measurement
+///
+/// Returned by [`Sensor::measure`].
+///
+/// [`Sensor::measure`]: struct.Sensor.html#method.measure
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    echo_us: u32,
+}
+
+impl Measurement {
+    /// Return the width of the echo pulse in microseconds
+    ///
+    /// This is the time of flight of the ultrasonic burst, to the obstacle
+    /// and back.
+    pub fn echo_us(&self) -> u32 {
+        self.echo_us
+    }
+
+    /// Return the distance to the obstacle in millimeters
+    ///
+    /// Assumes a speed of sound of 343 m/s, which is the case in dry air at
+    /// 20 °C.
+    pub fn distance_mm(&self) -> u32 {
+        // The sound travels 0.343 mm/µs, and it travels the distance twice.
+        self.echo_us * 343 / 2000
+    }
+}
+
+/// An error that can occur during a measurement
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// No complete echo pulse has been received within the timeout
+    Timeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Timeout => f.write_str("no ultrasonic echo received"),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! A monotonic millisecond counter that keeps counting during deep-sleep mode
+//!
+//! Devices that sleep most of the time still need a notion of time, for
+//! example for protocol timeouts. The MRT is a precise time base while the
+//! microcontroller is running, but it is clocked by the system clock, which
+//! stops in deep-sleep mode. The WKT keeps running in deep-sleep mode, but has
+//! a low resolution, if it runs from the low-power clock.
+//!
+//! [`Uptime`] combines both into a single counter: An MRT channel measures the
+//! time while running (and in regular sleep mode), and the WKT measures the
+//! time spent in deep-sleep mode, entered using [`Uptime::deep_sleep`]. No
+//! periodic interrupt is required.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     clock::Ticks, syscon::WktWakeup, uptime::Uptime, Peripherals,
+//! };
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut pmu = p.PMU.split();
+//! let mut syscon = p.SYSCON.split();
+//! let [timer, _, _, _] = p.MRT0.split(&mut syscon.handle);
+//! let mut wkt = p.WKT.enable(&mut syscon.handle);
+//! let clock = pmu.low_power_clock.enable(&mut pmu.handle);
+//!
+//! syscon.handle.enable_interrupt_wakeup::<WktWakeup>();
+//!
+//! let mut uptime = Uptime::new(timer);
+//!
+//! let start = uptime.now_ms();
+//!
+//! // Sleep for one second, assuming a 10 kHz low-power clock
+//! let ticks = Ticks { value: 10_000, clock: &clock };
+//! unsafe { uptime.deep_sleep(ticks, &mut pmu.handle, &mut p.SCB, &mut wkt) };
+//!
+//! let elapsed_ms = uptime.now_ms() - start;
+//! ```
+//!
+//! [`Uptime`]: struct.Uptime.html
+//! [`Uptime::deep_sleep`]: struct.Uptime.html#method.deep_sleep
+
+use cortex_m::interrupt;
+use embedded_hal::timer::CountDown as _;
+
+use crate::{
+    clock::{self, Ticks},
+    mrt::MrtChannel,
+    pac::{self, Interrupt, NVIC},
+    pmu, syscon,
+    timestamp::{Instant, Timestamps},
+    wkt::{self, WKT},
+};
+
+/// A monotonic millisecond counter
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Uptime {
+    timestamps: Timestamps,
+
+    // The point in time up to which running time has been accounted for
+    last: Instant,
+
+    // Time spent running, in MRT ticks
+    running_ticks: u64,
+
+    // Time spent in deep-sleep mode, in microseconds
+    sleeping_us: u64,
+}
+
+impl Uptime {
+    /// Start counting from zero
+    ///
+    /// # Limitations
+    ///
+    /// The MRT channel wraps around, as described in [`Timestamps::new`]:
+    /// after about 178 seconds on the LPC82x, but after only about 1.4
+    /// seconds on the LPC845. [`now_ms`] (or [`deep_sleep`]) must be called at
+    /// least that often while the microcontroller is running, or time will be
+    /// lost.
+    ///
+    /// [`Timestamps::new`]: ../timestamp/struct.Timestamps.html#method.new
+    /// [`now_ms`]: #method.now_ms
+    /// [`deep_sleep`]: #method.deep_sleep
+    pub fn new(timer: MrtChannel) -> Self {
+        let timestamps = Timestamps::new(timer);
+        let last = timestamps.now();
+
+        Uptime {
+            timestamps,
+            last,
+            running_ticks: 0,
+            sleeping_us: 0,
+        }
+    }
+
+    /// Return the number of milliseconds since the counter was created
+    pub fn now_ms(&mut self) -> u64 {
+        self.update();
+        (syscon::ticks_to_us(self.running_ticks) + self.sleeping_us) / 1000
+    }
+
+    /// Enter deep-sleep mode, and account for the time spent there
+    ///
+    /// Uses the WKT, clocked by `ticks.clock`, to wake up after `ticks`. The
+    /// microcontroller can be woken up earlier by any other interrupt that is
+    /// enabled for wake-up. Either way, the time actually spent in deep-sleep
+    /// mode is measured using the WKT.
+    ///
+    /// The WKT interrupt must be enabled for wake-up using
+    /// [`syscon::Handle::enable_interrupt_wakeup`]. No WKT interrupt handler
+    /// is required, as the interrupt is unmasked only for the duration of this
+    /// method, and cleared before it returns.
+    ///
+    /// Returns the number of milliseconds since the counter was created, after
+    /// waking up.
+    ///
+    /// # Safety
+    ///
+    /// This method enters deep-sleep mode. Please refer to
+    /// [`pmu::Handle::enter_deep_sleep_mode`] for the requirements.
+    ///
+    /// [`syscon::Handle::enable_interrupt_wakeup`]: ../syscon/struct.Handle.html#method.enable_interrupt_wakeup
+    /// [`pmu::Handle::enter_deep_sleep_mode`]: ../pmu/struct.Handle.html#method.enter_deep_sleep_mode
+    pub unsafe fn deep_sleep<'clock, C>(
+        &mut self,
+        ticks: Ticks<'clock, C>,
+        pmu: &mut pmu::Handle,
+        scb: &mut pac::SCB,
+        wkt: &mut WKT,
+    ) -> u64
+    where
+        C: clock::Enabled + clock::Frequency + wkt::Clock,
+    {
+        // If we try to sleep for zero cycles, we'll never wake up again.
+        if ticks.value == 0 {
+            return self.now_ms();
+        }
+
+        // Account for the running time so far. The MRT is stopped in
+        // deep-sleep mode, and continues counting after waking up.
+        self.update();
+
+        wkt.select_clock::<C>();
+        wkt.start(ticks.value);
+
+        // Interrupt handlers won't run within the critical section, but the
+        // WKT interrupt still wakes the microcontroller up.
+        interrupt::free(|_| {
+            NVIC::unmask(Interrupt::WKT);
+            pmu.enter_deep_sleep_mode(scb);
+            NVIC::mask(Interrupt::WKT);
+        });
+
+        let slept = ticks.value - wkt.remaining();
+        self.sleeping_us += slept as u64 * 1_000_000 / ticks.clock.hz() as u64;
+
+        // Make sure the WKT interrupt won't fire, once it's unmasked again.
+        wkt.clear();
+        NVIC::unpend(Interrupt::WKT);
+
+        self.now_ms()
+    }
+
+    /// Release the MRT channel
+    pub fn free(self) -> MrtChannel {
+        self.timestamps.free()
+    }
+
+    fn update(&mut self) {
+        let now = self.timestamps.now();
+        self.running_ticks += now.duration_since(self.last).ticks() as u64;
+        self.last = now;
+    }
+}
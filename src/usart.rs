@@ -5,6 +5,16 @@
 //!
 //! The USART peripheral is described in the user manual, chapter 13.
 //!
+//! # Smart cards
+//!
+//! Smart card (ISO 7816) mode is out of scope. Unlike some other NXP parts,
+//! the LPC8xx USART has no SCICTRL register, so there's no hardware support
+//! for retransmission on NACK, guard times, or the inverse convention. Even
+//! parity and two stop bits can be configured using [`Settings`], and
+//! [`USART::enable_sync_master`] can output a continuous clock on SCLK to
+//! drive the card, but the rest of the protocol would have to be implemented
+//! in software.
+//!
 //! # Examples
 //!
 //! ``` no_run
@@ -54,19 +64,27 @@
 //! Please refer to the [examples in the repository] for more example code.
 //!
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
+//! [`Settings`]: struct.Settings.html
+//! [`USART::enable_sync_master`]: struct.USART.html#method.enable_sync_master
 
 use core::fmt;
+use core::mem::MaybeUninit;
 use core::ops::Deref;
+use core::slice;
+use core::sync::atomic::{compiler_fence, Ordering};
 
 use embedded_hal::blocking::serial::write::Default as BlockingWriteDefault;
 use embedded_hal::serial::{Read, Write};
+use embedded_hal::timer::CountDown;
 use nb::{self, block};
 use void::Void;
 
 use crate::{
     dma, init_state,
+    mrt::MrtChannel,
     pac::{self, usart0::TXDAT, Interrupt, NVIC},
-    swm::{self, FunctionTrait, PinTrait},
+    pmu::{Wakeup, WakeupSource},
+    swm::{self, pin_state, FunctionTrait, Pin, PinTrait},
     syscon::{self, clocksource::UsartClock, PeripheralClock},
 };
 
@@ -81,7 +99,7 @@ use crate::{
 /// [module documentation]: index.html
 pub struct USART<I, State = init_state::Enabled> {
     usart: I,
-    _state: State,
+    state: State,
 }
 
 impl<I> USART<I, init_state::Disabled>
@@ -91,7 +109,7 @@ where
     pub(crate) fn new(usart: I) -> Self {
         USART {
             usart,
-            _state: init_state::Disabled,
+            state: init_state::Disabled,
         }
     }
 
@@ -104,6 +122,9 @@ where
     /// Consumes this instance of `USART` and returns another instance that has
     /// its `State` type parameter set to [`Enabled`].
     ///
+    /// The switch matrix functions for RX and TX are kept by the returned
+    /// instance. You can get them back by calling [`USART::disable`].
+    ///
     /// # Limitations
     ///
     /// For USART to function correctly, the UARTFRG reset must be cleared. This
@@ -117,14 +138,15 @@ where
     /// [`Disabled`]: ../init_state/struct.Disabled.html
     /// [`Enabled`]: ../init_state/struct.Enabled.html
     /// [`BaudRate`]: struct.BaudRate.html
+    /// [`USART::disable`]: #method.disable
     /// [module documentation]: index.html
     pub fn enable<RxPin, TxPin, CLOCK>(
         self,
         clock: &UsartClock<CLOCK>,
         syscon: &mut syscon::Handle,
-        _: swm::Function<I::Rx, swm::state::Assigned<RxPin>>,
-        _: swm::Function<I::Tx, swm::state::Assigned<TxPin>>,
-    ) -> USART<I, init_state::Enabled>
+        rx: swm::Function<I::Rx, swm::state::Assigned<RxPin>>,
+        tx: swm::Function<I::Tx, swm::state::Assigned<TxPin>>,
+    ) -> USART<I, init_state::Enabled<Functions<I, RxPin, TxPin>>>
     where
         RxPin: PinTrait,
         TxPin: PinTrait,
@@ -169,12 +191,12 @@ where
 
         USART {
             usart: self.usart,
-            _state: init_state::Enabled(()),
+            state: init_state::Enabled((rx, tx)),
         }
     }
 }
 
-impl<I> USART<I, init_state::Enabled>
+impl<I, Pins> USART<I, init_state::Enabled<Pins>>
 where
     I: Instance,
 {
@@ -185,20 +207,25 @@ where
     /// disabled will not compile.
     ///
     /// Consumes this instance of `USART` and returns another instance that has
-    /// its `State` type parameter set to [`Disabled`].
+    /// its `State` type parameter set to [`Disabled`], together with the
+    /// switch matrix functions that were passed to [`USART::enable`]. Those
+    /// can then be unassigned, to use their pins for something else.
     ///
     /// [`Enabled`]: ../init_state/struct.Enabled.html
     /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`USART::enable`]: #method.enable
     pub fn disable(
         self,
         syscon: &mut syscon::Handle,
-    ) -> USART<I, init_state::Disabled> {
+    ) -> (USART<I, init_state::Disabled>, Pins) {
         syscon.disable_clock(&self.usart);
 
-        USART {
+        let usart = USART {
             usart: self.usart,
-            _state: init_state::Disabled,
-        }
+            state: init_state::Disabled,
+        };
+
+        (usart, self.state.0)
     }
 
     /// Enable the USART interrupts
@@ -211,18 +238,334 @@ where
         unsafe { NVIC::unmask(I::INTERRUPT) };
     }
 
+    /// Enable the start interrupt
+    ///
+    /// The interrupt is triggered, when a start bit is detected on the RX
+    /// line. This can be used to wake up the microcontroller from deep-sleep
+    /// mode, when data arrives (see [`pmu::Handle::deep_sleep`]). It will not
+    /// actually work unless the interrupts for this peripheral have also been
+    /// enabled via the NVIC. See [`enable_interrupts`].
+    ///
+    /// [`pmu::Handle::deep_sleep`]: ../pmu/struct.Handle.html#method.deep_sleep
+    /// [`enable_interrupts`]: #method.enable_interrupts
+    pub fn enable_start_interrupt(&mut self) {
+        self.usart.intenset.write(|w| w.starten().set_bit());
+    }
+
+    /// Disable the start interrupt
+    pub fn disable_start_interrupt(&mut self) {
+        self.usart.intenclr.write(|w| w.startclr().set_bit());
+    }
+
+    /// Ignore all received characters, until `delimiter` is received
+    ///
+    /// Uses the address detection of the USART, so the delimiter is matched
+    /// in hardware. Characters other than the delimiter are discarded without
+    /// setting RXRDY, which means the RXRDY interrupt only fires for the
+    /// delimiter. See user manual, section 13.6.2.
+    ///
+    /// The hardware doesn't stop matching after the delimiter has been
+    /// received. Call [`disable_delimiter_match`] to receive the characters
+    /// that follow it. [`PacketReceiver::new_delimited`] does this
+    /// automatically.
+    ///
+    /// Returns [`DelimiterError::MsbClear`], if the most significant bit of
+    /// `delimiter` is not set, as the hardware ignores such characters in
+    /// this mode. This rules out delimiters like 0x7E, as used by HDLC.
+    ///
+    /// Waits until the transmitter is idle, then temporarily disables the
+    /// USART, like [`configure`].
+    ///
+    /// [`disable_delimiter_match`]: #method.disable_delimiter_match
+    /// [`PacketReceiver::new_delimited`]: struct.PacketReceiver.html#method.new_delimited
+    /// [`DelimiterError::MsbClear`]: enum.DelimiterError.html#variant.MsbClear
+    /// [`configure`]: #method.configure
+    pub fn enable_delimiter_match(
+        &mut self,
+        delimiter: u8,
+    ) -> Result<(), DelimiterError> {
+        self.tx().bdrain();
+        set_delimiter(&self.usart, delimiter)?;
+        self.usart.ctl.modify(|_, w| w.addrdet().enabled());
+
+        Ok(())
+    }
+
+    /// Stop ignoring characters other than the delimiter
+    ///
+    /// Please refer to [`enable_delimiter_match`] for more information.
+    ///
+    /// [`enable_delimiter_match`]: #method.enable_delimiter_match
+    pub fn disable_delimiter_match(&mut self) {
+        self.usart.ctl.modify(|_, w| w.addrdet().disabled());
+    }
+
+    /// Change the character format
+    ///
+    /// [`USART::enable`] configures the USART for 8 data bits, no parity, and
+    /// 1 stop bit. This method can be used to change that.
+    ///
+    /// Waits until the transmitter is idle, then temporarily disables the
+    /// USART, as required by the user manual, section 13.6.1. Characters that
+    /// are received while this method runs might be lost.
+    ///
+    /// [`USART::enable`]: #method.enable
+    pub fn configure(&mut self, settings: &Settings) {
+        self.tx().bdrain();
+
+        self.usart.cfg.modify(|_, w| w.enable().disabled());
+        self.usart.cfg.modify(|_, w| {
+            match settings.parity {
+                Parity::None => w.paritysel().no_parity(),
+                Parity::Even => w.paritysel().even_parity(),
+                Parity::Odd => w.paritysel().odd_parity(),
+            };
+            match settings.stop_bits {
+                StopBits::One => w.stoplen().bit_1(),
+                StopBits::Two => w.stoplen().bits_2(),
+            };
+            w
+        });
+        self.usart.cfg.modify(|_, w| w.enable().enabled());
+    }
+
+    /// Switch to synchronous master mode, with the clock output on SCLK
+    ///
+    /// In synchronous mode, characters are transmitted and received in sync
+    /// with the clock that the USART outputs on SCLK. Received data is sampled
+    /// on `edge` of that clock. The clock frequency is the USART clock divided
+    /// by the baud rate divider. The oversampling value is not used.
+    ///
+    /// By default, SCLK is only active while a character is being
+    /// transmitted. If `continuous` is `true`, it runs all the time instead,
+    /// as required to clock a smart card, for example.
+    ///
+    /// Waits until the transmitter is idle, then temporarily disables the
+    /// USART, like [`configure`]. The returned [`Sclk`] needs to be passed to
+    /// [`disable_sync_master`], to switch back to asynchronous mode.
+    ///
+    /// [`configure`]: #method.configure
+    /// [`Sclk`]: struct.Sclk.html
+    /// [`disable_sync_master`]: #method.disable_sync_master
+    pub fn enable_sync_master<SclkPin>(
+        &mut self,
+        sclk: swm::Function<I::Sclk, swm::state::Assigned<SclkPin>>,
+        edge: ClockEdge,
+        continuous: bool,
+    ) -> Sclk<I::Sclk, SclkPin>
+    where
+        SclkPin: PinTrait,
+        I::Sclk: FunctionTrait<SclkPin>,
+    {
+        self.tx().bdrain();
+
+        self.usart.cfg.modify(|_, w| w.enable().disabled());
+        self.usart.cfg.modify(|_, w| {
+            w.syncen().synchronous_mode();
+            w.syncmst().master();
+            match edge {
+                ClockEdge::Falling => w.clkpol().falling_edge(),
+                ClockEdge::Rising => w.clkpol().rising_edge(),
+            }
+        });
+        self.usart.ctl.modify(|_, w| {
+            if continuous {
+                w.cc().continous_clock()
+            } else {
+                w.cc().clock_on_character()
+            }
+        });
+        self.usart.cfg.modify(|_, w| w.enable().enabled());
+
+        Sclk { function: sclk }
+    }
+
+    /// Switch back to asynchronous mode
+    ///
+    /// Undoes [`enable_sync_master`] and returns the SCLK function, so it can
+    /// be unassigned. Waits until the transmitter is idle, then temporarily
+    /// disables the USART, like [`configure`].
+    ///
+    /// [`enable_sync_master`]: #method.enable_sync_master
+    /// [`configure`]: #method.configure
+    pub fn disable_sync_master<SclkPin>(
+        &mut self,
+        sclk: Sclk<I::Sclk, SclkPin>,
+    ) -> swm::Function<I::Sclk, swm::state::Assigned<SclkPin>> {
+        self.tx().bdrain();
+
+        self.usart.cfg.modify(|_, w| w.enable().disabled());
+        self.usart.ctl.modify(|_, w| w.cc().clock_on_character());
+        self.usart.cfg.modify(|_, w| {
+            w.syncen().asynchronous_mode();
+            w.syncmst().slave();
+            w.clkpol().falling_edge()
+        });
+        self.usart.cfg.modify(|_, w| w.enable().enabled());
+
+        sclk.function
+    }
+
+    /// Change the clock configuration
+    ///
+    /// This can be used to change the baud rate or the oversampling value.
+    /// Please refer to the documentation of [`UsartClock`] for details on the
+    /// oversampling value.
+    ///
+    /// Waits until the transmitter is idle, then temporarily disables the
+    /// USART. Characters that are received while this method runs might be
+    /// lost.
+    ///
+    /// [`UsartClock`]: ../syscon/clocksource/struct.UsartClock.html
+    pub fn set_clock<CLOCK>(
+        &mut self,
+        clock: &UsartClock<CLOCK>,
+        syscon: &mut syscon::Handle,
+    ) where
+        UsartClock<CLOCK>: PeripheralClock<I>,
+    {
+        self.tx().bdrain();
+
+        self.usart.cfg.modify(|_, w| w.enable().disabled());
+
+        clock.select_clock(syscon);
+        self.usart
+            .brg
+            .write(|w| unsafe { w.brgval().bits(clock.psc) });
+        self.usart
+            .osr
+            .write(|w| unsafe { w.osrval().bits(clock.osrval) });
+
+        self.usart.cfg.modify(|_, w| w.enable().enabled());
+    }
+
     /// Return USART receiver
     pub fn rx(&self) -> Rx<I> {
-        Rx(self)
+        Rx(&self.usart)
     }
 
     /// Return USART transmitter
     pub fn tx(&self) -> Tx<I> {
-        Tx(self)
+        Tx(&self.usart)
+    }
+}
+
+#[cfg(feature = "fault_injection")]
+impl<I, Pins> USART<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    /// Connect the transmitter to the receiver internally
+    ///
+    /// In loopback mode, the receiver reads the transmitter's output instead
+    /// of the RX pin. See user manual, section 13.6.1.
+    ///
+    /// This method is only available, if the `fault_injection` feature is
+    /// enabled.
+    pub fn enable_loopback(&mut self) {
+        self.usart.cfg.modify(|_, w| w.loop_().loopback());
+    }
+
+    /// Disconnect the transmitter from the receiver
+    ///
+    /// Reverts [`enable_loopback`].
+    ///
+    /// This method is only available, if the `fault_injection` feature is
+    /// enabled.
+    ///
+    /// [`enable_loopback`]: #method.enable_loopback
+    pub fn disable_loopback(&mut self) {
+        self.usart.cfg.modify(|_, w| w.loop_().normal());
+    }
+
+    /// Transmit a character with an incorrect parity bit
+    ///
+    /// Waits until the transmitter is idle, then switches to the opposite
+    /// parity for the duration of one character, so a receiver that uses the
+    /// configured parity reports [`Error::Parity`].
+    ///
+    /// The USART's own receiver always uses the same parity as the
+    /// transmitter, so it won't detect the error, even in loopback mode. To
+    /// receive the corrupted character on the same microcontroller, assign
+    /// the RX function of another USART to the TX pin.
+    ///
+    /// Returns [`FaultError::NoParity`], if the USART is configured without
+    /// parity (see [`configure`]).
+    ///
+    /// This method is only available, if the `fault_injection` feature is
+    /// enabled.
+    ///
+    /// [`Error::Parity`]: enum.Error.html#variant.Parity
+    /// [`FaultError::NoParity`]: enum.FaultError.html#variant.NoParity
+    /// [`configure`]: #method.configure
+    pub fn inject_parity_error(&mut self, word: u8) -> Result<(), FaultError> {
+        let parity = self.usart.cfg.read().paritysel();
+        let (configured, inverted) = if parity.is_even_parity() {
+            (Parity::Even, Parity::Odd)
+        } else if parity.is_odd_parity() {
+            (Parity::Odd, Parity::Even)
+        } else {
+            return Err(FaultError::NoParity);
+        };
+
+        self.tx().bdrain();
+        self.set_parity(inverted);
+
+        // Can't fail, as the error type is `Void`.
+        let _ = block!(self.tx().write(word));
+        self.tx().bdrain();
+
+        self.set_parity(configured);
+
+        Ok(())
+    }
+
+    /// Cause a framing error in the USART's own receiver
+    ///
+    /// Transmits a break, until the receiver has detected it. The receiver
+    /// then reports [`Error::Framing`] for the character it received during
+    /// the break.
+    ///
+    /// This method blocks forever, unless the receiver can see the
+    /// transmitter's output. Use [`enable_loopback`], or connect the TX and
+    /// RX pins.
+    ///
+    /// This method is only available, if the `fault_injection` feature is
+    /// enabled.
+    ///
+    /// [`Error::Framing`]: enum.Error.html#variant.Framing
+    /// [`enable_loopback`]: #method.enable_loopback
+    pub fn inject_framing_error(&mut self) {
+        let mut tx = self.tx();
+
+        tx.start_break();
+        while self.usart.stat.read().rxbrk().bit_is_clear() {}
+        tx.end_break();
+    }
+
+    fn set_parity(&mut self, parity: Parity) {
+        // The USART must be disabled while its configuration is changed. See
+        // user manual, section 13.6.1.
+        self.usart.cfg.modify(|_, w| w.enable().disabled());
+        self.usart.cfg.modify(|_, w| match parity {
+            Parity::None => w.paritysel().no_parity(),
+            Parity::Even => w.paritysel().even_parity(),
+            Parity::Odd => w.paritysel().odd_parity(),
+        });
+        self.usart.cfg.modify(|_, w| w.enable().enabled());
+    }
+}
+
+impl<I, Pins> WakeupSource for USART<I, init_state::Enabled<Pins>>
+where
+    I: Instance,
+{
+    fn wakeup(&self) -> Wakeup {
+        Wakeup::Interrupt(I::WAKEUP)
     }
 }
 
-impl<I, State> USART<I, State> {
+impl<I> USART<I, init_state::Disabled> {
     /// Return the raw peripheral
     ///
     /// This method serves as an escape hatch from the HAL API. It returns the
@@ -240,8 +583,44 @@ impl<I, State> USART<I, State> {
     }
 }
 
+impl<I, Pins> USART<I, init_state::Enabled<Pins>> {
+    /// Return the raw peripheral and the switch matrix functions
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// Unlike [`USART::disable`], this leaves the USART enabled. The switch
+    /// matrix functions that were passed to [`USART::enable`] are returned as
+    /// well, so they can be unassigned and their pins used for something
+    /// else.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    /// [`USART::disable`]: #method.disable
+    /// [`USART::enable`]: #method.enable
+    pub fn free(self) -> (I, Pins) {
+        (self.usart, self.state.0)
+    }
+}
+
+/// The switch matrix functions held by an enabled [`USART`]
+///
+/// Returned by [`USART::disable`].
+///
+/// [`USART`]: struct.USART.html
+/// [`USART::disable`]: struct.USART.html#method.disable
+pub type Functions<I, RxPin, TxPin> = (
+    swm::Function<<I as Instance>::Rx, swm::state::Assigned<RxPin>>,
+    swm::Function<<I as Instance>::Tx, swm::state::Assigned<TxPin>>,
+);
+
 /// USART receiver
-pub struct Rx<'usart, I: 'usart>(&'usart USART<I>);
+pub struct Rx<'usart, I: 'usart>(&'usart I);
 
 impl<'usart, I> Rx<'usart, I>
 where
@@ -255,12 +634,59 @@ where
     ///
     /// [`enable_interrupts`]: #method.enable_interrupts
     pub fn enable_rxrdy_interrupt(&mut self) {
-        self.0.usart.intenset.write(|w| w.rxrdyen().set_bit());
+        self.0.intenset.write(|w| w.rxrdyen().set_bit());
     }
 
     /// Disable the RXRDY interrupt
     pub fn disable_rxrdy_interrupt(&mut self) {
-        self.0.usart.intenclr.write(|w| w.rxrdyclr().set_bit());
+        self.0.intenclr.write(|w| w.rxrdyclr().set_bit());
+    }
+
+    /// Read all available characters into an uninitialized buffer
+    ///
+    /// Reads characters, until either none is available, or `buffer` is full.
+    /// Returns the part of `buffer` that has been written to, as a regular
+    /// slice of exactly that length. This means the buffer doesn't need to be
+    /// initialized first. Call this method from the RXRDY interrupt handler
+    /// (see [`enable_rxrdy_interrupt`]), to receive into a larger buffer.
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)`, if no character is available and
+    /// `buffer` isn't empty. If a character was received with an error, that
+    /// error is returned, along with the characters that were read before it
+    /// during this call. Those can be empty.
+    ///
+    /// [`enable_rxrdy_interrupt`]: #method.enable_rxrdy_interrupt
+    pub fn read_uninit<'b>(
+        &mut self,
+        buffer: &'b mut [MaybeUninit<u8>],
+    ) -> nb::Result<&'b mut [u8], (Error, &'b mut [u8])> {
+        let mut len = 0;
+        let mut error = None;
+        for slot in buffer.iter_mut() {
+            match self.read() {
+                Ok(word) => *slot = MaybeUninit::new(word),
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(err)) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+            len += 1;
+        }
+
+        if len == 0 && error.is_none() && !buffer.is_empty() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Sound, as the first `len` elements have been initialized above.
+        let init = unsafe {
+            slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, len)
+        };
+
+        match error {
+            Some(error) => Err(nb::Error::Other((error, init))),
+            None => Ok(init),
+        }
     }
 }
 
@@ -271,7 +697,7 @@ where
     type Error = Error;
 
     fn read(&mut self) -> nb::Result<u8, Self::Error> {
-        let stat = self.0.usart.stat.read();
+        let stat = self.0.stat.read();
 
         if stat.rxbrk().bit_is_set() {
             return Err(nb::Error::WouldBlock);
@@ -280,7 +706,7 @@ where
         if stat.rxrdy().bit_is_set() {
             // It's important to read this register all at once, as reading
             // it changes the status flags.
-            let rx_dat_stat = self.0.usart.rxdatstat.read();
+            let rx_dat_stat = self.0.rxdatstat.read();
 
             if stat.overrunint().bit_is_set() {
                 Err(nb::Error::Other(Error::Overrun))
@@ -303,7 +729,7 @@ where
 }
 
 /// USART transmitter
-pub struct Tx<'usart, I: 'usart>(&'usart USART<I>);
+pub struct Tx<'usart, I: 'usart>(&'usart I);
 
 impl<'usart, I> Tx<'usart, I>
 where
@@ -317,12 +743,123 @@ where
     ///
     /// [`enable_interrupts`]: #method.enable_interrupts
     pub fn enable_txrdy_interrupt(&mut self) {
-        self.0.usart.intenset.write(|w| w.txrdyen().set_bit());
+        self.0.intenset.write(|w| w.txrdyen().set_bit());
     }
 
     /// Disable the TXRDY interrupt
     pub fn disable_txrdy_interrupt(&mut self) {
-        self.0.usart.intenclr.write(|w| w.txrdyclr().set_bit());
+        self.0.intenclr.write(|w| w.txrdyclr().set_bit());
+    }
+
+    /// Wait until the transmitter is idle
+    ///
+    /// Returns `Ok(())` once the transmitter is idle, i.e. the last character
+    /// has been shifted out completely, including its stop bits. Returns
+    /// `Err(nb::Error::WouldBlock)` otherwise.
+    ///
+    /// This checks the TXIDLE flag, not just TXRDY. TXRDY only indicates that
+    /// TXDAT can accept another character, while the previous one might still
+    /// be in the transmit shift register. Use this method before disabling the
+    /// USART, switching the direction of an RS-485 transceiver, or entering a
+    /// low-power mode. See user manual, section 13.6.3.
+    ///
+    /// This is also what [`Write::flush`] does. Please refer to [`bdrain`],
+    /// if you need a blocking variant.
+    ///
+    /// [`Write::flush`]: #method.flush
+    /// [`bdrain`]: #method.bdrain
+    pub fn drain(&mut self) -> nb::Result<(), Void> {
+        if self.0.stat.read().txidle().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        Ok(())
+    }
+
+    /// Block until the transmitter is idle
+    ///
+    /// This is the blocking variant of [`drain`]. Please refer to its
+    /// documentation for details.
+    ///
+    /// [`drain`]: #method.drain
+    pub fn bdrain(&mut self) {
+        // Can't fail, as the error type is `Void`.
+        let _ = block!(self.drain());
+    }
+
+    /// Write characters from an iterator, as long as TXDAT can accept them
+    ///
+    /// Writes characters from `words` to TXDAT, until either the iterator is
+    /// exhausted, or TXRDY indicates that TXDAT is full. The iterator is only
+    /// advanced when a character can be written, so no characters are lost.
+    ///
+    /// Returns `Ok(())`, once the iterator is exhausted. Returns
+    /// `Err(nb::Error::WouldBlock)`, if the iterator might have characters
+    /// left. Call this method again with the same iterator in that case, for
+    /// example from the TXRDY interrupt handler.
+    ///
+    /// This allows sending generated data, without copying it into a buffer
+    /// first. Please refer to [`bwrite_iter`] for a blocking variant.
+    ///
+    /// [`bwrite_iter`]: #method.bwrite_iter
+    pub fn write_iter(
+        &mut self,
+        words: &mut impl Iterator<Item = u8>,
+    ) -> nb::Result<(), Void> {
+        loop {
+            if self.0.stat.read().txrdy().bit_is_clear() {
+                return Err(nb::Error::WouldBlock);
+            }
+
+            match words.next() {
+                Some(word) => unsafe {
+                    self.0.txdat.write(|w| w.txdat().bits(word as u16));
+                },
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Write all characters from an iterator, blocking until TXDAT takes them
+    ///
+    /// This is the blocking variant of [`write_iter`]. It returns, once the
+    /// last character has been written to TXDAT, which means it might still
+    /// be transmitting. Use [`bdrain`] to wait until the transmitter is idle.
+    ///
+    /// [`write_iter`]: #method.write_iter
+    /// [`bdrain`]: #method.bdrain
+    pub fn bwrite_iter(&mut self, words: impl IntoIterator<Item = u8>) {
+        let mut words = words.into_iter();
+
+        // Can't fail, as the error type is `Void`.
+        let _ = block!(self.write_iter(&mut words));
+    }
+
+    /// Start sending a break
+    ///
+    /// Waits for the current character to be sent completely, then holds the
+    /// TX line low until [`end_break`] is called. See user manual, section
+    /// 13.6.2.
+    ///
+    /// [`end_break`]: #method.end_break
+    pub fn start_break(&mut self) {
+        // Disable the transmitter first, so the current character isn't
+        // corrupted.
+        self.0.ctl.modify(|_, w| w.txdis().disabled());
+        while self.0.stat.read().txdisstat().bit_is_clear() {}
+
+        self.0.ctl.modify(|_, w| w.txbrken().set_bit());
+    }
+
+    /// Stop sending a break
+    ///
+    /// Releases the TX line and re-enables the transmitter. Please refer to
+    /// [`start_break`] for more information.
+    ///
+    /// [`start_break`]: #method.start_break
+    pub fn end_break(&mut self) {
+        self.0.ctl.modify(|_, w| w.txbrken().normal());
+        self.0.ctl.modify(|_, w| w.txdis().enabled());
     }
 }
 
@@ -333,28 +870,25 @@ where
     type Error = Void;
 
     fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
-        if self.0.usart.stat.read().txrdy().bit_is_clear() {
+        if self.0.stat.read().txrdy().bit_is_clear() {
             return Err(nb::Error::WouldBlock);
         }
 
         unsafe {
-            self.0.usart.txdat.write(|w| w.txdat().bits(word as u16));
+            self.0.txdat.write(|w| w.txdat().bits(word as u16));
         }
 
         Ok(())
     }
 
     fn flush(&mut self) -> nb::Result<(), Self::Error> {
-        if self.0.usart.stat.read().txidle().bit_is_clear() {
-            return Err(nb::Error::WouldBlock);
-        }
-
-        Ok(())
+        self.drain()
     }
 }
 
 impl<'usart, I> BlockingWriteDefault<u8> for Tx<'usart, I> where I: Instance {}
 
+#[cfg(feature = "fmt")]
 impl<'usart, I> fmt::Write for Tx<'usart, I>
 where
     Self: BlockingWriteDefault<u8>,
@@ -381,7 +915,674 @@ where
     }
 
     fn end_addr(&mut self) -> *mut u8 {
-        &self.0.usart.txdat as *const _ as *mut TXDAT as *mut u8
+        &self.0.txdat as *const _ as *mut TXDAT as *mut u8
+    }
+}
+
+impl<'usart, I> dma::Source for Rx<'usart, I>
+where
+    I: Instance,
+{
+    type Error = Void;
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // Nothing to wrap up. The DMA controller reads RXDAT directly.
+        Ok(())
+    }
+
+    fn end_addr(&mut self) -> *const u8 {
+        &self.0.rxdat as *const _ as *const u8
+    }
+}
+
+/// Receives variable-length frames using DMA and an inter-character timeout
+///
+/// Received bytes are written into a pool of `N` buffers of `S` bytes each, by
+/// the DMA controller. A frame is considered complete, once no byte has been
+/// received for the timeout that is passed to [`PacketReceiver::new`]. The
+/// next frame is then received into the next free buffer. This means no
+/// interrupt is required per byte.
+///
+/// The timeout is checked by [`PacketReceiver::poll`], using an MRT channel.
+/// `poll` needs to be called regularly, either from the main loop, or from the
+/// MRT interrupt handler. In the latter case, enable the interrupt of the MRT
+/// channel before passing it to [`PacketReceiver::new`].
+///
+/// Since the timer isn't restarted for every byte, the end of a frame is
+/// detected between one and two timeouts after its last byte.
+///
+/// Frames that are longer than `S` bytes are not split cleanly. Once a buffer
+/// is full, it is completed at the next timeout check. Bytes received until
+/// then are lost, and whatever remains of the frame afterwards is received as
+/// a new frame. Choose `S` to fit the longest expected frame.
+///
+/// If frames start with a delimiter, use [`PacketReceiver::new_delimited`] to
+/// have the hardware discard everything between the end of one frame and the
+/// delimiter of the next.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{
+///     dma, init_state, mrt::MrtChannel, pac::USART0, usart::PacketReceiver,
+///     usart::USART,
+/// };
+///
+/// static mut BUFFERS: [[u8; 64]; 4] = [[0; 64]; 4];
+///
+/// fn receive<Pins>(
+///     usart: &USART<USART0, init_state::Enabled<Pins>>,
+///     channel: dma::Channel<
+///         dma::Channel0,
+///         init_state::Enabled<&dma::Handle>,
+///     >,
+///     timer: MrtChannel,
+/// ) -> ! {
+///     use lpc8xx_hal::prelude::*;
+///
+///     // Sound, as this is the only place that accesses `BUFFERS`.
+///     let buffers = unsafe { &mut BUFFERS };
+///
+///     let mut receiver =
+///         PacketReceiver::new(usart, channel, timer, 500.micros(), buffers);
+///
+///     loop {
+///         receiver.poll().ok();
+///
+///         receiver.receive(|frame| {
+///             // Process frame
+///         });
+///     }
+/// }
+/// ```
+///
+/// [`PacketReceiver::new`]: #method.new
+/// [`PacketReceiver::poll`]: #method.poll
+/// [`PacketReceiver::new_delimited`]: #method.new_delimited
+pub struct PacketReceiver<'dma, 'usart, T, I, const N: usize, const S: usize>
+where
+    T: dma::ChannelTrait,
+    I: 'usart,
+{
+    transfer:
+        Option<dma::ReadTransfer<'dma, T, Rx<'usart, I>, &'static mut [u8]>>,
+    timer: MrtChannel,
+
+    // The DMA channel and the receiver, while waiting for a delimiter.
+    // Exactly one of `transfer` and `idle` is `Some` between method calls.
+    idle: Option<(
+        dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        Rx<'usart, I>,
+    )>,
+    delimiter: Option<u8>,
+
+    // Only accessed through raw pointers, as one of the buffers is being
+    // written to by the DMA controller at any time.
+    buffers: *mut [[u8; S]; N],
+    lens: [usize; N],
+
+    // The buffers are used as a ring. Starting with `first`, there are
+    // `complete` buffers that contain complete frames, followed by the buffer
+    // that is being received into.
+    first: usize,
+    complete: usize,
+
+    // The number of bytes received at the last timeout check
+    received: usize,
+}
+
+impl<'dma, 'usart, T, I, const N: usize, const S: usize>
+    PacketReceiver<'dma, 'usart, T, I, N, S>
+where
+    T: dma::ChannelTrait,
+    I: Instance,
+{
+    /// Create a packet receiver and start receiving
+    ///
+    /// `buffers` need to be kept in a `static`, as the DMA controller writes to
+    /// them while a transfer is ongoing.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `N` is smaller than 2, or if `S` is zero or larger than 1024.
+    /// Also panics, if `timeout` is longer than `timer` supports (see
+    /// [`MrtChannel::max_us`]).
+    ///
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new<Pins>(
+        usart: &'usart USART<I, init_state::Enabled<Pins>>,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        timer: MrtChannel,
+        timeout: fugit::MicrosDurationU32,
+        buffers: &'static mut [[u8; S]; N],
+    ) -> Self {
+        Self::create(usart, channel, timer, timeout, None, buffers)
+    }
+
+    /// Create a packet receiver for frames that start with a delimiter
+    ///
+    /// Like [`new`], but every frame has to start with `delimiter`. The
+    /// hardware discards everything that is received before the delimiter
+    /// (see [`USART::enable_delimiter_match`]). The delimiter itself is not
+    /// part of the received frame. The end of a frame is still detected using
+    /// the timeout.
+    ///
+    /// Once the delimiter has been received, [`on_interrupt`] needs to start
+    /// the DMA transfer for the rest of the frame. Call it from the USART's
+    /// interrupt handler and unmask the interrupt (see
+    /// [`USART::enable_interrupts`]). The interrupt handler must run before
+    /// the character after the delimiter has been received completely, or
+    /// that character might be lost.
+    ///
+    /// Returns [`DelimiterError::MsbClear`], if the most significant bit of
+    /// `delimiter` is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `N` is smaller than 2, or if `S` is zero or larger than 1024.
+    /// Also panics, if `timeout` is longer than `timer` supports (see
+    /// [`MrtChannel::max_us`]).
+    ///
+    /// [`new`]: #method.new
+    /// [`USART::enable_delimiter_match`]: struct.USART.html#method.enable_delimiter_match
+    /// [`on_interrupt`]: #method.on_interrupt
+    /// [`USART::enable_interrupts`]: struct.USART.html#method.enable_interrupts
+    /// [`DelimiterError::MsbClear`]: enum.DelimiterError.html#variant.MsbClear
+    /// [`MrtChannel::max_us`]: ../mrt/struct.MrtChannel.html#method.max_us
+    pub fn new_delimited<Pins>(
+        usart: &'usart USART<I, init_state::Enabled<Pins>>,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        timer: MrtChannel,
+        timeout: fugit::MicrosDurationU32,
+        delimiter: u8,
+        buffers: &'static mut [[u8; S]; N],
+    ) -> Result<Self, DelimiterError> {
+        usart.tx().bdrain();
+        set_delimiter(&usart.usart, delimiter)?;
+
+        Ok(Self::create(
+            usart,
+            channel,
+            timer,
+            timeout,
+            Some(delimiter),
+            buffers,
+        ))
+    }
+
+    fn create<Pins>(
+        usart: &'usart USART<I, init_state::Enabled<Pins>>,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        mut timer: MrtChannel,
+        timeout: fugit::MicrosDurationU32,
+        delimiter: Option<u8>,
+        buffers: &'static mut [[u8; S]; N],
+    ) -> Self {
+        assert!(N >= 2);
+        assert!(S > 0 && S <= 1024);
+
+        if let Err(error) = timer.start_duration(timeout) {
+            panic!("{}", error);
+        }
+
+        let mut receiver = PacketReceiver {
+            transfer: None,
+            timer,
+
+            idle: None,
+            delimiter,
+
+            buffers,
+            lens: [0; N],
+
+            first: 0,
+            complete: 0,
+
+            received: 0,
+        };
+        receiver.start(channel, usart.rx());
+
+        receiver
+    }
+
+    /// Check whether the current frame is complete
+    ///
+    /// Does nothing, unless the timeout has expired since the last call. If
+    /// it has, and no byte has been received in the meantime, the current
+    /// frame is complete and reception continues in the next buffer.
+    ///
+    /// Returns [`PacketError::QueueFull`], if the frame had to be dropped,
+    /// because no free buffer was left.
+    ///
+    /// [`PacketError::QueueFull`]: enum.PacketError.html#variant.QueueFull
+    pub fn poll(&mut self) -> Result<(), PacketError> {
+        if self.timer.wait().is_err() {
+            return Ok(());
+        }
+
+        // There's no ongoing transfer, while waiting for a delimiter.
+        let transfer = match &self.transfer {
+            Some(transfer) => transfer,
+            None => return Ok(()),
+        };
+
+        let received = transfer.transferred();
+        if received == 0 || received < S && received != self.received {
+            self.received = received;
+            return Ok(());
+        }
+
+        let transfer = self.transfer.take().unwrap();
+        let (received, channel, rx, _) = transfer.abort();
+
+        let mut result = Ok(());
+        if self.complete < N - 1 {
+            let i = self.index(self.complete);
+            self.lens[i] = received;
+            self.complete += 1;
+        } else {
+            result = Err(PacketError::QueueFull);
+        }
+
+        self.start(channel, rx);
+
+        result
+    }
+
+    /// Start receiving a frame, if its delimiter has been received
+    ///
+    /// Only required for receivers created using [`new_delimited`]. Call this
+    /// from the USART's interrupt handler. Does nothing, unless the receiver
+    /// is waiting for a delimiter, and one has been received. A delimiter that
+    /// was received with an error is ignored.
+    ///
+    /// [`new_delimited`]: #method.new_delimited
+    pub fn on_interrupt(&mut self) {
+        let (channel, mut rx) = match self.idle.take() {
+            Some(idle) => idle,
+            None => return,
+        };
+
+        // Only the delimiter can be received at this point, as the hardware
+        // discards everything else.
+        if rx.read().is_err() {
+            self.idle = Some((channel, rx));
+            return;
+        }
+
+        rx.0.ctl.modify(|_, w| w.addrdet().disabled());
+        rx.disable_rxrdy_interrupt();
+
+        self.start_transfer(channel, rx);
+    }
+
+    /// Process the oldest complete frame
+    ///
+    /// Calls `f` with the oldest complete frame and returns its result. The
+    /// frame's buffer is available for receiving again afterwards. Returns
+    /// `None`, if no complete frame is available.
+    pub fn receive<R>(&mut self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        if self.complete == 0 {
+            return None;
+        }
+
+        compiler_fence(Ordering::SeqCst);
+
+        // Sound, as this buffer is not being written to by the DMA controller.
+        let frame = unsafe {
+            let buffer = self.buffer(0);
+            slice::from_raw_parts(buffer, self.lens[self.first])
+        };
+        let result = f(frame);
+
+        self.first = self.index(1);
+        self.complete -= 1;
+
+        Some(result)
+    }
+
+    /// Stop receiving and release the resources used by this instance
+    ///
+    /// Any frame that hasn't been completed yet is discarded.
+    pub fn free(
+        mut self,
+    ) -> (
+        dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        Rx<'usart, I>,
+        MrtChannel,
+        &'static mut [[u8; S]; N],
+    ) {
+        let (channel, mut rx) = match self.transfer.take() {
+            Some(transfer) => {
+                let (_, channel, rx, _) = transfer.abort();
+                (channel, rx)
+            }
+            // Can't panic, as there's either an ongoing transfer or an idle
+            // channel between method calls.
+            None => self.idle.take().unwrap(),
+        };
+
+        if self.delimiter.is_some() {
+            rx.0.ctl.modify(|_, w| w.addrdet().disabled());
+            rx.disable_rxrdy_interrupt();
+        }
+
+        // Sound, as the DMA transfer has been aborted and its buffer dropped,
+        // so there's no other reference to the buffers left.
+        let buffers = unsafe { &mut *self.buffers };
+
+        (channel, rx, self.timer, buffers)
+    }
+
+    /// Start receiving the next frame
+    ///
+    /// If frames start with a delimiter, waits for it first. The transfer is
+    /// then started by `on_interrupt`.
+    fn start(
+        &mut self,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        mut rx: Rx<'usart, I>,
+    ) {
+        if self.delimiter.is_some() {
+            rx.0.ctl.modify(|_, w| w.addrdet().enabled());
+            rx.enable_rxrdy_interrupt();
+
+            self.idle = Some((channel, rx));
+            return;
+        }
+
+        self.start_transfer(channel, rx);
+    }
+
+    /// Start receiving into the buffer after the complete frames
+    fn start_transfer(
+        &mut self,
+        channel: dma::Channel<T, init_state::Enabled<&'dma dma::Handle>>,
+        rx: Rx<'usart, I>,
+    ) {
+        // Sound, as this buffer doesn't contain a complete frame, which means
+        // there's no other reference to it.
+        let buffer =
+            unsafe { slice::from_raw_parts_mut(self.buffer(self.complete), S) };
+
+        self.received = 0;
+        self.transfer = Some(channel.start_read(rx, buffer));
+    }
+
+    fn buffer(&self, offset: usize) -> *mut u8 {
+        let i = self.index(offset);
+
+        // Sound, as `index` always returns a valid index.
+        unsafe { (self.buffers as *mut [u8; S]).add(i) as *mut u8 }
+    }
+
+    fn index(&self, offset: usize) -> usize {
+        (self.first + offset) % N
+    }
+}
+
+/// An error that can occur while receiving frames using [`PacketReceiver`]
+///
+/// [`PacketReceiver`]: struct.PacketReceiver.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PacketError {
+    /// A frame has been dropped, as all buffers contained complete frames
+    QueueFull,
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PacketError::QueueFull => {
+                f.write_str("USART frame dropped, as packet queue is full")
+            }
+        }
+    }
+}
+
+/// Match `delimiter` in hardware, once address detection is enabled
+///
+/// CFG can only be changed while the USART is disabled, and nothing is being
+/// transmitted. See user manual, section 13.6.1.
+fn set_delimiter(
+    usart: &pac::usart0::RegisterBlock,
+    delimiter: u8,
+) -> Result<(), DelimiterError> {
+    // In address detection mode, the hardware ignores all characters whose
+    // most significant bit is not set. See user manual, section 13.6.2.
+    if delimiter & 0x80 == 0 {
+        return Err(DelimiterError::MsbClear);
+    }
+
+    usart.addr.write(|w| unsafe { w.address().bits(delimiter) });
+
+    usart.cfg.modify(|_, w| w.enable().disabled());
+    usart.cfg.modify(|_, w| w.autoaddr().enabled());
+    usart.cfg.modify(|_, w| w.enable().enabled());
+
+    Ok(())
+}
+
+/// An error that can occur while setting up hardware delimiter matching
+///
+/// Returned by [`USART::enable_delimiter_match`] and
+/// [`PacketReceiver::new_delimited`].
+///
+/// [`USART::enable_delimiter_match`]: struct.USART.html#method.enable_delimiter_match
+/// [`PacketReceiver::new_delimited`]: struct.PacketReceiver.html#method.new_delimited
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DelimiterError {
+    /// The most significant bit of the delimiter is not set
+    ///
+    /// The hardware can only match characters that have it set.
+    MsbClear,
+}
+
+impl fmt::Display for DelimiterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DelimiterError::MsbClear => f.write_str(
+                "USART delimiter must have its most significant bit set",
+            ),
+        }
+    }
+}
+
+/// A USART in single-wire half-duplex mode
+///
+/// RX and TX are assigned to the same pin. Since the transmitter would drive
+/// the line even while idle, TX is only assigned to the pin while
+/// transmitting, and unassigned while receiving. This is used by buses like
+/// the one of Dynamixel servos.
+///
+/// The `Mode` type parameter tracks the current direction. It is either
+/// [`Transmit`] or [`Receive`].
+///
+/// Created by [`USART::into_half_duplex`].
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc82x_hal::prelude::*;
+/// use lpc82x_hal::Peripherals;
+/// use lpc82x_hal::usart::BaudRate;
+///
+/// let mut p = Peripherals::take().unwrap();
+///
+/// let mut syscon = p.SYSCON.split();
+/// let mut swm    = p.SWM.split();
+///
+/// syscon.uartfrg.set_clkdiv(6);
+/// syscon.uartfrg.set_frgmult(22);
+/// syscon.uartfrg.set_frgdiv(0xff);
+/// let baud_rate = BaudRate::new(&syscon.uartfrg, 0);
+///
+/// // Assign both RX and TX to the same pin
+/// let (u0_rxd, pio0_4) = swm.movable_functions.u0_rxd.assign(
+///     swm.pins.pio0_4.into_swm_pin(),
+///     &mut swm.handle,
+/// );
+/// let (u0_txd, pio0_4) = swm.movable_functions.u0_txd.assign(
+///     pio0_4,
+///     &mut swm.handle,
+/// );
+///
+/// let serial = p.USART0.enable(
+///     &baud_rate,
+///     &mut syscon.handle,
+///     u0_rxd,
+///     u0_txd,
+/// );
+///
+/// let serial = serial.into_half_duplex(pio0_4);
+/// serial.tx().bwrite_all(b"request");
+///
+/// // Waits for the request to be sent, then releases the line
+/// let serial = serial.into_receive(&mut swm.handle);
+/// let response = nb::block!(serial.rx().read());
+/// ```
+///
+/// [`Transmit`]: struct.Transmit.html
+/// [`Receive`]: struct.Receive.html
+/// [`USART::into_half_duplex`]: struct.USART.html#method.into_half_duplex
+pub struct HalfDuplex<I: Instance, P, Mode> {
+    usart: I,
+    rx: swm::Function<I::Rx, swm::state::Assigned<P>>,
+    mode: Mode,
+}
+
+/// Indicates that a [`HalfDuplex`] USART is transmitting
+///
+/// TX is assigned to the pin. Holds the TX function and the pin.
+///
+/// [`HalfDuplex`]: struct.HalfDuplex.html
+pub struct Transmit<T, P: PinTrait, Is> {
+    tx: swm::Function<T, swm::state::Assigned<P>>,
+    pin: Pin<P, pin_state::Swm<((),), Is>>,
+}
+
+/// Indicates that a [`HalfDuplex`] USART is receiving
+///
+/// TX is unassigned from the pin. Holds the TX function and the pin.
+///
+/// [`HalfDuplex`]: struct.HalfDuplex.html
+pub struct Receive<T, P: PinTrait, Is> {
+    tx: swm::Function<T, swm::state::Unassigned>,
+    pin: Pin<P, pin_state::Swm<(), Is>>,
+}
+
+impl<I, P> USART<I, init_state::Enabled<Functions<I, P, P>>>
+where
+    I: Instance,
+    P: PinTrait,
+{
+    /// Switch to single-wire half-duplex mode
+    ///
+    /// Only available, if RX and TX are assigned to the same pin. That pin
+    /// must be passed here, so TX can be unassigned from it while receiving.
+    ///
+    /// The returned [`HalfDuplex`] instance is in transmit mode.
+    ///
+    /// [`HalfDuplex`]: struct.HalfDuplex.html
+    pub fn into_half_duplex<Is>(
+        self,
+        pin: Pin<P, pin_state::Swm<((),), Is>>,
+    ) -> HalfDuplex<I, P, Transmit<I::Tx, P, Is>> {
+        let (rx, tx) = self.state.0;
+
+        HalfDuplex {
+            usart: self.usart,
+            rx,
+            mode: Transmit { tx, pin },
+        }
+    }
+}
+
+impl<I, P, Is> HalfDuplex<I, P, Transmit<I::Tx, P, Is>>
+where
+    I: Instance,
+    I::Tx: FunctionTrait<P, Kind = swm::Output>,
+    P: PinTrait,
+{
+    /// Return the transmitter
+    pub fn tx(&self) -> Tx<I> {
+        Tx(&self.usart)
+    }
+
+    /// Switch to receive mode
+    ///
+    /// Waits until the transmitter is idle, so the last character is not cut
+    /// off, then unassigns TX from the pin, releasing the line. Any characters
+    /// received while transmitting (the echo of the transmitted ones) are
+    /// discarded.
+    pub fn into_receive(
+        self,
+        swm: &mut swm::Handle,
+    ) -> HalfDuplex<I, P, Receive<I::Tx, P, Is>> {
+        self.tx().bdrain();
+
+        let (tx, pin) = self.mode.tx.unassign(self.mode.pin, swm);
+
+        while self.usart.stat.read().rxrdy().bit_is_set() {
+            let _ = self.usart.rxdat.read();
+        }
+        self.usart.stat.write(|w| w.overrunint().set_bit());
+
+        HalfDuplex {
+            usart: self.usart,
+            rx: self.rx,
+            mode: Receive { tx, pin },
+        }
+    }
+
+    /// Leave half-duplex mode
+    ///
+    /// Returns the USART and the pin that were passed to
+    /// [`USART::into_half_duplex`].
+    ///
+    /// [`USART::into_half_duplex`]: struct.USART.html#method.into_half_duplex
+    pub fn into_full_duplex(
+        self,
+    ) -> (
+        USART<I, init_state::Enabled<Functions<I, P, P>>>,
+        Pin<P, pin_state::Swm<((),), Is>>,
+    ) {
+        let usart = USART {
+            usart: self.usart,
+            state: init_state::Enabled((self.rx, self.mode.tx)),
+        };
+
+        (usart, self.mode.pin)
+    }
+}
+
+impl<I, P, Is> HalfDuplex<I, P, Receive<I::Tx, P, Is>>
+where
+    I: Instance,
+    I::Tx: FunctionTrait<P, Kind = swm::Output>,
+    P: PinTrait,
+{
+    /// Return the receiver
+    pub fn rx(&self) -> Rx<I> {
+        Rx(&self.usart)
+    }
+
+    /// Switch to transmit mode
+    ///
+    /// Assigns TX to the pin, which starts driving the line.
+    pub fn into_transmit(
+        self,
+        swm: &mut swm::Handle,
+    ) -> HalfDuplex<I, P, Transmit<I::Tx, P, Is>> {
+        let (tx, pin) = self.mode.tx.assign(self.mode.pin, swm);
+
+        HalfDuplex {
+            usart: self.usart,
+            rx: self.rx,
+            mode: Transmit { tx, pin },
+        }
     }
 }
 
@@ -398,11 +1599,18 @@ pub trait Instance:
     /// The interrupt that is triggered for this USART peripheral
     const INTERRUPT: Interrupt;
 
+    /// The bit in STARTERP1 that enables this USART's interrupt for wake-up
+    const WAKEUP: u8;
+
     /// The movable function that needs to be assigned to this USART's RX pin
     type Rx;
 
     /// The movable function that needs to be assigned to this USART's TX pin
     type Tx;
+
+    /// The movable function that needs to be assigned to this USART's SCLK
+    /// pin
+    type Sclk;
 }
 
 macro_rules! instances {
@@ -410,35 +1618,143 @@ macro_rules! instances {
         $(
             $instance:ident,
             $interrupt:ident,
+            $wakeup:expr,
             $rx:ident,
-            $tx:ident;
+            $tx:ident,
+            $sclk:ident;
         )*
     ) => {
         $(
             impl Instance for pac::$instance {
                 const INTERRUPT: Interrupt = Interrupt::$interrupt;
+                const WAKEUP: u8 = $wakeup;
 
                 type Rx = swm::$rx;
                 type Tx = swm::$tx;
+                type Sclk = swm::$sclk;
             }
         )*
     };
 }
 
 instances!(
-    USART0, USART0, U0_RXD, U0_TXD;
-    USART1, USART1, U1_RXD, U1_TXD;
-    USART2, USART2, U2_RXD, U2_TXD;
+    USART0, USART0, 3, U0_RXD, U0_TXD, U0_SCLK;
+    USART1, USART1, 4, U1_RXD, U1_TXD, U1_SCLK;
+    USART2, USART2, 5, U2_RXD, U2_TXD, U2_SCLK;
 );
 
 #[cfg(feature = "845")]
 instances!(
-    USART3, PIN_INT6_USART3, U3_RXD, U3_TXD;
-    USART4, PIN_INT7_USART4, U4_RXD, U4_TXD;
+    USART3, PIN_INT6_USART3, 30, U3_RXD, U3_TXD, U3_SCLK;
+    USART4, PIN_INT7_USART4, 31, U4_RXD, U4_TXD, U4_SCLK;
 );
 
+/// Character format settings for a USART
+///
+/// Can be passed to [`USART::configure`]. The default settings match what
+/// [`USART::enable`] configures.
+///
+/// [`USART::configure`]: struct.USART.html#method.configure
+/// [`USART::enable`]: struct.USART.html#method.enable
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Settings {
+    /// The parity mode
+    pub parity: Parity,
+
+    /// The number of stop bits
+    pub stop_bits: StopBits,
+}
+
+impl Settings {
+    /// Set the parity mode
+    pub fn parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    /// Set the number of stop bits
+    pub fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+}
+
+/// The parity mode
+///
+/// Used in [`Settings`].
+///
+/// [`Settings`]: struct.Settings.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Parity {
+    /// No parity bit
+    None,
+
+    /// Even parity
+    Even,
+
+    /// Odd parity
+    Odd,
+}
+
+impl Default for Parity {
+    fn default() -> Self {
+        Parity::None
+    }
+}
+
+/// The number of stop bits
+///
+/// Used in [`Settings`].
+///
+/// [`Settings`]: struct.Settings.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StopBits {
+    /// 1 stop bit
+    One,
+
+    /// 2 stop bits
+    Two,
+}
+
+impl Default for StopBits {
+    fn default() -> Self {
+        StopBits::One
+    }
+}
+
+/// The edge of SCLK on which received data is sampled
+///
+/// Passed to [`USART::enable_sync_master`].
+///
+/// [`USART::enable_sync_master`]: struct.USART.html#method.enable_sync_master
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockEdge {
+    /// Sample on the falling edge
+    Falling,
+
+    /// Sample on the rising edge
+    Rising,
+}
+
+/// The clock output of a USART in synchronous master mode
+///
+/// Returned by [`USART::enable_sync_master`]. Pass it to
+/// [`USART::disable_sync_master`], to switch back to asynchronous mode and get
+/// the SCLK function back.
+///
+/// [`USART::enable_sync_master`]: struct.USART.html#method.enable_sync_master
+/// [`USART::disable_sync_master`]: struct.USART.html#method.disable_sync_master
+pub struct Sclk<F, P> {
+    function: swm::Function<F, swm::state::Assigned<P>>,
+}
+
 /// A USART error
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Character received with a stop bit missing at the expected location
     Framing,
@@ -452,3 +1768,51 @@ pub enum Error {
     /// Parity error detected in received character
     Parity,
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Error::Framing => "USART framing error",
+            Error::Noise => "USART noise error",
+            Error::Overrun => "USART receiver overrun",
+            Error::Parity => "USART parity error",
+        };
+
+        f.write_str(description)
+    }
+}
+
+/// An error that can occur while injecting a fault
+///
+/// This type is only available, if the `fault_injection` feature is enabled.
+#[cfg(feature = "fault_injection")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultError {
+    /// A parity error can't be injected, as the USART uses no parity
+    NoParity,
+}
+
+#[cfg(feature = "fault_injection")]
+impl fmt::Display for FaultError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FaultError::NoParity => {
+                f.write_str("can't inject parity error without parity")
+            }
+        }
+    }
+}
+
+/// Collect the registers of a USART for a [`debug_dump`] snapshot
+///
+/// [`debug_dump`]: ../debug_dump/index.html
+#[cfg(feature = "debug_dump")]
+pub(crate) fn snapshot(
+    usart: &pac::usart0::RegisterBlock,
+) -> crate::debug_dump::Usart {
+    crate::debug_dump::Usart {
+        cfg: usart.cfg.read().bits(),
+        stat: usart.stat.read().bits(),
+    }
+}
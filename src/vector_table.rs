@@ -0,0 +1,157 @@
+//! Vector table in RAM, for installing interrupt handlers at runtime
+//!
+//! Interrupt handlers are usually defined at compile time, and the vector
+//! table that points to them lives in flash. Bootloaders, and applications
+//! that need to install or replace interrupt handlers while running, need a
+//! vector table in RAM instead.
+//!
+//! [`VectorTable::relocate`] copies the current vector table into a
+//! [`RamVectorTable`] and points the Cortex-M0+ VTOR register at it. After
+//! that, handlers can be replaced using [`VectorTable::set_handler`].
+//!
+//! The vector table is read from wherever VTOR points, which is address 0
+//! after reset. On LPC8xx, the SYSMEMREMAP register in SYSCON selects
+//! whether boot ROM, RAM, or flash is mapped to address 0 (see user manual,
+//! section 5.6.1 (LPC82x) or 8.6.1 (LPC845)). [`VectorTable::relocate`]
+//! copies from the current location, whatever is mapped there. Once VTOR
+//! points to the RAM vector table, the memory remap setting no longer
+//! affects interrupts. The memory remap can be changed using
+//! [`syscon::Handle::set_memory_map`].
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     pac::Interrupt,
+//!     vector_table::{RamVectorTable, VectorTable},
+//!     Peripherals,
+//! };
+//!
+//! static mut VECTORS: RamVectorTable = RamVectorTable::new();
+//!
+//! extern "C" fn on_wkt() {
+//!     // Handle the interrupt
+//! }
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! // Sound, as this is the only place that accesses `VECTORS`.
+//! let vectors = unsafe { &mut VECTORS };
+//!
+//! let mut vectors = VectorTable::relocate(vectors, &mut p.SCB);
+//! vectors.set_handler(Interrupt::WKT, on_wkt);
+//! ```
+//!
+//! [`VectorTable::relocate`]: struct.VectorTable.html#method.relocate
+//! [`VectorTable::set_handler`]: struct.VectorTable.html#method.set_handler
+//! [`RamVectorTable`]: struct.RamVectorTable.html
+//! [`syscon::Handle::set_memory_map`]: ../syscon/struct.Handle.html#method.set_memory_map
+
+use core::ptr;
+
+use cortex_m::interrupt::{self, Nr};
+
+use crate::pac::{self, Interrupt};
+
+/// The number of entries in the vector table
+///
+/// The initial stack pointer, 15 system exceptions, and 32 interrupts.
+pub const LEN: usize = 16 + 32;
+
+/// The storage for a vector table in RAM
+///
+/// VTOR requires the vector table to be aligned to the next power of two of
+/// its size, which this type guarantees.
+#[repr(C, align(256))]
+pub struct RamVectorTable {
+    vectors: [usize; LEN],
+}
+
+impl RamVectorTable {
+    /// Create an empty vector table
+    ///
+    /// It is filled in by [`VectorTable::relocate`].
+    ///
+    /// [`VectorTable::relocate`]: struct.VectorTable.html#method.relocate
+    pub const fn new() -> Self {
+        RamVectorTable { vectors: [0; LEN] }
+    }
+}
+
+/// The active vector table, in RAM
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct VectorTable {
+    table: &'static mut RamVectorTable,
+    previous: u32,
+}
+
+impl VectorTable {
+    /// Copy the current vector table to RAM, and make the copy active
+    ///
+    /// All handlers stay the same, until they are replaced using
+    /// [`set_handler`].
+    ///
+    /// [`set_handler`]: #method.set_handler
+    pub fn relocate(
+        table: &'static mut RamVectorTable,
+        scb: &mut pac::SCB,
+    ) -> Self {
+        let previous = scb.vtor.read();
+
+        for (i, vector) in table.vectors.iter_mut().enumerate() {
+            // Sound, as VTOR points to a valid vector table of at least `LEN`
+            // entries.
+            *vector = unsafe {
+                ptr::read_volatile((previous as *const usize).add(i))
+            };
+        }
+
+        // Both tables have the same contents, so it doesn't matter which one
+        // an interrupt that happens during the switch uses.
+        unsafe { scb.vtor.write(table.vectors.as_ptr() as u32) };
+
+        VectorTable { table, previous }
+    }
+
+    /// Replace the handler of an interrupt
+    ///
+    /// Returns the address of the previous handler, which might be the
+    /// default handler. The new handler is used from the next occurrence of
+    /// the interrupt on. The interrupt still needs to be unmasked in the NVIC.
+    pub fn set_handler(
+        &mut self,
+        interrupt: Interrupt,
+        handler: extern "C" fn(),
+    ) -> usize {
+        let i = 16 + interrupt.nr() as usize;
+
+        interrupt::free(|_| {
+            let previous = self.table.vectors[i];
+
+            // The vector table is read by the hardware, so make sure the
+            // write actually happens.
+            unsafe {
+                ptr::write_volatile(
+                    &mut self.table.vectors[i],
+                    handler as usize,
+                )
+            };
+
+            previous
+        })
+    }
+
+    /// Return the vector table to where it was before, and release the RAM
+    ///
+    /// Handlers that have been installed using [`set_handler`] are no longer
+    /// used afterwards.
+    ///
+    /// [`set_handler`]: #method.set_handler
+    pub fn free(self, scb: &mut pac::SCB) -> &'static mut RamVectorTable {
+        unsafe { scb.vtor.write(self.previous) };
+        self.table
+    }
+}
@@ -0,0 +1,190 @@
+//! Playback of digital waveforms on a GPIO pin, using DMA
+//!
+//! Some protocols or test signals are easiest to generate by precomputing the
+//! level of a pin for every sample period, then outputting those levels with a
+//! precise timing. [`Waveform`] does that without any CPU load: The SCT
+//! generates a DMA request once per sample period, and the DMA controller
+//! writes the next sample to the pin's word pin register.
+//!
+//! Every sample is a byte. A sample of `0` drives the pin low, any other value
+//! drives it high. After the last sample, the pin keeps the level of that
+//! sample.
+//!
+//! The SCT is used for pacing, as it is the only timer that can trigger DMA
+//! transfers on both LPC82x and LPC845. It runs as a single 32-bit counter, so
+//! the whole SCT is required, instead of one of the halves provided by the
+//! [`sct`] module.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{dma::DescriptorTable, waveform::Waveform, Peripherals};
+//!
+//! static mut DESCRIPTORS: DescriptorTable = DescriptorTable::new();
+//! static SAMPLES: [u8; 8] = [1, 0, 1, 1, 0, 0, 1, 0];
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let gpio = p.GPIO;
+//! #[cfg(feature = "845")]
+//! let gpio = p.GPIO.enable(&mut syscon.handle);
+//!
+//! let dma = p.DMA.split(unsafe { &mut DESCRIPTORS });
+//! let dma_handle = dma.handle.enable(&mut syscon.handle);
+//! let channel = dma.channels.channel0.enable(&dma_handle);
+//!
+//! let mut pin = swm.pins.pio0_12.into_gpio_pin(&gpio).into_output();
+//!
+//! let waveform = Waveform::new(p.SCT0, &mut syscon.handle);
+//!
+//! // 120 ticks of the 12 MHz system clock, for a 100 kHz sample rate
+//! let playback = waveform.play(
+//!     channel,
+//!     &SAMPLES,
+//!     pin.word_register(),
+//!     &p.INPUTMUX,
+//!     120,
+//! );
+//!
+//! let (waveform, channel, _, _) = playback.wait();
+//! ```
+//!
+//! [`Waveform`]: struct.Waveform.html
+//! [`sct`]: ../sct/index.html
+
+use embedded_dma::ReadBuffer;
+use void::ResultVoidExt as _;
+
+use crate::{
+    dma::{self, ChannelTrait},
+    gpio::WordRegister,
+    init_state, pac,
+    route::{route, SctDmaRequest0},
+    sct::{self, SCT},
+    syscon,
+};
+
+/// Plays back waveforms on a GPIO pin
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Waveform {
+    sct: SCT,
+}
+
+impl Waveform {
+    /// Create the waveform helper
+    ///
+    /// Enables the SCT clock.
+    pub fn new(sct: SCT, syscon: &mut syscon::Handle) -> Self {
+        syscon.enable_clock(&sct.sct);
+        Waveform { sct }
+    }
+
+    /// Start playing back a waveform
+    ///
+    /// Writes one sample from `samples` to `pin` every `period` ticks of the
+    /// system clock, starting one period after this method has been called.
+    /// `inputmux` is used to connect the SCT to the DMA channel's hardware
+    /// trigger.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `period` is `0`.
+    ///
+    /// # Limitations
+    ///
+    /// The length of `samples` must be 1024 or less.
+    pub fn play<'dma, 'pin, C, B>(
+        self,
+        channel: dma::Channel<C, init_state::Enabled<&'dma dma::Handle>>,
+        samples: B,
+        pin: WordRegister<'pin>,
+        inputmux: &pac::INPUTMUX,
+        period: u32,
+    ) -> Playback<'dma, 'pin, C, B>
+    where
+        C: ChannelTrait,
+        B: ReadBuffer<Word = u8>,
+    {
+        assert!(period > 0);
+
+        route(inputmux, &SctDmaRequest0, &channel);
+
+        let sct = &self.sct.sct;
+
+        // Run the SCT as a single 32-bit counter, that is reset by match 0 and
+        // generates event 0 and DMA request 0 at the same time.
+        sct::halt_all(sct);
+        sct.config.write(|w| {
+            w.unify().unified_counter();
+            w.autolimit_l().set_bit()
+        });
+
+        // Sound, as all values are valid according to the user manual.
+        unsafe {
+            sct.sctmatch0().write(|w| w.bits(period - 1));
+            sct.sctmatchrel0().write(|w| w.bits(period - 1));
+            sct.event[0].state.write(|w| w.statemskn().bits(1 << 0));
+            sct.event[0].ctrl.write(|w| w.combmode().match_());
+            sct.dma0request.write(|w| w.dev_0().bits(1 << 0));
+        }
+
+        let transfer = channel.start_paced_transfer(samples, pin);
+
+        // Start the counter. This clears the HALT_L bit.
+        sct.ctrl.write(|w| w.clrctr_l().set_bit());
+
+        Playback {
+            transfer,
+            sct: self.sct,
+        }
+    }
+
+    /// Disable the SCT and return it
+    pub fn free(self, syscon: &mut syscon::Handle) -> SCT {
+        syscon.disable_clock(&self.sct.sct);
+        self.sct
+    }
+}
+
+/// A waveform that is being played back
+///
+/// Returned by [`Waveform::play`].
+///
+/// [`Waveform::play`]: struct.Waveform.html#method.play
+pub struct Playback<'dma, 'pin, C, B>
+where
+    C: ChannelTrait,
+{
+    transfer: dma::Transfer<'dma, C, B, WordRegister<'pin>>,
+    sct: SCT,
+}
+
+impl<'dma, 'pin, C, B> Playback<'dma, 'pin, C, B>
+where
+    C: ChannelTrait,
+{
+    /// Wait for the playback to finish
+    ///
+    /// Stops the SCT afterwards and returns the resources used.
+    pub fn wait(
+        self,
+    ) -> (
+        Waveform,
+        dma::Channel<C, init_state::Enabled<&'dma dma::Handle>>,
+        B,
+        WordRegister<'pin>,
+    ) {
+        let (channel, samples, pin) = self.transfer.wait().void_unwrap();
+
+        sct::halt_all(&self.sct.sct);
+
+        (Waveform { sct: self.sct }, channel, samples, pin)
+    }
+}
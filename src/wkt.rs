@@ -38,7 +38,7 @@ use void::Void;
 use crate::{
     init_state,
     pac::{self, wkt::ctrl},
-    pmu::LowPowerClock,
+    pmu::{LowPowerClock, Wakeup, WakeupSource},
     syscon::{self, IoscDerivedClock},
 };
 
@@ -138,6 +138,21 @@ impl WKT<init_state::Enabled> {
             w
         });
     }
+
+    /// Return the number of clock ticks until the timer expires
+    pub(crate) fn remaining(&self) -> u32 {
+        self.wkt.count.read().value().bits()
+    }
+
+    /// Stop the timer and reset the alarm flag
+    pub(crate) fn clear(&mut self) {
+        // The alarm flag is cleared by writing 1. See user manual, section
+        // 9.6.1.
+        self.wkt.ctrl.modify(|_, w| {
+            w.alarmflag().set_bit();
+            w.clearctr().set_bit()
+        });
+    }
 }
 
 impl timer::CountDown for WKT<init_state::Enabled> {
@@ -169,6 +184,12 @@ impl timer::CountDown for WKT<init_state::Enabled> {
     }
 }
 
+impl WakeupSource for WKT<init_state::Enabled> {
+    fn wakeup(&self) -> Wakeup {
+        Wakeup::Interrupt(15)
+    }
+}
+
 impl<State> WKT<State> {
     /// Return the raw peripheral
     ///
@@ -0,0 +1,384 @@
+//! API for the windowed watchdog timer (WWDT)
+//!
+//! The entry point to this API is [`WWDT`].
+//!
+//! The WWDT peripheral is described in the user manual, chapter 16 (LPC82x) or
+//! chapter 19 (LPC845).
+//!
+//! # Examples
+//!
+//! ``` no_run
+//! use lpc82x_hal::prelude::*;
+//! use lpc82x_hal::Peripherals;
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut wwdt = p.WWDT.enable(&mut syscon.handle);
+//!
+//! // The watchdog counter is decremented at a quarter of the watchdog
+//! // oscillator frequency.
+//! wwdt.start(0x00ff_ffffu32);
+//!
+//! loop {
+//!     // do stuff
+//!
+//!     wwdt.feed();
+//! }
+//! ```
+//!
+//! # Crash logging
+//!
+//! The WWDT can trigger a warning interrupt some time before it resets the
+//! microcontroller. This can be used to save diagnostic information, before
+//! it's lost in the reset. Set the warning time using
+//! [`WWDT::set_warning_interrupt`], register a hook using [`set_warning_hook`],
+//! and call [`handle_warning_interrupt`] from the WDT interrupt handler.
+//!
+//! [`CrashLog`] is a log buffer that the hook can write to. If it is placed
+//! into RAM that isn't initialized on startup, its contents can be read back
+//! after the watchdog reset.
+//!
+//! ``` no_run
+//! use core::fmt::Write as _;
+//!
+//! use lpc8xx_hal::wwdt::{self, CrashLog};
+//!
+//! #[link_section = ".uninit.CRASH_LOG"]
+//! static mut CRASH_LOG: CrashLog<128> = CrashLog::new();
+//!
+//! fn on_warning() {
+//!     // Sound, as the log is only accessed from here and during startup,
+//!     // when this hook can't run yet.
+//!     let log = unsafe { &mut CRASH_LOG };
+//!     let _ = write!(log, "watchdog timeout");
+//! }
+//!
+//! // During startup
+//! let log = unsafe { &mut CRASH_LOG };
+//! if let Some(message) = log.read() {
+//!     // Report the message from before the last reset
+//! }
+//! log.clear();
+//!
+//! wwdt::set_warning_hook(on_warning);
+//!
+//! // In the WDT interrupt handler
+//! wwdt::handle_warning_interrupt();
+//! ```
+//!
+//! [`WWDT::set_warning_interrupt`]: struct.WWDT.html#method.set_warning_interrupt
+//! [`set_warning_hook`]: fn.set_warning_hook.html
+//! [`handle_warning_interrupt`]: fn.handle_warning_interrupt.html
+//! [`CrashLog`]: struct.CrashLog.html
+
+use core::{cell::Cell, fmt};
+
+use cortex_m::interrupt::{self, Mutex};
+use embedded_hal::watchdog::{Watchdog, WatchdogEnable};
+
+use crate::{init_state, pac, syscon};
+
+/// Marks a [`CrashLog`] as containing a message
+///
+/// [`CrashLog`]: struct.CrashLog.html
+const CRASH_LOG_MAGIC: u32 = 0x4c47_4f4c;
+
+static WARNING_HOOK: Mutex<Cell<Option<fn()>>> = Mutex::new(Cell::new(None));
+
+/// Interface to the windowed watchdog timer (WWDT)
+///
+/// Controls the WWDT. Use [`Peripherals`] to gain access to an instance of this
+/// struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct WWDT<State = init_state::Enabled> {
+    wwdt: pac::WWDT,
+    _state: State,
+
+    // Whether the watchdog oscillator is kept powered in deep-sleep mode
+    in_deep_sleep: bool,
+}
+
+impl WWDT<init_state::Disabled> {
+    pub(crate) fn new(wwdt: pac::WWDT) -> Self {
+        WWDT {
+            wwdt,
+            _state: init_state::Disabled,
+            in_deep_sleep: false,
+        }
+    }
+
+    /// Enable the WWDT
+    ///
+    /// This method is only available, if `WWDT` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Powers up the watchdog oscillator and enables the WWDT clock. This does
+    /// not start the watchdog itself. Use [`WatchdogEnable::start`] for that.
+    ///
+    /// Consumes this instance of `WWDT` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`WatchdogEnable::start`]: #impl-WatchdogEnable
+    pub fn enable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> WWDT<init_state::Enabled> {
+        syscon.power_up(&self.wwdt);
+        syscon.enable_clock(&self.wwdt);
+
+        WWDT {
+            wwdt: self.wwdt,
+            _state: init_state::Enabled(()),
+            in_deep_sleep: false,
+        }
+    }
+}
+
+impl WWDT<init_state::Enabled> {
+    /// Disable the WWDT
+    ///
+    /// This method is only available, if `WWDT` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Please note that once the watchdog has been started, it can't be
+    /// stopped again until the next reset. Disabling the WWDT clock doesn't
+    /// change that, it just stops the watchdog from being fed.
+    ///
+    /// Consumes this instance of `WWDT` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(
+        mut self,
+        syscon: &mut syscon::Handle,
+    ) -> WWDT<init_state::Disabled> {
+        self.stop_in_deep_sleep(syscon);
+
+        syscon.disable_clock(&self.wwdt);
+
+        WWDT {
+            wwdt: self.wwdt,
+            _state: init_state::Disabled,
+            in_deep_sleep: false,
+        }
+    }
+
+    /// Keep the watchdog running in deep-sleep and power-down mode
+    ///
+    /// The WWDT is clocked by the watchdog oscillator, which is powered down
+    /// in those modes by default. That pauses the watchdog, which is usually
+    /// what's wanted. Call this method, if the watchdog should also protect
+    /// against the microcontroller not waking up, or to use the watchdog
+    /// warning interrupt to wake up.
+    ///
+    /// Uses [`syscon::Handle::keep_powered_in_deep_sleep`], so the watchdog
+    /// oscillator is tracked along with other users. Does nothing, if called
+    /// again.
+    ///
+    /// [`syscon::Handle::keep_powered_in_deep_sleep`]: ../syscon/struct.Handle.html#method.keep_powered_in_deep_sleep
+    pub fn keep_running_in_deep_sleep(&mut self, syscon: &mut syscon::Handle) {
+        if !self.in_deep_sleep {
+            syscon.keep_powered_in_deep_sleep(&self.wwdt);
+            self.in_deep_sleep = true;
+        }
+    }
+
+    /// Pause the watchdog in deep-sleep and power-down mode
+    ///
+    /// Undoes [`keep_running_in_deep_sleep`]. The watchdog oscillator stays
+    /// powered in those modes, if another user still needs it. Also called by
+    /// [`disable`].
+    ///
+    /// [`keep_running_in_deep_sleep`]: #method.keep_running_in_deep_sleep
+    /// [`disable`]: #method.disable
+    pub fn stop_in_deep_sleep(&mut self, syscon: &mut syscon::Handle) {
+        if self.in_deep_sleep {
+            syscon.release_in_deep_sleep(&self.wwdt);
+            self.in_deep_sleep = false;
+        }
+    }
+
+    /// Set the time of the warning interrupt
+    ///
+    /// The warning interrupt is triggered when the watchdog counter reaches
+    /// `ticks`, which means `ticks` watchdog clock ticks are left until the
+    /// reset. Values above 1023 are treated as 1023, `0` disables the warning
+    /// interrupt. See user manual, section 16.6.6 (LPC82x) or 19.6.6 (LPC845).
+    ///
+    /// The interrupt still needs to be enabled in the NVIC. Please refer to
+    /// the [module documentation] for how to handle it.
+    ///
+    /// [module documentation]: index.html#crash-logging
+    pub fn set_warning_interrupt(&mut self, ticks: u16) {
+        let ticks = ticks.min(0x3ff);
+        self.wwdt
+            .warnint
+            .write(|w| unsafe { w.warnint().bits(ticks) });
+    }
+}
+
+impl WatchdogEnable for WWDT<init_state::Enabled> {
+    /// The timeout in watchdog clock ticks
+    ///
+    /// The counter is 24 bits wide. Values below `0xff` are treated as `0xff`
+    /// by the hardware.
+    type Time = u32;
+
+    fn start<T>(&mut self, period: T)
+    where
+        T: Into<Self::Time>,
+    {
+        // See user manual, section 16.6.3/19.6.3.
+        self.wwdt
+            .tc
+            .write(|w| unsafe { w.count().bits(period.into() & 0x00ff_ffff) });
+
+        // Enable the watchdog and let it reset the chip on timeout. See user
+        // manual, section 16.6.1/19.6.1. Once set, these bits can't be
+        // cleared by software.
+        self.wwdt.mod_.modify(|_, w| {
+            w.wden().set_bit();
+            w.wdreset().set_bit()
+        });
+
+        // The watchdog only starts running after it has been fed.
+        self.feed();
+    }
+}
+
+impl Watchdog for WWDT<init_state::Enabled> {
+    fn feed(&mut self) {
+        // The feed sequence must not be interrupted. See user manual, section
+        // 16.6.4/19.6.4.
+        interrupt::free(|_| {
+            self.wwdt.feed.write(|w| unsafe { w.feed().bits(0xaa) });
+            self.wwdt.feed.write(|w| unsafe { w.feed().bits(0x55) });
+        });
+    }
+}
+
+impl<State> WWDT<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::WWDT {
+        self.wwdt
+    }
+}
+
+/// Register the hook that is called on the watchdog warning interrupt
+///
+/// Replaces any hook that has been registered before. The hook is called by
+/// [`handle_warning_interrupt`] and only has the time until the watchdog
+/// reset to do its work.
+///
+/// [`handle_warning_interrupt`]: fn.handle_warning_interrupt.html
+pub fn set_warning_hook(hook: fn()) {
+    interrupt::free(|cs| WARNING_HOOK.borrow(cs).set(Some(hook)));
+}
+
+/// Handle the watchdog warning interrupt
+///
+/// Call this function from the WDT interrupt handler. If the warning
+/// interrupt is pending, it calls the hook registered with
+/// [`set_warning_hook`] and clears the interrupt flag.
+///
+/// [`set_warning_hook`]: fn.set_warning_hook.html
+pub fn handle_warning_interrupt() {
+    // Sound, as the interrupt flag is only accessed here, and the other bits
+    // in MOD are either preserved or can't be cleared by writing to them.
+    let wwdt = unsafe { &*pac::WWDT::ptr() };
+
+    if wwdt.mod_.read().wdint().bit_is_clear() {
+        return;
+    }
+
+    if let Some(hook) = interrupt::free(|cs| WARNING_HOOK.borrow(cs).get()) {
+        hook();
+    }
+
+    // The flag is cleared by writing a 1 to it. See user manual, section
+    // 16.6.1/19.6.1.
+    wwdt.mod_.modify(|_, w| w.wdint().set_bit());
+}
+
+/// A log buffer that can survive a watchdog reset
+///
+/// Can hold a message of up to `N` bytes. Longer messages are truncated.
+/// Please refer to the [module documentation] for more information.
+///
+/// All bit patterns are valid for this struct, so it can be placed into RAM
+/// that isn't initialized on startup. After a power-on reset, the RAM will
+/// contain random data, which [`CrashLog::read`] is very likely, but not
+/// guaranteed, to reject.
+///
+/// [module documentation]: index.html#crash-logging
+/// [`CrashLog::read`]: #method.read
+#[repr(C)]
+pub struct CrashLog<const N: usize> {
+    magic: u32,
+    len: u32,
+    buffer: [u8; N],
+}
+
+impl<const N: usize> CrashLog<N> {
+    /// Create an empty crash log
+    pub const fn new() -> Self {
+        CrashLog {
+            magic: 0,
+            len: 0,
+            buffer: [0; N],
+        }
+    }
+
+    /// Return the message in the log, if there is one
+    pub fn read(&self) -> Option<&[u8]> {
+        let len = self.len as usize;
+        if self.magic != CRASH_LOG_MAGIC || len > N {
+            return None;
+        }
+
+        Some(&self.buffer[..len])
+    }
+
+    /// Remove the message from the log
+    pub fn clear(&mut self) {
+        self.magic = 0;
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> fmt::Write for CrashLog<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.read().is_none() {
+            self.len = 0;
+            self.magic = CRASH_LOG_MAGIC;
+        }
+
+        let len = self.len as usize;
+        let n = s.len().min(N - len);
+        self.buffer[len..len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n as u32;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+//! Firmware updates via XMODEM-CRC, directly into flash
+//!
+//! The entry point to this API is [`Xmodem`]. It receives a file from a
+//! terminal program over a [`Console`], using the XMODEM-CRC protocol, and
+//! writes it to flash using the IAP functions (see [`iap`]), block by block.
+//! This allows for a firmware update in the field, without a debugger and
+//! without the ROM bootloader.
+//!
+//! The CRC engine checks every block against its CRC-16, and calculates a
+//! CRC-32 of the complete image once it has been written, which can be
+//! compared against the checksum of the file that was sent.
+//!
+//! Only the original 128-byte blocks are supported, not the 1024-byte blocks
+//! of XMODEM-1K or YMODEM, as the LPC8xx parts have little RAM to spare. The
+//! last block is padded by the sender, usually with `0x1a`, so the image in
+//! flash might be slightly longer than the file.
+//!
+//! The image should be written to pages that don't contain the running
+//! firmware, for example the second half of flash. Please refer to
+//! [`write_page`] for further restrictions.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     console::Console, crc::CRC, mrt::MrtChannel, xmodem::Xmodem,
+//! };
+//!
+//! fn update(console: &mut dyn Console, timer: MrtChannel, crc: &mut CRC) {
+//!     let mut xmodem = Xmodem::new(timer);
+//!     xmodem.set_progress_hook(Some(|_bytes| { /* toggle LED */ }));
+//!
+//!     // Receive up to 8 KiB into the second half of flash (LPC822)
+//!     match xmodem.receive(console, crc, 128, 128) {
+//!         Ok(image) => {
+//!             // Compare `image.crc32` against the expected checksum, then
+//!             // mark the image as valid.
+//!         }
+//!         Err(_) => {
+//!             // Keep running the current firmware
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`Xmodem`]: struct.Xmodem.html
+//! [`Console`]: ../console/trait.Console.html
+//! [`iap`]: ../iap/index.html
+//! [`write_page`]: ../iap/fn.write_page.html
+
+use core::fmt;
+
+use embedded_hal::timer::CountDown as _;
+use nb::block;
+
+use crate::{
+    console::Console,
+    crc::{self, CRC},
+    iap::{self, Page, PAGE_SIZE},
+    mrt::MrtChannel,
+};
+
+const SOH: u8 = 0x01;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+
+/// Requests a transfer with CRC-16 instead of the arithmetic checksum
+const CRC_REQUEST: u8 = b'C';
+
+/// The size of a block of data
+const BLOCK_SIZE: usize = 128;
+
+/// The number of flash pages per block
+const PAGES_PER_BLOCK: u32 = (BLOCK_SIZE / PAGE_SIZE) as u32;
+
+/// The time between requests to start the transfer
+const START_TIMEOUT_US: u32 = 3_000_000;
+
+/// The maximum time the sender may pause within a transfer
+const BYTE_TIMEOUT_US: u32 = 1_000_000;
+
+/// The maximum number of consecutive failed attempts
+const MAX_RETRIES: u32 = 10;
+
+/// Receives firmware images via XMODEM-CRC
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Xmodem {
+    timer: MrtChannel,
+    progress: Option<fn(usize)>,
+}
+
+impl Xmodem {
+    /// Create the XMODEM receiver
+    ///
+    /// `timer` is used for the protocol's timeouts.
+    pub fn new(timer: MrtChannel) -> Self {
+        Xmodem {
+            timer,
+            progress: None,
+        }
+    }
+
+    /// Set the hook that is called after each block
+    ///
+    /// The hook receives the number of bytes that have been written to flash
+    /// so far. It is called before the block is acknowledged, so it delays
+    /// the transfer and should return quickly.
+    pub fn set_progress_hook(&mut self, progress: Option<fn(usize)>) {
+        self.progress = progress;
+    }
+
+    /// Receive a file and write it to flash
+    ///
+    /// Requests the transfer by sending `C`, until the sender starts, then
+    /// writes the received blocks to flash, starting at page `first_page`. At
+    /// most `pages` pages are written. The transfer is cancelled, if the file
+    /// doesn't fit.
+    ///
+    /// Blocks until the transfer has finished, or failed. Damaged blocks are
+    /// requested again, up to 10 times in a row. If nothing is received for
+    /// 30 seconds after this method has been called, it gives up.
+    ///
+    /// Interrupts are disabled while a block is written to flash. This
+    /// method assumes that the system clock runs at 12 MHz.
+    pub fn receive(
+        &mut self,
+        console: &mut dyn Console,
+        crc: &mut CRC,
+        first_page: u32,
+        pages: u32,
+    ) -> Result<Image, Error> {
+        let mut block = [Page([0; PAGE_SIZE]); PAGES_PER_BLOCK as usize];
+        let mut expected: u8 = 1;
+        let mut page = first_page;
+        let mut len = 0;
+        let mut started = false;
+        let mut retries = 0;
+
+        loop {
+            if !started {
+                console.bwrite_all(&[CRC_REQUEST]);
+            }
+
+            let timeout = if started {
+                BYTE_TIMEOUT_US * MAX_RETRIES
+            } else {
+                START_TIMEOUT_US
+            };
+
+            let header = match self.read(console, timeout) {
+                Some(header) => header,
+                None => {
+                    retries += 1;
+                    if retries >= MAX_RETRIES {
+                        cancel(console);
+                        return Err(Error::Timeout);
+                    }
+                    if started {
+                        console.bwrite_all(&[NAK]);
+                    }
+                    continue;
+                }
+            };
+
+            match header {
+                SOH => started = true,
+                EOT => {
+                    console.bwrite_all(&[ACK]);
+                    break;
+                }
+                CAN => return Err(Error::Cancelled),
+                // Noise between blocks, or a sender that isn't ready
+                _ => continue,
+            }
+
+            let number = match self.read_block(console, crc, &mut block) {
+                Some(number) => number,
+                None => {
+                    self.purge(console);
+
+                    retries += 1;
+                    if retries >= MAX_RETRIES {
+                        cancel(console);
+                        return Err(Error::Retries);
+                    }
+
+                    console.bwrite_all(&[NAK]);
+                    continue;
+                }
+            };
+
+            retries = 0;
+
+            if number == expected.wrapping_sub(1) {
+                // The sender didn't get our acknowledgement, and sent the
+                // previous block again.
+                console.bwrite_all(&[ACK]);
+                continue;
+            }
+            if number != expected {
+                cancel(console);
+                return Err(Error::Sequence);
+            }
+
+            if page + PAGES_PER_BLOCK > first_page + pages {
+                cancel(console);
+                return Err(Error::TooLarge);
+            }
+
+            for data in &block {
+                if let Err(error) = iap::write_page(page, data) {
+                    cancel(console);
+                    return Err(Error::Iap(error));
+                }
+                page += 1;
+            }
+
+            len += BLOCK_SIZE;
+            expected = expected.wrapping_add(1);
+
+            if let Some(progress) = self.progress {
+                progress(len);
+            }
+
+            console.bwrite_all(&[ACK]);
+        }
+
+        let mut hasher = crc.start(crc::Config::CRC32);
+        for page in first_page..page {
+            hasher.write_bytes(&iap::read_page(page).0);
+        }
+
+        Ok(Image {
+            first_page,
+            len,
+            crc32: hasher.sum(),
+        })
+    }
+
+    /// Release the timer
+    pub fn free(self) -> MrtChannel {
+        self.timer
+    }
+
+    /// Read the rest of a block, after its header
+    ///
+    /// Returns the block number, if the block has been received completely
+    /// and its CRC matches.
+    fn read_block(
+        &mut self,
+        console: &mut dyn Console,
+        crc: &mut CRC,
+        block: &mut [Page; PAGES_PER_BLOCK as usize],
+    ) -> Option<u8> {
+        let number = self.read(console, BYTE_TIMEOUT_US)?;
+        let complement = self.read(console, BYTE_TIMEOUT_US)?;
+
+        for data in block.iter_mut() {
+            for byte in data.0.iter_mut() {
+                *byte = self.read(console, BYTE_TIMEOUT_US)?;
+            }
+        }
+
+        let high = self.read(console, BYTE_TIMEOUT_US)?;
+        let low = self.read(console, BYTE_TIMEOUT_US)?;
+
+        if number != !complement {
+            return None;
+        }
+
+        let mut hasher = crc.start(crc::Config::XMODEM);
+        for data in block.iter() {
+            hasher.write_bytes(&data.0);
+        }
+        if hasher.sum() as u16 != u16::from_be_bytes([high, low]) {
+            return None;
+        }
+
+        Some(number)
+    }
+
+    /// Read a character, waiting at most `timeout_us` microseconds
+    ///
+    /// Returns `None` on timeout, or if the character was received with an
+    /// error.
+    fn read(
+        &mut self,
+        console: &mut dyn Console,
+        timeout_us: u32,
+    ) -> Option<u8> {
+        // The timeouts can be longer than the MRT channel supports, so they
+        // are waited for in multiple steps.
+        let mut remaining_us = timeout_us;
+        let mut step_us = remaining_us.min(self.timer.max_us());
+        // Can't fail, as the step is limited to the maximum.
+        let _ = self.timer.start_us(step_us);
+
+        loop {
+            match console.read() {
+                Ok(word) => return Some(word),
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(_)) => return None,
+            }
+
+            if self.timer.wait().is_ok() {
+                remaining_us -= step_us;
+                if remaining_us == 0 {
+                    return None;
+                }
+
+                step_us = remaining_us.min(self.timer.max_us());
+                // Can't fail, as the step is limited to the maximum.
+                let _ = self.timer.start_us(step_us);
+            }
+        }
+    }
+
+    /// Discard characters, until the sender is silent
+    fn purge(&mut self, console: &mut dyn Console) {
+        while self.read(console, BYTE_TIMEOUT_US).is_some() {}
+    }
+}
+
+/// Tell the sender to abort the transfer
+fn cancel(console: &mut dyn Console) {
+    // Sending it twice is recommended, so a single corrupted character
+    // doesn't cancel a transfer by accident.
+    console.bwrite_all(&[CAN, CAN]);
+    // Can't fail, as the error type is `Void`.
+    let _ = block!(console.flush());
+}
+
+/// A firmware image that has been received
+///
+/// Returned by [`Xmodem::receive`].
+///
+/// [`Xmodem::receive`]: struct.Xmodem.html#method.receive
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Image {
+    /// The first page of the image
+    pub first_page: u32,
+
+    /// The length of the image in bytes, including the padding of the last
+    /// block
+    pub len: usize,
+
+    /// The CRC-32 of the image, as read back from flash
+    pub crc32: u32,
+}
+
+/// An error that can occur while receiving a firmware image
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The sender didn't start or continue the transfer in time
+    Timeout,
+
+    /// Too many damaged blocks in a row
+    Retries,
+
+    /// A block was received out of order
+    Sequence,
+
+    /// The image doesn't fit into the pages it should be written to
+    TooLarge,
+
+    /// The sender cancelled the transfer
+    Cancelled,
+
+    /// Writing to flash failed
+    Iap(iap::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Timeout => f.write_str("XMODEM transfer timed out"),
+            Error::Retries => f.write_str("XMODEM retries exhausted"),
+            Error::Sequence => f.write_str("XMODEM block out of order"),
+            Error::TooLarge => f.write_str("XMODEM image too large"),
+            Error::Cancelled => f.write_str("XMODEM transfer cancelled"),
+            Error::Iap(error) => write!(f, "XMODEM: {}", error),
+        }
+    }
+}
+
+impl From<iap::Error> for Error {
+    fn from(error: iap::Error) -> Self {
+        Error::Iap(error)
+    }
+}